@@ -0,0 +1,102 @@
+use bevy::prelude::*;
+use crate::camera::bookmarks::{BookmarkEvent, CameraBookmarks, FlyThrough};
+use crate::config::theme::{DEFAULT_FONT, ThemeColor};
+use crate::theme::restyle::{ThemedBackground, ThemedText};
+
+#[derive(Component, Default, Debug)]
+pub struct BookmarkMenu;
+
+/// The menu lists the bookmarks, with the key each is bound to and the one last recalled
+/// highlighted, and is rebuilt when a bookmark is saved or recalled, or a fly-through starts or
+/// stops.
+pub(crate) fn update_bookmark_menu(
+    mut commands: Commands,
+    bookmarks: Res<CameraBookmarks>,
+    fly_through: Res<FlyThrough>,
+    asset_server: Res<AssetServer>,
+    menus: Query<Entity, With<BookmarkMenu>>,
+    mut was_playing: Local<bool>,
+) {
+    if !bookmarks.is_changed() && *was_playing == fly_through.playing() {
+        return;
+    }
+    *was_playing = fly_through.playing();
+    menus.iter().for_each(|menu| commands.entity(menu).despawn_recursive());
+
+    let menu = commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                right: Val::Px(10.0),
+                bottom: Val::Px(10.0),
+                display: Display::Flex,
+                flex_direction: FlexDirection::Row,
+                flex_wrap: FlexWrap::Wrap,
+                ..default()
+            },
+            ..default()
+        },
+        BookmarkMenu::default()
+    )).id();
+
+    let mut entries = bookmarks.bookmarks.iter()
+        .enumerate()
+        .map(|(index, bookmark)| {
+            let color = if bookmarks.current == Some(index) { ThemeColor::PanelHighlight } else { ThemeColor::PanelEntry };
+            let label = bookmark.key.as_ref()
+                .map(|key| format!("{} [{}]", &bookmark.name, key))
+                .or(Some(bookmark.name.clone()))
+                .unwrap();
+            (label, color, BookmarkEvent::Recall(index))
+        })
+        .collect::<Vec<(String, ThemeColor, BookmarkEvent)>>();
+    entries.push(("Bookmark View".to_string(), ThemeColor::Panel, BookmarkEvent::Save));
+    if fly_through.playing() {
+        entries.push(("Stop".to_string(), ThemeColor::Panel, BookmarkEvent::Stop));
+    } else if !bookmarks.bookmarks.is_empty() {
+        entries.push(("Fly Through".to_string(), ThemeColor::Panel, BookmarkEvent::Play));
+    }
+
+    for (label, color, event) in entries.into_iter() {
+        let button = commands.spawn((
+            ButtonBundle {
+                style: Style {
+                    padding: UiRect::all(Val::Px(4.0)),
+                    margin: UiRect::all(Val::Px(2.0)),
+                    ..default()
+                },
+                ..default()
+            },
+            ThemedBackground(color),
+            BookmarkMenuAction(event)
+        ))
+            .with_children(|button| {
+                button.spawn((
+                    TextBundle {
+                        text: Text::from_section(label, TextStyle {
+                            font_size: 16.0,
+                            font: asset_server.load(DEFAULT_FONT),
+                            ..default()
+                        }),
+                        ..default()
+                    },
+                    ThemedText(ThemeColor::PanelText)
+                ));
+            })
+            .id();
+        commands.entity(menu).add_child(button);
+    }
+}
+
+/// The event a button of the bookmark menu sends when it is pressed.
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct BookmarkMenuAction(pub(crate) BookmarkEvent);
+
+pub(crate) fn select_bookmark(
+    interactions: Query<(&Interaction, &BookmarkMenuAction), Changed<Interaction>>,
+    mut bookmark_writer: EventWriter<BookmarkEvent>,
+) {
+    interactions.iter()
+        .filter(|(interaction, _)| matches!(interaction, Interaction::Pressed))
+        .for_each(|(_, BookmarkMenuAction(event))| bookmark_writer.send(event.clone()));
+}
@@ -0,0 +1,348 @@
+use std::fs;
+use std::fs::read_to_string;
+use std::path::Path;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::camera::{ActiveCamera, CameraMode, reset_pitch_yaw, sync_orbit, ZoomableDraggableCamera};
+use crate::config::keymap::{KeyAction, KeyBinding};
+use crate::search::SearchState;
+use crate::shortcuts::command_palette::CommandPaletteState;
+use crate::shortcuts::keymap::KeyActionEvent;
+use crate::ui_components::menu_components::menu_types::form_input::FormInputFocus;
+use crate::workspace::{CameraPose, WORKSPACE_DIR, WorkspaceError};
+
+pub const BOOKMARK_FILE: &str = "bookmarks.json";
+/// How long the camera takes to fly to a bookmark that is recalled.
+pub const RECALL_SECONDS: f32 = 1.5;
+/// How long the camera takes to fly between the bookmarks of a fly-through, and how long it stays
+/// at each of them.
+pub const FLY_THROUGH_SECONDS: f32 = 3.0;
+pub const DWELL_SECONDS: f32 = 1.0;
+/// The keys bookmarks are bound to when they are saved, while one is free.
+pub const BOOKMARK_KEYS: [&str; 9] = ["1", "2", "3", "4", "5", "6", "7", "8", "9"];
+
+/// A named camera pose. The distance is the distance to the focus when the bookmark was saved, so
+/// that orbiting after recalling it turns around the same point. The key is a binding in the form
+/// of the keymap, such as `1` or `Ctrl+1`, that recalls the bookmark.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CameraBookmark {
+    pub(crate) name: String,
+    pub(crate) pose: CameraPose,
+    pub(crate) distance: f32,
+    #[serde(default)]
+    pub(crate) key: Option<String>
+}
+
+/// The bookmarks saved to the bookmark file, in the order they are flown through, and the one that
+/// was last recalled.
+#[derive(Resource, Debug)]
+pub struct CameraBookmarks {
+    pub(crate) path: String,
+    pub(crate) bookmarks: Vec<CameraBookmark>,
+    pub(crate) current: Option<usize>
+}
+
+impl Default for CameraBookmarks {
+    fn default() -> Self {
+        let path = Path::new(WORKSPACE_DIR).join(BOOKMARK_FILE)
+            .to_str()
+            .map(|path| path.to_string())
+            .or(Some(format!("{}/{}", WORKSPACE_DIR, BOOKMARK_FILE)))
+            .unwrap();
+        let bookmarks = if Path::new(&path).exists() {
+            CameraBookmarks::read(&path)
+                .or_else(|e| {
+                    error!("Could not read camera bookmarks: {:?}", e);
+                    Err(e)
+                })
+                .or(Ok::<Vec<CameraBookmark>, WorkspaceError>(vec![]))
+                .unwrap()
+        } else {
+            vec![]
+        };
+        Self {
+            path,
+            bookmarks,
+            current: None
+        }
+    }
+}
+
+impl CameraBookmarks {
+    pub(crate) fn read(path: &str) -> Result<Vec<CameraBookmark>, WorkspaceError> {
+        let contents = read_to_string(path)
+            .map_err(|e| WorkspaceError::Read { path: path.to_string(), message: e.to_string() })?;
+        serde_json::from_str::<Vec<CameraBookmark>>(&contents)
+            .map_err(|e| WorkspaceError::Parse { path: path.to_string(), message: e.to_string() })
+    }
+
+    pub(crate) fn write(&self) -> Result<String, WorkspaceError> {
+        let write_error = |message: String| WorkspaceError::Write { path: self.path.clone(), message };
+        let _ = Path::new(&self.path).parent()
+            .map(|dir| fs::create_dir_all(dir))
+            .or(Some(Ok(())))
+            .unwrap()
+            .map_err(|e| write_error(e.to_string()))?;
+        serde_json::to_string_pretty(&self.bookmarks)
+            .map_err(|e| write_error(e.to_string()))
+            .and_then(|contents| fs::write(&self.path, contents).map_err(|e| write_error(e.to_string())))?;
+        Ok(self.path.clone())
+    }
+
+    /// Adds the bookmark, replacing the bookmark with the same name, and returns its index.
+    pub(crate) fn save(&mut self, bookmark: CameraBookmark) -> usize {
+        match self.bookmarks.iter().position(|saved| saved.name == bookmark.name) {
+            Some(index) => {
+                self.bookmarks[index] = bookmark;
+                index
+            }
+            None => {
+                self.bookmarks.push(bookmark);
+                self.bookmarks.len() - 1
+            }
+        }
+    }
+
+    /// The first name of the form `view-n` that is not taken.
+    pub(crate) fn next_name(&self) -> String {
+        (1..)
+            .map(|n| format!("view-{}", n))
+            .find(|name| !self.bookmarks.iter().any(|bookmark| &bookmark.name == name))
+            .unwrap()
+    }
+
+    /// The first of the bookmark keys that no bookmark is bound to.
+    pub(crate) fn next_key(&self) -> Option<String> {
+        BOOKMARK_KEYS.iter()
+            .find(|key| !self.bookmarks.iter().any(|bookmark| bookmark.key.as_deref() == Some(**key)))
+            .map(|key| key.to_string())
+    }
+
+    /// The bookmark after the one last recalled, wrapping around to the first.
+    pub(crate) fn next_index(&self) -> Option<usize> {
+        if self.bookmarks.is_empty() {
+            return None;
+        }
+        Some(self.current.map(|current| (current + 1) % self.bookmarks.len()).or(Some(0)).unwrap())
+    }
+}
+
+/// Sent from the keys and the bookmark menu to save the current view as a new bookmark, fly to a
+/// bookmark, or start or stop flying through all of them.
+#[derive(Event, Debug, Clone, PartialEq)]
+pub enum BookmarkEvent {
+    Save,
+    Recall(usize),
+    Play,
+    Stop
+}
+
+/// The bookmarks being flown to. Each stop is flown to from where the camera was when the previous
+/// stop was reached, and the camera mode is restored once the last stop is reached.
+#[derive(Resource, Default, Debug)]
+pub struct FlyThrough {
+    pub(crate) stops: Vec<usize>,
+    pub(crate) index: usize,
+    pub(crate) elapsed: f32,
+    pub(crate) seconds: f32,
+    pub(crate) dwell: f32,
+    pub(crate) start: Option<(Vec3, Quat)>,
+    pub(crate) resume_mode: CameraMode
+}
+
+impl FlyThrough {
+    pub(crate) fn playing(&self) -> bool {
+        !self.stops.is_empty()
+    }
+}
+
+/// Eases in and out, so that the camera speeds up leaving a stop and slows down reaching the next.
+pub(crate) fn ease_in_out(t: f32) -> f32 {
+    let t = t.clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// The pose the fraction t of the way between the poses, eased.
+pub(crate) fn eased_pose(from: (Vec3, Quat), to: (Vec3, Quat), t: f32) -> (Vec3, Quat) {
+    let eased = ease_in_out(t);
+    (from.0.lerp(to.0, eased), from.1.slerp(to.1, eased))
+}
+
+pub(crate) fn bookmark_pose(transform: &Transform, cam: &ZoomableDraggableCamera) -> CameraPose {
+    CameraPose {
+        translation: transform.translation.to_array(),
+        rotation: transform.rotation.to_array(),
+        pitch: cam.pitch,
+        yaw: cam.yaw
+    }
+}
+
+/// The bookmark actions of the keymap, and the keys the bookmarks are bound to. The keys are not
+/// read while text is being typed.
+pub(crate) fn read_bookmark_keys(
+    input: Res<Input<KeyCode>>,
+    mut key_events: EventReader<KeyActionEvent>,
+    bookmarks: Res<CameraBookmarks>,
+    fly_through: Res<FlyThrough>,
+    focus: Res<FormInputFocus>,
+    palette: Res<CommandPaletteState>,
+    search: Res<SearchState>,
+    mut bookmark_writer: EventWriter<BookmarkEvent>,
+) {
+    for KeyActionEvent(action) in key_events.iter() {
+        match action {
+            KeyAction::SaveBookmark => bookmark_writer.send(BookmarkEvent::Save),
+            KeyAction::NextBookmark => {
+                bookmarks.next_index()
+                    .map(|index| bookmark_writer.send(BookmarkEvent::Recall(index)));
+            }
+            KeyAction::PlayFlyThrough if fly_through.playing() => bookmark_writer.send(BookmarkEvent::Stop),
+            KeyAction::PlayFlyThrough => bookmark_writer.send(BookmarkEvent::Play),
+            _ => {}
+        }
+    }
+    if focus.focused.is_some() || palette.open || search.open {
+        return;
+    }
+    bookmarks.bookmarks.iter()
+        .enumerate()
+        .filter(|(_, bookmark)| bookmark.key.as_ref()
+            .and_then(|key| KeyBinding::parse(key).ok())
+            .map(|binding| binding.just_pressed(&input))
+            .or(Some(false))
+            .unwrap()
+        )
+        .for_each(|(index, _)| bookmark_writer.send(BookmarkEvent::Recall(index)));
+}
+
+/// Saving bookmarks the current view under the next free name and key and writes the bookmarks.
+/// Recalling flies to the bookmark, and playing flies through each bookmark in order.
+pub(crate) fn apply_bookmark_events(
+    mut bookmark_events: EventReader<BookmarkEvent>,
+    mut bookmarks: ResMut<CameraBookmarks>,
+    mut fly_through: ResMut<FlyThrough>,
+    mut cam: ResMut<ZoomableDraggableCamera>,
+    camera_query: Query<&Transform, With<ActiveCamera>>,
+) {
+    for event in bookmark_events.iter() {
+        match event {
+            BookmarkEvent::Save => {
+                let transform = match camera_query.get_single() {
+                    Ok(transform) => transform,
+                    Err(_) => continue
+                };
+                let bookmark = CameraBookmark {
+                    name: bookmarks.next_name(),
+                    pose: bookmark_pose(transform, &cam),
+                    distance: cam.current_distance,
+                    key: bookmarks.next_key()
+                };
+                info!("Saved camera bookmark {} bound to {:?}.", &bookmark.name, &bookmark.key);
+                let index = bookmarks.save(bookmark);
+                bookmarks.current = Some(index);
+                let _ = bookmarks.write()
+                    .or_else(|e| {
+                        error!("Could not save camera bookmarks: {:?}", e);
+                        Err(e)
+                    });
+            }
+            BookmarkEvent::Recall(index) if *index < bookmarks.bookmarks.len() => {
+                start_fly_through(&mut fly_through, &mut cam, vec![*index], RECALL_SECONDS, 0.0);
+            }
+            BookmarkEvent::Play if !bookmarks.bookmarks.is_empty() => {
+                let stops = (0..bookmarks.bookmarks.len()).collect();
+                start_fly_through(&mut fly_through, &mut cam, stops, FLY_THROUGH_SECONDS, DWELL_SECONDS);
+            }
+            BookmarkEvent::Stop if fly_through.playing() => {
+                let _ = camera_query.get_single()
+                    .map(|transform| finish_fly_through(&mut fly_through, &mut cam, transform));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The camera flies in fly mode, so that the orbit does not place it, and the mode it was in is
+/// restored at the end. A fly-through that is already playing keeps the mode it will restore.
+fn start_fly_through(
+    fly_through: &mut FlyThrough,
+    cam: &mut ZoomableDraggableCamera,
+    stops: Vec<usize>,
+    seconds: f32,
+    dwell: f32,
+) {
+    let resume_mode = if fly_through.playing() { fly_through.resume_mode } else { cam.mode };
+    *fly_through = FlyThrough {
+        stops,
+        index: 0,
+        elapsed: 0.0,
+        seconds,
+        dwell,
+        start: None,
+        resume_mode
+    };
+    cam.mode = CameraMode::Fly;
+    cam.target_focus = None;
+}
+
+/// The camera is left where it is, with the pitch and yaw of its rotation, and orbits around the
+/// point in front of it when the mode it is returned to is orbiting.
+fn finish_fly_through(fly_through: &mut FlyThrough, cam: &mut ZoomableDraggableCamera, transform: &Transform) {
+    cam.mode = fly_through.resume_mode;
+    cam.target_rotation = None;
+    reset_pitch_yaw(cam, transform);
+    if cam.mode == CameraMode::Orbit {
+        sync_orbit(cam, transform);
+    }
+    *fly_through = FlyThrough::default();
+}
+
+/// Each frame the targets of the camera are set to the eased pose along the way to the current
+/// stop, which the camera rotation system then moves the camera towards.
+pub(crate) fn play_fly_through(
+    time: Res<Time>,
+    mut bookmarks: ResMut<CameraBookmarks>,
+    mut fly_through: ResMut<FlyThrough>,
+    mut cam: ResMut<ZoomableDraggableCamera>,
+    camera_query: Query<&Transform, With<ActiveCamera>>,
+) {
+    if !fly_through.playing() {
+        return;
+    }
+    let transform = match camera_query.get_single() {
+        Ok(transform) => transform,
+        Err(_) => return
+    };
+    let bookmark = match bookmarks.bookmarks.get(fly_through.stops[fly_through.index]) {
+        Some(bookmark) => bookmark.clone(),
+        None => {
+            finish_fly_through(&mut fly_through, &mut cam, transform);
+            return;
+        }
+    };
+    let start = *fly_through.start.get_or_insert((transform.translation, transform.rotation));
+    fly_through.elapsed += time.delta_seconds();
+    let target = bookmark.pose.transform();
+    let (translation, rotation) = eased_pose(
+        start,
+        (target.translation, target.rotation),
+        fly_through.elapsed / fly_through.seconds.max(f32::EPSILON)
+    );
+    cam.target_translation = Some(translation);
+    cam.target_rotation = Some(rotation);
+    let stop = Some(fly_through.stops[fly_through.index]);
+    if bookmarks.current != stop {
+        bookmarks.current = stop;
+    }
+
+    if fly_through.elapsed < fly_through.seconds + fly_through.dwell {
+        return;
+    }
+    fly_through.index += 1;
+    fly_through.elapsed = 0.0;
+    fly_through.start = None;
+    if fly_through.index == fly_through.stops.len() {
+        cam.current_distance = bookmark.distance;
+        finish_fly_through(&mut fly_through, &mut cam, &target);
+    }
+}
@@ -3,16 +3,21 @@ use bevy::input::keyboard::KeyboardInput;
 use bevy::input::mouse::{MouseButtonInput, MouseMotion, MouseWheel};
 use bevy::prelude::*;
 use bevy::math::Vec4Swizzles;
-use crate::camera::{FORWARD_SENSITIVITY, MOUSE_SENSITIVITY, ZoomableDraggableCamera};
+use crate::camera::bookmarks::FlyThrough;
+use crate::camera::{ActiveCamera, CameraMode, FORWARD_SENSITIVITY, MAX_PITCH, MIN_PITCH, MOUSE_SENSITIVITY, ZoomableDraggableCamera};
 use crate::camera::raycast_select::BevyPickingState;
+use crate::search::SearchState;
+use crate::shortcuts::command_palette::CommandPaletteState;
+use crate::ui_components::menu_components::menu_types::form_input::FormInputFocus;
 
 pub(crate) fn camera_rotation_system(
     time: Res<Time>,
     mut camera_drag_data: ResMut<ZoomableDraggableCamera>,
-    mut camera_query: Query<(&Camera, &mut Transform)>,
+    mut camera_query: Query<(&Camera, &mut Transform), With<ActiveCamera>>,
     mouse_button_input: Res<Input<MouseButton>>,
     mut keyboard_events: EventReader<KeyboardInput>,
     mut mouse_button_input_events: EventReader<MouseButtonInput>,
+    fly_through: Res<FlyThrough>,
 ) {
 
     for input in mouse_button_input_events.iter() {
@@ -20,10 +25,15 @@ pub(crate) fn camera_rotation_system(
             camera_drag_data.target_rotation = None;
         }
     }
+    // When orbiting, the camera is placed by the orbit.
+    if camera_drag_data.mode == CameraMode::Orbit {
+        return;
+    }
     for (cam, mut transform) in camera_query.iter_mut() {
-        // Use slerp to gradually interpolate from the current rotation to the target rotation
+        // Use slerp to gradually interpolate from the current rotation to the target rotation,
+        // while dragging or flying through the bookmarks.
         camera_drag_data.target_rotation.map(|t| {
-            if mouse_button_input.pressed(MouseButton::Left) {
+            if mouse_button_input.pressed(MouseButton::Left) || fly_through.playing() {
                 transform.rotation = transform.rotation.slerp(
                     t,
                     time.delta_seconds() * 20.0
@@ -40,21 +50,32 @@ pub(crate) fn camera_rotation_system(
     }
 }
 
+/// The keys move the camera, or the focus when orbiting. The camera is not controlled while text
+/// is being typed into a form input, the command palette or the search.
 pub(crate) fn camera_control(
     mut commands: Commands,
     mouse_button_input: Res<Input<MouseButton>>,
     pick_state: Res<BevyPickingState>,
     mut camera_drag_data: ResMut<ZoomableDraggableCamera>,
-    mut camera_query: Query<(&Camera, &mut Transform)>,
+    mut camera_query: Query<(&Camera, &mut Transform), With<ActiveCamera>>,
     mut mouse_wheel: EventReader<MouseWheel>,
     mut ev_mousse: EventReader<MouseMotion>,
     mut keyboard_events: EventReader<KeyboardInput>,
+    focus: Res<FormInputFocus>,
+    palette: Res<CommandPaletteState>,
+    search: Res<SearchState>,
 ) {
 
     if pick_state.picked_ui_flag {
         return;
     }
 
+    // the keys typed are dropped, so that they do not move the camera once the text loses focus.
+    if focus.focused.is_some() || palette.open || search.open {
+        keyboard_events.clear();
+        return;
+    }
+
     for key in keyboard_events.iter() {
         let (_cam, mut transform) = camera_query.single_mut();
         let cam_matrix = transform.compute_matrix();
@@ -72,14 +93,20 @@ pub(crate) fn camera_control(
         }
         if movement.length() != 0.0 {
             movement = movement.normalize() * FORWARD_SENSITIVITY;
-            camera_drag_data.target_translation = Some(transform.translation + movement);
+            if camera_drag_data.mode == CameraMode::Orbit {
+                let focus = camera_drag_data.target_focus.or(Some(camera_drag_data.focus)).unwrap();
+                camera_drag_data.target_focus = Some(focus + movement);
+            } else {
+                camera_drag_data.target_translation = Some(transform.translation + movement);
+            }
         }
     }
 
     for event in ev_mousse.iter() {
         if mouse_button_input.pressed(MouseButton::Left) {
             camera_drag_data.yaw -= event.delta.x * MOUSE_SENSITIVITY;
-            camera_drag_data.pitch -= event.delta.y * MOUSE_SENSITIVITY;
+            camera_drag_data.pitch = (camera_drag_data.pitch - event.delta.y * MOUSE_SENSITIVITY)
+                .clamp(MIN_PITCH, MAX_PITCH);
 
             let yaw_quat = Quat::from_axis_angle(Vec3::Y, camera_drag_data.yaw.to_radians());
             let pitch_quat = Quat::from_axis_angle(Vec3::X, camera_drag_data.pitch.to_radians());
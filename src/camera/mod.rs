@@ -5,22 +5,54 @@ use bevy::math::Vec4Swizzles;
 use bevy::prelude::*;
 use bevy::prelude::shape::Quad;
 use bevy::render::camera::Viewport;
+use bevy::render::view::RenderLayers;
 use bevy::sprite::MaterialMesh2dBundle;
 use bevy::window::{CursorGrabMode, WindowRef};
 use bevy_mod_picking::DefaultPickingPlugins;
+use crate::camera::bookmark_menu::{select_bookmark, update_bookmark_menu};
+use crate::camera::bookmarks::{apply_bookmark_events, BookmarkEvent, CameraBookmarks, FlyThrough, play_fly_through, read_bookmark_keys};
 use crate::camera::lerping_camera::{camera_control, camera_rotation_system};
+use crate::camera::orbit_camera::{frame_entity, orbit_camera, pan_camera, toggle_camera_mode, zoom_camera};
+use crate::camera::viewports::{apply_viewport_layout, assign_render_layers, cycle_viewport_layout, load_viewport_layout, resize_viewports, route_camera_input, ViewportCamera, Viewports};
 use crate::pickable_events::PickableEvent;
+use crate::search::SearchState;
+use crate::shortcuts::command_palette::CommandPaletteState;
+use crate::shortcuts::keymap::KeyActionEvent;
+use crate::ui_components::menu_components::menu_types::form_input::FormInputFocus;
 
 pub(crate) mod raycast_select;
 pub(crate) mod lerping_camera;
+/// The orbit mode of the camera, with zooming, panning and framing an entity.
+pub(crate) mod orbit_camera;
+/// Named camera poses that are flown to, one at a time or through each in order.
+pub(crate) mod bookmarks;
+/// The menu listing the camera bookmarks, to fly to them, save the view and play a fly-through.
+pub(crate) mod bookmark_menu;
+/// Splitting the window into viewports with a camera each, for the network and the graphs.
+pub(crate) mod viewports;
 
 pub const MOUSE_SENSITIVITY: f32 = 0.2;
 pub const MIN_PITCH: f32 = -89.0;
 pub const MAX_PITCH: f32 = 89.0;
 pub const FORWARD_SENSITIVITY: f32 = 16.0;
+pub const INITIAL_CAMERA_POSITION: Vec3 = Vec3::new(50.0, 50.0, 100.0);
 
+/// Flying moves the camera itself, and orbiting moves the camera around the focus point, at the
+/// current distance from it.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CameraMode {
+    Fly,
+    #[default]
+    Orbit
+}
+
+/// The zoom is the distance the current distance is moved towards, and the target focus is the
+/// point the focus is moved towards, when the camera is orbiting.
 #[derive(Resource, Default)]
 pub struct ZoomableDraggableCamera {
+    pub(crate) mode: CameraMode,
+    pub(crate) focus: Vec3,
+    pub(crate) target_focus: Option<Vec3>,
     pub(crate) zoom: f32,
     pub(crate) cursor_position: Vec2,
     pub(crate) camera_position: Vec2,
@@ -41,44 +73,100 @@ pub struct NnFeCameraPlugin;
 impl Plugin for NnFeCameraPlugin{
     fn build(&self, app: &mut App) {
         app.insert_resource(ZoomableDraggableCamera {
-                min_distance: -5000.0,
-                max_distance: 1000.0,
-                current_distance: -1000.0,
-                zoom_sensitivity: 10.0,
+                min_distance: 5.0,
+                max_distance: 5000.0,
+                current_distance: INITIAL_CAMERA_POSITION.length(),
+                zoom: INITIAL_CAMERA_POSITION.length(),
+                zoom_sensitivity: 0.1,
                 initialized: false,
                 ..default()
             })
             .add_plugins(DefaultPickingPlugins.build())
             .add_startup_system(setup_camera)
-            .add_system(camera_rotation_system)
+            .add_startup_system(load_viewport_layout)
+            .init_resource::<Viewports>()
+            .init_resource::<CameraBookmarks>()
+            .init_resource::<FlyThrough>()
+            .init_resource::<FormInputFocus>()
+            .init_resource::<CommandPaletteState>()
+            .init_resource::<SearchState>()
+            .add_system(camera_rotation_system.after(play_fly_through))
             .add_system(camera_control)
+            .add_system(toggle_camera_mode)
+            .add_system(zoom_camera)
+            .add_system(pan_camera)
+            .add_system(frame_entity)
+            .add_system(orbit_camera
+                .after(camera_control)
+                .after(zoom_camera)
+                .after(pan_camera)
+                .after(frame_entity)
+            )
+            .add_system(read_bookmark_keys)
+            .add_system(select_bookmark)
+            .add_system(apply_bookmark_events.after(read_bookmark_keys).after(select_bookmark))
+            .add_system(play_fly_through.after(apply_bookmark_events))
+            .add_system(update_bookmark_menu.after(play_fly_through))
+            .add_system(cycle_viewport_layout)
+            .add_system(route_camera_input.after(apply_viewport_layout))
+            .add_system(apply_viewport_layout.after(cycle_viewport_layout))
+            .add_system(resize_viewports.after(apply_viewport_layout))
+            .add_system(assign_render_layers)
+            .add_event::<FrameEntity>()
+            .add_event::<BookmarkEvent>()
+            .add_event::<KeyActionEvent>()
             .add_event::<PickableEvent>();
     }
 }
 
+/// The camera starts, and is reset to, looking at the origin from above and to the side.
+pub(crate) fn initial_camera_transform() -> Transform {
+    Transform::from_translation(INITIAL_CAMERA_POSITION)
+        .looking_at(Vec3::ZERO, Vec3::Y)
+}
+
+/// Sets the pitch and yaw used by the mouse rotation from the rotation of the transform.
+pub(crate) fn reset_pitch_yaw(cam: &mut ZoomableDraggableCamera, transform: &Transform) {
+    let forward = transform.compute_matrix().z_axis.normalize();
+    cam.pitch = (-forward.y).asin().to_degrees();
+    cam.yaw = forward.x.atan2(forward.z).to_degrees();
+}
+
+/// Places the focus in front of the camera at the current distance, so that orbiting starts from
+/// where the camera is.
+pub(crate) fn sync_orbit(cam: &mut ZoomableDraggableCamera, transform: &Transform) {
+    reset_pitch_yaw(cam, transform);
+    cam.focus = transform.translation + transform.forward() * cam.current_distance;
+    cam.zoom = cam.current_distance;
+    cam.target_focus = None;
+}
+
+/// The camera moved by the camera input and the camera systems. When the window is split into
+/// viewports, it is the camera of the viewport under the cursor.
+#[derive(Component, Default, Debug)]
+pub struct ActiveCamera;
+
+/// Sent to move the camera so that the entity, with its children, fills the view.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct FrameEntity(pub Entity);
+
 pub(crate) fn setup_camera(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     mut cam: ResMut<ZoomableDraggableCamera>
 ) {
-    let initial_position = Vec3::new(50.0, 50.0, 100.0); // Replace with your desired initial position
-
-    let mut initial = Transform::from_translation(initial_position)
-        .looking_at(Vec3::ZERO, Vec3::Y);
-
-    let forward = initial.compute_matrix().z_axis.normalize();
-    let pitch = (-forward.y).asin().to_degrees();
-    let yaw = forward.x.atan2(forward.z).to_degrees();
-
-    cam.pitch = pitch;
-    cam.yaw = yaw;
+    let initial = initial_camera_transform();
+    reset_pitch_yaw(&mut cam, &initial);
 
     commands.spawn((
         Camera3dBundle {
             transform: initial,
             ..default()
         },
+        RenderLayers::all(),
+        ViewportCamera::main(),
+        ActiveCamera
     ));
 
     // commands.spawn(PointLightBundle {
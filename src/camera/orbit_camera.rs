@@ -0,0 +1,240 @@
+use std::f32::consts::FRAC_PI_4;
+use bevy::input::mouse::{MouseMotion, MouseScrollUnit, MouseWheel};
+use bevy::prelude::*;
+use bevy::render::primitives::Aabb;
+use crate::camera::{ActiveCamera, CameraMode, FORWARD_SENSITIVITY, FrameEntity, sync_orbit, ZoomableDraggableCamera};
+use crate::camera::raycast_select::BevyPickingState;
+use crate::config::keymap::KeyAction;
+use crate::graph::GraphParent;
+use crate::network::{Layer, Network};
+use crate::shortcuts::keymap::KeyActionEvent;
+
+/// How far the camera is panned for each pixel the mouse is dragged, at a distance of 100.
+pub const PAN_SENSITIVITY: f32 = 0.2;
+/// How many pixels of a touchpad scroll are one line of a mouse wheel.
+pub const PIXELS_PER_LINE: f32 = 100.0;
+/// How quickly the focus and the distance move towards their targets.
+pub const FRAME_SPEED: f32 = 4.0;
+pub const ROTATION_SPEED: f32 = 20.0;
+/// The space left around an entity that is framed, as a factor of its size.
+pub const FRAME_MARGIN: f32 = 1.2;
+pub const MIN_FRAME_RADIUS: f32 = 1.0;
+
+pub(crate) fn toggle_camera_mode(
+    mut key_events: EventReader<KeyActionEvent>,
+    mut cam: ResMut<ZoomableDraggableCamera>,
+    camera_query: Query<&Transform, With<ActiveCamera>>,
+) {
+    if !key_events.iter().any(|KeyActionEvent(action)| *action == KeyAction::ToggleCameraMode) {
+        return;
+    }
+    match cam.mode {
+        CameraMode::Fly => {
+            let _ = camera_query.get_single()
+                .map(|transform| sync_orbit(&mut cam, transform));
+            cam.mode = CameraMode::Orbit;
+        }
+        CameraMode::Orbit => {
+            cam.target_translation = None;
+            cam.target_rotation = None;
+            cam.mode = CameraMode::Fly;
+        }
+    }
+    info!("Switched to {:?} camera.", cam.mode);
+}
+
+/// The distance after zooming by the lines scrolled, where each line moves the camera the zoom
+/// sensitivity of the way towards the focus.
+pub(crate) fn zoom_distance(distance: f32, lines: f32, cam: &ZoomableDraggableCamera) -> f32 {
+    (distance * (1.0 - cam.zoom_sensitivity).powf(lines))
+        .clamp(cam.min_distance, cam.max_distance)
+}
+
+/// The mouse wheel zooms towards the focus when orbiting, and moves the camera forward when flying.
+pub(crate) fn zoom_camera(
+    mut mouse_wheel: EventReader<MouseWheel>,
+    pick_state: Res<BevyPickingState>,
+    mut cam: ResMut<ZoomableDraggableCamera>,
+    camera_query: Query<&Transform, With<ActiveCamera>>,
+) {
+    let lines = mouse_wheel.iter()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y,
+            MouseScrollUnit::Pixel => event.y / PIXELS_PER_LINE
+        })
+        .sum::<f32>();
+    if lines == 0.0 || pick_state.picked_ui_flag {
+        return;
+    }
+    match cam.mode {
+        CameraMode::Orbit => cam.zoom = zoom_distance(cam.zoom, lines, &cam),
+        CameraMode::Fly => {
+            let _ = camera_query.get_single()
+                .map(|transform| {
+                    let from = cam.target_translation.or(Some(transform.translation)).unwrap();
+                    cam.target_translation = Some(from + transform.forward() * lines * FORWARD_SENSITIVITY);
+                });
+        }
+    }
+}
+
+/// Dragging with the middle button moves the focus, or the camera when flying, across the view.
+pub(crate) fn pan_camera(
+    mouse_button_input: Res<Input<MouseButton>>,
+    mut mouse_motion: EventReader<MouseMotion>,
+    pick_state: Res<BevyPickingState>,
+    mut cam: ResMut<ZoomableDraggableCamera>,
+    camera_query: Query<&Transform, With<ActiveCamera>>,
+) {
+    let delta = mouse_motion.iter()
+        .map(|event| event.delta)
+        .sum::<Vec2>();
+    if !mouse_button_input.pressed(MouseButton::Middle) || delta == Vec2::ZERO || pick_state.picked_ui_flag {
+        return;
+    }
+    let transform = match camera_query.get_single() {
+        Ok(transform) => transform,
+        Err(_) => return
+    };
+    let scale = match cam.mode {
+        CameraMode::Orbit => cam.current_distance / 100.0,
+        CameraMode::Fly => 1.0
+    };
+    let offset = (transform.left() * delta.x + transform.up() * delta.y) * PAN_SENSITIVITY * scale;
+    match cam.mode {
+        CameraMode::Orbit => {
+            cam.focus += offset;
+            cam.target_focus = None;
+        }
+        CameraMode::Fly => {
+            let from = cam.target_translation.or(Some(transform.translation)).unwrap();
+            cam.target_translation = Some(from + offset);
+        }
+    }
+}
+
+/// The focus and distance are moved towards their targets, and the camera is placed at the
+/// distance from the focus, looking at it from the pitch and yaw.
+pub(crate) fn orbit_camera(
+    time: Res<Time>,
+    mut cam: ResMut<ZoomableDraggableCamera>,
+    mut camera_query: Query<&mut Transform, With<ActiveCamera>>,
+) {
+    if cam.mode != CameraMode::Orbit {
+        return;
+    }
+    let step = (time.delta_seconds() * FRAME_SPEED).min(1.0);
+    if let Some(target) = cam.target_focus {
+        cam.focus = cam.focus.lerp(target, step);
+        if cam.focus.distance(target) < 0.01 {
+            cam.focus = target;
+            cam.target_focus = None;
+        }
+    }
+    if cam.current_distance != cam.zoom {
+        cam.current_distance += (cam.zoom - cam.current_distance) * step;
+        if (cam.zoom - cam.current_distance).abs() < 0.01 {
+            cam.current_distance = cam.zoom;
+        }
+    }
+    let rotation = Quat::from_axis_angle(Vec3::Y, cam.yaw.to_radians())
+        * Quat::from_axis_angle(Vec3::X, cam.pitch.to_radians());
+    for mut transform in camera_query.iter_mut() {
+        transform.rotation = transform.rotation.slerp(rotation, (time.delta_seconds() * ROTATION_SPEED).min(1.0));
+        transform.translation = cam.focus + transform.rotation * Vec3::Z * cam.current_distance;
+    }
+}
+
+/// The layer, network or graph the entity is part of, which is framed when the entity is
+/// selected. Entities that are not part of one are framed themselves.
+pub(crate) fn frame_target(
+    entity: Entity,
+    parents: &Query<&Parent>,
+    framable: &Query<(), Or<(With<Layer>, With<Network>, With<GraphParent>)>>,
+) -> Entity {
+    let mut current = entity;
+    loop {
+        if framable.contains(current) {
+            return current;
+        }
+        match parents.get(current) {
+            Ok(parent) => current = parent.get(),
+            Err(_) => return entity
+        }
+    }
+}
+
+/// The sphere enclosing the spheres, given as their center and radius.
+pub(crate) fn enclosing_sphere(spheres: &Vec<(Vec3, f32)>) -> Option<(Vec3, f32)> {
+    if spheres.is_empty() {
+        return None;
+    }
+    let min = spheres.iter().fold(Vec3::splat(f32::MAX), |min, (center, radius)| min.min(*center - Vec3::splat(*radius)));
+    let max = spheres.iter().fold(Vec3::splat(f32::MIN), |max, (center, radius)| max.max(*center + Vec3::splat(*radius)));
+    Some(((min + max) / 2.0, ((max - min).length() / 2.0).max(MIN_FRAME_RADIUS)))
+}
+
+/// The distance from the center of the sphere at which it fills the field of view.
+pub(crate) fn fit_distance(radius: f32, fov: f32) -> f32 {
+    radius * FRAME_MARGIN / (fov / 2.0).sin()
+}
+
+/// The bounds of the entity and its descendants. Meshes are bounded by their bounding boxes, and
+/// other entities by their position.
+pub(crate) fn entity_spheres(
+    entity: Entity,
+    children: &Query<&Children>,
+    bounds: &Query<(&GlobalTransform, Option<&Aabb>)>,
+) -> Vec<(Vec3, f32)> {
+    let mut spheres = vec![];
+    let mut entities = vec![entity];
+    while let Some(next) = entities.pop() {
+        if let Ok((global_transform, aabb)) = bounds.get(next) {
+            spheres.push(aabb
+                .map(|aabb| (
+                    global_transform.transform_point(Vec3::from(aabb.center)),
+                    (Vec3::from(aabb.half_extents) * global_transform.compute_transform().scale.abs()).length()
+                ))
+                .or(Some((global_transform.translation(), 0.0)))
+                .unwrap());
+        }
+        if let Ok(next_children) = children.get(next) {
+            entities.extend(next_children.iter());
+        }
+    }
+    spheres
+}
+
+/// The camera is moved, keeping its rotation, to where the entity fills the view.
+pub(crate) fn frame_entity(
+    mut frame_events: EventReader<FrameEntity>,
+    mut cam: ResMut<ZoomableDraggableCamera>,
+    camera_query: Query<(&Transform, &Projection), With<ActiveCamera>>,
+    children: Query<&Children>,
+    bounds: Query<(&GlobalTransform, Option<&Aabb>)>,
+) {
+    let framed = frame_events.iter().last();
+    let (transform, projection) = match (framed, camera_query.get_single()) {
+        (Some(_), Ok(camera)) => camera,
+        _ => return
+    };
+    let FrameEntity(entity) = framed.unwrap();
+    let sphere = enclosing_sphere(&entity_spheres(*entity, &children, &bounds));
+    if sphere.is_none() {
+        return;
+    }
+    let (center, radius) = sphere.unwrap();
+    let fov = match projection {
+        Projection::Perspective(perspective) => perspective.fov,
+        _ => FRAC_PI_4
+    };
+    let distance = fit_distance(radius, fov).clamp(cam.min_distance, cam.max_distance);
+    info!("Framing {:?} at {:?} from {}.", entity, center, distance);
+    match cam.mode {
+        CameraMode::Orbit => {
+            cam.target_focus = Some(center);
+            cam.zoom = distance;
+        }
+        CameraMode::Fly => cam.target_translation = Some(center - transform.forward() * distance)
+    }
+}
@@ -0,0 +1,415 @@
+use std::f32::consts::FRAC_PI_4;
+use std::mem::swap;
+use bevy::core_pipeline::clear_color::ClearColorConfig;
+use bevy::prelude::*;
+use bevy::render::camera::Viewport;
+use bevy::render::primitives::Aabb;
+use bevy::render::view::RenderLayers;
+use bevy::utils::HashMap;
+use bevy::window::PrimaryWindow;
+use crate::camera::{ActiveCamera, FrameEntity, initial_camera_transform, INITIAL_CAMERA_POSITION, sync_orbit, ZoomableDraggableCamera};
+use crate::camera::bookmarks::FlyThrough;
+use crate::camera::orbit_camera::{enclosing_sphere, entity_spheres, fit_distance};
+use crate::config::ConfigurationProperties;
+use crate::config::keymap::KeyAction;
+use crate::config::viewport::{MAX_GRAPH_VIEWPORTS, ViewportLayout, ViewportSubject};
+use crate::graph::GridAxis;
+use crate::menu::config_menu_event::interaction_config_event_writer::{GraphMenuResultBuilder, NetworkMenuResultBuilder};
+use crate::shortcuts::keymap::KeyActionEvent;
+
+/// The render layers the network and the graphs are drawn on. Entities without render layers are
+/// on layer 0, which each viewport shows.
+pub const NETWORK_LAYER: u8 = 1;
+pub const GRAPHS_LAYER: u8 = 2;
+pub const FIRST_GRAPH_LAYER: u8 = 3;
+/// The UI is drawn over the viewports by a camera of its own, after the viewport cameras.
+pub const UI_CAMERA_ORDER: isize = 100;
+
+/// The current layout, and the viewport whose camera is active.
+#[derive(Resource, Default, Debug)]
+pub struct Viewports {
+    pub(crate) layout: ViewportLayout,
+    pub(crate) active: usize
+}
+
+/// The camera of a viewport. The camera state of the active camera is in the camera resource, and
+/// the state of each other camera is saved with it, so that each viewport keeps its own view.
+#[derive(Component)]
+pub struct ViewportCamera {
+    pub(crate) index: usize,
+    pub(crate) subject: ViewportSubject,
+    pub(crate) saved: ZoomableDraggableCamera
+}
+
+impl ViewportCamera {
+    pub(crate) fn main() -> ViewportCamera {
+        ViewportCamera {
+            index: 0,
+            subject: ViewportSubject::All,
+            saved: ZoomableDraggableCamera::default()
+        }
+    }
+}
+
+#[derive(Component, Default, Debug)]
+pub struct UiOverlayCamera;
+
+/// The layers a viewport camera shows.
+pub(crate) fn subject_layers(subject: ViewportSubject) -> RenderLayers {
+    match subject {
+        ViewportSubject::All => RenderLayers::all(),
+        ViewportSubject::Network => RenderLayers::layer(0).with(NETWORK_LAYER),
+        ViewportSubject::Graphs => RenderLayers::layer(0).with(GRAPHS_LAYER),
+        ViewportSubject::Graph(index) => RenderLayers::layer(0).with(FIRST_GRAPH_LAYER + index as u8)
+    }
+}
+
+/// The layers of the graph with the index, which is shown in its own viewport of the grid and with
+/// the other graphs. The axes, given without an index, are shown with each graph.
+pub(crate) fn graph_layers(graph: Option<usize>) -> RenderLayers {
+    match graph {
+        Some(index) if index < MAX_GRAPH_VIEWPORTS => RenderLayers::layer(GRAPHS_LAYER).with(FIRST_GRAPH_LAYER + index as u8),
+        Some(_) => RenderLayers::layer(GRAPHS_LAYER),
+        None => (0..MAX_GRAPH_VIEWPORTS)
+            .fold(RenderLayers::layer(GRAPHS_LAYER), |layers, index| layers.with(FIRST_GRAPH_LAYER + index as u8))
+    }
+}
+
+/// The part of the window, in physical pixels, that the fraction of the window covers.
+pub(crate) fn physical_viewport(rect: Rect, window_size: UVec2) -> Viewport {
+    let size = window_size.as_vec2();
+    let position = (rect.min * size).round().as_uvec2();
+    Viewport {
+        physical_position: position,
+        physical_size: ((rect.max * size).round().as_uvec2() - position).max(UVec2::ONE),
+        ..default()
+    }
+}
+
+/// Where the viewport of the camera starts in the window, in logical pixels, which is added to the
+/// positions the camera projects into its viewport to place them in the window.
+pub(crate) fn viewport_offset(camera: &Camera) -> Vec2 {
+    camera.viewport.as_ref()
+        .map(|viewport| viewport.physical_position.as_vec2() / camera.target_scaling_factor().or(Some(1.0)).unwrap())
+        .or(Some(Vec2::ZERO))
+        .unwrap()
+}
+
+/// The index of the viewport of the layout under the cursor, in logical pixels of the window.
+pub(crate) fn viewport_at(layout: ViewportLayout, cursor: Vec2, window_size: Vec2) -> Option<usize> {
+    if window_size.x <= 0.0 || window_size.y <= 0.0 {
+        return None;
+    }
+    let position = cursor / window_size;
+    layout.viewports().iter()
+        .position(|(rect, _)| rect.contains(position))
+}
+
+/// The graphs under the graph parent, in the order they were added, leaving out the axes.
+pub(crate) fn graph_entities(graph_parent: Entity, children: &Query<&Children>, axes: &Query<(), With<GridAxis>>) -> Vec<Entity> {
+    children.get(graph_parent)
+        .map(|children| children.iter()
+            .filter(|child| !axes.contains(**child))
+            .cloned()
+            .collect::<Vec<Entity>>()
+        )
+        .or(Ok::<Vec<Entity>, bevy::ecs::query::QueryEntityError>(vec![]))
+        .unwrap()
+}
+
+fn subject_entity(
+    subject: ViewportSubject,
+    network_menu: &NetworkMenuResultBuilder,
+    graph_menu: &GraphMenuResultBuilder,
+    children: &Query<&Children>,
+    axes: &Query<(), With<GridAxis>>,
+) -> Option<Entity> {
+    match subject {
+        ViewportSubject::All => None,
+        ViewportSubject::Network => network_menu.network_parent_entity,
+        ViewportSubject::Graphs => graph_menu.graph_parent_entity,
+        ViewportSubject::Graph(index) => graph_menu.graph_parent_entity
+            .and_then(|graph_parent| graph_entities(graph_parent, children, axes).get(index).cloned())
+    }
+}
+
+/// The state of a new viewport camera, orbiting the point it looks at from the distance.
+pub(crate) fn viewport_camera_state(cam: &ZoomableDraggableCamera, transform: &Transform, distance: f32) -> ZoomableDraggableCamera {
+    let mut state = ZoomableDraggableCamera {
+        min_distance: cam.min_distance,
+        max_distance: cam.max_distance,
+        zoom_sensitivity: cam.zoom_sensitivity,
+        current_distance: distance,
+        initialized: true,
+        ..default()
+    };
+    sync_orbit(&mut state, transform);
+    state
+}
+
+pub(crate) fn load_viewport_layout(config: Res<ConfigurationProperties>, mut viewports: ResMut<Viewports>) {
+    viewports.layout = config.viewport.layout;
+}
+
+pub(crate) fn cycle_viewport_layout(
+    mut key_events: EventReader<KeyActionEvent>,
+    mut viewports: ResMut<Viewports>,
+) {
+    if key_events.iter().any(|KeyActionEvent(action)| *action == KeyAction::CycleViewportLayout) {
+        viewports.layout = viewports.layout.next();
+        info!("Switched to the {} viewport layout.", viewports.layout.name());
+    }
+}
+
+/// When the layout changes the main camera becomes the camera of the first viewport, and a camera
+/// is spawned for each other viewport, looking at what the viewport shows from the direction the
+/// camera starts from. The UI is drawn by a camera of its own while there are several viewports,
+/// as the UI of a camera is confined to its viewport.
+pub(crate) fn apply_viewport_layout(
+    mut commands: Commands,
+    mut viewports: ResMut<Viewports>,
+    mut applied: Local<Option<ViewportLayout>>,
+    mut cam: ResMut<ZoomableDraggableCamera>,
+    mut cameras: Query<(Entity, &mut ViewportCamera, &mut Camera, Option<&ActiveCamera>)>,
+    overlays: Query<Entity, With<UiOverlayCamera>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    network_menu: Res<NetworkMenuResultBuilder>,
+    graph_menu: Res<GraphMenuResultBuilder>,
+    children: Query<&Children>,
+    axes: Query<(), With<GridAxis>>,
+    bounds: Query<(&GlobalTransform, Option<&Aabb>)>,
+    mut frame_writer: EventWriter<FrameEntity>,
+) {
+    let layout = viewports.layout;
+    if *applied == Some(layout) {
+        return;
+    }
+    *applied = Some(layout);
+    let specs = layout.viewports();
+    let window_size = windows.get_single()
+        .map(|window| UVec2::new(window.physical_width(), window.physical_height()))
+        .ok();
+    let split = layout != ViewportLayout::Single;
+
+    for (entity, mut viewport, _, active) in cameras.iter_mut() {
+        if active.is_some() && viewport.index != 0 {
+            swap(&mut *cam, &mut viewport.saved);
+            commands.entity(entity).remove::<ActiveCamera>();
+        }
+    }
+    for (entity, mut viewport, mut camera, active) in cameras.iter_mut() {
+        if viewport.index != 0 {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        if active.is_none() {
+            swap(&mut *cam, &mut viewport.saved);
+            commands.entity(entity).insert(ActiveCamera);
+        }
+        viewport.subject = specs[0].1;
+        camera.viewport = window_size
+            .filter(|_| split)
+            .map(|size| physical_viewport(specs[0].0, size));
+        commands.entity(entity).insert((subject_layers(viewport.subject), UiCameraConfig { show_ui: !split }));
+        subject_entity(viewport.subject, &network_menu, &graph_menu, &children, &axes)
+            .map(|subject| frame_writer.send(FrameEntity(subject)));
+    }
+    viewports.active = 0;
+
+    for (index, (rect, subject)) in specs.iter().enumerate().skip(1) {
+        let framed = subject_entity(*subject, &network_menu, &graph_menu, &children, &axes)
+            .and_then(|entity| enclosing_sphere(&entity_spheres(entity, &children, &bounds)))
+            .map(|(center, radius)| (center, fit_distance(radius, FRAC_PI_4).clamp(cam.min_distance, cam.max_distance)));
+        let (transform, distance) = framed
+            .map(|(center, distance)| (
+                Transform::from_translation(center + INITIAL_CAMERA_POSITION.normalize() * distance)
+                    .looking_at(center, Vec3::Y),
+                distance
+            ))
+            .or(Some((initial_camera_transform(), INITIAL_CAMERA_POSITION.length())))
+            .unwrap();
+        commands.spawn((
+            Camera3dBundle {
+                camera: Camera {
+                    order: index as isize,
+                    viewport: window_size.map(|size| physical_viewport(*rect, size)),
+                    ..default()
+                },
+                // The first camera clears the window, so the others draw over it.
+                camera_3d: Camera3d {
+                    clear_color: ClearColorConfig::None,
+                    ..default()
+                },
+                transform,
+                ..default()
+            },
+            subject_layers(*subject),
+            UiCameraConfig { show_ui: false },
+            ViewportCamera {
+                index,
+                subject: *subject,
+                saved: viewport_camera_state(&cam, &transform, distance)
+            }
+        ));
+    }
+
+    if !split {
+        overlays.iter().for_each(|overlay| commands.entity(overlay).despawn_recursive());
+    } else if overlays.is_empty() {
+        commands.spawn((
+            Camera2dBundle {
+                camera: Camera {
+                    order: UI_CAMERA_ORDER,
+                    ..default()
+                },
+                camera_2d: Camera2d {
+                    clear_color: ClearColorConfig::None
+                },
+                ..default()
+            },
+            UiOverlayCamera::default()
+        ));
+    }
+    info!("Applied the {} viewport layout.", layout.name());
+}
+
+/// The viewports follow the size of the window.
+pub(crate) fn resize_viewports(
+    viewports: Res<Viewports>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut cameras: Query<(&ViewportCamera, &mut Camera)>,
+) {
+    let window = match windows.get_single() {
+        Ok(window) => window,
+        Err(_) => return
+    };
+    let window_size = UVec2::new(window.physical_width(), window.physical_height());
+    let specs = viewports.layout.viewports();
+    for (viewport, mut camera) in cameras.iter_mut() {
+        let resized = specs.get(viewport.index)
+            .filter(|_| viewports.layout != ViewportLayout::Single)
+            .map(|(rect, _)| physical_viewport(*rect, window_size));
+        let current = camera.viewport.as_ref()
+            .map(|current| (current.physical_position, current.physical_size));
+        if current != resized.as_ref().map(|resized| (resized.physical_position, resized.physical_size)) {
+            camera.viewport = resized;
+        }
+    }
+}
+
+/// The camera of the viewport under the cursor becomes the active camera, which the camera input
+/// moves. The camera is not switched while a mouse button is held, so that a drag that leaves its
+/// viewport keeps moving the same camera, or while flying through the bookmarks.
+pub(crate) fn route_camera_input(
+    mut commands: Commands,
+    mut viewports: ResMut<Viewports>,
+    mut cam: ResMut<ZoomableDraggableCamera>,
+    fly_through: Res<FlyThrough>,
+    mouse_button_input: Res<Input<MouseButton>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut cameras: Query<(Entity, &mut ViewportCamera)>,
+) {
+    if viewports.layout == ViewportLayout::Single
+        || fly_through.playing()
+        || mouse_button_input.get_pressed().next().is_some() {
+        return;
+    }
+    let hovered = windows.get_single().ok()
+        .and_then(|window| window.cursor_position()
+            .and_then(|cursor| viewport_at(viewports.layout, cursor, Vec2::new(window.width(), window.height())))
+        );
+    let index = match hovered {
+        Some(index) if index != viewports.active && cameras.iter().any(|(_, viewport)| viewport.index == index) => index,
+        _ => return
+    };
+    for (entity, mut viewport) in cameras.iter_mut() {
+        if viewport.index == viewports.active {
+            swap(&mut *cam, &mut viewport.saved);
+            commands.entity(entity).remove::<ActiveCamera>();
+        }
+    }
+    for (entity, mut viewport) in cameras.iter_mut() {
+        if viewport.index == index {
+            swap(&mut *cam, &mut viewport.saved);
+            commands.entity(entity).insert(ActiveCamera);
+        }
+    }
+    viewports.active = index;
+}
+
+/// The network and each graph, with their descendants, are put on their render layers as they are
+/// added, so that each viewport camera shows only its subject. Only the children of the entities
+/// whose children changed are checked, and a child is walked only when it is not yet on the render
+/// layers it inherits, so that existing subtrees are not walked again.
+pub(crate) fn assign_render_layers(
+    mut commands: Commands,
+    network_menu: Res<NetworkMenuResultBuilder>,
+    graph_menu: Res<GraphMenuResultBuilder>,
+    changed_children: Query<(Entity, &Children), Changed<Children>>,
+    children: Query<&Children>,
+    axes: Query<(), With<GridAxis>>,
+    render_layers: Query<Option<&RenderLayers>>,
+) {
+    if changed_children.is_empty() {
+        return;
+    }
+    let mut roots = HashMap::new();
+    if let Some(network) = network_menu.network_parent_entity {
+        roots.insert(network, RenderLayers::layer(NETWORK_LAYER));
+    }
+    if let Some(graph_parent) = graph_menu.graph_parent_entity {
+        roots.insert(graph_parent, graph_layers(None));
+        children.get(graph_parent).iter()
+            .flat_map(|children| children.iter())
+            .filter(|child| axes.contains(**child))
+            .for_each(|axis| { roots.insert(*axis, graph_layers(None)); });
+        graph_entities(graph_parent, &children, &axes).into_iter()
+            .enumerate()
+            .for_each(|(index, graph)| { roots.insert(graph, graph_layers(Some(index))); });
+    }
+
+    let assigned = |entity: Entity| render_layers.get(entity).ok().flatten().copied();
+    for (root, layers) in roots.iter() {
+        if assigned(*root) != Some(*layers) {
+            assign_subtree(&mut commands, *root, *layers, &roots, &children, &render_layers);
+        }
+    }
+    for (parent, parent_children) in changed_children.iter() {
+        let Some(inherited) = roots.get(&parent).copied().or_else(|| assigned(parent)) else {
+            continue;
+        };
+        for child in parent_children.iter() {
+            let layers = roots.get(child).copied().or(Some(inherited)).unwrap();
+            if assigned(*child) != Some(layers) {
+                assign_subtree(&mut commands, *child, layers, &roots, &children, &render_layers);
+            }
+        }
+    }
+}
+
+/// Puts the entity and its descendants on the render layers, except the subtrees of the roots,
+/// which are on their own render layers.
+fn assign_subtree(
+    commands: &mut Commands,
+    entity: Entity,
+    layers: RenderLayers,
+    roots: &HashMap<Entity, RenderLayers>,
+    children: &Query<&Children>,
+    render_layers: &Query<Option<&RenderLayers>>,
+) {
+    let mut entities = vec![(entity, layers)];
+    while let Some((next, layers)) = entities.pop() {
+        let layers = roots.get(&next).copied().or(Some(layers)).unwrap();
+        set_render_layers(commands, next, layers, render_layers);
+        if let Ok(next_children) = children.get(next) {
+            entities.extend(next_children.iter().map(|child| (*child, layers)));
+        }
+    }
+}
+
+fn set_render_layers(commands: &mut Commands, entity: Entity, layers: RenderLayers, render_layers: &Query<Option<&RenderLayers>>) {
+    let current = render_layers.get(entity).ok().flatten();
+    if current != Some(&layers) {
+        commands.entity(entity).insert(layers);
+    }
+}
@@ -0,0 +1,256 @@
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use bevy::log::{Level, LogPlugin};
+use bevy::prelude::default;
+use crate::config::ConfigurationProperties;
+use crate::config::data_source::DataSource;
+use crate::headless::{EXPORT_ARG, GLTF_ARG, HEADLESS_ARG, HeadlessMode};
+
+pub const USAGE: &str = "\
+Usage: nn-fe [OPTIONS]
+
+Options:
+  --config <FILE>        The configuration file, instead of CONFIG_PROPS or resources/config.toml
+  --source <SOURCE>      Where the metrics are read from: kafka, file or replay
+  --recording <FILE>     The recorded node metric events, one json event per line
+  --brokers <HOSTS>      The Kafka brokers, separated by commas
+  --topics <TOPICS>      The Kafka topics to subscribe to, separated by commas
+  --workspace <NAME>     The workspace opened at startup
+  --headless             Write the graphs to images without a window
+  --export               Export the history of the metrics without a window
+  --gltf                 Write the series of the graph as a glTF scene without a window
+  --log-level <LEVEL>    error, warn, info, debug or trace
+  --demo                 Draw the demo network at startup
+  --no-demo              Start without the demo network
+  -h, --help             Print this help
+";
+
+/// Why the arguments could not be used. Each is printed with the usage before exiting.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CliError {
+    UnknownArgument(String),
+    MissingValue(String),
+    InvalidValue {
+        flag: String,
+        value: String,
+        expected: String
+    },
+    Conflict(String),
+    Config(String)
+}
+
+impl Display for CliError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CliError::UnknownArgument(arg) => write!(f, "unknown argument {}", arg),
+            CliError::MissingValue(flag) => write!(f, "{} needs a value", flag),
+            CliError::InvalidValue { flag, value, expected } => {
+                write!(f, "{} is not a valid value for {}, expected {}", value, flag, expected)
+            }
+            CliError::Conflict(message) => write!(f, "{}", message),
+            CliError::Config(message) => write!(f, "{}", message)
+        }
+    }
+}
+
+/// The startup options. Each one that is given overrides the configuration file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CliOptions {
+    pub(crate) config: Option<String>,
+    pub(crate) source: Option<DataSource>,
+    pub(crate) recording: Option<String>,
+    pub(crate) brokers: Vec<String>,
+    pub(crate) topics: Vec<String>,
+    pub(crate) workspace: Option<String>,
+    pub(crate) headless: bool,
+    pub(crate) export: bool,
+    pub(crate) gltf: bool,
+    pub(crate) log_level: Option<Level>,
+    pub(crate) demo: bool,
+    pub(crate) no_demo: bool,
+    pub(crate) help: bool
+}
+
+impl CliOptions {
+    /// Reads the arguments after the name of the program. Values are given either as the next
+    /// argument or after an `=`, as in `--source=replay`.
+    pub(crate) fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<CliOptions, CliError> {
+        let mut options = CliOptions::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (flag, inline_value) = arg.split_once('=')
+                .filter(|(flag, _)| flag.starts_with("--"))
+                .map(|(flag, value)| (flag.to_string(), Some(value.to_string())))
+                .or(Some((arg.clone(), None)))
+                .unwrap();
+            let mut value = || inline_value.clone()
+                .or_else(|| args.next())
+                .ok_or(CliError::MissingValue(flag.clone()));
+            match flag.as_str() {
+                "--config" => options.config = Some(value()?),
+                "--source" => {
+                    let source = value()?;
+                    options.source = Some(DataSource::parse(&source).ok_or(CliError::InvalidValue {
+                        flag: flag.clone(),
+                        value: source,
+                        expected: "kafka, file or replay".to_string()
+                    })?);
+                }
+                "--recording" => options.recording = Some(value()?),
+                "--brokers" => options.brokers = split_list(&value()?),
+                "--topics" => options.topics = split_list(&value()?),
+                "--workspace" => options.workspace = Some(value()?),
+                "--log-level" => {
+                    let level = value()?;
+                    options.log_level = Some(level.parse::<Level>().map_err(|_| CliError::InvalidValue {
+                        flag: flag.clone(),
+                        value: level,
+                        expected: "error, warn, info, debug or trace".to_string()
+                    })?);
+                }
+                HEADLESS_ARG if inline_value.is_none() => options.headless = true,
+                EXPORT_ARG if inline_value.is_none() => options.export = true,
+                GLTF_ARG if inline_value.is_none() => options.gltf = true,
+                "--demo" if inline_value.is_none() => options.demo = true,
+                "--no-demo" if inline_value.is_none() => options.no_demo = true,
+                "--help" | "-h" if inline_value.is_none() => options.help = true,
+                _ => return Err(CliError::UnknownArgument(arg))
+            }
+        }
+        Ok(options)
+    }
+
+    /// The options that cannot be used together, whatever the configuration file contains.
+    pub(crate) fn validate(&self) -> Result<(), CliError> {
+        if self.demo && self.no_demo {
+            return Err(CliError::Conflict("--demo and --no-demo cannot be used together".to_string()));
+        }
+        if self.source == Some(DataSource::Kafka) && self.recording.is_some() {
+            return Err(CliError::Conflict("--recording cannot be used with --source kafka".to_string()));
+        }
+        if let Some(source) = self.source.filter(|source| *source != DataSource::Kafka) {
+            if let Some(flag) = self.kafka_flag() {
+                return Err(CliError::Conflict(format!("{} can only be used with --source kafka, not {:?}", flag, source)));
+            }
+        }
+        if self.headless_mode().any() {
+            if self.workspace.is_some() {
+                return Err(CliError::Conflict(format!(
+                    "--workspace opens a workspace in the window and cannot be used with {}, {} or {}",
+                    HEADLESS_ARG, EXPORT_ARG, GLTF_ARG
+                )));
+            }
+            if self.demo || self.no_demo {
+                return Err(CliError::Conflict(format!(
+                    "the demo network is only drawn in the window, not with {}, {} or {}",
+                    HEADLESS_ARG, EXPORT_ARG, GLTF_ARG
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn kafka_flag(&self) -> Option<&str> {
+        if !self.brokers.is_empty() {
+            Some("--brokers")
+        } else if !self.topics.is_empty() {
+            Some("--topics")
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn headless_mode(&self) -> HeadlessMode {
+        HeadlessMode {
+            snapshots: self.headless,
+            export: self.export,
+            scene: self.gltf
+        }
+    }
+
+    /// Reads the configuration file and applies the options to it. A recording given without a
+    /// source is replayed, and the merged data source is checked, as the source may come from the
+    /// file.
+    pub(crate) fn configuration(&self) -> Result<ConfigurationProperties, CliError> {
+        let config_file = self.config.clone()
+            .or_else(|| Some(ConfigurationProperties::config_file()))
+            .unwrap();
+        let mut config = ConfigurationProperties::read_config_file(&config_file)
+            .map_err(CliError::Config)?;
+        self.apply(&mut config);
+        self.check_data_source(&config)?;
+        Ok(config)
+    }
+
+    pub(crate) fn apply(&self, config: &mut ConfigurationProperties) {
+        let data_source = &mut config.data_source;
+        if let Some(source) = self.source {
+            data_source.source = Some(source);
+        }
+        if let Some(recording) = self.recording.as_ref() {
+            data_source.recording = Some(recording.clone());
+            if data_source.source.is_none() {
+                data_source.source = Some(DataSource::Replay);
+            }
+        }
+        if self.demo || self.no_demo {
+            data_source.demo_network = self.demo;
+        }
+        if !self.brokers.is_empty() {
+            config.kafka.hosts = self.brokers.clone();
+        }
+        if !self.topics.is_empty() {
+            config.kafka.topics = self.topics.clone();
+        }
+    }
+
+    pub(crate) fn check_data_source(&self, config: &ConfigurationProperties) -> Result<(), CliError> {
+        let data_source = &config.data_source;
+        match data_source.source {
+            Some(DataSource::Kafka) => {
+                if self.recording.is_some() {
+                    return Err(CliError::Conflict("--recording cannot be used with the kafka source".to_string()));
+                }
+            }
+            Some(source) => {
+                if let Some(flag) = self.kafka_flag() {
+                    return Err(CliError::Conflict(format!("{} can only be used with the kafka source, not {:?}", flag, source)));
+                }
+                let Some(recording) = data_source.recording.as_ref() else {
+                    return Err(CliError::Config(format!("the {:?} source needs a recording, pass --recording <FILE>", source)));
+                };
+                if !Path::new(recording).is_file() {
+                    return Err(CliError::Config(format!("the recording {} does not exist", recording)));
+                }
+            }
+            None => {
+                if let Some(flag) = self.kafka_flag() {
+                    return Err(CliError::Conflict(format!("{} can only be used with --source kafka", flag)));
+                }
+                if self.headless_mode().any() {
+                    return Err(CliError::Config(format!(
+                        "{}, {} and {} need a data source, pass --source or --recording",
+                        HEADLESS_ARG, EXPORT_ARG, GLTF_ARG
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The log plugin at the level of the option, or at the default level.
+    pub(crate) fn log_plugin(&self) -> LogPlugin {
+        self.log_level
+            .map(|level| LogPlugin { level, ..default() })
+            .or(Some(LogPlugin::default()))
+            .unwrap()
+    }
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    value.split(',')
+        .map(|item| item.trim())
+        .filter(|item| !item.is_empty())
+        .map(|item| item.to_string())
+        .collect()
+}
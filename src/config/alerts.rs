@@ -0,0 +1,88 @@
+use serde::Deserialize;
+
+/// Rules that are evaluated against each value of the incoming metrics. The metric and column are
+/// matched with `*` as a wildcard.
+#[derive(Deserialize, Clone, Debug)]
+pub struct AlertConfiguration {
+    #[serde(default)]
+    pub(crate) rules: Vec<AlertRule>,
+    /// When set, alerts are published to this topic.
+    #[serde(default)]
+    pub(crate) publish_topic: Option<String>
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct AlertRule {
+    pub(crate) name: String,
+    pub(crate) metric: String,
+    #[serde(default)]
+    pub(crate) column: Option<String>,
+    pub(crate) kind: AlertKind
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type")]
+pub enum AlertKind {
+    /// The value is NaN or infinite.
+    NonFinite,
+    /// The z-score of the latest first derivative against the previous derivatives.
+    Spike {
+        z_score: f32,
+        #[serde(default = "default_min_history")]
+        min_history: usize
+    },
+    Threshold {
+        #[serde(default)]
+        above: Option<f32>,
+        #[serde(default)]
+        below: Option<f32>
+    },
+    /// The value grew by more than the factor over the window, such as exploding weight variance.
+    Growth {
+        factor: f32,
+        window: usize
+    },
+    /// The value changed by less than the tolerance over the window.
+    Plateau {
+        window: usize,
+        tolerance: f32
+    }
+}
+
+fn default_min_history() -> usize {
+    5
+}
+
+impl Default for AlertConfiguration {
+    fn default() -> Self {
+        Self {
+            rules: vec![
+                AlertRule {
+                    name: "non-finite".to_string(),
+                    metric: "*".to_string(),
+                    column: None,
+                    kind: AlertKind::NonFinite,
+                },
+                AlertRule {
+                    name: "loss-spike".to_string(),
+                    metric: "*loss*".to_string(),
+                    column: None,
+                    kind: AlertKind::Spike { z_score: 4.0, min_history: default_min_history() },
+                },
+                AlertRule {
+                    name: "loss-plateau".to_string(),
+                    metric: "*loss*".to_string(),
+                    column: None,
+                    kind: AlertKind::Plateau { window: 20, tolerance: 1e-4 },
+                },
+                AlertRule {
+                    name: "exploding-variance".to_string(),
+                    metric: "*variance*".to_string(),
+                    column: None,
+                    kind: AlertKind::Growth { factor: 10.0, window: 10 },
+                },
+            ],
+            publish_topic: None,
+        }
+    }
+}
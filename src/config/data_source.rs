@@ -0,0 +1,58 @@
+use serde::Deserialize;
+
+/// Where the metric events are read from. Kafka subscribes to the topics of the brokers, File
+/// reads all of the events of the recording at once, and Replay sends them a number each frame, as
+/// they were received.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DataSource {
+    Kafka,
+    File,
+    Replay
+}
+
+impl DataSource {
+    pub(crate) fn parse(source: &str) -> Option<DataSource> {
+        match source.to_lowercase().as_str() {
+            "kafka" => Some(DataSource::Kafka),
+            "file" => Some(DataSource::File),
+            "replay" => Some(DataSource::Replay),
+            _ => None
+        }
+    }
+}
+
+/// The source of the metric events, if any, and whether the demo network is drawn at startup. The
+/// recording is a file of node metric events, one json event per line.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct DataSourceConfiguration {
+    pub(crate) source: Option<DataSource>,
+    pub(crate) recording: Option<String>,
+    pub(crate) events_per_frame: usize,
+    pub(crate) demo_network: bool
+}
+
+impl Default for DataSourceConfiguration {
+    fn default() -> Self {
+        Self {
+            source: None,
+            recording: None,
+            events_per_frame: 100,
+            demo_network: true
+        }
+    }
+}
+
+impl DataSourceConfiguration {
+    pub(crate) fn live(&self) -> bool {
+        self.source == Some(DataSource::Kafka)
+    }
+
+    /// The events sent each frame: all of them when the recording is read as a file.
+    pub(crate) fn events_per_frame(&self, remaining: usize) -> usize {
+        match self.source {
+            Some(DataSource::File) => remaining,
+            _ => self.events_per_frame.min(remaining)
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use serde::Deserialize;
+use crate::data_subscriber::metric_event::MetricComponentType;
+
+/// The file formats the metric history is exported as. Arrow is written as an Arrow IPC file.
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Parquet,
+    Arrow,
+    Json
+}
+
+impl ExportFormat {
+    pub(crate) fn all() -> [ExportFormat; 4] {
+        [ExportFormat::Csv, ExportFormat::Parquet, ExportFormat::Arrow, ExportFormat::Json]
+    }
+
+    pub(crate) fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Parquet => "parquet",
+            ExportFormat::Arrow => "arrow",
+            ExportFormat::Json => "json"
+        }
+    }
+
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "CSV",
+            ExportFormat::Parquet => "Parquet",
+            ExportFormat::Arrow => "Arrow",
+            ExportFormat::Json => "JSON"
+        }
+    }
+}
+
+/// Where the metric history is exported, and the format used when all of the metrics are exported
+/// at once. When the component type is set, only the metrics indexed by that type are exported.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct ExportConfiguration {
+    pub(crate) output_dir: String,
+    pub(crate) format: ExportFormat,
+    pub(crate) component_type: Option<MetricComponentType>
+}
+
+impl Default for ExportConfiguration {
+    fn default() -> Self {
+        Self {
+            output_dir: "exports".to_string(),
+            format: ExportFormat::default(),
+            component_type: None
+        }
+    }
+}
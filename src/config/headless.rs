@@ -0,0 +1,55 @@
+use serde::Deserialize;
+
+/// The image formats the graphs are written as.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SnapshotFormat {
+    Png,
+    Svg
+}
+
+impl SnapshotFormat {
+    pub(crate) fn extension(&self) -> &'static str {
+        match self {
+            SnapshotFormat::Png => "png",
+            SnapshotFormat::Svg => "svg"
+        }
+    }
+}
+
+/// The plane the 3d series are projected onto: the x and y axes seen from the front, the z and y
+/// axes seen from the side, or the x and z axes seen from above.
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Projection {
+    #[default]
+    Front,
+    Side,
+    Top
+}
+
+/// How the graphs are written by the headless mode. The snapshots are written once the recording
+/// has been read and no events were received for the idle frames, or after the max frames.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct HeadlessConfiguration {
+    pub(crate) output_dir: String,
+    pub(crate) formats: Vec<SnapshotFormat>,
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) projection: Projection,
+    pub(crate) idle_frames: u32,
+    pub(crate) max_frames: u32
+}
+
+impl Default for HeadlessConfiguration {
+    fn default() -> Self {
+        Self {
+            output_dir: "reports".to_string(),
+            formats: vec![SnapshotFormat::Png, SnapshotFormat::Svg],
+            width: 800,
+            height: 600,
+            projection: Projection::default(),
+            idle_frames: 10,
+            max_frames: 10000
+        }
+    }
+}
@@ -4,7 +4,9 @@ use serde::Deserialize;
 pub struct KafkaConfiguration {
     pub(crate) hosts: Vec<String>,
     pub(crate) consumer_group_id: String,
-    pub(crate) client_id: String
+    pub(crate) client_id: String,
+    #[serde(default)]
+    pub(crate) topics: Vec<String>
 }
 
 impl Default for KafkaConfiguration {
@@ -12,7 +14,8 @@ impl Default for KafkaConfiguration {
         Self {
             hosts: vec!["localhost:9092".to_string()],
             consumer_group_id: "consumer".to_string(),
-            client_id: "nn-fe".to_string()
+            client_id: "nn-fe".to_string(),
+            topics: vec![]
         }
     }
 }
@@ -0,0 +1,221 @@
+use std::collections::HashMap;
+use bevy::prelude::{Input, KeyCode};
+use serde::Deserialize;
+
+/// The actions that can be bound to a key. The bindings are written as the key name, optionally
+/// prefixed by modifiers, such as `G`, `Space`, `F12` or `Ctrl+P`.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub enum KeyAction {
+    ToggleNetwork,
+    ToggleGraph,
+    ResetCamera,
+    FocusSelected,
+    ToggleCameraMode,
+    SaveBookmark,
+    NextBookmark,
+    PlayFlyThrough,
+    PauseStream,
+    Screenshot,
+    SaveWorkspace,
+    CycleTheme,
+    Help,
+    CommandPalette,
+    CycleViewportLayout,
+    Search,
+    ExportScene
+}
+
+impl KeyAction {
+    pub(crate) fn all() -> Vec<KeyAction> {
+        vec![
+            KeyAction::ToggleNetwork,
+            KeyAction::ToggleGraph,
+            KeyAction::ResetCamera,
+            KeyAction::FocusSelected,
+            KeyAction::ToggleCameraMode,
+            KeyAction::SaveBookmark,
+            KeyAction::NextBookmark,
+            KeyAction::PlayFlyThrough,
+            KeyAction::PauseStream,
+            KeyAction::Screenshot,
+            KeyAction::SaveWorkspace,
+            KeyAction::CycleTheme,
+            KeyAction::Help,
+            KeyAction::CommandPalette,
+            KeyAction::CycleViewportLayout,
+            KeyAction::Search,
+            KeyAction::ExportScene
+        ]
+    }
+
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            KeyAction::ToggleNetwork => "Toggle Network",
+            KeyAction::ToggleGraph => "Toggle Graph",
+            KeyAction::ResetCamera => "Reset Camera",
+            KeyAction::FocusSelected => "Focus Selected",
+            KeyAction::ToggleCameraMode => "Toggle Orbit/Fly Camera",
+            KeyAction::SaveBookmark => "Save Camera Bookmark",
+            KeyAction::NextBookmark => "Next Camera Bookmark",
+            KeyAction::PlayFlyThrough => "Play/Stop Fly-Through",
+            KeyAction::PauseStream => "Pause Stream",
+            KeyAction::Screenshot => "Screenshot",
+            KeyAction::SaveWorkspace => "Save Workspace",
+            KeyAction::CycleTheme => "Cycle Theme",
+            KeyAction::Help => "Help",
+            KeyAction::CommandPalette => "Command Palette",
+            KeyAction::CycleViewportLayout => "Cycle Viewport Layout",
+            KeyAction::Search => "Search",
+            KeyAction::ExportScene => "Export Scene as glTF"
+        }
+    }
+
+    fn default_binding(&self) -> &'static str {
+        match self {
+            KeyAction::ToggleNetwork => "N",
+            KeyAction::ToggleGraph => "G",
+            KeyAction::ResetCamera => "Home",
+            KeyAction::FocusSelected => "F",
+            KeyAction::ToggleCameraMode => "C",
+            KeyAction::SaveBookmark => "Ctrl+B",
+            KeyAction::NextBookmark => "B",
+            KeyAction::PlayFlyThrough => "Shift+B",
+            KeyAction::PauseStream => "Space",
+            KeyAction::Screenshot => "F12",
+            KeyAction::SaveWorkspace => "Ctrl+S",
+            KeyAction::CycleTheme => "Ctrl+T",
+            KeyAction::Help => "F1",
+            KeyAction::CommandPalette => "Ctrl+P",
+            KeyAction::CycleViewportLayout => "Ctrl+L",
+            KeyAction::Search => "Ctrl+F",
+            KeyAction::ExportScene => "Ctrl+E"
+        }
+    }
+}
+
+/// The keys bound to the actions. Actions that are not bound use the default binding.
+#[derive(Deserialize, Clone, Debug, Default)]
+pub struct KeymapConfiguration {
+    #[serde(default)]
+    pub(crate) bindings: HashMap<KeyAction, String>
+}
+
+impl KeymapConfiguration {
+    pub(crate) fn binding(&self, action: &KeyAction) -> &str {
+        self.bindings.get(action)
+            .map(|binding| binding.as_str())
+            .or(Some(action.default_binding()))
+            .unwrap()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyBinding {
+    pub(crate) key: KeyCode,
+    pub(crate) ctrl: bool,
+    pub(crate) shift: bool,
+    pub(crate) alt: bool
+}
+
+impl KeyBinding {
+    pub(crate) fn parse(binding: &str) -> Result<KeyBinding, String> {
+        let mut parts = binding.split('+')
+            .map(|part| part.trim())
+            .collect::<Vec<&str>>();
+        let key = parts.pop()
+            .filter(|key| !key.is_empty())
+            .ok_or(format!("Key binding {} has no key.", binding))?;
+        let mut key_binding = KeyBinding {
+            key: parse_key_code(key).ok_or(format!("Unknown key {} in key binding {}.", key, binding))?,
+            ctrl: false,
+            shift: false,
+            alt: false,
+        };
+        for modifier in parts {
+            match modifier.to_lowercase().as_str() {
+                "ctrl" | "control" => key_binding.ctrl = true,
+                "shift" => key_binding.shift = true,
+                "alt" => key_binding.alt = true,
+                _ => return Err(format!("Unknown modifier {} in key binding {}.", modifier, binding))
+            }
+        }
+        Ok(key_binding)
+    }
+
+    /// The key was just pressed with exactly the modifiers of the binding held.
+    pub(crate) fn just_pressed(&self, input: &Input<KeyCode>) -> bool {
+        input.just_pressed(self.key)
+            && self.ctrl == input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+            && self.shift == input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight])
+            && self.alt == input.any_pressed([KeyCode::AltLeft, KeyCode::AltRight])
+    }
+
+    pub(crate) fn label(&self) -> String {
+        let mut label = String::new();
+        if self.ctrl {
+            label.push_str("Ctrl+");
+        }
+        if self.shift {
+            label.push_str("Shift+");
+        }
+        if self.alt {
+            label.push_str("Alt+");
+        }
+        label.push_str(format!("{:?}", self.key).trim_start_matches("Key"));
+        label
+    }
+}
+
+fn parse_key_code(key: &str) -> Option<KeyCode> {
+    let letters = [
+        KeyCode::A, KeyCode::B, KeyCode::C, KeyCode::D, KeyCode::E, KeyCode::F, KeyCode::G,
+        KeyCode::H, KeyCode::I, KeyCode::J, KeyCode::K, KeyCode::L, KeyCode::M, KeyCode::N,
+        KeyCode::O, KeyCode::P, KeyCode::Q, KeyCode::R, KeyCode::S, KeyCode::T, KeyCode::U,
+        KeyCode::V, KeyCode::W, KeyCode::X, KeyCode::Y, KeyCode::Z
+    ];
+    let digits = [
+        KeyCode::Key0, KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4,
+        KeyCode::Key5, KeyCode::Key6, KeyCode::Key7, KeyCode::Key8, KeyCode::Key9
+    ];
+    let function_keys = [
+        KeyCode::F1, KeyCode::F2, KeyCode::F3, KeyCode::F4, KeyCode::F5, KeyCode::F6,
+        KeyCode::F7, KeyCode::F8, KeyCode::F9, KeyCode::F10, KeyCode::F11, KeyCode::F12
+    ];
+    let key = key.to_lowercase();
+    let mut chars = key.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        if c.is_ascii_lowercase() {
+            return Some(letters[(c as u8 - b'a') as usize]);
+        } else if c.is_ascii_digit() {
+            return Some(digits[(c as u8 - b'0') as usize]);
+        }
+    }
+    if let Some(number) = key.strip_prefix('f').and_then(|number| number.parse::<usize>().ok()) {
+        return function_keys.get(number.wrapping_sub(1)).cloned();
+    }
+    let named = HashMap::from([
+        ("space", KeyCode::Space),
+        ("escape", KeyCode::Escape),
+        ("esc", KeyCode::Escape),
+        ("tab", KeyCode::Tab),
+        ("enter", KeyCode::Return),
+        ("return", KeyCode::Return),
+        ("backspace", KeyCode::Back),
+        ("delete", KeyCode::Delete),
+        ("home", KeyCode::Home),
+        ("end", KeyCode::End),
+        ("pageup", KeyCode::PageUp),
+        ("pagedown", KeyCode::PageDown),
+        ("up", KeyCode::Up),
+        ("down", KeyCode::Down),
+        ("left", KeyCode::Left),
+        ("right", KeyCode::Right),
+        ("comma", KeyCode::Comma),
+        ("period", KeyCode::Period),
+        ("slash", KeyCode::Slash),
+        ("minus", KeyCode::Minus),
+        ("equals", KeyCode::Equals),
+        ("grave", KeyCode::Grave),
+    ]);
+    named.get(key.as_str()).cloned()
+}
@@ -8,6 +8,8 @@ pub struct MetricsConfiguration {
     pub(crate) metric_type: HashMap<MetricTypeMatcher, String>,
     pub(crate) dim_type: HashMap<GraphDimType, Vec<String>>,
     pub(crate) dim_axis: HashMap<GridAxis, Vec<String>>,
+    #[serde(default)]
+    pub(crate) derived: HashMap<String, String>,
 }
 
 impl MetricsConfiguration {
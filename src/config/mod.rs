@@ -6,6 +6,14 @@ use std::path::Path;
 use bevy::log::error;
 use bevy::prelude::Resource;
 use serde::Deserialize;
+use alerts::AlertConfiguration;
+use keymap::KeymapConfiguration;
+use theme::ThemeConfiguration;
+use palette::PaletteConfiguration;
+use viewport::ViewportConfiguration;
+use headless::HeadlessConfiguration;
+use export::ExportConfiguration;
+use data_source::DataSourceConfiguration;
 use kafka::KafkaConfiguration;
 use layer::LayerTypeConfiguration;
 use metrics::MetricsConfiguration;
@@ -17,13 +25,37 @@ use crate::metrics::network_metrics::{MetricType, MetricTypeMatcher};
 pub(crate) mod kafka;
 pub(crate) mod metrics;
 pub(crate) mod layer;
+pub(crate) mod alerts;
+pub(crate) mod keymap;
+pub(crate) mod theme;
+pub(crate) mod palette;
+pub(crate) mod viewport;
+pub(crate) mod headless;
+pub(crate) mod export;
+pub(crate) mod data_source;
 
 
 #[derive(Deserialize, Resource)]
 pub struct ConfigurationProperties {
     pub(crate) kafka: KafkaConfiguration,
     pub(crate) metrics: MetricsConfiguration,
-    pub(crate) network: LayerTypeConfiguration
+    pub(crate) network: LayerTypeConfiguration,
+    #[serde(default)]
+    pub(crate) alerts: AlertConfiguration,
+    #[serde(default)]
+    pub(crate) keymap: KeymapConfiguration,
+    #[serde(default)]
+    pub(crate) theme: ThemeConfiguration,
+    #[serde(default)]
+    pub(crate) palette: PaletteConfiguration,
+    #[serde(default)]
+    pub(crate) viewport: ViewportConfiguration,
+    #[serde(default)]
+    pub(crate) headless: HeadlessConfiguration,
+    #[serde(default)]
+    pub(crate) export: ExportConfiguration,
+    #[serde(default)]
+    pub(crate) data_source: DataSourceConfiguration
 }
 
 impl Default for ConfigurationProperties {
@@ -34,10 +66,26 @@ impl Default for ConfigurationProperties {
 
 impl ConfigurationProperties {
 
-    pub(crate) fn read_config() -> ConfigurationProperties {
-        let config_file = env::var("CONFIG_PROPS")
+    /// The configuration file, CONFIG_PROPS, or the configuration in the resources.
+    pub(crate) fn config_file() -> String {
+        env::var("CONFIG_PROPS")
             .or(Ok::<String, VarError>("resources/config.toml".to_string()))
-            .unwrap();
+            .unwrap()
+    }
+
+    /// Reads the configuration file, with the reason when it cannot be read or parsed.
+    pub(crate) fn read_config_file(config_file: &str) -> Result<ConfigurationProperties, String> {
+        let toml = read_to_string(Path::new(config_file))
+            .map_err(|e| format!("could not read the configuration {}: {}", config_file, e))?;
+        let config = toml::from_str::<ConfigurationProperties>(toml.as_str())
+            .map_err(|e| format!("could not parse the configuration {}: {}", config_file, e))?;
+        config.palette.check()
+            .map_err(|e| format!("invalid configuration {}: {}", config_file, e))?;
+        Ok(config)
+    }
+
+    pub(crate) fn read_config() -> ConfigurationProperties {
+        let config_file = Self::config_file();
         let config = read_to_string(Path::new(&config_file))
             .map(|toml| toml::from_str::<ConfigurationProperties>(toml.as_str()).ok())
             .or_else(|e| {
@@ -0,0 +1,311 @@
+use std::collections::HashMap;
+use bevy::prelude::Color;
+use serde::Deserialize;
+use crate::util::gen_color_from_list;
+
+/// The smallest CIELAB distance between two colors of a palette for them to be told apart.
+pub const MIN_COLOR_DISTANCE: f32 = 5.0;
+
+/// Okabe and Ito's palette, which can be told apart with each form of color blindness. The black of
+/// the palette is replaced by a gray, so that it shows on dark backgrounds.
+pub const OKABE_ITO: [Color; 8] = [
+    Color::rgb(0.90, 0.62, 0.0),
+    Color::rgb(0.34, 0.71, 0.91),
+    Color::rgb(0.0, 0.62, 0.45),
+    Color::rgb(0.94, 0.89, 0.26),
+    Color::rgb(0.0, 0.45, 0.70),
+    Color::rgb(0.84, 0.37, 0.0),
+    Color::rgb(0.80, 0.47, 0.65),
+    Color::rgb(0.6, 0.6, 0.6),
+];
+
+/// The Tableau 10 palette. The colors are easier to tell apart than evenly spaced hues with normal
+/// color vision, but not with red-green color blindness.
+pub const TABLEAU_10: [Color; 10] = [
+    Color::rgb(0.306, 0.475, 0.655),
+    Color::rgb(0.949, 0.557, 0.169),
+    Color::rgb(0.882, 0.341, 0.349),
+    Color::rgb(0.463, 0.718, 0.698),
+    Color::rgb(0.349, 0.631, 0.31),
+    Color::rgb(0.929, 0.788, 0.282),
+    Color::rgb(0.69, 0.478, 0.631),
+    Color::rgb(1.0, 0.616, 0.655),
+    Color::rgb(0.612, 0.459, 0.373),
+    Color::rgb(0.729, 0.69, 0.675),
+];
+
+pub const VIRIDIS: [Color; 5] = [
+    Color::rgb(0.267, 0.004, 0.329),
+    Color::rgb(0.231, 0.322, 0.545),
+    Color::rgb(0.129, 0.569, 0.549),
+    Color::rgb(0.369, 0.788, 0.384),
+    Color::rgb(0.992, 0.906, 0.145),
+];
+
+pub const CIVIDIS: [Color; 5] = [
+    Color::rgb(0.0, 0.125, 0.302),
+    Color::rgb(0.255, 0.302, 0.42),
+    Color::rgb(0.486, 0.482, 0.471),
+    Color::rgb(0.737, 0.686, 0.435),
+    Color::rgb(1.0, 0.918, 0.275),
+];
+
+/// ColorBrewer's red to blue diverging map, light in the middle.
+pub const RD_BU: [Color; 9] = [
+    Color::rgb(0.698, 0.094, 0.169),
+    Color::rgb(0.839, 0.376, 0.302),
+    Color::rgb(0.957, 0.647, 0.51),
+    Color::rgb(0.992, 0.859, 0.78),
+    Color::rgb(0.969, 0.969, 0.969),
+    Color::rgb(0.82, 0.898, 0.941),
+    Color::rgb(0.573, 0.773, 0.871),
+    Color::rgb(0.263, 0.576, 0.765),
+    Color::rgb(0.129, 0.4, 0.675),
+];
+
+/// The number of colors a colormap is sampled at when it is cycled through.
+pub const COLORMAP_SWATCHES: usize = 8;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PaletteKind {
+    /// Distinct colors for unordered values, such as the columns of a metric.
+    Categorical,
+    /// Colors from dark to light for ordered values.
+    Sequential,
+    /// Colors that are dark at both ends and light in the middle, for values around a midpoint.
+    Diverging
+}
+
+/// The palettes series and networks can be colored with. Hues is the evenly spaced hues the
+/// columns of a metric were colored with before palettes could be chosen.
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Palette {
+    #[default]
+    OkabeIto,
+    Tableau10,
+    Hues,
+    Viridis,
+    Cividis,
+    RdBu
+}
+
+impl Palette {
+    pub(crate) fn all() -> Vec<Palette> {
+        vec![
+            Palette::OkabeIto,
+            Palette::Tableau10,
+            Palette::Hues,
+            Palette::Viridis,
+            Palette::Cividis,
+            Palette::RdBu
+        ]
+    }
+
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Palette::OkabeIto => "Okabe-Ito",
+            Palette::Tableau10 => "Tableau 10",
+            Palette::Hues => "Hues",
+            Palette::Viridis => "Viridis",
+            Palette::Cividis => "Cividis",
+            Palette::RdBu => "RdBu"
+        }
+    }
+
+    pub(crate) fn kind(&self) -> PaletteKind {
+        match self {
+            Palette::OkabeIto | Palette::Tableau10 | Palette::Hues => PaletteKind::Categorical,
+            Palette::Viridis | Palette::Cividis => PaletteKind::Sequential,
+            Palette::RdBu => PaletteKind::Diverging
+        }
+    }
+
+    /// The colors of a categorical palette, or the stops of a colormap, which are interpolated
+    /// between. The hues are generated for the count of colors instead.
+    fn stops(&self) -> &'static [Color] {
+        match self {
+            Palette::Hues => &[],
+            Palette::OkabeIto => &OKABE_ITO,
+            Palette::Tableau10 => &TABLEAU_10,
+            Palette::Viridis => &VIRIDIS,
+            Palette::Cividis => &CIVIDIS,
+            Palette::RdBu => &RD_BU
+        }
+    }
+
+    /// The color of the colormap at the position from 0 to 1, interpolated between the stops. The
+    /// colors of a categorical palette are not interpolated, the nearest is taken, and the hues
+    /// are generated at the position.
+    pub(crate) fn sample(&self, position: f32) -> Color {
+        let position = position.clamp(0.0, 1.0);
+        if *self == Palette::Hues {
+            return Color::hsl(360.0 * position, 0.5, 0.5);
+        }
+        let stops = self.stops();
+        if self.kind() == PaletteKind::Categorical {
+            return stops[(position * (stops.len() - 1) as f32).round() as usize];
+        }
+        let position = position * (stops.len() - 1) as f32;
+        let index = (position.floor() as usize).min(stops.len() - 2);
+        let t = position - index as f32;
+        let [r1, g1, b1, _] = stops[index].as_rgba_f32();
+        let [r2, g2, b2, _] = stops[index + 1].as_rgba_f32();
+        Color::rgb(r1 + (r2 - r1) * t, g1 + (g2 - g1) * t, b1 + (b2 - b1) * t)
+    }
+
+    /// The color of the index of the count of values. A categorical palette is cycled through,
+    /// and a colormap is sampled evenly from end to end.
+    pub(crate) fn color_at(&self, index: usize, count: usize) -> Color {
+        if *self == Palette::Hues {
+            return gen_color_from_list(count.max(1) as f32)[index % count.max(1)];
+        }
+        match self.kind() {
+            PaletteKind::Categorical => self.stops()[index % self.stops().len()],
+            _ if count <= 1 => self.sample(0.5),
+            _ => self.sample(index as f32 / (count - 1) as f32)
+        }
+    }
+
+    pub(crate) fn colors(&self, count: usize) -> Vec<Color> {
+        if *self == Palette::Hues {
+            return gen_color_from_list(count as f32);
+        }
+        (0..count).map(|index| self.color_at(index, count)).collect()
+    }
+
+    /// The colors a series is cycled through when its color is changed.
+    pub(crate) fn swatches(&self) -> Vec<Color> {
+        match self.kind() {
+            PaletteKind::Categorical if *self != Palette::Hues => self.stops().to_vec(),
+            _ => self.colors(COLORMAP_SWATCHES)
+        }
+    }
+
+    /// Whether the count of colors can all be told apart from each other, with each form of color
+    /// vision. A categorical palette repeats once the count is more than its colors.
+    pub(crate) fn distinguishable(&self, count: usize) -> bool {
+        if self.kind() == PaletteKind::Categorical && !self.stops().is_empty() && count > self.stops().len() {
+            return false;
+        }
+        let colors = self.colors(count);
+        ColorVision::all().into_iter()
+            .all(|vision| min_distance(&colors, vision) >= MIN_COLOR_DISTANCE)
+    }
+}
+
+/// Normal color vision, and the forms of dichromacy, simulated with the matrices of Machado,
+/// Oliveira and Fernandes (2009).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorVision {
+    Normal,
+    Protanopia,
+    Deuteranopia,
+    Tritanopia
+}
+
+impl ColorVision {
+    pub(crate) fn all() -> Vec<ColorVision> {
+        vec![ColorVision::Normal, ColorVision::Protanopia, ColorVision::Deuteranopia, ColorVision::Tritanopia]
+    }
+
+    fn matrix(&self) -> [[f32; 3]; 3] {
+        match self {
+            ColorVision::Normal => [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+            ColorVision::Protanopia => [[0.152286, 1.052583, -0.204868], [0.114503, 0.786281, 0.099216], [-0.003882, -0.048116, 1.051998]],
+            ColorVision::Deuteranopia => [[0.367322, 0.860646, -0.227968], [0.280085, 0.672501, 0.047413], [-0.011820, 0.042940, 0.968881]],
+            ColorVision::Tritanopia => [[1.255528, -0.076749, -0.178779], [-0.078411, 0.930809, 0.147602], [0.004733, 0.691367, 0.303900]]
+        }
+    }
+
+    /// The linear rgb of the color as it is seen.
+    fn simulate(&self, color: Color) -> [f32; 3] {
+        let [r, g, b, _] = color.as_linear_rgba_f32();
+        let matrix = self.matrix();
+        let mut simulated = [0.0; 3];
+        for (i, row) in matrix.iter().enumerate() {
+            simulated[i] = (row[0] * r + row[1] * g + row[2] * b).clamp(0.0, 1.0);
+        }
+        simulated
+    }
+}
+
+/// The CIELAB lightness, a and b of the linear rgb, with the D65 white point.
+fn lab([r, g, b]: [f32; 3]) -> [f32; 3] {
+    let x = (0.4124 * r + 0.3576 * g + 0.1805 * b) / 0.95047;
+    let y = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+    let z = (0.0193 * r + 0.1192 * g + 0.9505 * b) / 1.08883;
+    let f = |t: f32| if t > 0.008856 { t.cbrt() } else { 7.787 * t + 16.0 / 116.0 };
+    [116.0 * f(y) - 16.0, 500.0 * (f(x) - f(y)), 200.0 * (f(y) - f(z))]
+}
+
+pub(crate) fn lightness(color: Color) -> f32 {
+    lab(ColorVision::Normal.simulate(color))[0]
+}
+
+/// The CIE76 distance between the colors as they are seen with the color vision.
+pub(crate) fn color_distance(first: Color, second: Color, vision: ColorVision) -> f32 {
+    let first = lab(vision.simulate(first));
+    let second = lab(vision.simulate(second));
+    first.iter().zip(second.iter())
+        .map(|(first, second)| (first - second).powi(2))
+        .sum::<f32>()
+        .sqrt()
+}
+
+/// The distance between the two colors that are closest to each other.
+pub(crate) fn min_distance(colors: &Vec<Color>, vision: ColorVision) -> f32 {
+    let mut min = f32::MAX;
+    for (i, first) in colors.iter().enumerate() {
+        for second in colors.iter().skip(i + 1) {
+            min = min.min(color_distance(*first, *second, vision));
+        }
+    }
+    min
+}
+
+/// The WCAG contrast ratio of the colors, from 1 for the same luminance to 21 for black on white.
+pub(crate) fn contrast_ratio(first: Color, second: Color) -> f32 {
+    let luminance = |color: Color| {
+        let [r, g, b, _] = color.as_linear_rgba_f32();
+        0.2126 * r + 0.7152 * g + 0.0722 * b
+    };
+    let (first, second) = (luminance(first), luminance(second));
+    (first.max(second) + 0.05) / (first.min(second) + 0.05)
+}
+
+/// The palette the series of each graph are colored with, by the name of the metric, and the
+/// palette the nodes of the network are colored with by their latest metric value. The nodes are
+/// colored by the theme when no network palette is given.
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct PaletteConfiguration {
+    pub(crate) graph: Palette,
+    pub(crate) graphs: HashMap<String, Palette>,
+    pub(crate) network: Option<Palette>
+}
+
+impl PaletteConfiguration {
+    /// Only a colormap can color the nodes by their value, as the colors of a categorical palette
+    /// are not ordered.
+    pub(crate) fn check(&self) -> Result<(), String> {
+        match self.network {
+            Some(palette) if palette.kind() == PaletteKind::Categorical => Err(format!(
+                "the network palette {} is categorical, use Viridis, Cividis or RdBu",
+                palette.name()
+            )),
+            _ => Ok(())
+        }
+    }
+
+    /// The network palette, unless it is categorical.
+    pub(crate) fn network_palette(&self) -> Option<Palette> {
+        self.network.filter(|palette| palette.kind() != PaletteKind::Categorical)
+    }
+
+    pub(crate) fn graph_palette(&self, metric_name: &str) -> Palette {
+        self.graphs.get(metric_name)
+            .cloned()
+            .or(Some(self.graph))
+            .unwrap()
+    }
+}
@@ -0,0 +1,243 @@
+use std::collections::HashMap;
+use std::fs::read_to_string;
+use std::path::Path;
+use bevy::prelude::{Color, Resource};
+use serde::Deserialize;
+
+pub const DEFAULT_FONT: &str = "fonts/FiraSans-Bold.ttf";
+pub const DEFAULT_MONO_FONT: &str = "fonts/FiraMono-Medium.ttf";
+pub const THEME_DIR: &str = "resources/themes";
+
+/// The roles colors are used for in the scene and the UI. A theme has a color for each role.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub enum ThemeColor {
+    Background,
+    Grid,
+    Node,
+    Connection,
+    Layer,
+    MenuBackground,
+    MenuButton,
+    MenuOption,
+    MenuText,
+    MenuHovered,
+    MenuPressed,
+    MenuSelected,
+    MenuDeselected,
+    MenuCheckmark,
+    Input,
+    InputFocused,
+    InputInvalid,
+    SliderTrack,
+    SliderHandle,
+    Panel,
+    PanelText,
+    PanelMutedText,
+    PanelEntry,
+    PanelHighlight,
+    Alert,
+    Selection,
+    SearchMatch,
+    Link,
+    Code,
+    CodeBackground
+}
+
+impl ThemeColor {
+    pub(crate) fn all() -> Vec<ThemeColor> {
+        vec![
+            ThemeColor::Background,
+            ThemeColor::Grid,
+            ThemeColor::Node,
+            ThemeColor::Connection,
+            ThemeColor::Layer,
+            ThemeColor::MenuBackground,
+            ThemeColor::MenuButton,
+            ThemeColor::MenuOption,
+            ThemeColor::MenuText,
+            ThemeColor::MenuHovered,
+            ThemeColor::MenuPressed,
+            ThemeColor::MenuSelected,
+            ThemeColor::MenuDeselected,
+            ThemeColor::MenuCheckmark,
+            ThemeColor::Input,
+            ThemeColor::InputFocused,
+            ThemeColor::InputInvalid,
+            ThemeColor::SliderTrack,
+            ThemeColor::SliderHandle,
+            ThemeColor::Panel,
+            ThemeColor::PanelText,
+            ThemeColor::PanelMutedText,
+            ThemeColor::PanelEntry,
+            ThemeColor::PanelHighlight,
+            ThemeColor::Alert,
+            ThemeColor::Selection,
+            ThemeColor::SearchMatch,
+            ThemeColor::Link,
+            ThemeColor::Code,
+            ThemeColor::CodeBackground
+        ]
+    }
+}
+
+/// The themes that are built in. Light is the original look of the application.
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ThemePreset {
+    #[default]
+    Light,
+    Dark,
+    HighContrast
+}
+
+impl ThemePreset {
+    pub(crate) fn all() -> Vec<ThemePreset> {
+        vec![ThemePreset::Light, ThemePreset::Dark, ThemePreset::HighContrast]
+    }
+
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            ThemePreset::Light => "Light",
+            ThemePreset::Dark => "Dark",
+            ThemePreset::HighContrast => "High Contrast"
+        }
+    }
+
+    /// The light, dark and high contrast color of each role.
+    fn color(&self, role: ThemeColor) -> Color {
+        let (light, dark, high_contrast) = match role {
+            ThemeColor::Background => (Color::rgb(0.4, 0.4, 0.4), Color::rgb(0.08, 0.08, 0.1), Color::BLACK),
+            ThemeColor::Grid => (Color::GREEN, Color::rgb(0.3, 0.7, 0.4), Color::WHITE),
+            ThemeColor::Node => (Color::GREEN, Color::rgb(0.3, 0.8, 0.5), Color::YELLOW),
+            ThemeColor::Connection => (Color::BLUE, Color::rgb(0.35, 0.5, 0.9), Color::CYAN),
+            ThemeColor::Layer => (Color::rgb(0.4, 0.4, 0.4), Color::rgb(0.15, 0.15, 0.2), Color::rgb(0.2, 0.2, 0.2)),
+            ThemeColor::MenuBackground => (Color::BLACK, Color::rgb(0.12, 0.12, 0.15), Color::BLACK),
+            ThemeColor::MenuButton => (Color::BLUE, Color::rgb(0.2, 0.3, 0.55), Color::BLACK),
+            ThemeColor::MenuOption => (Color::BLACK, Color::rgb(0.18, 0.18, 0.22), Color::BLACK),
+            ThemeColor::MenuText => (Color::BLACK, Color::rgb(0.9, 0.9, 0.9), Color::WHITE),
+            ThemeColor::MenuHovered => (Color::YELLOW, Color::rgb(0.35, 0.35, 0.5), Color::rgb(0.0, 0.0, 0.6)),
+            ThemeColor::MenuPressed => (Color::BLUE, Color::rgb(0.3, 0.45, 0.8), Color::rgb(0.0, 0.4, 0.0)),
+            ThemeColor::MenuSelected => (Color::BLUE, Color::rgb(0.3, 0.45, 0.8), Color::rgb(0.0, 0.0, 0.6)),
+            ThemeColor::MenuDeselected => (Color::WHITE, Color::rgb(0.25, 0.25, 0.3), Color::BLACK),
+            ThemeColor::MenuCheckmark => (Color::GREEN, Color::rgb(0.3, 0.8, 0.5), Color::YELLOW),
+            ThemeColor::Input => (Color::GRAY, Color::rgb(0.25, 0.25, 0.3), Color::rgb(0.15, 0.15, 0.15)),
+            ThemeColor::InputFocused => (Color::WHITE, Color::rgb(0.35, 0.35, 0.42), Color::BLACK),
+            ThemeColor::InputInvalid => (Color::RED, Color::rgb(1.0, 0.45, 0.45), Color::rgb(1.0, 0.3, 0.3)),
+            ThemeColor::SliderTrack => (Color::GREEN, Color::rgb(0.3, 0.6, 0.4), Color::WHITE),
+            ThemeColor::SliderHandle => (Color::ORANGE, Color::rgb(0.95, 0.6, 0.2), Color::YELLOW),
+            ThemeColor::Panel => (Color::WHITE, Color::rgb(0.14, 0.14, 0.17), Color::BLACK),
+            ThemeColor::PanelText => (Color::BLACK, Color::rgb(0.9, 0.9, 0.9), Color::WHITE),
+            ThemeColor::PanelMutedText => (Color::GRAY, Color::rgb(0.6, 0.6, 0.65), Color::rgb(0.8, 0.8, 0.8)),
+            ThemeColor::PanelEntry => (Color::rgb(0.85, 0.85, 0.85), Color::rgb(0.22, 0.22, 0.27), Color::rgb(0.1, 0.1, 0.1)),
+            ThemeColor::PanelHighlight => (Color::rgb(0.7, 0.7, 0.9), Color::rgb(0.3, 0.3, 0.5), Color::rgb(0.0, 0.0, 0.6)),
+            ThemeColor::Alert => (Color::RED, Color::rgb(1.0, 0.4, 0.4), Color::rgb(1.0, 0.2, 0.2)),
+            ThemeColor::Selection => (Color::ORANGE, Color::rgb(0.95, 0.7, 0.25), Color::CYAN),
+            ThemeColor::SearchMatch => (Color::YELLOW, Color::rgb(1.0, 0.9, 0.35), Color::WHITE),
+            ThemeColor::Link => (Color::rgb(0.1, 0.3, 0.8), Color::rgb(0.45, 0.65, 1.0), Color::CYAN),
+            ThemeColor::Code => (Color::rgb(0.6, 0.1, 0.3), Color::rgb(0.95, 0.55, 0.7), Color::YELLOW),
+            ThemeColor::CodeBackground => (Color::rgb(0.93, 0.93, 0.93), Color::rgb(0.2, 0.2, 0.24), Color::rgb(0.15, 0.15, 0.15)),
+        };
+        match self {
+            ThemePreset::Light => light,
+            ThemePreset::Dark => dark,
+            ThemePreset::HighContrast => high_contrast
+        }
+    }
+}
+
+/// The theme selected at startup by name, and the directory theme files are loaded from.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct ThemeConfiguration {
+    pub(crate) name: String,
+    pub(crate) dir: String
+}
+
+impl Default for ThemeConfiguration {
+    fn default() -> Self {
+        Self {
+            name: ThemePreset::Light.name().to_string(),
+            dir: THEME_DIR.to_string()
+        }
+    }
+}
+
+/// A theme file. The colors are hex strings, and the roles that are not given are taken from the
+/// base preset.
+#[derive(Deserialize, Clone, Debug)]
+pub struct ThemeDefinition {
+    pub(crate) name: String,
+    #[serde(default)]
+    pub(crate) base: ThemePreset,
+    #[serde(default)]
+    pub(crate) font: Option<String>,
+    #[serde(default)]
+    pub(crate) mono_font: Option<String>,
+    #[serde(default)]
+    pub(crate) colors: HashMap<ThemeColor, String>
+}
+
+/// The colors and fonts the builders and drawing systems style the entities with.
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct Theme {
+    pub(crate) name: String,
+    pub(crate) font: String,
+    pub(crate) mono_font: String,
+    pub(crate) colors: HashMap<ThemeColor, Color>
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::preset(ThemePreset::Light)
+    }
+}
+
+impl Theme {
+    pub(crate) fn preset(preset: ThemePreset) -> Theme {
+        Theme {
+            name: preset.name().to_string(),
+            font: DEFAULT_FONT.to_string(),
+            mono_font: DEFAULT_MONO_FONT.to_string(),
+            colors: ThemeColor::all().into_iter()
+                .map(|role| (role, preset.color(role)))
+                .collect()
+        }
+    }
+
+    pub(crate) fn color(&self, role: ThemeColor) -> Color {
+        self.colors.get(&role)
+            .cloned()
+            .or(Some(ThemePreset::Light.color(role)))
+            .unwrap()
+    }
+
+    pub(crate) fn from_definition(definition: ThemeDefinition) -> Result<Theme, String> {
+        let mut theme = Theme::preset(definition.base);
+        theme.name = definition.name;
+        definition.font.map(|font| theme.font = font);
+        definition.mono_font.map(|font| theme.mono_font = font);
+        for (role, hex) in definition.colors.iter() {
+            let color = Color::hex(hex.trim_start_matches('#'))
+                .map_err(|e| format!("Invalid color {} for {:?} in theme {}: {:?}.", hex, role, &theme.name, e))?;
+            theme.colors.insert(*role, color);
+        }
+        Ok(theme)
+    }
+
+    pub(crate) fn read(path: &Path) -> Result<Theme, String> {
+        read_to_string(path)
+            .map_err(|e| format!("Could not read theme {}: {}.", path.display(), e))
+            .and_then(|toml| toml::from_str::<ThemeDefinition>(toml.as_str())
+                .map_err(|e| format!("Could not parse theme {}: {}.", path.display(), e))
+            )
+            .and_then(Theme::from_definition)
+    }
+
+    /// Theme names are matched ignoring case, spaces, dashes and underscores.
+    pub(crate) fn matches(&self, name: &str) -> bool {
+        let normalize = |name: &str| name.chars()
+            .filter(|c| !matches!(c, ' ' | '-' | '_'))
+            .flat_map(|c| c.to_lowercase())
+            .collect::<String>();
+        normalize(&self.name) == normalize(name)
+    }
+}
@@ -0,0 +1,68 @@
+use bevy::math::{Rect, Vec2};
+use serde::Deserialize;
+
+/// The most graphs that are shown in viewports of their own.
+pub const MAX_GRAPH_VIEWPORTS: usize = 4;
+
+/// What a viewport shows. The graphs are numbered in the order they were added to the graph parent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ViewportSubject {
+    All,
+    Network,
+    Graphs,
+    Graph(usize)
+}
+
+/// A single viewport showing everything, the network on the left and the graphs on the right, or
+/// a grid of two by two graphs.
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ViewportLayout {
+    #[default]
+    Single,
+    NetworkAndGraphs,
+    GraphGrid
+}
+
+impl ViewportLayout {
+    pub(crate) fn all() -> Vec<ViewportLayout> {
+        vec![ViewportLayout::Single, ViewportLayout::NetworkAndGraphs, ViewportLayout::GraphGrid]
+    }
+
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            ViewportLayout::Single => "Single",
+            ViewportLayout::NetworkAndGraphs => "Network and Graphs",
+            ViewportLayout::GraphGrid => "Graph Grid"
+        }
+    }
+
+    pub(crate) fn next(&self) -> ViewportLayout {
+        let layouts = ViewportLayout::all();
+        let index = layouts.iter().position(|layout| layout == self).unwrap();
+        layouts[(index + 1) % layouts.len()]
+    }
+
+    /// The viewports of the layout, as the part of the window each covers, in fractions of the
+    /// window from the top left, and what each shows. The first viewport is the main camera's.
+    pub(crate) fn viewports(&self) -> Vec<(Rect, ViewportSubject)> {
+        match self {
+            ViewportLayout::Single => vec![(Rect::new(0.0, 0.0, 1.0, 1.0), ViewportSubject::All)],
+            ViewportLayout::NetworkAndGraphs => vec![
+                (Rect::new(0.0, 0.0, 0.5, 1.0), ViewportSubject::Network),
+                (Rect::new(0.5, 0.0, 1.0, 1.0), ViewportSubject::Graphs)
+            ],
+            ViewportLayout::GraphGrid => (0..MAX_GRAPH_VIEWPORTS)
+                .map(|index| {
+                    let min = Vec2::new((index % 2) as f32 * 0.5, (index / 2) as f32 * 0.5);
+                    (Rect::from_corners(min, min + Vec2::splat(0.5)), ViewportSubject::Graph(index))
+                })
+                .collect()
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct ViewportConfiguration {
+    pub(crate) layout: ViewportLayout
+}
@@ -82,31 +82,7 @@ pub enum PickableComponent {
 }
 
 fn get_entity(picking_event: &PickableEvent) -> Entity {
-    match picking_event {
-        PickableEvent::Selection(selected) => {
-            match selected {
-                SelectionEvent::JustSelected(e) => {
-                    *e
-                }
-                SelectionEvent::JustDeselected(e) => {
-                    *e
-                }
-            }
-        }
-        PickableEvent::Hover(hover) => {
-            match hover {
-                HoverEvent::JustEntered(e) => {
-                    *e
-                }
-                HoverEvent::JustLeft(e) => {
-                    *e
-                }
-            }
-        }
-        PickableEvent::Clicked(clicked) => {
-            *clicked
-        }
-    }
+    picking_event.entity()
 }
 
 use crate::interactions::PickingEvent;
@@ -141,6 +117,15 @@ macro_rules! ray_cast_events_system {
                  else if let PickableEvent::Clicked(e) = i {
                     intersected.picked_ui_flag = true;
                 }
+                if let PickableEvent::Hover(HoverEvent::JustLeft(e)) = i {
+                    if !mouse_button_input.pressed(MouseButton::Left) {
+                        intersected.picked_ui_flag = false;
+                    }
+                }
+                // an entity that is dragged is moved by the drag rather than the camera.
+                if let PickableEvent::Dragged(e, _) = i {
+                    intersected.picked_ui_flag = true;
+                }
 
             }
         }
@@ -176,6 +161,15 @@ macro_rules! ray_cast_events_system {
                  else if let PickableEvent::Clicked(e) = i {
                     intersected.picked_ui_flag = true;
                 }
+                if let PickableEvent::Hover(HoverEvent::JustLeft(e)) = i {
+                    if !mouse_button_input.pressed(MouseButton::Left) {
+                        intersected.picked_ui_flag = false;
+                    }
+                }
+                // an entity that is dragged is moved by the drag rather than the camera.
+                if let PickableEvent::Dragged(e, _) = i {
+                    intersected.picked_ui_flag = true;
+                }
 
                 $(
                     if <InteractionEvent<$event_writer_type> as MatchesPickingEvent>::matches(&i, $event_query_ident.get(get_entity(&i))) {
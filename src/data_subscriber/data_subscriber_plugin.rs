@@ -1,13 +1,14 @@
 use bevy::prelude::*;
 use bevy::tasks::AsyncComputeTaskPool;
 
-use crate::data_subscriber::kafka_data_subscriber::{EventReceiver, KafkaClientProvider, KafkaMessageSubscriber, write_events};
+use crate::data_subscriber::kafka_data_subscriber::{EventReceiver, KafkaClientProvider, KafkaMessageSubscriber, StreamState, write_events};
 use crate::data_subscriber::metric_event::{LayerMetricEvent, NetworkEvent, NetworkMetricEvent, NodeChildrenMetricEvent, NodeMetricEvent};
 use crate::metrics::network_metrics::Metric;
 use crate::network::{Layer, MetricChildNodes, Network, Node};
 use crate::data_subscriber::network_metadata_event::NetworkMetadataEvent;
 use crate::data_subscriber::metric_event::MetricsState;
 use crate::data_subscriber::data_subscriber::DataSubscriber;
+use crate::config::ConfigurationProperties;
 
 pub struct DataSubscriberPlugin;
 
@@ -33,14 +34,19 @@ macro_rules! network_plugin {
     ($($event_type:ident, $component_ty:ty),*) => {
         impl Plugin for DataSubscriberPlugin {
             fn build(&self, app: &mut App) {
-                app.insert_resource(KafkaClientProvider::default())
+                let provider = app.world.get_resource::<ConfigurationProperties>()
+                    .map(KafkaClientProvider::from_config)
+                    .or_else(|| Some(KafkaClientProvider::default()))
+                    .unwrap();
+                app.insert_resource(provider)
+                    .init_resource::<StreamState>()
                     $(
                         .add_event::<$event_type>()
                         .insert_resource::<EventReceiver<$event_type>>(EventReceiver::default())
                         .add_startup_system(KafkaMessageSubscriber::<$event_type>::subscribe)
                         .add_system(write_events::<$event_type>)
                     )*
-                    .insert_resource(MetricsState::default())
+                    .init_resource::<MetricsState>()
                     .add_event::<NetworkMetadataEvent>()
                 ;
             }
@@ -44,6 +44,13 @@ where
     receiver: Option<Receiver<T>>
 }
 
+/// When the stream is paused the events are left in the channel, which fills and applies
+/// backpressure to the consumers until the stream is resumed.
+#[derive(Resource, Default, Debug)]
+pub struct StreamState {
+    pub(crate) paused: bool
+}
+
 #[derive(Resource)]
 pub struct KafkaClientProvider {
     kafka_client: Option<AdminClient<DefaultClientContext>>,
@@ -51,7 +58,8 @@ pub struct KafkaClientProvider {
     client_id: String,
     group_id: String,
     num_consumers_per_event: u8,
-    consumers: KafkaConsumerContainer
+    consumers: KafkaConsumerContainer,
+    topics: Vec<String>
 }
 
 #[derive(Default, Clone)]
@@ -63,7 +71,12 @@ impl MessageClientProvider for KafkaClientProvider {}
 
 impl Default for KafkaClientProvider {
     fn default() -> Self {
-        let properties = ConfigurationProperties::read_config();
+        Self::from_config(&ConfigurationProperties::read_config())
+    }
+}
+
+impl KafkaClientProvider {
+    pub(crate) fn from_config(properties: &ConfigurationProperties) -> Self {
         let client_config = Self::admin_client_config_properties_set(&properties, properties.kafka.hosts.join(","));
         let mut kc = AdminClient::from_config(&client_config)
             .or_else(|e| {
@@ -73,16 +86,27 @@ impl Default for KafkaClientProvider {
             .ok();
         Self {
             kafka_client: kc,
-            group_id: properties.kafka.consumer_group_id,
-            client_id: properties.kafka.client_id,
-            hosts: properties.kafka.hosts,
+            group_id: properties.kafka.consumer_group_id.clone(),
+            client_id: properties.kafka.client_id.clone(),
+            hosts: properties.kafka.hosts.clone(),
             num_consumers_per_event: 1,
             consumers: Default::default(),
+            topics: properties.kafka.topics.clone(),
         }
     }
-}
 
-impl KafkaClientProvider {
+    /// The topics for the event type: the configured topics that match its pattern, or the pattern
+    /// itself when no topics are configured.
+    pub(crate) fn topics_for(&self, topic_matcher: &str) -> Vec<String> {
+        if self.topics.is_empty() {
+            return vec![topic_matcher.to_string()];
+        }
+        self.topics.iter()
+            .filter(|topic| topic_matches(topic_matcher, topic))
+            .cloned()
+            .collect()
+    }
+
     pub(crate) async fn get_consumer(&mut self, topics: Vec<&str>) -> Result<StreamConsumer, KafkaError> {
         let client_config = self.admin_client_config_properties();
 
@@ -135,7 +159,7 @@ impl KafkaClientProvider {
             .map(|all_topics_metadata| {
                 all_topics_metadata.topics().iter()
                     .filter(|topic| topics.iter()
-                        .any(|topic_match| matches!(topic.name(), topic_match))
+                        .any(|topic_match| topic_matches(topic_match, topic.name()))
                     )
                     .map(|topic| topic.name().to_string())
                     .for_each(|topic| topic_to_subcribe.push(topic));
@@ -168,6 +192,7 @@ impl KafkaClientProvider {
             hosts: vec![format!("localhost:{}", port)],
             num_consumers_per_event: 1,
             consumers: Default::default(),
+            topics: properties.kafka.topics,
         }
     }
 
@@ -192,13 +217,26 @@ impl KafkaClientProvider {
     }
 }
 
+/// Whether the topic is matched by the pattern, which is either the name of the topic or a prefix
+/// ending in `*`, such as `node_metric_*`.
+pub(crate) fn topic_matches(pattern: &str, topic: &str) -> bool {
+    pattern.strip_suffix('*')
+        .map(|prefix| topic.starts_with(prefix))
+        .or(Some(pattern == topic))
+        .unwrap()
+}
+
 pub(crate) fn write_events<E>
 (
     mut event_writer: EventWriter<E>,
-    mut receiver_handler: ResMut<EventReceiver<E>>
+    mut receiver_handler: ResMut<EventReceiver<E>>,
+    stream_state: Res<StreamState>
 )
 where E: NetworkEvent + 'static + Debug
 {
+    if stream_state.paused {
+        return;
+    }
     info!("Checking events.");
     if receiver_handler.receiver.is_none() {
         error!("Received event but there was no receiver handler set.");
@@ -233,7 +271,14 @@ impl <E> DataSubscriber<E, KafkaClientProvider> for KafkaMessageSubscriber<E>
     )
     {
 
-        let topics = vec![E::topic_matcher()];
+        let topics = consumer_config.topics_for(E::topic_matcher());
+        if topics.is_empty() {
+            info!("No configured topics match {}, not subscribing.", E::topic_matcher());
+            return;
+        }
+        let topics = topics.iter()
+            .map(|topic| topic.as_str())
+            .collect::<Vec<&str>>();
         let mut consumers = vec![];
         let mut task_pool = AsyncComputeTaskPool::get();
 
@@ -257,7 +302,7 @@ impl <E> DataSubscriber<E, KafkaClientProvider> for KafkaMessageSubscriber<E>
             .lock()
             .map(|mut c| {
                 let consumer = consumers.iter()
-                    .map(|c| (c.clone(), vec![E::topic_matcher().to_string()]))
+                    .map(|c| (c.clone(), topics.iter().map(|topic| topic.to_string()).collect()))
                     .collect::<Vec<(Arc<StreamConsumer>, Vec<String>)>>();
                 c.extend(consumer);
             })
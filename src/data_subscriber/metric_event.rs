@@ -1,11 +1,12 @@
 use serde::{Deserialize, Serialize};
-use bevy::prelude::{Component, Entity, Event};
-use std::collections::{HashMap, LinkedList};
+use bevy::prelude::{Color, Component, Entity, Event};
+use std::collections::{BTreeMap, HashMap, LinkedList};
 use std::fmt::Debug;
 use std::hash::Hash;
 use std::sync::{Arc, mpsc, Mutex};
 use bevy::prelude::{Condition, error, Events, EventWriter, Res, ResMut, Resource, World};
 use bevy::utils::petgraph::visit::Walker;
+use crate::config::palette::Palette;
 use crate::metrics::network_metrics::Metric;
 use crate::network::{Layer, MetricChildNodes, Network, Node};
 
@@ -13,6 +14,9 @@ pub trait NetworkEvent: for<'a> Deserialize<'a> + Send + Sync + Event{
     fn topic_matcher() -> &'static str;
 }
 
+/// Metrics from events that do not include a run are added to the default run.
+pub const DEFAULT_RUN_ID: &str = "default";
+
 pub trait NetworkMetricsServiceEvent<C>: for<'a> Deserialize<'a> + Send + Sync + NetworkEvent + Event
 where C: Component
 {
@@ -22,6 +26,16 @@ where C: Component
     fn get_data(&self) -> Vec<f32>;
     fn get_included(&self) -> &Vec<u32>;
     fn get_columns(&self) -> Option<HashMap<String, usize>>;
+    fn run_id(&self) -> &str;
+    fn wall_clock(&self) -> Option<u64>;
+    fn new(
+        run_id: &str,
+        metric_name: String,
+        shape: Vec<usize>,
+        data: Vec<f32>,
+        columns: HashMap<String, usize>,
+        metric_indices: HashMap<MetricComponentType, Vec<String>>
+    ) -> Self;
 }
 
 #[derive(Default, Clone, Debug, Hash, Ord, PartialOrd, Eq, PartialEq, Deserialize, Serialize)]
@@ -43,7 +57,9 @@ macro_rules! network_events {
                 pub(crate) metric_name: String,
                 pub(crate) included: Vec<u32>,
                 pub(crate) columns: Option<HashMap<String, usize>>,
-                pub(crate) metric_indices: Option<HashMap<MetricComponentType, Vec<String>>>
+                pub(crate) metric_indices: Option<HashMap<MetricComponentType, Vec<String>>>,
+                pub(crate) run_id: Option<String>,
+                pub(crate) wall_clock: Option<u64>
             }
 
             impl NetworkEvent for $event_type {
@@ -84,6 +100,34 @@ macro_rules! network_events {
                 fn get_columns(&self) -> Option<HashMap<String, usize>> {
                     self.columns.clone()
                 }
+                fn run_id(&self) -> &str {
+                    self.run_id.as_ref()
+                        .map(|run_id| run_id.as_str())
+                        .or(Some(DEFAULT_RUN_ID))
+                        .unwrap()
+                }
+                fn wall_clock(&self) -> Option<u64> {
+                    self.wall_clock
+                }
+                fn new(
+                    run_id: &str,
+                    metric_name: String,
+                    shape: Vec<usize>,
+                    data: Vec<f32>,
+                    columns: HashMap<String, usize>,
+                    metric_indices: HashMap<MetricComponentType, Vec<String>>
+                ) -> Self {
+                    Self {
+                        shape,
+                        data: Mutex::new(Some(data)),
+                        metric_name,
+                        included: vec![],
+                        columns: Some(columns),
+                        metric_indices: Some(metric_indices),
+                        run_id: Some(run_id.to_string()),
+                        wall_clock: None
+                    }
+                }
             }
         )*
     }
@@ -97,22 +141,89 @@ network_events!(
     MetricMessage, Metric<Network>, "metric_*"
 );
 
+/// The metrics are keyed by the run and the metric name, so that the same metric from multiple
+/// runs can be overlaid on the same graph. The run and the name are also kept by the metric
+/// entity, as they are looked up for each metric every frame.
 #[derive(Resource, Debug, Default)]
 pub struct MetricsState {
-    pub(crate) entities: HashMap<String, (Entity, u64)>,
+    pub(crate) entities: HashMap<(String, String), (Entity, u64)>,
+    pub(crate) run_metrics: HashMap<Entity, (String, String)>,
+    pub(crate) runs: BTreeMap<String, RunState>,
+}
+
+pub const RUN_LIGHTNESS_STEP: f32 = 0.15;
+
+#[derive(Debug, Clone)]
+pub struct RunState {
+    pub(crate) color: Color,
+    pub(crate) visible: bool,
+    pub(crate) index: usize,
+}
+
+impl RunState {
+    /// The first run is drawn in the colors of the palette. The colors of each run after it are
+    /// shifted in lightness, alternately darker and lighter, so that the runs overlaid on a graph
+    /// can be told apart.
+    pub(crate) fn shade(&self, color: Color) -> Color {
+        if self.index == 0 {
+            return color;
+        }
+        let [hue, saturation, lightness, alpha] = color.as_hsla_f32();
+        let step = ((self.index + 1) / 2) as f32 * RUN_LIGHTNESS_STEP;
+        let lightness = if self.index % 2 == 1 { lightness - step } else { lightness + step };
+        Color::hsla(hue, saturation, lightness.clamp(0.15, 0.85), alpha)
+    }
+
+    /// The columns of a run are the colors of the palette, shaded for the run.
+    pub(crate) fn column_colors(&self, palette: Palette, num_columns: usize) -> Vec<Color> {
+        palette.colors(num_columns)
+            .into_iter()
+            .map(|color| self.shade(color))
+            .collect()
+    }
 }
 
 impl MetricsState {
-    pub(crate) fn get_entity(&self, name: &str) -> Option<(Entity, u64)> {
-        self.entities.get(name)
+    pub(crate) fn get_entity(&self, run_id: &str, name: &str) -> Option<(Entity, u64)> {
+        self.entities.get(&(run_id.to_string(), name.to_string()))
             .map(|(entity, timestep)| (*entity, *timestep))
     }
 
-    pub(crate) fn increment_entity(&mut self, name: &str) {
-        self.entities.get_mut(name)
+    pub(crate) fn insert_entity(&mut self, run_id: &str, name: &str, entity: Entity, timestep: u64) {
+        let run_metric = (run_id.to_string(), name.to_string());
+        self.run_metrics.insert(entity, run_metric.clone());
+        self.entities.insert(run_metric, (entity, timestep));
+    }
+
+    /// The run and the name of the metric for the metric entity.
+    pub(crate) fn run_metric(&self, entity: Entity) -> Option<&(String, String)> {
+        self.run_metrics.get(&entity)
+    }
+
+    pub(crate) fn increment_entity(&mut self, run_id: &str, name: &str) {
+        self.entities.get_mut(&(run_id.to_string(), name.to_string()))
             .as_mut()
             .map(|entity_state| {
                 entity_state.1 += 1;
             });
     }
+
+    /// Runs are numbered in the order they are received, and shown in the first color of the
+    /// palette, shaded for the run.
+    pub(crate) fn add_run(&mut self, run_id: &str, palette: Palette) -> &RunState {
+        let index = self.runs.len();
+        self.runs.entry(run_id.to_string())
+            .or_insert_with(|| {
+                let mut run = RunState { color: Color::default(), visible: true, index };
+                run.color = run.shade(palette.color_at(0, 1));
+                run
+            })
+    }
+
+    pub(crate) fn is_visible(&self, run_id: &str) -> bool {
+        self.runs.get(run_id)
+            .map(|run| run.visible)
+            .or(Some(true))
+            .unwrap()
+    }
 }
@@ -2,4 +2,5 @@ pub(crate) mod data_subscriber_plugin;
 pub(crate) mod kafka_data_subscriber;
 pub(crate) mod metric_event;
 pub(crate) mod network_metadata_event;
-pub(crate) mod data_subscriber;
\ No newline at end of file
+pub(crate) mod data_subscriber;
+pub(crate) mod recording;
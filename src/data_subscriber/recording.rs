@@ -0,0 +1,66 @@
+use std::collections::VecDeque;
+use std::fs;
+use bevy::prelude::*;
+use crate::config::ConfigurationProperties;
+use crate::data_subscriber::metric_event::NodeMetricEvent;
+
+/// The metric events read from the recording, sent all at once or a number of events each frame.
+#[derive(Resource, Default, Debug)]
+pub struct RecordedStream {
+    pub(crate) events: VecDeque<NodeMetricEvent>
+}
+
+impl RecordedStream {
+    /// Reads one json event per line, leaving out the empty lines and the lines that cannot be
+    /// read as an event.
+    pub(crate) fn parse(recording: &str) -> Self {
+        let events = recording.lines()
+            .filter(|line| !line.trim().is_empty())
+            .flat_map(|line| serde_json::from_str::<NodeMetricEvent>(line)
+                .or_else(|e| {
+                    error!("Could not read recorded event: {:?}.", e);
+                    Err(e)
+                })
+                .ok())
+            .collect();
+        Self { events }
+    }
+}
+
+/// Reads the metric events from the recording of the data source, instead of from Kafka.
+pub struct RecordingPlugin;
+
+impl Plugin for RecordingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RecordedStream>()
+            .add_event::<NodeMetricEvent>()
+            .add_startup_system(load_recording)
+            .add_system(replay_recording);
+    }
+}
+
+pub(crate) fn load_recording(
+    config_properties: Res<ConfigurationProperties>,
+    mut recorded: ResMut<RecordedStream>,
+) {
+    let Some(recording) = config_properties.data_source.recording.as_ref() else {
+        return;
+    };
+    let _ = fs::read_to_string(recording)
+        .map(|recording| *recorded = RecordedStream::parse(&recording))
+        .or_else(|e| {
+            error!("Could not read recording {}: {:?}.", recording, e);
+            Err(e)
+        });
+    info!("Replaying {} recorded events.", recorded.events.len());
+}
+
+pub(crate) fn replay_recording(
+    config_properties: Res<ConfigurationProperties>,
+    mut recorded: ResMut<RecordedStream>,
+    mut metric_events: EventWriter<NodeMetricEvent>,
+) {
+    let count = config_properties.data_source.events_per_frame(recorded.events.len());
+    recorded.events.drain(..count)
+        .for_each(|event| metric_events.send(event));
+}
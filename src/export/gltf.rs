@@ -0,0 +1,311 @@
+use bevy::prelude::{Color, Mat4, Mesh, StandardMaterial, Vec3};
+use bevy::render::mesh::{PrimitiveTopology, VertexAttributeValues};
+use bevy_polyline::prelude::PolylineMaterial;
+use serde_json::{json, Value};
+
+pub const GLB_MAGIC: u32 = 0x46546C67;
+pub const GLB_VERSION: u32 = 2;
+pub const GLB_JSON_CHUNK: u32 = 0x4E4F534A;
+pub const GLB_BIN_CHUNK: u32 = 0x004E4942;
+
+const FLOAT: u32 = 5126;
+const UNSIGNED_INT: u32 = 5125;
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+const MODE_LINE_STRIP: u32 = 3;
+const MODE_TRIANGLES: u32 = 4;
+const KHR_MATERIALS_UNLIT: &str = "KHR_materials_unlit";
+
+/// A material of the scene. The colors are linear, as glTF expects them. Lines are unlit, as the
+/// polylines are drawn.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GltfMaterial {
+    pub(crate) name: String,
+    pub(crate) base_color: [f32; 4],
+    pub(crate) emissive: [f32; 3],
+    pub(crate) metallic: f32,
+    pub(crate) roughness: f32,
+    pub(crate) unlit: bool
+}
+
+impl GltfMaterial {
+    pub(crate) fn from_standard(name: &str, material: &StandardMaterial) -> Self {
+        let [r, g, b, _] = material.emissive.as_linear_rgba_f32();
+        Self {
+            name: name.to_string(),
+            base_color: material.base_color.as_linear_rgba_f32(),
+            emissive: [r.clamp(0.0, 1.0), g.clamp(0.0, 1.0), b.clamp(0.0, 1.0)],
+            metallic: material.metallic,
+            roughness: material.perceptual_roughness,
+            unlit: material.unlit
+        }
+    }
+
+    pub(crate) fn from_polyline(name: &str, material: &PolylineMaterial) -> Self {
+        Self::unlit(name, material.color)
+    }
+
+    pub(crate) fn unlit(name: &str, color: Color) -> Self {
+        Self {
+            name: name.to_string(),
+            base_color: color.as_linear_rgba_f32(),
+            emissive: [0.0; 3],
+            metallic: 0.0,
+            roughness: 1.0,
+            unlit: true
+        }
+    }
+}
+
+/// The triangles of a mesh, with the normals when the mesh has one for each position, or the
+/// vertices of a polyline drawn as a line strip.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GltfGeometry {
+    Triangles {
+        positions: Vec<Vec3>,
+        normals: Vec<Vec3>,
+        indices: Vec<u32>
+    },
+    LineStrip {
+        positions: Vec<Vec3>
+    }
+}
+
+impl GltfGeometry {
+    /// The geometry of a mesh of triangles or of a line strip. Other topologies are not exported.
+    pub(crate) fn from_mesh(mesh: &Mesh) -> Option<GltfGeometry> {
+        let positions = match mesh.attribute(Mesh::ATTRIBUTE_POSITION)? {
+            VertexAttributeValues::Float32x3(positions) => positions.iter()
+                .map(|position| Vec3::from_array(*position))
+                .collect::<Vec<Vec3>>(),
+            _ => return None
+        };
+        match mesh.primitive_topology() {
+            PrimitiveTopology::TriangleList => {
+                let normals = match mesh.attribute(Mesh::ATTRIBUTE_NORMAL) {
+                    Some(VertexAttributeValues::Float32x3(normals)) if normals.len() == positions.len() => normals.iter()
+                        .map(|normal| Vec3::from_array(*normal))
+                        .collect(),
+                    _ => vec![]
+                };
+                let indices = mesh.indices()
+                    .map(|indices| indices.iter().map(|index| index as u32).collect())
+                    .or(Some((0..positions.len() as u32).collect()))
+                    .unwrap();
+                Some(GltfGeometry::Triangles { positions, normals, indices })
+            }
+            PrimitiveTopology::LineStrip => Some(GltfGeometry::LineStrip { positions }),
+            _ => None
+        }
+    }
+
+    pub(crate) fn positions(&self) -> &Vec<Vec3> {
+        match self {
+            GltfGeometry::Triangles { positions, .. } => positions,
+            GltfGeometry::LineStrip { positions } => positions
+        }
+    }
+}
+
+/// A named node of the scene with its mesh, placed by the global transform of the entity.
+#[derive(Debug, Clone)]
+pub struct GltfObject {
+    pub(crate) name: String,
+    pub(crate) transform: Mat4,
+    pub(crate) geometry: GltfGeometry,
+    pub(crate) material: usize
+}
+
+/// The layers, nodes, connections and series to be written as a glTF 2.0 binary. The scene is flat,
+/// each object is a node of the root of the scene.
+#[derive(Debug, Clone, Default)]
+pub struct GltfScene {
+    pub(crate) materials: Vec<GltfMaterial>,
+    pub(crate) objects: Vec<GltfObject>
+}
+
+impl GltfScene {
+    /// Adds the object, sharing the material with the objects that have an equal material. An
+    /// object without any positions is left out, as glTF does not allow empty accessors.
+    pub(crate) fn add(&mut self, name: &str, transform: Mat4, geometry: GltfGeometry, material: GltfMaterial) {
+        if geometry.positions().is_empty() {
+            return;
+        }
+        let material = self.materials.iter()
+            .position(|added| *added == material)
+            .or_else(|| {
+                self.materials.push(material);
+                Some(self.materials.len() - 1)
+            })
+            .unwrap();
+        self.objects.push(GltfObject { name: name.to_string(), transform, geometry, material });
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    /// The glTF json of the scene, with the buffer that the accessors read from.
+    pub(crate) fn to_gltf(&self) -> (Value, Vec<u8>) {
+        let mut buffer = GltfBuffer::default();
+        let meshes = self.objects.iter()
+            .map(|object| {
+                let (attributes, indices, mode) = match &object.geometry {
+                    GltfGeometry::Triangles { positions, normals, indices } => {
+                        let mut attributes = json!({ "POSITION": buffer.push_vec3(positions) });
+                        if !normals.is_empty() {
+                            attributes["NORMAL"] = json!(buffer.push_vec3(normals));
+                        }
+                        (attributes, Some(buffer.push_indices(indices)), MODE_TRIANGLES)
+                    }
+                    GltfGeometry::LineStrip { positions } => {
+                        (json!({ "POSITION": buffer.push_vec3(positions) }), None, MODE_LINE_STRIP)
+                    }
+                };
+                let mut primitive = json!({
+                    "attributes": attributes,
+                    "material": object.material,
+                    "mode": mode
+                });
+                if let Some(indices) = indices {
+                    primitive["indices"] = json!(indices);
+                }
+                json!({ "name": object.name, "primitives": [primitive] })
+            })
+            .collect::<Vec<Value>>();
+        let nodes = self.objects.iter()
+            .enumerate()
+            .map(|(index, object)| json!({
+                "name": object.name,
+                "mesh": index,
+                "matrix": object.transform.to_cols_array()
+            }))
+            .collect::<Vec<Value>>();
+        let materials = self.materials.iter()
+            .map(|material| {
+                let mut value = json!({
+                    "name": material.name,
+                    "pbrMetallicRoughness": {
+                        "baseColorFactor": material.base_color,
+                        "metallicFactor": material.metallic,
+                        "roughnessFactor": material.roughness
+                    },
+                    "emissiveFactor": material.emissive
+                });
+                if material.base_color[3] < 1.0 {
+                    value["alphaMode"] = json!("BLEND");
+                }
+                if material.unlit {
+                    value["extensions"] = json!({ KHR_MATERIALS_UNLIT: {} });
+                }
+                value
+            })
+            .collect::<Vec<Value>>();
+        let mut gltf = json!({
+            "asset": { "version": "2.0", "generator": "nn-fe" },
+            "scene": 0,
+            "scenes": [{ "name": "nn-fe", "nodes": (0..self.objects.len()).collect::<Vec<usize>>() }],
+            "nodes": nodes,
+            "meshes": meshes,
+            "materials": materials
+        });
+        if self.materials.iter().any(|material| material.unlit) {
+            gltf["extensionsUsed"] = json!([KHR_MATERIALS_UNLIT]);
+        }
+        if !buffer.bytes.is_empty() {
+            gltf["accessors"] = json!(buffer.accessors);
+            gltf["bufferViews"] = json!(buffer.views);
+            gltf["buffers"] = json!([{ "byteLength": buffer.bytes.len() }]);
+        }
+        (gltf, buffer.bytes)
+    }
+
+    /// The scene as a glb file: the header, then the json chunk padded with spaces and the binary
+    /// chunk padded with zeros, each to four bytes.
+    pub(crate) fn to_glb(&self) -> Vec<u8> {
+        let (gltf, mut bin) = self.to_gltf();
+        let mut json = gltf.to_string().into_bytes();
+        pad(&mut json, b' ');
+        pad(&mut bin, 0);
+        let bin_chunk = if bin.is_empty() { 0 } else { 8 + bin.len() };
+        let length = 12 + 8 + json.len() + bin_chunk;
+        let mut glb = Vec::with_capacity(length);
+        glb.extend(GLB_MAGIC.to_le_bytes());
+        glb.extend(GLB_VERSION.to_le_bytes());
+        glb.extend((length as u32).to_le_bytes());
+        glb.extend((json.len() as u32).to_le_bytes());
+        glb.extend(GLB_JSON_CHUNK.to_le_bytes());
+        glb.extend(json);
+        if !bin.is_empty() {
+            glb.extend((bin.len() as u32).to_le_bytes());
+            glb.extend(GLB_BIN_CHUNK.to_le_bytes());
+            glb.extend(bin);
+        }
+        glb
+    }
+}
+
+/// The binary buffer of the scene, with a buffer view and an accessor for each attribute and
+/// each list of indices.
+#[derive(Debug, Default)]
+struct GltfBuffer {
+    bytes: Vec<u8>,
+    views: Vec<Value>,
+    accessors: Vec<Value>
+}
+
+impl GltfBuffer {
+    fn push_view(&mut self, bytes: Vec<u8>, target: u32) -> usize {
+        pad(&mut self.bytes, 0);
+        self.views.push(json!({
+            "buffer": 0,
+            "byteOffset": self.bytes.len(),
+            "byteLength": bytes.len(),
+            "target": target
+        }));
+        self.bytes.extend(bytes);
+        self.views.len() - 1
+    }
+
+    /// The accessors of positions need the bounds, so they are written for every vec3.
+    fn push_vec3(&mut self, values: &Vec<Vec3>) -> usize {
+        let (min, max) = values.iter().fold(
+            (Vec3::splat(f32::MAX), Vec3::splat(f32::MIN)),
+            |(min, max), value| (min.min(*value), max.max(*value))
+        );
+        let bytes = values.iter()
+            .flat_map(|value| value.to_array())
+            .flat_map(|value| value.to_le_bytes())
+            .collect::<Vec<u8>>();
+        let view = self.push_view(bytes, ARRAY_BUFFER);
+        self.accessors.push(json!({
+            "bufferView": view,
+            "componentType": FLOAT,
+            "count": values.len(),
+            "type": "VEC3",
+            "min": min.to_array(),
+            "max": max.to_array()
+        }));
+        self.accessors.len() - 1
+    }
+
+    fn push_indices(&mut self, indices: &Vec<u32>) -> usize {
+        let bytes = indices.iter()
+            .flat_map(|index| index.to_le_bytes())
+            .collect::<Vec<u8>>();
+        let view = self.push_view(bytes, ELEMENT_ARRAY_BUFFER);
+        self.accessors.push(json!({
+            "bufferView": view,
+            "componentType": UNSIGNED_INT,
+            "count": indices.len(),
+            "type": "SCALAR"
+        }));
+        self.accessors.len() - 1
+    }
+}
+
+fn pad(bytes: &mut Vec<u8>, with: u8) {
+    while bytes.len() % 4 != 0 {
+        bytes.push(with);
+    }
+}
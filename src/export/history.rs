@@ -0,0 +1,147 @@
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+use arrow::array::{ArrayRef, Float32Array, StringArray, UInt64Array};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::error::ArrowError;
+use arrow::record_batch::RecordBatch;
+use bevy::prelude::Component;
+use parquet::arrow::ArrowWriter;
+use serde::Serialize;
+use crate::config::export::ExportFormat;
+use crate::data_subscriber::metric_event::MetricComponentType;
+use crate::metrics::network_metrics::Metric;
+
+/// A value of the history of a metric: the element at the index of the label at the timestep. The
+/// indices of the metric are joined with `;` so that each row is flat, as pandas reads it.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct HistoryRow {
+    pub(crate) run_id: String,
+    pub(crate) metric_name: String,
+    pub(crate) label: String,
+    pub(crate) timestep: u64,
+    pub(crate) index: usize,
+    pub(crate) value: f32,
+    pub(crate) wall_clock: Option<u64>,
+    pub(crate) network_indices: String,
+    pub(crate) layer_indices: String,
+    pub(crate) node_indices: String
+}
+
+/// The rows of the metric, by label in the order of the columns of the metric, then by timestep
+/// and index.
+pub(crate) fn history_rows<T: Component>(run_id: &str, metric_name: &str, metric: &Metric<T>) -> Vec<HistoryRow> {
+    let indices = |component_type: MetricComponentType| metric.metric_indices.get(&component_type)
+        .map(|indices| indices.join(";"))
+        .or(Some(String::new()))
+        .unwrap();
+    let (network_indices, layer_indices, node_indices) = (
+        indices(MetricComponentType::Network),
+        indices(MetricComponentType::Layer),
+        indices(MetricComponentType::Node)
+    );
+    let mut labels = metric.historical.labels.iter()
+        .collect::<Vec<(&String, &usize)>>();
+    labels.sort_by_key(|(label, column)| (**column, (*label).clone()));
+    labels.into_iter()
+        .flat_map(|(label, _)| metric.historical.retrieve_series(label)
+            .into_iter()
+            .flat_map(|(timestep, values)| values.into_iter()
+                .enumerate()
+                .map(move |(index, value)| (timestep, index, value)))
+            .map(|(timestep, index, value)| HistoryRow {
+                run_id: run_id.to_string(),
+                metric_name: metric_name.to_string(),
+                label: label.clone(),
+                timestep,
+                index,
+                value,
+                wall_clock: metric.historical.wall_clock.get(&timestep).cloned(),
+                network_indices: network_indices.clone(),
+                layer_indices: layer_indices.clone(),
+                node_indices: node_indices.clone()
+            })
+            .collect::<Vec<HistoryRow>>())
+        .collect()
+}
+
+/// Whether the metric is exported when only the metrics of the component type are, which is when
+/// it is indexed by that type.
+pub(crate) fn has_component_type<T: Component>(metric: &Metric<T>, component_type: &Option<MetricComponentType>) -> bool {
+    component_type.as_ref()
+        .map(|component_type| metric.metric_indices.get(component_type)
+            .filter(|indices| !indices.is_empty())
+            .is_some())
+        .or(Some(true))
+        .unwrap()
+}
+
+pub(crate) fn history_schema() -> Schema {
+    Schema::new(vec![
+        Field::new("run_id", DataType::Utf8, false),
+        Field::new("metric_name", DataType::Utf8, false),
+        Field::new("label", DataType::Utf8, false),
+        Field::new("timestep", DataType::UInt64, false),
+        Field::new("index", DataType::UInt64, false),
+        Field::new("value", DataType::Float32, false),
+        Field::new("wall_clock", DataType::UInt64, true),
+        Field::new("network_indices", DataType::Utf8, false),
+        Field::new("layer_indices", DataType::Utf8, false),
+        Field::new("node_indices", DataType::Utf8, false)
+    ])
+}
+
+pub(crate) fn record_batch(rows: &Vec<HistoryRow>) -> Result<RecordBatch, ArrowError> {
+    let strings = |value: fn(&HistoryRow) -> &str| -> ArrayRef {
+        Arc::new(StringArray::from(rows.iter().map(value).collect::<Vec<&str>>()))
+    };
+    RecordBatch::try_new(Arc::new(history_schema()), vec![
+        strings(|row| row.run_id.as_str()),
+        strings(|row| row.metric_name.as_str()),
+        strings(|row| row.label.as_str()),
+        Arc::new(UInt64Array::from(rows.iter().map(|row| row.timestep).collect::<Vec<u64>>())),
+        Arc::new(UInt64Array::from(rows.iter().map(|row| row.index as u64).collect::<Vec<u64>>())),
+        Arc::new(Float32Array::from(rows.iter().map(|row| row.value).collect::<Vec<f32>>())),
+        Arc::new(UInt64Array::from(rows.iter().map(|row| row.wall_clock).collect::<Vec<Option<u64>>>())),
+        strings(|row| row.network_indices.as_str()),
+        strings(|row| row.layer_indices.as_str()),
+        strings(|row| row.node_indices.as_str())
+    ])
+}
+
+/// Writes the rows to the file in the format. Csv, Arrow and Parquet are written from the same
+/// record batch, so the columns and their types are the same in each.
+pub(crate) fn write_history(path: &Path, rows: &Vec<HistoryRow>, format: ExportFormat) -> Result<(), String> {
+    let file = File::create(path)
+        .map_err(|e| e.to_string())?;
+    if format == ExportFormat::Json {
+        return serde_json::to_writer_pretty(file, rows)
+            .map_err(|e| e.to_string());
+    }
+    let batch = record_batch(rows)
+        .map_err(|e| e.to_string())?;
+    match format {
+        ExportFormat::Csv => {
+            arrow::csv::Writer::new(file)
+                .write(&batch)
+                .map_err(|e| e.to_string())
+        }
+        ExportFormat::Arrow => {
+            let mut writer = arrow::ipc::writer::FileWriter::try_new(file, &batch.schema())
+                .map_err(|e| e.to_string())?;
+            writer.write(&batch)
+                .and_then(|_| writer.finish())
+                .map_err(|e| e.to_string())
+        }
+        ExportFormat::Parquet => {
+            let mut writer = ArrowWriter::try_new(file, batch.schema(), None)
+                .map_err(|e| e.to_string())?;
+            writer.write(&batch)
+                .map_err(|e| e.to_string())?;
+            writer.close()
+                .map(|_| ())
+                .map_err(|e| e.to_string())
+        }
+        ExportFormat::Json => Ok(())
+    }
+}
@@ -0,0 +1,279 @@
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use bevy::prelude::*;
+use bevy_polyline::prelude::{Polyline, PolylineMaterial};
+use crate::config::ConfigurationProperties;
+use crate::config::export::{ExportConfiguration, ExportFormat};
+use crate::config::keymap::KeyAction;
+use crate::config::theme::ThemeColor;
+use crate::data_subscriber::metric_event::MetricsState;
+use crate::export::gltf::{GltfGeometry, GltfMaterial, GltfScene};
+use crate::export::history::{has_component_type, history_rows, HistoryRow, write_history};
+use crate::graph::GraphDimComponent;
+use crate::graph::draw_graph_points::SeriesPolylines;
+use crate::menu::graphing_menu::context_menu::{ContextMenuAction, ContextMenuActionEvent, ContextMenuTarget, metric_label, select_context_menu_action};
+use crate::metrics::network_metrics::Metric;
+use crate::network::{Layer, MetricChildNodes, Network, Node};
+use crate::shortcuts::keymap::KeyActionEvent;
+use crate::theme::restyle::ThemedPolyline;
+use crate::util::file_name;
+
+/// Exporting the history of the metrics as tables, to be read by pandas.
+pub(crate) mod history;
+/// Writing the network and the graphs as a glTF 2.0 binary.
+pub(crate) mod gltf;
+
+pub struct ExportPlugin;
+
+impl Plugin for ExportPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ContextMenuActionEvent>()
+            .add_event::<KeyActionEvent>()
+            .init_resource::<MetricsState>()
+            .add_system(apply_export_actions::<Node>.after(select_context_menu_action))
+            .add_system(apply_export_actions::<Layer>.after(select_context_menu_action))
+            .add_system(apply_export_actions::<Network>.after(select_context_menu_action))
+            .add_system(apply_export_actions::<MetricChildNodes>.after(select_context_menu_action))
+            .add_system(export_all_metrics.after(select_context_menu_action))
+            .add_system(export_scene);
+    }
+}
+
+/// Exporting a metric from the context menu of one of its series writes the history of all of
+/// its labels.
+pub(crate) fn apply_export_actions<T>(
+    mut action_events: EventReader<ContextMenuActionEvent>,
+    metrics_state: Res<MetricsState>,
+    config_properties: Res<ConfigurationProperties>,
+    metrics: Query<&Metric<T>>,
+    series: Query<&Parent, With<GraphDimComponent>>,
+)
+    where T: Component
+{
+    for event in action_events.iter() {
+        let (ContextMenuTarget::Series(target), ContextMenuAction::ExportMetric(format)) = (event.target, &event.action) else {
+            continue;
+        };
+        let Ok(metric_entity) = series.get(target).map(|parent| parent.get()) else {
+            continue;
+        };
+        let Ok(metric) = metrics.get(metric_entity) else {
+            continue;
+        };
+        let (run_id, metric_name) = metric_names(&metrics_state, metric_entity, metric);
+        let rows = history_rows(&run_id, &metric_name, metric);
+        let _ = export_rows(&config_properties.export, &format!("{}_{}", run_id, metric_name), &rows, *format)
+            .map(|path| info!("Exported metric history to {}.", path))
+            .or_else(|e| {
+                error!("Could not export metric history: {}.", e);
+                Err(e)
+            });
+    }
+}
+
+/// Exporting all metrics writes the metrics of the nodes, layers, networks and child nodes, or
+/// those of the configured component type, to a single file.
+pub(crate) fn export_all_metrics(
+    mut action_events: EventReader<ContextMenuActionEvent>,
+    metrics_state: Res<MetricsState>,
+    config_properties: Res<ConfigurationProperties>,
+    nodes: Query<(Entity, &Metric<Node>)>,
+    layers: Query<(Entity, &Metric<Layer>)>,
+    networks: Query<(Entity, &Metric<Network>)>,
+    child_nodes: Query<(Entity, &Metric<MetricChildNodes>)>,
+) {
+    if action_events.iter().filter(|event| event.action == ContextMenuAction::ExportAllMetrics).count() == 0 {
+        return;
+    }
+    let export = &config_properties.export;
+    let mut rows = metric_rows(export, &metrics_state, nodes.iter());
+    rows.extend(metric_rows(export, &metrics_state, layers.iter()));
+    rows.extend(metric_rows(export, &metrics_state, networks.iter()));
+    rows.extend(metric_rows(export, &metrics_state, child_nodes.iter()));
+    let _ = write_metric_rows(export, rows)
+        .map(|path| info!("Exported metric history to {}.", path))
+        .or_else(|e| {
+            error!("Could not export metric history: {}.", e);
+            Err(e)
+        });
+}
+
+/// The run and name the metric was received with.
+pub(crate) fn metric_names<T: Component>(metrics_state: &MetricsState, entity: Entity, metric: &Metric<T>) -> (String, String) {
+    metrics_state.run_metric(entity)
+        .cloned()
+        .or(Some((metric.run_id.clone(), format!("{:?}", entity))))
+        .unwrap()
+}
+
+/// Writes the metrics of the configured component type to one file, named after the component type,
+/// in the configured format.
+pub(crate) fn export_metrics<'a, T: Component>(
+    export: &ExportConfiguration,
+    metrics_state: &MetricsState,
+    metrics: impl Iterator<Item = (Entity, &'a Metric<T>)>
+) -> Result<String, String> {
+    write_metric_rows(export, metric_rows(export, metrics_state, metrics))
+}
+
+/// The history rows of each of the metrics of the configured component type, with the run and
+/// name of the metric.
+pub(crate) fn metric_rows<'a, T: Component>(
+    export: &ExportConfiguration,
+    metrics_state: &MetricsState,
+    metrics: impl Iterator<Item = (Entity, &'a Metric<T>)>
+) -> Vec<((String, String), Vec<HistoryRow>)> {
+    metrics
+        .filter(|(_, metric)| has_component_type(metric, &export.component_type))
+        .map(|(entity, metric)| {
+            let (run_id, metric_name) = metric_names(metrics_state, entity, metric);
+            let rows = history_rows(&run_id, &metric_name, metric);
+            ((run_id, metric_name), rows)
+        })
+        .collect()
+}
+
+/// Writes the rows of the metrics, sorted by run and name, to one file named after the configured
+/// component type.
+pub(crate) fn write_metric_rows(export: &ExportConfiguration, mut metrics: Vec<((String, String), Vec<HistoryRow>)>) -> Result<String, String> {
+    metrics.sort_by(|(first, _), (second, _)| first.cmp(second));
+    let rows = metrics.into_iter()
+        .flat_map(|(_, rows)| rows.into_iter())
+        .collect::<Vec<HistoryRow>>();
+    let name = export.component_type.as_ref()
+        .map(|component_type| format!("{:?}_metrics", component_type).to_lowercase())
+        .or(Some("metrics".to_string()))
+        .unwrap();
+    export_rows(export, &name, &rows, export.format)
+}
+
+pub(crate) fn export_rows(export: &ExportConfiguration, name: &str, rows: &Vec<HistoryRow>, format: ExportFormat) -> Result<String, String> {
+    let dir = Path::new(&export.output_dir);
+    fs::create_dir_all(dir)
+        .map_err(|e| e.to_string())?;
+    let path = dir.join(format!("{}.{}", file_name(name), format.extension()));
+    write_history(&path, rows, format)?;
+    Ok(path.display().to_string())
+}
+
+/// The visible layers and nodes as meshes, and the visible polylines, the connections between the
+/// nodes and the series of the graph, as line strips, each named after what it is drawn for.
+pub(crate) fn export_scene(
+    mut action_events: EventReader<KeyActionEvent>,
+    config_properties: Res<ConfigurationProperties>,
+    metrics_state: Res<MetricsState>,
+    meshes: Res<Assets<Mesh>>,
+    materials: Res<Assets<StandardMaterial>>,
+    polylines: Res<Assets<Polyline>>,
+    polyline_materials: Res<Assets<PolylineMaterial>>,
+    layers: Query<(&Layer, &Handle<Mesh>, &Handle<StandardMaterial>, &GlobalTransform, Option<&ComputedVisibility>)>,
+    nodes: Query<(&Node, &Parent, &Handle<Mesh>, &Handle<StandardMaterial>, &GlobalTransform, Option<&ComputedVisibility>)>,
+    lines: Query<(Entity, &Handle<Polyline>, &Handle<PolylineMaterial>, &GlobalTransform, Option<&ComputedVisibility>, Option<&Parent>, Option<&ThemedPolyline>)>,
+    series: Query<(&GraphDimComponent, &SeriesPolylines, &Parent)>,
+    layer_names: Query<&Layer>,
+) {
+    if !action_events.iter().any(|KeyActionEvent(action)| *action == KeyAction::ExportScene) {
+        return;
+    }
+    let mut scene = GltfScene::default();
+    for (layer, mesh, material, transform, visibility) in layers.iter() {
+        if !is_visible(visibility) {
+            continue;
+        }
+        add_mesh(&mut scene, layer.name, "layer", mesh, material, transform, &meshes, &materials);
+    }
+    for (node, parent, mesh, material, transform, visibility) in nodes.iter() {
+        if !is_visible(visibility) {
+            continue;
+        }
+        let name = layer_names.get(parent.get())
+            .map(|layer| layer.node_id(node))
+            .or(Ok::<String, ()>(format!("node.{}", node.node_pos)))
+            .unwrap();
+        add_mesh(&mut scene, &name, "node", mesh, material, transform, &meshes, &materials);
+    }
+    for (entity, polyline, material, transform, visibility, parent, themed) in lines.iter() {
+        if !is_visible(visibility) {
+            continue;
+        }
+        let parent = parent.map(|parent| parent.get());
+        let (name, material_name) = parent
+            .and_then(|parent| series.get(parent).ok())
+            .and_then(|(graph_dim, series_polylines, metric)| series_polylines.lines.iter()
+                .position(|(line, _)| *line == entity)
+                .map(|index| (
+                    format!("{} {} {}", metric_label(&metrics_state, metric.get()), graph_dim.name, index),
+                    "series"
+                )))
+            .or_else(|| themed
+                .filter(|ThemedPolyline(color)| *color == ThemeColor::Connection)
+                .map(|_| (
+                    parent.and_then(|parent| layer_names.get(parent).ok())
+                        .map(|layer| format!("connection {}", layer.name))
+                        .or(Some("connection".to_string()))
+                        .unwrap(),
+                    "connection"
+                )))
+            .or(Some((format!("line {}", entity.index()), "line")))
+            .unwrap();
+        let (Some(polyline), Some(material)) = (polylines.get(polyline), polyline_materials.get(material)) else {
+            continue;
+        };
+        scene.add(
+            &name,
+            transform.compute_matrix(),
+            GltfGeometry::LineStrip { positions: polyline.vertices.clone() },
+            GltfMaterial::from_polyline(material_name, material)
+        );
+    }
+    let millis = SystemTime::now().duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis())
+        .or(Ok::<u128, ()>(0))
+        .unwrap();
+    let _ = write_scene(&config_properties.export, &format!("scene-{}", millis), &scene)
+        .map(|path| info!("Exported {} objects of the scene to {}.", scene.objects.len(), path))
+        .or_else(|e| {
+            error!("Could not export the scene: {}.", e);
+            Err(e)
+        });
+}
+
+fn is_visible(visibility: Option<&ComputedVisibility>) -> bool {
+    visibility.map(|visibility| visibility.is_visible())
+        .or(Some(true))
+        .unwrap()
+}
+
+fn add_mesh(
+    scene: &mut GltfScene,
+    name: &str,
+    material_name: &str,
+    mesh: &Handle<Mesh>,
+    material: &Handle<StandardMaterial>,
+    transform: &GlobalTransform,
+    meshes: &Assets<Mesh>,
+    materials: &Assets<StandardMaterial>,
+) {
+    let geometry = meshes.get(mesh)
+        .and_then(GltfGeometry::from_mesh);
+    let material = materials.get(material)
+        .map(|material| GltfMaterial::from_standard(material_name, material));
+    if let (Some(geometry), Some(material)) = (geometry, material) {
+        scene.add(name, transform.compute_matrix(), geometry, material);
+    }
+}
+
+/// Writes the scene as a glb file named after the name, in the export directory.
+pub(crate) fn write_scene(export: &ExportConfiguration, name: &str, scene: &GltfScene) -> Result<String, String> {
+    if scene.is_empty() {
+        return Err("there is nothing in the scene to export".to_string());
+    }
+    let dir = Path::new(&export.output_dir);
+    fs::create_dir_all(dir)
+        .map_err(|e| e.to_string())?;
+    let path = dir.join(format!("{}.glb", file_name(name)));
+    fs::write(&path, scene.to_glb())
+        .map_err(|e| e.to_string())?;
+    Ok(path.display().to_string())
+}
@@ -4,32 +4,88 @@ use std::fmt::Debug;
 use std::marker::PhantomData;
 use bevy::log::error;
 use bevy::pbr::Material;
-use bevy::prelude::{Added, Assets, BuildChildren, Changed, Children, Color, Commands, Component, default, Entity, info, MaterialMeshBundle, Mesh, Mut, Or, Parent, Query, ResMut, Vec3, With, Without};
+use bevy::prelude::{Added, Assets, BuildChildren, Changed, Children, Color, Commands, Component, default, Entity, info, MaterialMeshBundle, Mesh, Mut, Or, Parent, Query, RemovedComponents, Res, ResMut, Resource, Vec3, With, Without};
+use bevy::asset::Handle;
 use bevy_mod_picking::PickableBundle;
-use bevy_polyline::prelude::{Polyline, PolylineMaterial};
+use bevy_polyline::prelude::{Polyline, PolylineBundle, PolylineMaterial};
 use ndarray::{Array1, s, SliceInfoElem};
 use crate::graph::{DataSeries, Graph, GraphConfigurationResource, GraphDim, GraphDimComponent, GraphDimType, GraphParent, SeriesStep};
 use crate::graph::graph_data_event_reader::HistoricalUpdated;
 use crate::graph::radial::calculate_radial_time;
-use crate::lines::line_list::{create_3d_line, LineList};
+use crate::graph::run_comparison::RunComparison;
 use crate::metrics::network_metrics::{HistoricalData, Metric};
 
+pub const SERIES_THICKNESS: f32 = 1.0;
+
+/// Calculates the points to add to the graph for the timestep. The points are then appended to
+/// the series polylines owned by the GraphDimComponent.
 pub trait GraphingStrategy<T>
     where
         T: Component + Send + Sync + 'static,
 {
+    /// The name the strategy is saved as in a workspace.
+    const NAME: &'static str;
+
     fn create_update_graph(
-        commands: &mut Commands,
         metric: &Metric<T>,
         series: &mut Mut<DataSeries>,
         columns: &mut Vec<GraphDim>,
-        meshes: &mut ResMut<Assets<Mesh>>,
-        polylines: &mut ResMut<Assets<Polyline>>,
-        materials: &mut ResMut<Assets<PolylineMaterial>>,
         num_col: usize,
         key: &u64
-    );
+    ) -> Vec<SeriesPoint>;
+
+}
+
+/// A segment of a series for a timestep. The index is the index of the value in the column, so
+/// that each value of the column is drawn as its own line.
+#[derive(Debug, Clone)]
+pub struct SeriesPoint {
+    pub(crate) graph_dim_entity: Entity,
+    pub(crate) color: Color,
+    pub(crate) index: usize,
+    pub(crate) start: Vec3,
+    pub(crate) end: Vec3,
+    pub(crate) step: u64,
+}
+
+/// One polyline for each value in the column of the GraphDimComponent. The vertices are appended
+/// in place as the timesteps are drawn, and the timestep of each vertex of each line is kept in
+/// steps. The start of the first segment of a line is given the timestep of its end.
+#[derive(Component, Debug, Default)]
+pub struct SeriesPolylines {
+    pub(crate) lines: Vec<(Entity, Handle<Polyline>)>,
+    pub(crate) steps: Vec<Vec<u64>>,
+}
 
+/// Materials are shared between all series with the same color and thickness. The polylines are
+/// tracked so that the assets can be removed when the GraphDimComponent is deleted.
+#[derive(Resource, Debug, Default)]
+pub struct SeriesAssets {
+    materials: HashMap<(u32, u32), Handle<PolylineMaterial>>,
+    polylines: HashMap<Entity, Vec<Handle<Polyline>>>,
+}
+
+impl SeriesAssets {
+    pub(crate) fn get_material(
+        &mut self,
+        color: Color,
+        thickness: f32,
+        polyline_materials: &mut ResMut<Assets<PolylineMaterial>>
+    ) -> Handle<PolylineMaterial> {
+        self.materials.entry((color.as_rgba_u32(), thickness.to_bits()))
+            .or_insert_with(|| polyline_materials.add(PolylineMaterial {
+                color,
+                width: thickness,
+                ..default()
+            }))
+            .clone()
+    }
+
+    fn add_polyline(&mut self, graph_dim_entity: Entity, polyline: &Handle<Polyline>) {
+        self.polylines.entry(graph_dim_entity)
+            .or_insert_with(Vec::new)
+            .push(polyline.clone_weak());
+    }
 }
 
 fn matches(graph_dim_type: &GraphDimType) -> bool {
@@ -39,7 +95,7 @@ fn matches(graph_dim_type: &GraphDimType) -> bool {
     }
 }
 
-type WithDataSeriesChangedHistorical = (With<DataSeries>, Or<(Changed<HistoricalUpdated>, Added<HistoricalUpdated>)>);
+pub(crate) type WithDataSeriesChangedHistorical = (With<DataSeries>, Or<(Changed<HistoricalUpdated>, Added<HistoricalUpdated>)>);
 
 /// TODO: The layer should be wide, and then the node losses should be set inside of them. So the
 ///     layer will be from radians a to b, and the width of the line will be the size of this, and
@@ -50,11 +106,12 @@ pub(crate) fn draw_graph_points<T, P>(
         (Entity, &Metric<T>, &mut DataSeries),
         WithDataSeriesChangedHistorical
     >,
-    mut metric_dims: Query<(Entity, &GraphDimComponent)>,
+    mut metric_dims: Query<&mut SeriesPolylines, With<GraphDimComponent>>,
     mut dims: ResMut<GraphConfigurationResource<T>>,
-    mut meshes: ResMut<Assets<Mesh>>,
+    mut series_assets: ResMut<SeriesAssets>,
     mut polylines: ResMut<Assets<Polyline>>,
     mut materials: ResMut<Assets<PolylineMaterial>>,
+    run_comparison: Res<RunComparison>,
 )
 where
     T: Component + Send + Sync + 'static + Debug,
@@ -62,64 +119,138 @@ where
 {
 
     for (metric_entity, metric, mut series) in metrics.iter_mut() {
-        let last = *series.drawn
-            .last().or(Some(&1)).unwrap();
         if !dims.series_dims.contains_key(&metric_entity) {
             continue;
         }
         let num_col = dims.series_dims.get(&metric_entity).unwrap().len();
-        let added = metric.historical.timestep.iter()
-            .skip_while(|(key, val)| **key != last)
-            .flat_map(|(key, val)| {
-                if *key != last {
-
-                    P::create_update_graph(
-                        &mut commands,
-                        metric,
-                        &mut series,
-                        &mut dims.series_dims.get_mut(&metric_entity).unwrap(),
-                        &mut meshes,
-                        &mut polylines,
-                        &mut materials,
-                        num_col,
-                        key
-                    );
-
-                    vec![*key]
-
-                }
-                else {
-                    vec![]
-                }
-            })
-            .collect::<Vec<u64>>();
+        let added = new_timesteps(metric_entity, metric, &series, &run_comparison);
+        for key in added.iter() {
+            let mut points = P::create_update_graph(
+                metric,
+                &mut series,
+                &mut dims.series_dims.get_mut(&metric_entity).unwrap(),
+                num_col,
+                key
+            );
+
+            run_comparison.align_points(metric_entity, metric, &mut points);
+
+            points.into_iter()
+                .for_each(|point| append_series_point(
+                    &mut commands,
+                    &mut metric_dims,
+                    &mut series_assets,
+                    &mut polylines,
+                    &mut materials,
+                    point
+                ));
+        }
 
         series.drawn.extend(added);
 
     }
 }
 
-pub(crate) fn create_data_segment(
+/// The timesteps of the metric after the last one drawn, up to the horizon of the runs it is
+/// compared with. The first timestep is not drawn, as each point is drawn from the timestep
+/// before it.
+pub(crate) fn new_timesteps<T: Component>(
+    metric_entity: Entity,
+    metric: &Metric<T>,
+    series: &DataSeries,
+    run_comparison: &RunComparison
+) -> Vec<u64> {
+    let last = *series.drawn
+        .last().or(Some(&1)).unwrap();
+    metric.historical.timestep.keys()
+        .skip_while(|key| **key != last)
+        .take_while(|key| run_comparison.within_horizon(metric_entity, metric, **key))
+        .filter(|key| **key != last)
+        .cloned()
+        .collect()
+}
+
+/// Appends the end of the segment to the polyline for the series, creating the polyline the
+/// first time a value of the column is drawn.
+pub(crate) fn append_series_point(
+    commands: &mut Commands,
+    metric_dims: &mut Query<&mut SeriesPolylines, With<GraphDimComponent>>,
+    series_assets: &mut ResMut<SeriesAssets>,
+    polylines: &mut ResMut<Assets<Polyline>>,
+    polyline_materials: &mut ResMut<Assets<PolylineMaterial>>,
+    point: SeriesPoint
+) {
+    let _ = metric_dims.get_mut(point.graph_dim_entity)
+        .map(|mut series_polylines| {
+            let series_polylines = &mut *series_polylines;
+            if let Some((_, polyline)) = series_polylines.lines.get(point.index) {
+                let steps = &mut series_polylines.steps[point.index];
+                polylines.get_mut(polyline)
+                    .map(|polyline| {
+                        if polyline.vertices.is_empty() {
+                            polyline.vertices.push(point.start);
+                            steps.push(point.step);
+                        }
+                        polyline.vertices.push(point.end);
+                        steps.push(point.step);
+                    });
+            } else if series_polylines.lines.len() == point.index {
+                let line = create_series_line(commands, series_assets, polylines, polyline_materials, &point);
+                series_polylines.lines.push(line);
+                series_polylines.steps.push(vec![point.step, point.step]);
+            } else {
+                error!("Series index {} was drawn before the previous indices for {:?}.", point.index, point.graph_dim_entity);
+            }
+        })
+        .or_else(|e| {
+            error!("Could not find series polylines for graph dim: {:?}.", e);
+            Err(e)
+        });
+}
+
+fn create_series_line(
     commands: &mut Commands,
-    start: Vec3,
-    end: Vec3,
-    mut meshes: &mut ResMut<Assets<Mesh>>,
-    mut polylines: &mut ResMut<Assets<Polyline>>,
-    mut polyline_materials: &mut ResMut<Assets<PolylineMaterial>>,
-    thickness: f32
-) -> Entity {
-
-    let line_bundler = create_3d_line(LineList {
-        color: Color::GREEN,
-        lines: vec![(start, end)],
-        thickness,
-    }, polylines, polyline_materials);
-
-    commands
+    series_assets: &mut ResMut<SeriesAssets>,
+    polylines: &mut ResMut<Assets<Polyline>>,
+    polyline_materials: &mut ResMut<Assets<PolylineMaterial>>,
+    point: &SeriesPoint
+) -> (Entity, Handle<Polyline>) {
+    let polyline = polylines.add(Polyline {
+        vertices: vec![point.start, point.end]
+    });
+    series_assets.add_polyline(point.graph_dim_entity, &polyline);
+
+    let line_entity = commands
         .spawn((
-            line_bundler,
+            PolylineBundle {
+                polyline: polyline.clone(),
+                material: series_assets.get_material(point.color, SERIES_THICKNESS, polyline_materials),
+                ..default()
+            },
             SeriesStep {}
         ))
-        .id()
+        .id();
+
+    commands.get_entity(point.graph_dim_entity)
+        .as_mut()
+        .map(|graph_dim_entity| graph_dim_entity.add_child(line_entity));
+
+    (line_entity, polyline)
+}
 
+/// When a GraphDimComponent is removed, the polylines for its series are removed from the assets.
+pub(crate) fn remove_deleted_series(
+    mut removed: RemovedComponents<GraphDimComponent>,
+    mut series_assets: ResMut<SeriesAssets>,
+    mut polylines: ResMut<Assets<Polyline>>,
+) {
+    for removed_entity in removed.iter() {
+        series_assets.polylines.remove(&removed_entity)
+            .map(|handles| handles.iter()
+                .for_each(|handle| {
+                    info!("Removing series polyline for {:?}.", removed_entity);
+                    polylines.remove(handle);
+                })
+            );
+    }
 }
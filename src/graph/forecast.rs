@@ -0,0 +1,421 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use bevy::prelude::{Assets, BuildChildren, Color, Commands, Component, default, debug, DespawnRecursiveExt, Entity, error, PbrBundle, Query, Res, ResMut, Resource, Vec3};
+use bevy::asset::Handle;
+use bevy_polyline::prelude::{Polyline, PolylineBundle, PolylineMaterial};
+use ndarray::{Array1, s};
+use crate::graph::{DataSeries, GraphConfigurationResource, GraphDimType};
+use crate::graph::draw_graph_points::WithDataSeriesChangedHistorical;
+use crate::graph::radial::calculate_derivatives;
+use crate::metrics::network_metrics::{Metric, MetricType};
+
+pub const FORECAST_LINE_THICKNESS: f32 = 1.0;
+pub const FORECAST_BAND_THICKNESS: f32 = 0.5;
+pub const FORECAST_BAND_ALPHA: f32 = 0.35;
+
+/// Parameters used to project each loss series forward. The forecast is a damped trend fit from
+/// the first and second derivatives of the historical values, and the band widens with the spread
+/// of the first derivative.
+#[derive(Resource, Clone, Debug)]
+pub struct ForecastConfiguration {
+    pub(crate) horizon: usize,
+    pub(crate) target_loss: f32,
+    pub(crate) ema_alpha: f32,
+    pub(crate) confidence_z: f32,
+    pub(crate) max_damping: f32,
+}
+
+impl Default for ForecastConfiguration {
+    fn default() -> Self {
+        Self {
+            horizon: 20,
+            target_loss: 0.05,
+            ema_alpha: 0.88,
+            confidence_z: 1.96,
+            max_damping: 0.99,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LossForecast {
+    pub(crate) predicted: Vec<f32>,
+    pub(crate) lower: Vec<f32>,
+    pub(crate) upper: Vec<f32>,
+    /// Number of steps after the latest value until the predicted loss reaches the target.
+    pub(crate) steps_to_target: Option<usize>,
+}
+
+/// The forecasts for each column of a metric, one entry per element of the column.
+#[derive(Component, Clone, Debug, Default)]
+pub struct MetricForecast {
+    pub(crate) forecasts: HashMap<String, Vec<Option<LossForecast>>>,
+    /// The timestep at which each series is predicted to reach the target loss.
+    pub(crate) predicted_target_step: HashMap<String, Vec<Option<u64>>>,
+    series: HashMap<(String, usize), Entity>,
+}
+
+/// The dashed extension and the confidence band for a single series. The polyline assets are kept
+/// so that the vertices can be replaced in place when a new timestep arrives.
+#[derive(Component, Clone, Debug)]
+pub struct ForecastSeries {
+    pub(crate) column: String,
+    pub(crate) index: usize,
+    dashes: Vec<Handle<Polyline>>,
+    upper: Handle<Polyline>,
+    lower: Handle<Polyline>,
+}
+
+impl MetricForecast {
+    pub(crate) fn get_forecast(&self, column: &str, index: usize) -> Option<&LossForecast> {
+        self.forecasts.get(column)
+            .map(|forecasts| forecasts.get(index))
+            .flatten()
+            .map(|forecast| forecast.as_ref())
+            .flatten()
+    }
+}
+
+/// Projects the series `horizon` steps forward. Returns None if there are not enough values to
+/// calculate the second derivative.
+pub(crate) fn forecast_series(
+    values: &Array1<f32>,
+    config: &ForecastConfiguration
+) -> Option<LossForecast> {
+    if values.len() < 3 || config.horizon == 0 {
+        return None;
+    }
+
+    let derivs = calculate_derivatives(values, 2);
+    let first_deriv = derivs.get(0)?;
+    let second_deriv = derivs.get(1)?;
+
+    let slope = ema(first_deriv, config.ema_alpha);
+    let curvature = ema(second_deriv, config.ema_alpha);
+    let damping = estimate_damping(slope, curvature, config.max_damping);
+
+    let sigma = if first_deriv.len() > 1 {
+        first_deriv.std(1.0)
+    } else {
+        0.0
+    };
+
+    let latest = values[values.len() - 1];
+
+    let mut predicted = Vec::with_capacity(config.horizon);
+    let mut lower = Vec::with_capacity(config.horizon);
+    let mut upper = Vec::with_capacity(config.horizon);
+
+    let mut cumulative_trend = 0.0;
+    let mut damping_pow = 1.0;
+    for step in 1..config.horizon + 1 {
+        damping_pow *= damping;
+        cumulative_trend += slope * damping_pow;
+        let value = latest + cumulative_trend;
+        let spread = config.confidence_z * sigma * (step as f32).sqrt();
+        predicted.push(value);
+        lower.push(value - spread);
+        upper.push(value + spread);
+    }
+
+    let steps_to_target = steps_to_target(latest, &predicted, config.target_loss);
+
+    Some(LossForecast {
+        predicted,
+        lower,
+        upper,
+        steps_to_target,
+    })
+}
+
+fn ema(values: &Array1<f32>, alpha: f32) -> f32 {
+    if values.len() == 0 {
+        return 0.0;
+    }
+    let mut ema_value = values[0];
+    for i in 1..values.len() {
+        ema_value = alpha * values[i] + (1.0 - alpha) * ema_value;
+    }
+    ema_value
+}
+
+/// When the second derivative works against the slope, the slope is shrinking, so the ratio of
+/// the next slope to the current slope is used as the damping factor for the trend.
+fn estimate_damping(slope: f32, curvature: f32, max_damping: f32) -> f32 {
+    if slope == 0.0 || !slope.is_finite() || !curvature.is_finite() {
+        return 0.0;
+    }
+    ((slope + curvature) / slope)
+        .max(0.0)
+        .min(max_damping)
+}
+
+fn steps_to_target(latest: f32, predicted: &Vec<f32>, target: f32) -> Option<usize> {
+    if latest <= target {
+        return Some(0);
+    }
+    predicted.iter()
+        .position(|value| *value <= target)
+        .map(|position| position + 1)
+}
+
+/// Fits a forecast for each Loss metric when new historical data arrives, and replaces the
+/// vertices of the dashed extension and band for each series.
+pub(crate) fn update_forecasts<T>(
+    mut commands: Commands,
+    mut metrics: Query<
+        (Entity, &Metric<T>, &DataSeries, Option<&mut MetricForecast>),
+        WithDataSeriesChangedHistorical
+    >,
+    forecast_series_query: Query<&ForecastSeries>,
+    forecast_config: Res<ForecastConfiguration>,
+    dims: Res<GraphConfigurationResource<T>>,
+    mut polylines: ResMut<Assets<Polyline>>,
+    mut polyline_materials: ResMut<Assets<PolylineMaterial>>,
+)
+where
+    T: Component + Send + Sync + 'static + Debug
+{
+    for (metric_entity, metric, series, metric_forecast) in metrics.iter_mut() {
+        if !matches!(metric.metric_type, MetricType::Loss(_)) {
+            continue;
+        }
+
+        let columns = dims.series_dims.get(&metric_entity);
+        if columns.is_none() {
+            continue;
+        }
+        let columns = columns.unwrap();
+
+        let mut created_forecast = None;
+        let forecast = match metric_forecast {
+            Some(forecast) => forecast.into_inner(),
+            None => created_forecast.insert(MetricForecast::default())
+        };
+
+        let latest_timestep = metric.historical.timestep.keys().last().cloned()
+            .or(Some(0))
+            .unwrap();
+
+        let angle_increment = 2.0 * std::f32::consts::PI / columns.len() as f32;
+
+        columns.iter()
+            .enumerate()
+            .filter(|(_, graph_dim)| graph_dim.dim_type.iter()
+                .any(|dim_type| matches!(dim_type, GraphDimType::RadialCoordinate))
+            )
+            .for_each(|(column_index, graph_dim)| {
+                let historical = metric.historical.retrieve_historical_1d(&graph_dim.name);
+                let forecasts = historical.iter()
+                    .map(|values| {
+                        // the first row of the historical data is the zeroed placeholder.
+                        let values = values.slice(s![1..]).to_owned();
+                        forecast_series(&values, &forecast_config)
+                    })
+                    .collect::<Vec<Option<LossForecast>>>();
+
+                debug!("Updated {} forecasts for {}.", forecasts.len(), &graph_dim.name);
+
+                let target_steps = forecasts.iter()
+                    .map(|forecast| forecast.as_ref()
+                        .map(|f| f.steps_to_target.map(|steps| latest_timestep + steps as u64))
+                        .flatten()
+                    )
+                    .collect::<Vec<Option<u64>>>();
+
+                let dim_component = metric.metric_dim_component_children.get(&graph_dim.name);
+                if dim_component.is_none() {
+                    error!("Metric did not contain dimension {}. Not drawing forecast.", &graph_dim.name);
+                } else {
+                    let (dim_entity, color) = dim_component.unwrap();
+                    let angle = column_index as f32 * angle_increment;
+                    forecasts.iter()
+                        .enumerate()
+                        .for_each(|(index, next)| {
+                            if let Some(next) = next {
+                                let current_time = series.prev_convergence_times.get(&graph_dim.name)
+                                    .map(|times| times.get(index).cloned().flatten())
+                                    .flatten()
+                                    .or(Some(0.0))
+                                    .unwrap();
+                                let lines = ForecastLines::new(
+                                    next,
+                                    historical[index][historical[index].len() - 1],
+                                    current_time,
+                                    angle.sin(),
+                                );
+                                draw_forecast(
+                                    &mut commands,
+                                    &mut forecast.series,
+                                    &forecast_series_query,
+                                    &mut polylines,
+                                    &mut polyline_materials,
+                                    *dim_entity,
+                                    *color,
+                                    &graph_dim.name,
+                                    index,
+                                    lines,
+                                );
+                            } else {
+                                remove_forecast(&mut commands, &mut forecast.series, &graph_dim.name, index);
+                            }
+                        });
+                }
+
+                forecast.forecasts.insert(graph_dim.name.clone(), forecasts);
+                forecast.predicted_target_step.insert(graph_dim.name.clone(), target_steps);
+            });
+
+        if let Some(created) = created_forecast {
+            commands.entity(metric_entity)
+                .insert(created);
+        }
+    }
+}
+
+/// The forecast converted into the coordinates used by the radial graph.
+struct ForecastLines {
+    predicted: Vec<Vec3>,
+    upper: Vec<Vec3>,
+    lower: Vec<Vec3>,
+}
+
+impl ForecastLines {
+    /// The forecast spreads over the remaining convergence time, starting at the latest point.
+    fn new(forecast: &LossForecast, latest: f32, current_time: f32, sin: f32) -> Self {
+        let radius = 100.0;
+        let origin_height = 100.0;
+        let horizon = forecast.predicted.len() as f32;
+        let x_increment = (1.0 - current_time).max(0.0) / horizon;
+        let start = Vec3::new(1.0 - current_time, origin_height - latest, sin * radius);
+        let to_points = |values: &Vec<f32>| {
+            let mut points = vec![start];
+            values.iter()
+                .enumerate()
+                .for_each(|(step, value)| {
+                    let x = 1.0 - current_time - (step + 1) as f32 * x_increment;
+                    points.push(Vec3::new(x, origin_height - value, sin * radius));
+                });
+            points
+        };
+        Self {
+            predicted: to_points(&forecast.predicted),
+            upper: to_points(&forecast.upper),
+            lower: to_points(&forecast.lower),
+        }
+    }
+
+    /// Every other segment of the prediction, so the extension is drawn dashed.
+    fn dashes(&self) -> Vec<Vec<Vec3>> {
+        self.predicted.windows(2)
+            .step_by(2)
+            .map(|segment| segment.to_vec())
+            .collect()
+    }
+}
+
+fn draw_forecast(
+    commands: &mut Commands,
+    series_entities: &mut HashMap<(String, usize), Entity>,
+    forecast_series_query: &Query<&ForecastSeries>,
+    polylines: &mut ResMut<Assets<Polyline>>,
+    polyline_materials: &mut ResMut<Assets<PolylineMaterial>>,
+    dim_entity: Entity,
+    color: Color,
+    column: &str,
+    index: usize,
+    lines: ForecastLines,
+) {
+    let dashes = lines.dashes();
+    let existing = series_entities.get(&(column.to_string(), index))
+        .map(|entity| forecast_series_query.get(*entity).ok())
+        .flatten();
+
+    if let Some(existing) = existing {
+        if existing.dashes.len() == dashes.len() {
+            existing.dashes.iter()
+                .zip(dashes.into_iter())
+                .for_each(|(handle, vertices)| replace_vertices(polylines, handle, vertices));
+            replace_vertices(polylines, &existing.upper, lines.upper);
+            replace_vertices(polylines, &existing.lower, lines.lower);
+            return;
+        }
+    }
+
+    let line_material = polyline_materials.add(PolylineMaterial {
+        color,
+        width: FORECAST_LINE_THICKNESS,
+        ..default()
+    });
+    let band_material = polyline_materials.add(PolylineMaterial {
+        color: color.with_a(FORECAST_BAND_ALPHA),
+        width: FORECAST_BAND_THICKNESS,
+        ..default()
+    });
+
+    let mut children = vec![];
+    let dash_handles = dashes.into_iter()
+        .map(|vertices| {
+            let handle = polylines.add(Polyline { vertices });
+            children.push(commands.spawn(PolylineBundle {
+                polyline: handle.clone(),
+                material: line_material.clone(),
+                ..default()
+            }).id());
+            handle
+        })
+        .collect::<Vec<Handle<Polyline>>>();
+
+    let upper = polylines.add(Polyline { vertices: lines.upper });
+    let lower = polylines.add(Polyline { vertices: lines.lower });
+    children.push(commands.spawn(PolylineBundle {
+        polyline: upper.clone(),
+        material: band_material.clone(),
+        ..default()
+    }).id());
+    children.push(commands.spawn(PolylineBundle {
+        polyline: lower.clone(),
+        material: band_material,
+        ..default()
+    }).id());
+
+    let forecast_entity = commands.spawn((
+            ForecastSeries {
+                column: column.to_string(),
+                index,
+                dashes: dash_handles,
+                upper,
+                lower,
+            },
+            PbrBundle::default()
+        ))
+        .push_children(children.as_slice())
+        .id();
+
+    if let Some(prev) = series_entities.insert((column.to_string(), index), forecast_entity) {
+        commands.entity(prev).despawn_recursive();
+    }
+
+    commands.get_entity(dim_entity)
+        .as_mut()
+        .map(|dim| dim.add_child(forecast_entity));
+}
+
+/// The forecast of a series that can no longer be projected is removed, so that a stale forecast
+/// is not left drawn.
+fn remove_forecast(
+    commands: &mut Commands,
+    series_entities: &mut HashMap<(String, usize), Entity>,
+    column: &str,
+    index: usize,
+) {
+    if let Some(prev) = series_entities.remove(&(column.to_string(), index)) {
+        commands.get_entity(prev)
+            .map(|prev| prev.despawn_recursive());
+    }
+}
+
+fn replace_vertices(polylines: &mut ResMut<Assets<Polyline>>, handle: &Handle<Polyline>, vertices: Vec<Vec3>) {
+    polylines.get_mut(handle)
+        .map(|polyline| polyline.vertices = vertices);
+}
@@ -1,18 +1,19 @@
 use std::collections::{BTreeSet, HashMap};
 use std::fmt::Debug;
-use bevy::log::{error, info};
+use bevy::log::{error, info, warn};
 use bevy::pbr::PbrBundle;
-use bevy::prelude::{BuildChildren, Color, Commands, Component, Entity, EventReader, Mut, Query, Res, ResMut, Resource};
+use bevy::prelude::{BuildChildren, Color, Commands, Component, default, Entity, EventReader, Mut, Query, Res, ResMut, Resource, Visibility};
 use bevy_mod_picking::PickableBundle;
 use crate::config::ConfigurationProperties;
+use crate::config::palette::Palette;
 use crate::cursor_adapter::PickableComponent;
 use crate::data_subscriber::metric_event::{MetricsState, NetworkMetricsServiceEvent};
 use crate::graph::{DataSeries, GraphConfigurationResource, GraphDim, GraphDimComponent, GraphingMetricsResource, GridAxis};
+use crate::graph::draw_graph_points::SeriesPolylines;
 use crate::menu::graphing_menu::graph_menu::{ChangeGraphingMenu, GraphMenuPotential};
 use crate::menu::ui_menu_event::transition_groups::PropagateCreateMenu;
 use crate::metrics::network_metrics::{Metric, MetricType, MetricTypeMatcher};
 use crate::ndarray::get_arr_from_vec;
-use crate::util::gen_color_from_list;
 
 
 #[derive(Component, Default, Debug)]
@@ -37,13 +38,14 @@ pub fn read_metric_events<T, U>(
 {
     for mut event in event_read.iter() {
         let metric_name = event.metric_name();
-        info!("Receiving network event: {}.", metric_name);
-        if metrics_lookup.entities.contains_key(metric_name) {
+        let run_id = event.run_id();
+        info!("Receiving network event: {} for run {}.", metric_name, run_id);
+        if metrics_lookup.get_entity(run_id, metric_name).is_some() {
             add_data_to_current_metric(&mut commands, &mut metrics_lookup, &mut component_query, event, metric_name);
         } else {
             create_new_metric(&mut commands, &mut metrics_lookup, &config_properties, &mut graph_dim_config, &mut graph_config, event, metric_name);
         }
-        metrics_lookup.increment_entity(metric_name);
+        metrics_lookup.increment_entity(run_id, metric_name);
     }
 }
 
@@ -81,12 +83,11 @@ fn add_data_to_current_metric<T, U>(
         T: NetworkMetricsServiceEvent<U> + 'static + Debug,
         U: Component
 {
-    let (entity, timestep) = metrics_lookup.entities
-        .get(metric_name)
+    let (entity, timestep) = metrics_lookup.get_entity(event.run_id(), metric_name)
         .unwrap();
-    let _ = component_query.get_mut(*entity)
+    let _ = component_query.get_mut(entity)
         .as_mut()
-        .map(|(entity, metric)| extend_historical(&mut commands, event, timestep, entity, metric))
+        .map(|(entity, metric)| extend_historical(&mut commands, event, &timestep, entity, metric))
         .or_else(|e| {
             error!("Could not extend metric: {:?}.", e);
             Err(e)
@@ -117,7 +118,9 @@ fn create_add_metric<U, T>(
 
     let columns = get_graph_dims(&config_properties, &mut metric);
 
-    add_metric_to_world(&mut commands, &mut metrics_lookup, &mut graph_dim_config, &mut graph_config, metric_name, metric, columns);
+    let palette = config_properties.palette.graph_palette(metric_name);
+
+    add_metric_to_world(&mut commands, &mut metrics_lookup, &mut graph_dim_config, &mut graph_config, metric_name, metric, columns, palette);
 }
 
 fn create_metric_struct<U, T>(
@@ -134,7 +137,8 @@ fn create_metric_struct<U, T>(
         event.get_shape().clone(),
         metric_type,
         columns,
-        event.metric_indices()
+        event.metric_indices(),
+        event.run_id()
     );
     metric
 }
@@ -144,6 +148,8 @@ fn add_historical<U, T>(mut event: &T, mut metric: &mut Metric<U>)
         U: Component + 'static,
         T: NetworkMetricsServiceEvent<U> + Debug + 'static
 {
+    event.wall_clock()
+        .map(|wall_clock| metric.historical.add_wall_clock(1, wall_clock));
     let _ = get_arr_from_vec(event.get_data(), event.get_shape())
         .map(|arr| metric.historical.extend(arr, 1))
         .or_else(|e| {
@@ -160,11 +166,22 @@ fn add_metric_to_world<U>(
     metric_name: &str,
     mut metric: Metric<U>,
     columns: Vec<GraphDim>,
+    palette: Palette,
 )
     where U: Component + 'static
 {
 
-    let colors = gen_color_from_list(columns.len() as f32);
+    let run_id = metric.run_id.clone();
+    let run = metrics_lookup.add_run(&run_id, palette).clone();
+    if !palette.distinguishable(columns.len()) {
+        warn!("The {} columns of {} cannot all be told apart with the {} palette.", columns.len(), metric_name, palette.name());
+    }
+    let colors = run.column_colors(palette, columns.len());
+    let visibility = if run.visible {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
 
     let graph_dim_components = columns.iter()
         .enumerate()
@@ -173,6 +190,7 @@ fn add_metric_to_world<U>(
                 GraphDimComponent {
                     name: grid_dim.name.to_string(),
                 },
+                SeriesPolylines::default(),
                 PbrBundle::default(),
                 PickableBundle::default(),
                 PickableComponent::GraphDim,
@@ -201,7 +219,10 @@ fn add_metric_to_world<U>(
             drawn: BTreeSet::default(),
             prev_convergence_times: Default::default()
         },
-        PbrBundle::default(),
+        PbrBundle {
+            visibility,
+            ..default()
+        },
         PickableBundle::default(),
         HistoricalUpdated::default(),
         PickableComponent::Metric
@@ -221,7 +242,7 @@ fn add_metric_to_world<U>(
             metric.push_children(graph_dim_entities);
         });
 
-    metrics_lookup.entities.insert(metric_name.to_string(), (metric_id, 0));
+    metrics_lookup.insert_entity(&run_id, metric_name, metric_id, 0);
 
     graph_dim_config.series_dims.insert(metric_id, columns);
 
@@ -246,6 +267,8 @@ fn extend_historical<T, U>(mut commands: &mut Commands, mut event: &T, timestep:
         U: Component + 'static
 {
     info!("Extending network: {:?}.", &event);
+    event.wall_clock()
+        .map(|wall_clock| metric.historical.add_wall_clock(timestep + 1, wall_clock));
     let _ = get_arr_from_vec(event.get_data(), event.get_shape())
         .map(|arr| metric.historical.extend(arr, timestep + 1))
         .or_else(|e| {
@@ -2,8 +2,12 @@ use bevy::prelude::*;
 use bevy_polyline::PolylinePlugin;
 use bevy_polyline::prelude::PolylineMaterial;
 use crate::graph::{GraphParent, setup_graph, GraphConfigurationResource, GraphingMetricsResource};
-use crate::graph::draw_graph_points::draw_graph_points;
+use crate::graph::draw_graph_points::{draw_graph_points, remove_deleted_series, SeriesAssets};
+use crate::graph::forecast::{ForecastConfiguration, update_forecasts};
 use crate::graph::radial::RadialGraphPoints;
+use crate::graph::run_comparison::{RunComparison, show_runs_menu, toggle_alignment, toggle_runs, update_run_horizons, update_runs_menu};
+use crate::data_subscriber::metric_event::MetricsState;
+use crate::network::{Layer, MetricChildNodes, Network, Node};
 
 pub struct GraphPlugin;
 
@@ -12,7 +16,29 @@ impl Plugin for GraphPlugin {
         app.add_plugins(PolylinePlugin)
             .insert_resource(GraphConfigurationResource::<Node>::default())
             .add_startup_system(setup_graph::setup_graph)
+            .insert_resource(ForecastConfiguration::default())
+            .insert_resource(SeriesAssets::default())
+            .insert_resource(RunComparison::default())
+            .init_resource::<MetricsState>()
+            .add_system(update_runs_menu)
+            .add_system(toggle_runs)
+            .add_system(toggle_alignment)
+            .add_system(show_runs_menu)
             .add_system(draw_graph_points::<Node, RadialGraphPoints>)
+            .add_system(remove_deleted_series)
+            .add_system(update_forecasts::<Node>
+                .after(draw_graph_points::<Node, RadialGraphPoints>)
+            )
         ;
+        add_run_comparison_systems::<Node>(app);
+        add_run_comparison_systems::<Layer>(app);
+        add_run_comparison_systems::<Network>(app);
+        add_run_comparison_systems::<MetricChildNodes>(app);
     }
 }
+
+fn add_run_comparison_systems<T>(app: &mut App)
+    where T: Component
+{
+    app.add_system(update_run_horizons::<T>.before(draw_graph_points::<Node, RadialGraphPoints>));
+}
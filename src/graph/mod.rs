@@ -19,6 +19,10 @@ pub(crate) mod draw_graph_points;
 pub(crate) mod graph_plugin;
 pub(crate) mod radial;
 pub(crate) mod graph_data_event_reader;
+/// Projects loss series forward from their derivatives, drawn as a dashed extension with a band.
+pub(crate) mod forecast;
+/// Overlays the same metric from multiple runs, with a menu to toggle the runs.
+pub(crate) mod run_comparison;
 
 
 pub const GRID_SIZE: f32 = 100.0;
@@ -61,6 +65,26 @@ pub struct GraphingMetricsResource
     pub(crate) state_transition_completed: Vec<Entity>
 }
 
+impl GraphingMetricsResource {
+    /// The metrics indexed by any of the indices.
+    pub(crate) fn metrics_for_indices<'a>(&self, indices: impl IntoIterator<Item = &'a String>) -> HashSet<Entity> {
+        indices.into_iter()
+            .flat_map(|index| self.graphing_indices.get(index))
+            .flat_map(|metrics| metrics.iter().cloned())
+            .collect()
+    }
+
+    /// The metrics that share an index with the metric, not including the metric.
+    pub(crate) fn related_metrics(&self, metric: Entity) -> HashSet<Entity> {
+        let indices = self.graphing_indices.iter()
+            .filter(|(_, metrics)| metrics.contains(&metric))
+            .map(|(index, _)| index);
+        let mut related = self.metrics_for_indices(indices);
+        related.remove(&metric);
+        related
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Grid {
     x_axis: Entity,
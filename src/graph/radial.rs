@@ -9,7 +9,7 @@ use ndarray_stats::{CorrelationExt, SummaryStatisticsExt};
 use statrs::distribution::{ContinuousCDF, Normal};
 use crate::graph;
 use crate::graph::{DataSeries, GraphConfigurationResource, GraphDim, GraphDimType};
-use crate::graph::draw_graph_points::{create_data_segment, GraphingStrategy};
+use crate::graph::draw_graph_points::{GraphingStrategy, SeriesPoint};
 use crate::metrics::network_metrics::Metric;
 
 pub struct RadialGraphPoints;
@@ -18,17 +18,15 @@ impl<T> GraphingStrategy<T> for RadialGraphPoints
     where
         T: Component + Send + Sync + 'static
 {
+    const NAME: &'static str = "Radial";
+
     fn create_update_graph(
-        mut commands: &mut Commands,
         metric: &Metric<T>,
         series: &mut Mut<DataSeries>,
         columns: &mut Vec<GraphDim>,
-        mut meshes: &mut ResMut<Assets<Mesh>>,
-        mut polylines: &mut ResMut<Assets<Polyline>>,
-        mut polyline_materials: &mut ResMut<Assets<PolylineMaterial>>,
         num_col: usize,
         key: &u64,
-    ) {
+    ) -> Vec<SeriesPoint> {
         let angle_increment = 2.0 * PI / num_col as f32;
         let radius = 100.0;
         let origin_height = 100.0;
@@ -104,7 +102,15 @@ impl<T> GraphingStrategy<T> for RadialGraphPoints
                         if !metric.metric_dim_component_children.contains_key(graph_dim_name) {
                             error!("Metric did not contain dimension! Not adding graphing points to be drawn.");
                         } else {
-                            points.push((start, end, metric.metric_dim_component_children.get(graph_dim_name).unwrap()));
+                            let (graph_dim_entity, color) = metric.metric_dim_component_children.get(graph_dim_name).unwrap();
+                            points.push(SeriesPoint {
+                                graph_dim_entity: *graph_dim_entity,
+                                color: *color,
+                                index: i,
+                                start,
+                                end,
+                                step: *key,
+                            });
                         }
 
                     }
@@ -116,21 +122,7 @@ impl<T> GraphingStrategy<T> for RadialGraphPoints
 
         series.prev_convergence_times = convergence_times;
 
-        points.into_iter()
-            .for_each(|(start, end, graph_dim_component)| {
-                let data_segment = create_data_segment(
-                    &mut commands,
-                    start,
-                    end,
-                    &mut meshes,
-                    &mut polylines,
-                    &mut polyline_materials,
-                    1.0,
-                );
-                commands.get_entity(graph_dim_component.0)
-                    .as_mut()
-                    .map(|graph_dim_entity| graph_dim_entity.add_child(data_segment));
-            });
+        points
     }
 }
 
@@ -233,7 +225,7 @@ pub(crate) fn calculate_derivatives(loss_values: &Array1<f32>, n_derivatives: us
     let mut derivatives: Vec<Array1<f32>> = vec![];
 
     for i in 0..n_derivatives  {
-        let next_deriv_size = n - (i + 1);
+        let next_deriv_size = n.saturating_sub(i + 1);
 
         if next_deriv_size <= 0 {
             error!("Derivative size {} and number of derivatives {} not valid and produced negative derivative size.", next_deriv_size, n_derivatives);
@@ -0,0 +1,335 @@
+use std::collections::HashMap;
+use bevy::prelude::*;
+use bevy_polyline::prelude::Polyline;
+use crate::config::theme::{DEFAULT_FONT, Theme, ThemeColor};
+use crate::data_subscriber::metric_event::MetricsState;
+use crate::graph::{DataSeries, GraphDimComponent, GraphParent};
+use crate::graph::draw_graph_points::{SeriesPoint, SeriesPolylines};
+use crate::graph::graph_data_event_reader::HistoricalUpdated;
+use crate::metrics::network_metrics::Metric;
+use crate::theme::restyle::{ThemedBackground, ThemedText};
+
+/// How the same metric from multiple runs is lined up when it is overlaid on the graph.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub enum RunAlignment {
+    #[default]
+    Step,
+    WallClock
+}
+
+impl RunAlignment {
+    fn label(&self) -> &'static str {
+        match self {
+            RunAlignment::Step => "Align: Step",
+            RunAlignment::WallClock => "Align: Wall Clock"
+        }
+    }
+}
+
+/// The x distance a step, or a second of wall-clock time, is drawn apart when runs are compared.
+pub const ALIGNED_TIME_SCALE: f32 = 0.01;
+
+/// When a metric is received from more than one visible run, the series are only drawn up to the
+/// horizon, the latest aligned time all of the visible runs have reached, so that the overlays
+/// grow together. The points of the compared metrics are drawn at their aligned time along x, so
+/// that the runs start from the same origin.
+#[derive(Resource, Default, Debug)]
+pub struct RunComparison {
+    pub(crate) alignment: RunAlignment,
+    pub(crate) horizons: HashMap<Entity, f32>,
+}
+
+impl RunComparison {
+    pub(crate) fn is_compared(&self, metric_entity: Entity) -> bool {
+        self.horizons.contains_key(&metric_entity)
+    }
+
+    /// Moves the points of a compared metric along x to the aligned time of their timesteps, the
+    /// start of each point being the timestep before it.
+    pub(crate) fn align_points<T: Component>(&self, metric_entity: Entity, metric: &Metric<T>, points: &mut Vec<SeriesPoint>) {
+        if !self.is_compared(metric_entity) {
+            return;
+        }
+        let x = |timestep: u64| metric.historical.aligned_time(timestep, &self.alignment)
+            .map(|time| 1.0 - time * ALIGNED_TIME_SCALE);
+        for point in points.iter_mut() {
+            let previous = metric.historical.timestep.range(..point.step)
+                .next_back()
+                .map(|(timestep, _)| *timestep)
+                .or(Some(point.step))
+                .unwrap();
+            if let (Some(start), Some(end)) = (x(previous), x(point.step)) {
+                point.start.x = start;
+                point.end.x = end;
+            }
+        }
+    }
+
+    pub(crate) fn within_horizon<T: Component>(&self, metric_entity: Entity, metric: &Metric<T>, timestep: u64) -> bool {
+        self.horizons.get(&metric_entity)
+            .map(|horizon| metric.historical.aligned_time(timestep, &self.alignment)
+                .map(|time| time <= *horizon)
+                .or(Some(true))
+                .unwrap()
+            )
+            .or(Some(true))
+            .unwrap()
+    }
+}
+
+/// Clears what was drawn for the metric, keeping the polylines of the series to be appended to
+/// again, so that it is redrawn from its first timestep.
+pub(crate) fn clear_drawn_series<T: Component>(
+    metric: &Metric<T>,
+    series: &mut DataSeries,
+    metric_dims: &mut Query<&mut SeriesPolylines, With<GraphDimComponent>>,
+    polylines: &mut Assets<Polyline>,
+) {
+    series.drawn.clear();
+    series.prev_convergence_times.clear();
+    for (graph_dim_entity, _) in metric.metric_dim_component_children.values() {
+        let _ = metric_dims.get_mut(*graph_dim_entity)
+            .map(|mut series_polylines| {
+                let series_polylines = &mut *series_polylines;
+                series_polylines.lines.iter()
+                    .for_each(|(_, polyline)| {
+                        polylines.get_mut(polyline)
+                            .map(|polyline| polyline.vertices.clear());
+                    });
+                series_polylines.steps.iter_mut()
+                    .for_each(|steps| steps.clear());
+            });
+    }
+}
+
+#[derive(Component, Default, Debug)]
+pub struct RunsMenu;
+
+#[derive(Component, Debug)]
+pub struct RunToggle {
+    pub(crate) run_id: String
+}
+
+#[derive(Component, Default, Debug)]
+pub struct AlignmentToggle;
+
+/// Updates the horizons of the metrics of the component type that are compared, leaving those of
+/// the other component types. The metrics that start or stop being compared, and all of the
+/// compared metrics when the alignment changes, are cleared to be redrawn at their new x.
+pub(crate) fn update_run_horizons<T>(
+    mut commands: Commands,
+    metrics_state: Res<MetricsState>,
+    mut run_comparison: ResMut<RunComparison>,
+    mut drawn_alignment: Local<RunAlignment>,
+    mut metrics: Query<(&Metric<T>, &mut DataSeries)>,
+    mut metric_dims: Query<&mut SeriesPolylines, With<GraphDimComponent>>,
+    mut polylines: ResMut<Assets<Polyline>>,
+)
+    where T: Component
+{
+    let mut metric_runs: HashMap<&str, Vec<Entity>> = HashMap::new();
+    metrics_state.entities.iter()
+        .filter(|((run_id, _), (entity, _))| metrics_state.is_visible(run_id) && metrics.contains(*entity))
+        .for_each(|((_, metric_name), (entity, _))| {
+            metric_runs.entry(metric_name.as_str())
+                .or_insert_with(Vec::new)
+                .push(*entity);
+        });
+
+    let mut horizons = HashMap::new();
+
+    for entities in metric_runs.values().filter(|entities| entities.len() > 1) {
+        let horizon = entities.iter()
+            .flat_map(|entity| metrics.get(*entity).ok())
+            .flat_map(|(metric, _)| metric.historical.timestep.keys()
+                .last()
+                .and_then(|last| metric.historical.aligned_time(*last, &run_comparison.alignment))
+            )
+            .reduce(f32::min);
+        if let Some(horizon) = horizon {
+            entities.iter().for_each(|entity| {
+                horizons.insert(*entity, horizon);
+            });
+        }
+    }
+
+    let alignment_changed = *drawn_alignment != run_comparison.alignment;
+    *drawn_alignment = run_comparison.alignment.clone();
+
+    let previous = run_comparison.horizons.iter()
+        .filter(|(entity, _)| metrics.contains(**entity))
+        .map(|(entity, horizon)| (*entity, *horizon))
+        .collect::<HashMap<Entity, f32>>();
+
+    // the metrics that start or stop being compared, or are compared when the alignment changes,
+    // are redrawn from the start, and those that were held back when the horizon moves past them.
+    let cleared = horizons.keys()
+        .filter(|entity| alignment_changed || !previous.contains_key(*entity))
+        .chain(previous.keys().filter(|entity| !horizons.contains_key(*entity)))
+        .cloned()
+        .collect::<Vec<Entity>>();
+    for entity in cleared.iter() {
+        let _ = metrics.get_mut(*entity)
+            .map(|(metric, mut series)| clear_drawn_series(metric, &mut series, &mut metric_dims, &mut polylines));
+    }
+    let advanced = horizons.iter()
+        .filter(|(entity, horizon)| previous.get(*entity).map(|prev| prev < *horizon).or(Some(false)).unwrap())
+        .map(|(entity, _)| *entity);
+    for entity in cleared.iter().cloned().chain(advanced) {
+        commands.get_entity(entity)
+            .as_mut()
+            .map(|metric| metric.insert(HistoricalUpdated::default()));
+    }
+
+    if previous != horizons {
+        run_comparison.horizons.retain(|entity, _| !metrics.contains(*entity));
+        run_comparison.horizons.extend(horizons);
+    }
+}
+
+pub(crate) fn update_runs_menu(
+    mut commands: Commands,
+    metrics_state: Res<MetricsState>,
+    run_comparison: Res<RunComparison>,
+    asset_server: Res<AssetServer>,
+    runs_menu: Query<Entity, With<RunsMenu>>,
+    toggles: Query<&RunToggle>,
+) {
+    if !metrics_state.is_changed() || metrics_state.runs.len() < 2 {
+        return;
+    }
+
+    let runs_menu = runs_menu.get_single()
+        .ok()
+        .or_else(|| {
+            let runs_menu = commands.spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        right: Val::Px(10.0),
+                        top: Val::Px(10.0),
+                        display: Display::Flex,
+                        flex_direction: FlexDirection::Column,
+                        ..default()
+                    },
+                    ..default()
+                },
+                RunsMenu::default()
+            )).id();
+            let alignment_toggle = spawn_toggle(
+                &mut commands, &asset_server, run_comparison.alignment.label(), Color::NONE,
+                (AlignmentToggle::default(), ThemedBackground(ThemeColor::PanelEntry))
+            );
+            commands.entity(runs_menu).add_child(alignment_toggle);
+            Some(runs_menu)
+        })
+        .unwrap();
+
+    for (run_id, run) in metrics_state.runs.iter() {
+        if toggles.iter().any(|toggle| &toggle.run_id == run_id) {
+            continue;
+        }
+        let run_toggle = spawn_toggle(
+            &mut commands, &asset_server, run_id.as_str(), run.color,
+            RunToggle { run_id: run_id.clone() }
+        );
+        commands.entity(runs_menu).add_child(run_toggle);
+    }
+}
+
+fn spawn_toggle(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    name: &str,
+    color: Color,
+    toggle: impl Bundle
+) -> Entity {
+    commands.spawn((
+        ButtonBundle {
+            style: Style {
+                padding: UiRect::all(Val::Px(4.0)),
+                margin: UiRect::all(Val::Px(2.0)),
+                ..default()
+            },
+            background_color: color.into(),
+            ..default()
+        },
+        toggle
+    ))
+        .with_children(|button| {
+            button.spawn((
+                TextBundle {
+                    text: Text::from_section(name.to_string(), TextStyle {
+                        font_size: 16.0,
+                        font: asset_server.load(DEFAULT_FONT),
+                        ..default()
+                    }),
+                    ..default()
+                },
+                ThemedText(ThemeColor::PanelText)
+            ));
+        })
+        .id()
+}
+
+/// Clicking a run in the runs menu hides or shows all of the metrics received for the run, of
+/// every component type.
+pub(crate) fn toggle_runs(
+    mut commands: Commands,
+    mut metrics_state: ResMut<MetricsState>,
+    theme: Res<Theme>,
+    mut interactions: Query<(&Interaction, &RunToggle, &mut BackgroundColor), Changed<Interaction>>,
+) {
+    for (interaction, toggle, mut background) in interactions.iter_mut() {
+        if !matches!(interaction, Interaction::Pressed) {
+            continue;
+        }
+        let run = metrics_state.runs.get_mut(&toggle.run_id);
+        if run.is_none() {
+            continue;
+        }
+        let run = run.unwrap();
+        run.visible = !run.visible;
+        *background = BackgroundColor(if run.visible { run.color } else { theme.color(ThemeColor::MenuDeselected) });
+        let visibility = if run.visible { Visibility::Inherited } else { Visibility::Hidden };
+        metrics_state.entities.iter()
+            .filter(|((run_id, _), _)| *run_id == toggle.run_id)
+            .for_each(|(_, (entity, _))| {
+                commands.get_entity(entity)
+                    .as_mut()
+                    .map(|metric| metric.insert(visibility));
+            });
+    }
+}
+
+pub(crate) fn toggle_alignment(
+    mut run_comparison: ResMut<RunComparison>,
+    interactions: Query<(&Interaction, &Children), (Changed<Interaction>, With<AlignmentToggle>)>,
+    mut text: Query<&mut Text>,
+) {
+    for (interaction, children) in interactions.iter() {
+        if !matches!(interaction, Interaction::Pressed) {
+            continue;
+        }
+        run_comparison.alignment = match run_comparison.alignment {
+            RunAlignment::Step => RunAlignment::WallClock,
+            RunAlignment::WallClock => RunAlignment::Step
+        };
+        children.iter()
+            .for_each(|child| {
+                let _ = text.get_mut(*child)
+                    .map(|mut text| text.sections[0].value = run_comparison.alignment.label().to_string());
+            });
+    }
+}
+
+/// The runs menu is part of the graph menu, so it is shown with the graph.
+pub(crate) fn show_runs_menu(
+    graph: Query<&Visibility, (With<GraphParent>, Changed<Visibility>, Without<RunsMenu>)>,
+    mut runs_menu: Query<&mut Visibility, With<RunsMenu>>,
+) {
+    for graph_visibility in graph.iter() {
+        runs_menu.iter_mut()
+            .for_each(|mut visibility| *visibility = *graph_visibility);
+    }
+}
@@ -6,12 +6,14 @@ use bevy::pbr::{MaterialMeshBundle, PbrBundle};
 use bevy::hierarchy::BuildChildren;
 use bevy::log::error;
 use bevy_polyline::prelude::{Polyline, PolylineMaterial};
+use crate::config::theme::ThemeColor;
 use crate::event::state_transition::state_transitions_plugin::TransitionsState;
 use crate::graph::{Graph, GraphingMetricsResource, GraphParent, Grid, GRID_AXES_THICKNESS, GRID_LINES_THICKNESS, GRID_SIZE, GridAxis, NUM_GRIDLINES};
 use crate::lines::line_list::{create_3d_line, LineList};
 use crate::menu::config_menu_event::interaction_config_event_writer::{GraphMenuResultBuilder, NetworkMenuResultBuilder};
 use crate::menu::ui_menu_event::transition_groups::PropagateVisible;
 use crate::metrics::network_metrics::Metric;
+use crate::theme::restyle::ThemedPolyline;
 use crate::util;
 
 /// When a metric is added to the world, a graph is created for this metric, which has a series.
@@ -196,6 +198,7 @@ fn create_axes(
 }
 
 
+/// The line is colored by the grid color of the theme before it is first rendered.
 fn create_grid_line(
     commands: &mut Commands,
     start: Vec3,
@@ -210,11 +213,12 @@ fn create_grid_line(
     let polyline_bundle = create_3d_line(LineList {
         lines: vec![(start, end)],
         thickness,
-        color: Color::GREEN
+        color: Color::NONE
     }, polylines, polyline_materials);
 
     commands.spawn((
-           polyline_bundle
+           polyline_bundle,
+           ThemedPolyline(ThemeColor::Grid)
         ))
         .insert(parent)
         .id()
@@ -1,5 +1,6 @@
+use std::time::Duration;
 use bevy::a11y::AccessibilityPlugin;
-use bevy::app::PluginGroupBuilder;
+use bevy::app::{PluginGroupBuilder, ScheduleRunnerPlugin};
 use bevy::diagnostic::DiagnosticsPlugin;
 use bevy::input::InputPlugin;
 use bevy::log::LogPlugin;
@@ -10,7 +11,9 @@ use bevy::core_pipeline::CorePipelinePlugin;
 use bevy::pbr::PbrPlugin;
 use bevy::render::pipelined_rendering::PipelinedRenderingPlugin;
 use bevy::render::RenderPlugin;
+use bevy::render::settings::WgpuSettings;
 use bevy::sprite::SpritePlugin;
+use bevy::window::ExitCondition;
 use bevy::winit::WinitPlugin;
 
 pub struct NoRenderBevyIntegrationTestPlugin;
@@ -94,3 +97,25 @@ impl PluginGroup for NoRenderBevyIntegrationTestPlugin {
         group
     }
 }
+
+/// The integration test plugins without a window or a render backend, with the schedule run in a
+/// loop until an AppExit is sent, as there is no winit event loop to drive it.
+pub struct HeadlessPlugins;
+
+impl PluginGroup for HeadlessPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        NoRenderBevyIntegrationTestPlugin.build()
+            .set(RenderPlugin {
+                wgpu_settings: WgpuSettings {
+                    backends: None,
+                    ..default()
+                }
+            })
+            .set(WindowPlugin {
+                primary_window: None,
+                exit_condition: ExitCondition::DontExit,
+                close_when_requested: false
+            })
+            .add(ScheduleRunnerPlugin::run_loop(Duration::from_secs_f64(1.0 / 60.0)))
+    }
+}
@@ -0,0 +1,376 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Debug;
+use std::fs;
+use std::path::{Path, PathBuf};
+use bevy::app::AppExit;
+use bevy::log::LogPlugin;
+use bevy::prelude::*;
+use crate::config::ConfigurationProperties;
+use crate::config::headless::{HeadlessConfiguration, SnapshotFormat};
+use crate::data_subscriber::data_subscriber_plugin::DataSubscriberPlugin;
+use crate::data_subscriber::metric_event::{LayerMetricEvent, MetricsState, NetworkMetricEvent, NetworkMetricsServiceEvent, NodeChildrenMetricEvent, NodeMetricEvent};
+use crate::data_subscriber::recording::{RecordedStream, RecordingPlugin, replay_recording};
+use crate::export::{metric_rows, write_metric_rows, write_scene};
+use crate::export::gltf::{GltfGeometry, GltfMaterial, GltfScene};
+use crate::graph::{DataSeries, GraphConfigurationResource, GraphDimComponent, GraphingMetricsResource};
+use crate::graph::draw_graph_points::{GraphingStrategy, new_timesteps, SeriesPoint, WithDataSeriesChangedHistorical};
+use crate::graph::graph_data_event_reader::read_metric_events;
+use crate::graph::radial::RadialGraphPoints;
+use crate::graph::run_comparison::RunComparison;
+use crate::headless::default_plugins::HeadlessPlugins;
+use crate::headless::raster::{fit_lines, project, Raster, SnapshotLine, to_svg};
+use crate::metrics::derived_metrics::add_derived_metrics;
+use crate::metrics::network_metrics::Metric;
+use crate::network::{Layer, MetricChildNodes, Network, Node};
+use crate::util::file_name;
+
+/// The plugin groups used by the integration tests and the headless mode, without a window.
+pub(crate) mod default_plugins;
+/// Drawing the graphs to images on the CPU.
+pub(crate) mod raster;
+
+/// The argument that starts the headless mode instead of the window.
+pub const HEADLESS_ARG: &str = "--headless";
+/// The argument that runs without the window and exports the history of the metrics once the
+/// stream has finished.
+pub const EXPORT_ARG: &str = "--export";
+/// The argument that runs without the window and writes the series of the graph as a glTF scene
+/// once the stream has finished.
+pub const GLTF_ARG: &str = "--gltf";
+/// The name of the scene written by the headless mode, in the export directory.
+pub const HEADLESS_SCENE: &str = "scene";
+
+/// What is written once the stream has finished: the graphs as images, the history of the
+/// metrics, the series as a glTF scene, or any of them together.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeadlessMode {
+    pub(crate) snapshots: bool,
+    pub(crate) export: bool,
+    pub(crate) scene: bool
+}
+
+impl Default for HeadlessMode {
+    fn default() -> Self {
+        Self {
+            snapshots: true,
+            export: false,
+            scene: false
+        }
+    }
+}
+
+impl HeadlessMode {
+    pub(crate) fn any(&self) -> bool {
+        self.snapshots || self.export || self.scene
+    }
+}
+
+/// The series of each metric as they would be drawn on the graph, one line for each value of each
+/// column, keyed by the metric, the graph dim and the index of the value.
+#[derive(Resource, Default, Debug)]
+pub struct HeadlessSeries {
+    pub(crate) lines: HashMap<Entity, BTreeMap<(Entity, usize), (Color, Vec<Vec3>)>>
+}
+
+impl HeadlessSeries {
+    /// Appends the end of the segment, starting the line with the start of the segment the first
+    /// time the value is drawn, as the polylines of the graph are.
+    pub(crate) fn append(&mut self, metric: Entity, point: SeriesPoint) {
+        let (_, points) = self.lines.entry(metric)
+            .or_default()
+            .entry((point.graph_dim_entity, point.index))
+            .or_insert_with(|| (point.color, vec![point.start]));
+        points.push(point.end);
+    }
+
+    /// Each line as a line strip of the scene, named after the metric, the column and the index of
+    /// the value, with an unlit material of the color of the series.
+    pub(crate) fn gltf_scene(&self, metric_name: impl Fn(Entity) -> String, column_name: impl Fn(Entity) -> String) -> GltfScene {
+        let mut scene = GltfScene::default();
+        let mut metrics = self.lines.keys().cloned().collect::<Vec<Entity>>();
+        metrics.sort();
+        for metric in metrics.into_iter() {
+            for ((graph_dim, index), (color, points)) in self.lines[&metric].iter() {
+                scene.add(
+                    &format!("{} {} {}", metric_name(metric), column_name(*graph_dim), index),
+                    Mat4::IDENTITY,
+                    GltfGeometry::LineStrip { positions: points.clone() },
+                    GltfMaterial::unlit("series", *color)
+                );
+            }
+        }
+        scene
+    }
+
+    pub(crate) fn snapshot_lines(&self, metric: Entity, headless: &HeadlessConfiguration) -> Vec<SnapshotLine> {
+        self.lines.get(&metric)
+            .map(|lines| lines.values()
+                .map(|(color, points)| SnapshotLine {
+                    color: *color,
+                    points: points.iter()
+                        .map(|point| project(*point, headless.projection))
+                        .collect()
+                })
+                .collect())
+            .or(Some(vec![]))
+            .unwrap()
+    }
+}
+
+/// The frames run, and the frames in a row without any metric events, to know when the stream has
+/// finished.
+#[derive(Resource, Default, Debug)]
+pub struct HeadlessProgress {
+    pub(crate) frames: u32,
+    pub(crate) idle_frames: u32,
+    pub(crate) done: bool
+}
+
+impl HeadlessProgress {
+    pub(crate) fn finished(&self, headless: &HeadlessConfiguration, live: bool, remaining: usize) -> bool {
+        self.frames >= headless.max_frames
+            || (!live && remaining == 0 && self.idle_frames >= headless.idle_frames)
+    }
+}
+
+pub struct HeadlessPlugin;
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MetricsState>()
+            .init_resource::<GraphingMetricsResource>()
+            .insert_resource(RunComparison::default())
+            .init_resource::<RecordedStream>()
+            .init_resource::<HeadlessSeries>()
+            .init_resource::<HeadlessProgress>()
+            .init_resource::<HeadlessMode>();
+        add_headless_series::<NodeMetricEvent, Node>(app);
+        add_headless_series::<LayerMetricEvent, Layer>(app);
+        add_headless_series::<NetworkMetricEvent, Network>(app);
+        add_headless_series::<NodeChildrenMetricEvent, MetricChildNodes>(app);
+        app.add_system(write_snapshots.after(update_progress))
+            .add_system(export_history.after(update_progress))
+            .add_system(write_headless_scene.after(update_progress))
+            .add_system(exit_when_done
+                .after(write_snapshots)
+                .after(export_history)
+                .after(write_headless_scene));
+        add_derived_metrics(app);
+    }
+}
+
+/// Reads the metric events of the component type, and collects the points of its series before
+/// the progress is updated.
+fn add_headless_series<E, T>(app: &mut App)
+    where
+        E: NetworkMetricsServiceEvent<T> + Debug,
+        T: Component + Default + Send + Sync + 'static + Debug
+{
+    app.insert_resource(GraphConfigurationResource::<T>::default())
+        .add_event::<E>()
+        .add_system(read_metric_events::<E, T>.after(replay_recording))
+        .add_system(collect_series_points::<T, RadialGraphPoints>
+            .after(read_metric_events::<E, T>)
+            .before(update_progress));
+}
+
+/// Runs without a window, reading the metrics from the recording or from Kafka, and exits once
+/// the graphs, the history or the scene have been written.
+pub(crate) fn run_headless(config_properties: ConfigurationProperties, mode: HeadlessMode, log_plugin: LogPlugin) {
+    let live = config_properties.data_source.live();
+    let mut app = App::new();
+    app.insert_resource(config_properties)
+        .insert_resource(mode)
+        .add_plugins(HeadlessPlugins.build().set(log_plugin))
+        .add_plugins(HeadlessPlugin);
+    if live {
+        app.add_plugins(DataSubscriberPlugin);
+    } else {
+        app.add_plugins(RecordingPlugin);
+    }
+    app.run();
+}
+
+/// The same points the graph draws for each new timestep of the metric, kept as lines to be
+/// written instead of as polylines.
+pub(crate) fn collect_series_points<T, P>(
+    mut metrics: Query<
+        (Entity, &Metric<T>, &mut DataSeries),
+        WithDataSeriesChangedHistorical
+    >,
+    mut dims: ResMut<GraphConfigurationResource<T>>,
+    run_comparison: Res<RunComparison>,
+    mut headless_series: ResMut<HeadlessSeries>,
+)
+where
+    T: Component + Send + Sync + 'static + Debug,
+    P: GraphingStrategy<T>,
+{
+    for (metric_entity, metric, mut series) in metrics.iter_mut() {
+        let Some(num_col) = dims.series_dims.get(&metric_entity).map(|dims| dims.len()) else {
+            continue;
+        };
+        let added = new_timesteps(metric_entity, metric, &series, &run_comparison);
+        for key in added.iter() {
+            let mut points = P::create_update_graph(
+                metric,
+                &mut series,
+                dims.series_dims.get_mut(&metric_entity).unwrap(),
+                num_col,
+                key
+            );
+            run_comparison.align_points(metric_entity, metric, &mut points);
+            points.into_iter()
+                .for_each(|point| headless_series.append(metric_entity, point));
+        }
+        series.drawn.extend(added);
+    }
+}
+
+pub(crate) fn update_progress(
+    config_properties: Res<ConfigurationProperties>,
+    recorded: Res<RecordedStream>,
+    mut progress: ResMut<HeadlessProgress>,
+    mut metric_events: EventReader<NodeMetricEvent>,
+) {
+    progress.frames += 1;
+    if metric_events.iter().count() == 0 {
+        progress.idle_frames += 1;
+    } else {
+        progress.idle_frames = 0;
+    }
+    progress.done = progress.finished(
+        &config_properties.headless,
+        config_properties.data_source.live(),
+        recorded.events.len()
+    );
+}
+
+/// Once the stream has finished, writes each metric of each run in each of the formats, as
+/// `<output_dir>/<run>/<metric>.<format>`.
+pub(crate) fn write_snapshots(
+    config_properties: Res<ConfigurationProperties>,
+    metrics_state: Res<MetricsState>,
+    headless_series: Res<HeadlessSeries>,
+    progress: Res<HeadlessProgress>,
+    mode: Res<HeadlessMode>,
+) {
+    if !progress.done || !mode.snapshots {
+        return;
+    }
+    let headless = &config_properties.headless;
+    let mut written = 0;
+    for ((run_id, metric_name), (metric, _)) in metrics_state.entities.iter() {
+        let lines = headless_series.snapshot_lines(*metric, headless);
+        if lines.is_empty() {
+            continue;
+        }
+        let dir = Path::new(&headless.output_dir).join(file_name(run_id));
+        if fs::create_dir_all(&dir)
+            .or_else(|e| {
+                error!("Could not create {:?}: {:?}.", &dir, e);
+                Err(e)
+            })
+            .is_err() {
+            continue;
+        }
+        let fitted = fit_lines(&lines, headless.width, headless.height);
+        for format in headless.formats.iter() {
+            let path = dir.join(format!("{}.{}", file_name(metric_name), format.extension()));
+            if write_snapshot(&path, *format, &fitted, headless, &format!("{} ({})", metric_name, run_id)) {
+                written += 1;
+            }
+        }
+    }
+    info!("Wrote {} graphs to {} after {} frames.", written, headless.output_dir, progress.frames);
+}
+
+/// Once the stream has finished, exports the history of the metrics of the nodes, layers, networks
+/// and child nodes to a single file, as all metrics are exported from the context menu.
+pub(crate) fn export_history(
+    config_properties: Res<ConfigurationProperties>,
+    metrics_state: Res<MetricsState>,
+    progress: Res<HeadlessProgress>,
+    mode: Res<HeadlessMode>,
+    nodes: Query<(Entity, &Metric<Node>)>,
+    layers: Query<(Entity, &Metric<Layer>)>,
+    networks: Query<(Entity, &Metric<Network>)>,
+    child_nodes: Query<(Entity, &Metric<MetricChildNodes>)>,
+) {
+    if !progress.done || !mode.export {
+        return;
+    }
+    let export = &config_properties.export;
+    let mut rows = metric_rows(export, &metrics_state, nodes.iter());
+    rows.extend(metric_rows(export, &metrics_state, layers.iter()));
+    rows.extend(metric_rows(export, &metrics_state, networks.iter()));
+    rows.extend(metric_rows(export, &metrics_state, child_nodes.iter()));
+    let _ = write_metric_rows(export, rows)
+        .map(|path| info!("Exported metric history to {}.", path))
+        .or_else(|e| {
+            error!("Could not export metric history: {}.", e);
+            Err(e)
+        });
+}
+
+pub(crate) fn write_headless_scene(
+    config_properties: Res<ConfigurationProperties>,
+    metrics_state: Res<MetricsState>,
+    headless_series: Res<HeadlessSeries>,
+    progress: Res<HeadlessProgress>,
+    mode: Res<HeadlessMode>,
+    graph_dims: Query<&GraphDimComponent>,
+) {
+    if !progress.done || !mode.scene {
+        return;
+    }
+    let scene = headless_series.gltf_scene(
+        |metric| metrics_state.run_metric(metric)
+            .map(|(run_id, metric_name)| format!("{}/{}", run_id, metric_name))
+            .or(Some(format!("{:?}", metric)))
+            .unwrap(),
+        |graph_dim| graph_dims.get(graph_dim)
+            .map(|graph_dim| graph_dim.name.clone())
+            .or(Ok::<String, ()>(format!("{:?}", graph_dim)))
+            .unwrap()
+    );
+    let _ = write_scene(&config_properties.export, HEADLESS_SCENE, &scene)
+        .map(|path| info!("Wrote {} series to {}.", scene.objects.len(), path))
+        .or_else(|e| {
+            error!("Could not write the scene: {}.", e);
+            Err(e)
+        });
+}
+
+pub(crate) fn exit_when_done(
+    progress: Res<HeadlessProgress>,
+    mut exit: EventWriter<AppExit>,
+) {
+    if progress.done {
+        exit.send(AppExit);
+    }
+}
+
+fn write_snapshot(path: &PathBuf, format: SnapshotFormat, lines: &Vec<SnapshotLine>, headless: &HeadlessConfiguration, title: &str) -> bool {
+    match format {
+        SnapshotFormat::Png => {
+            let mut raster = Raster::new(headless.width, headless.height, Color::WHITE);
+            raster.draw_lines(lines);
+            raster.write_png(path)
+                .or_else(|e| {
+                    error!("Could not write {:?}: {:?}.", path, e);
+                    Err(e)
+                })
+                .is_ok()
+        }
+        SnapshotFormat::Svg => {
+            fs::write(path, to_svg(lines, headless.width, headless.height, Color::WHITE, title))
+                .or_else(|e| {
+                    error!("Could not write {:?}: {:?}.", path, e);
+                    Err(e)
+                })
+                .is_ok()
+        }
+    }
+}
+
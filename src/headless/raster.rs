@@ -0,0 +1,131 @@
+use std::path::Path;
+use bevy::prelude::{Color, Vec2, Vec3};
+use crate::config::headless::Projection;
+
+/// The space left around the series, in pixels.
+pub const SNAPSHOT_MARGIN: f32 = 20.0;
+
+/// A value of a column of a metric, flattened to the plane of the projection, in the order its
+/// points were drawn.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotLine {
+    pub(crate) color: Color,
+    pub(crate) points: Vec<Vec2>
+}
+
+pub(crate) fn project(point: Vec3, projection: Projection) -> Vec2 {
+    match projection {
+        Projection::Front => Vec2::new(point.x, point.y),
+        Projection::Side => Vec2::new(point.z, point.y),
+        Projection::Top => Vec2::new(point.x, point.z)
+    }
+}
+
+/// Scales and moves the lines into the image, keeping their aspect ratio, centered with the margin
+/// around them and the y axis pointing up.
+pub(crate) fn fit_lines(lines: &Vec<SnapshotLine>, width: u32, height: u32) -> Vec<SnapshotLine> {
+    let points = lines.iter().flat_map(|line| line.points.iter());
+    let (min, max) = points.fold(
+        (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)),
+        |(min, max), point| (min.min(*point), max.max(*point))
+    );
+    if min.x > max.x {
+        return vec![];
+    }
+    let size = (max - min).max(Vec2::splat(f32::EPSILON));
+    let available = (Vec2::new(width as f32, height as f32) - 2.0 * SNAPSHOT_MARGIN).max(Vec2::ONE);
+    let scale = (available.x / size.x).min(available.y / size.y);
+    let offset = (Vec2::new(width as f32, height as f32) - size * scale) / 2.0;
+    lines.iter()
+        .map(|line| SnapshotLine {
+            color: line.color,
+            points: line.points.iter()
+                .map(|point| {
+                    let fitted = (*point - min) * scale + offset;
+                    Vec2::new(fitted.x, height as f32 - fitted.y)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// An image drawn on the CPU, so that the graphs can be written without a GPU.
+#[derive(Debug, Clone)]
+pub struct Raster {
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pixels: Vec<[u8; 4]>
+}
+
+impl Raster {
+    pub(crate) fn new(width: u32, height: u32, background: Color) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![background.as_rgba_u8(); (width * height) as usize]
+        }
+    }
+
+    pub(crate) fn pixel(&self, x: u32, y: u32) -> Option<[u8; 4]> {
+        (x < self.width && y < self.height)
+            .then(|| self.pixels[(y * self.width + x) as usize])
+    }
+
+    /// Draws the line one pixel wide, stepping once per pixel along its longer axis. The parts of
+    /// the line outside of the image are left out.
+    pub(crate) fn draw_line(&mut self, start: Vec2, end: Vec2, color: Color) {
+        let color = color.as_rgba_u8();
+        let steps = (end - start).abs().max_element().ceil().max(1.0) as usize;
+        for step in 0..=steps {
+            let point = start.lerp(end, step as f32 / steps as f32).round();
+            if point.x < 0.0 || point.y < 0.0 || point.x >= self.width as f32 || point.y >= self.height as f32 {
+                continue;
+            }
+            self.pixels[(point.y as u32 * self.width + point.x as u32) as usize] = color;
+        }
+    }
+
+    pub(crate) fn draw_lines(&mut self, lines: &Vec<SnapshotLine>) {
+        for line in lines.iter() {
+            line.points.windows(2)
+                .for_each(|segment| self.draw_line(segment[0], segment[1], line.color));
+        }
+    }
+
+    pub(crate) fn write_png(&self, path: &Path) -> Result<(), image::ImageError> {
+        let bytes = self.pixels.iter()
+            .flat_map(|pixel| pixel.iter().cloned())
+            .collect::<Vec<u8>>();
+        image::save_buffer(path, &bytes, self.width, self.height, image::ColorType::Rgba8)
+    }
+}
+
+fn hex(color: Color) -> String {
+    let [r, g, b, _] = color.as_rgba_u8();
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// The lines as an svg document, one polyline each, with the title at the top left.
+pub(crate) fn to_svg(lines: &Vec<SnapshotLine>, width: u32, height: u32, background: Color, title: &str) -> String {
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">\n",
+        width, height, width, height
+    );
+    svg.push_str(&format!("  <rect width=\"100%\" height=\"100%\" fill=\"{}\"/>\n", hex(background)));
+    svg.push_str(&format!(
+        "  <text x=\"4\" y=\"14\" font-family=\"sans-serif\" font-size=\"12\">{}</text>\n",
+        title.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+    ));
+    for line in lines.iter().filter(|line| line.points.len() > 1) {
+        let points = line.points.iter()
+            .map(|point| format!("{:.2},{:.2}", point.x, point.y))
+            .collect::<Vec<String>>()
+            .join(" ");
+        svg.push_str(&format!(
+            "  <polyline fill=\"none\" stroke=\"{}\" stroke-width=\"1\" points=\"{}\"/>\n",
+            hex(line.color), points
+        ));
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
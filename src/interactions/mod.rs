@@ -2,11 +2,13 @@ use std::io::Cursor;
 use std::marker::PhantomData;
 use bevy::ecs::query::ReadOnlyWorldQuery;
 use bevy::input::mouse::MouseWheel;
-use bevy::prelude::{Entity, Event, Interaction};
+use bevy::prelude::{Entity, Event, Interaction, Vec2};
 use bevy::window::CursorMoved;
 use ndarray::s;
 use crate::pickable_events::PickableEvent;
 
+/// The nodes and layers that are selected together, highlighted, and acted on as one.
+pub(crate) mod selection_set;
 
 /// Convert all interactions to these events so that ui and 3d are comparable.
 #[derive(Event)]
@@ -37,6 +39,7 @@ pub enum PickingEvent<QueryFilterT: ReadOnlyWorldQuery> {
     Selection(SelectionEvent, PhantomData<QueryFilterT>),
     Hover(HoverEvent, PhantomData<QueryFilterT>),
     Clicked(Entity, PhantomData<QueryFilterT>),
+    Dragged(Entity, Vec2, PhantomData<QueryFilterT>),
 }
 
 impl<QueryFilterT: ReadOnlyWorldQuery> From<&PickableEvent> for PickingEvent<QueryFilterT> {
@@ -65,6 +68,9 @@ impl<QueryFilterT: ReadOnlyWorldQuery> From<&PickableEvent> for PickingEvent<Que
             PickableEvent::Clicked(clicked) => {
                 return PickingEvent::Clicked(*clicked, PhantomData::default());
             }
+            PickableEvent::Dragged(dragged, delta) => {
+                PickingEvent::Dragged(*dragged, *delta, PhantomData::default())
+            }
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,199 @@
+use std::collections::HashSet;
+use bevy::prelude::*;
+use bevy_mod_picking::prelude::PickSelection;
+use crate::graph::{GraphingMetricsResource, GraphParent};
+use crate::interactions::SelectionEvent;
+use crate::menu::graphing_menu::context_menu::{ContextMenuAction, ContextMenuActionEvent, ContextMenuTarget, set_visibility, target_indices};
+use crate::network::{Layer, Node};
+use crate::pickable_events::PickableEvent;
+use crate::theme::highlight::Highlight;
+
+/// The nodes and layers that are selected, in the order they were selected, with the metric
+/// indices of each. The actions on the selection act on all of them together.
+#[derive(Resource, Default, Debug)]
+pub struct SelectionSet {
+    pub(crate) selected: Vec<(Entity, Vec<String>)>
+}
+
+impl SelectionSet {
+    /// Adds the entity with its indices, returning false when it was already selected.
+    pub(crate) fn insert(&mut self, entity: Entity, indices: Vec<String>) -> bool {
+        if self.contains(entity) {
+            return false;
+        }
+        self.selected.push((entity, indices));
+        true
+    }
+
+    /// Removes the entity, returning false when it was not selected.
+    pub(crate) fn remove(&mut self, entity: Entity) -> bool {
+        let len = self.selected.len();
+        self.selected.retain(|(selected, _)| *selected != entity);
+        len != self.selected.len()
+    }
+
+    pub(crate) fn contains(&self, entity: Entity) -> bool {
+        self.selected.iter().any(|(selected, _)| *selected == entity)
+    }
+
+    pub(crate) fn entities(&self) -> Vec<Entity> {
+        self.selected.iter().map(|(entity, _)| *entity).collect()
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.selected.len()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.selected.is_empty()
+    }
+
+    /// The metric indices of all of the selected entities, sorted and without duplicates.
+    pub(crate) fn indices(&self) -> Vec<String> {
+        let mut indices = self.selected.iter()
+            .flat_map(|(_, indices)| indices.iter().cloned())
+            .collect::<Vec<String>>();
+        indices.sort();
+        indices.dedup();
+        indices
+    }
+
+    /// The metrics indexed by any of the selected entities, looked up in the graphing indices.
+    pub(crate) fn metrics(&self, graphing_metrics: &GraphingMetricsResource) -> HashSet<Entity> {
+        graphing_metrics.metrics_for_indices(self.indices().iter())
+    }
+}
+
+pub struct SelectionSetPlugin;
+
+impl Plugin for SelectionSetPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SelectionSet>()
+            .add_event::<PickableEvent>()
+            .add_event::<ContextMenuActionEvent>()
+            .add_system(update_selection_set)
+            .add_system(highlight_selection.after(update_selection_set))
+            .add_system(apply_selection_actions.after(update_selection_set));
+    }
+}
+
+/// The nodes and layers selected or deselected, by clicking, shift-clicking or dragging a box, are
+/// added to or removed from the selection set.
+pub(crate) fn update_selection_set(
+    mut picking_events: EventReader<PickableEvent>,
+    mut selection_set: ResMut<SelectionSet>,
+    nodes: Query<&Node>,
+    layers: Query<(Entity, &Layer)>,
+    parents: Query<&Parent>,
+) {
+    for event in picking_events.iter() {
+        match event {
+            PickableEvent::Selection(SelectionEvent::JustSelected(entity)) => {
+                let target = if nodes.contains(*entity) {
+                    ContextMenuTarget::Node(*entity)
+                } else if layers.contains(*entity) {
+                    ContextMenuTarget::Layer(*entity)
+                } else {
+                    continue;
+                };
+                selection_set.insert(*entity, target_indices(target, &nodes, &layers, &parents));
+            }
+            PickableEvent::Selection(SelectionEvent::JustDeselected(entity)) => {
+                selection_set.remove(*entity);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The selected entities are lit with the selection color of the theme in apply_highlights, unless
+/// they are highlighted by an alert.
+pub(crate) fn highlight_selection(
+    selection_set: Res<SelectionSet>,
+    added: Query<(), Added<Highlight>>,
+    mut highlights: Query<(Entity, &mut Highlight)>,
+) {
+    if !selection_set.is_changed() && added.is_empty() {
+        return;
+    }
+    for (entity, mut highlight) in highlights.iter_mut() {
+        let selected = selection_set.contains(entity);
+        if highlight.selected != selected {
+            highlight.selected = selected;
+        }
+    }
+}
+
+/// Selecting the nodes of a layer adds each of them to the selection. Graphing the selection shows
+/// the metrics of all of the selected entities, and comparing it also hides the other metrics so
+/// that only the selected are on the graph. Hiding the unselected hides the nodes and layers that
+/// are not selected, keeping the layers of the selected nodes, until all are shown again.
+pub(crate) fn apply_selection_actions(
+    mut commands: Commands,
+    mut action_events: EventReader<ContextMenuActionEvent>,
+    mut selection_set: ResMut<SelectionSet>,
+    graphing_metrics: Res<GraphingMetricsResource>,
+    mut graph: Query<&mut Visibility, With<GraphParent>>,
+    nodes: Query<(Entity, &Parent), With<Node>>,
+    layers: Query<(Entity, &Children), With<Layer>>,
+    mut selections: Query<&mut PickSelection>,
+    mut picking_writer: EventWriter<PickableEvent>,
+) {
+    for event in action_events.iter() {
+        match (&event.action, event.target) {
+            (ContextMenuAction::SelectLayerNodes, ContextMenuTarget::Layer(layer)) => {
+                let children = layers.get(layer).ok()
+                    .map(|(_, children)| children.iter().cloned().collect::<Vec<Entity>>())
+                    .or(Some(vec![]))
+                    .unwrap();
+                for node in children.into_iter().filter(|child| nodes.contains(*child)) {
+                    let _ = selections.get_mut(node)
+                        .map(|mut selection| if !selection.is_selected {
+                            selection.is_selected = true;
+                            picking_writer.send(PickableEvent::Selection(SelectionEvent::JustSelected(node)));
+                        });
+                }
+            }
+            (ContextMenuAction::GraphSelection, _) | (ContextMenuAction::CompareSelection, _) => {
+                let metrics = selection_set.metrics(&graphing_metrics);
+                info!("Graphing {} metrics of {} selected.", metrics.len(), selection_set.len());
+                if matches!(event.action, ContextMenuAction::CompareSelection) {
+                    graphing_metrics.metric_indices.keys()
+                        .filter(|metric| !metrics.contains(*metric))
+                        .for_each(|metric| set_visibility(&mut commands, *metric, Visibility::Hidden));
+                }
+                metrics.into_iter()
+                    .for_each(|metric| set_visibility(&mut commands, metric, Visibility::Inherited));
+                graph.iter_mut()
+                    .for_each(|mut visibility| *visibility = Visibility::Visible);
+            }
+            (ContextMenuAction::HideUnselected, _) => {
+                let kept_layers = nodes.iter()
+                    .filter(|(node, _)| selection_set.contains(*node))
+                    .map(|(_, layer)| layer.get())
+                    .collect::<HashSet<Entity>>();
+                nodes.iter()
+                    .filter(|(node, _)| !selection_set.contains(*node))
+                    .for_each(|(node, _)| set_visibility(&mut commands, node, Visibility::Hidden));
+                layers.iter()
+                    .filter(|(layer, _)| !selection_set.contains(*layer) && !kept_layers.contains(layer))
+                    .for_each(|(layer, _)| set_visibility(&mut commands, layer, Visibility::Hidden));
+            }
+            (ContextMenuAction::ShowAll, _) => {
+                nodes.iter()
+                    .for_each(|(node, _)| set_visibility(&mut commands, node, Visibility::Inherited));
+                layers.iter()
+                    .for_each(|(layer, _)| set_visibility(&mut commands, layer, Visibility::Inherited));
+            }
+            (ContextMenuAction::ClearSelection, _) => {
+                for entity in selection_set.entities().into_iter() {
+                    let _ = selections.get_mut(entity)
+                        .map(|mut selection| selection.is_selected = false);
+                    picking_writer.send(PickableEvent::Selection(SelectionEvent::JustDeselected(entity)));
+                }
+                selection_set.selected.clear();
+            }
+            _ => {}
+        }
+    }
+}
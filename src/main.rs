@@ -16,7 +16,7 @@ use graph::setup_graph;
 use crate::camera::{NnFeCameraPlugin, setup_camera, ZoomableDraggableCamera};
 use menu::ui_menu_event::ui_menu_event_plugin::UiEventPlugin;
 use menu::ui_menu_event::ui_state_change::GlobalState;
-use network::draw_network::{create_network, draw_network_initial, draw_node_connections, update_network};
+use network::draw_network::{color_nodes_by_metric, create_network, draw_network_initial, draw_node_connections, update_network};
 use crate::camera::lerping_camera::camera_rotation_system;
 use crate::camera::raycast_select::BevyPickingState;
 use crate::config::ConfigurationProperties;
@@ -24,7 +24,28 @@ use crate::cursor_adapter::{calculate_picks, event_merge_propagate, PickableComp
 use crate::data_subscriber::data_subscriber_plugin::DataSubscriberPlugin;
 use crate::graph::draw_graph_points::draw_graph_points;
 use crate::graph::graph_plugin::GraphPlugin;
-use crate::graph::{GraphDimComponent, GraphingMetricsResource};
+use crate::metrics::alerts::AlertsPlugin;
+use crate::metrics::derived_metrics::add_derived_metrics;
+use crate::menu::graphing_menu::context_menu::ContextMenuPlugin;
+use crate::ui_components::menu_components::MenuInputPlugin;
+use crate::ui_components::tooltip::TooltipPlugin;
+use crate::ui_components::notes_panel::NotesPlugin;
+use crate::shortcuts::ShortcutsPlugin;
+use crate::workspace::WorkspacePlugin;
+use crate::pickable_events::PickingAdapterPlugin;
+use crate::interactions::selection_set::SelectionSetPlugin;
+use crate::search::SearchPlugin;
+use crate::export::ExportPlugin;
+use crate::theme::ThemePlugin;
+use crate::headless::run_headless;
+use crate::cli::{CliOptions, USAGE};
+use crate::config::data_source::DataSource;
+use crate::data_subscriber::metric_event::{LayerMetricEvent, NetworkMetricEvent, NodeChildrenMetricEvent, NodeMetricEvent};
+use crate::data_subscriber::recording::RecordingPlugin;
+use crate::graph::graph_data_event_reader::read_metric_events;
+use crate::network::{Layer, MetricChildNodes, Network, Node};
+use crate::workspace::Workspaces;
+use crate::graph::{GraphConfigurationResource, GraphDimComponent, GraphingMetricsResource};
 use crate::graph::setup_graph::setup_graph;
 use crate::interactions::InteractionEvent;
 use crate::menu::config_menu_event::config_menu_event_plugin::ConfigMenuEventPlugin;
@@ -47,6 +68,13 @@ pub(crate) mod cursor_adapter;
 pub(crate) mod pickable_events;
 pub(crate) mod interactions;
 pub(crate) mod render_html;
+pub(crate) mod shortcuts;
+pub(crate) mod workspace;
+pub(crate) mod search;
+pub(crate) mod theme;
+pub(crate) mod headless;
+pub(crate) mod export;
+pub(crate) mod cli;
 pub(crate) mod test;
 
 /// 1. At the beginning, insert all TransitionGroups, which are components used to determine when
@@ -61,30 +89,96 @@ pub(crate) mod test;
 ///    associated EventReaders are InsertComponentInteractionEventReader, InteractionEventReader.
 #[tokio::main]
 async fn main() {
-    App::new()
-        .insert_resource(MenuResource::default())
+    let options = match CliOptions::parse(std::env::args().skip(1)) {
+        Ok(options) if options.help => {
+            print!("{}", USAGE);
+            return;
+        }
+        options => options
+    };
+    let parsed = options
+        .and_then(|options| options.validate().map(|_| options))
+        .and_then(|options| options.configuration().map(|config| (options, config)));
+    let (options, config_properties) = match parsed {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
+    let mode = options.headless_mode();
+    if mode.any() {
+        run_headless(config_properties, mode, options.log_plugin());
+        return;
+    }
+    let menu_file = MenuResource::menu_file();
+    let menu_resource = match MenuResource::read(&menu_file) {
+        Ok(menu_resource) => menu_resource,
+        Err(e) => {
+            eprintln!("error: the menu definition {} is invalid: {:?}", menu_file, e);
+            std::process::exit(2);
+        }
+    };
+    let source = config_properties.data_source.source;
+    let demo_network = config_properties.data_source.demo_network;
+    let mut app = App::new();
+    app.insert_resource(menu_resource)
         .insert_resource(NetworkMenuResultBuilder::default())
         .insert_resource(GraphMenuResultBuilder::default())
-        .insert_resource(ConfigurationProperties::default())
+        .insert_resource(config_properties)
         .insert_resource(BevyPickingState::default())
         .insert_resource(GlobalState::default())
         .insert_resource(GraphingMetricsResource::default())
-        .add_plugins(DefaultPlugins)
+        .add_plugins(DefaultPlugins.set(options.log_plugin()))
         .add_plugins(ShapePlugin)
+        .add_plugins(ThemePlugin)
         // .add_plugin(WorldInspectorPlugin::new())
         .add_plugins(UiEventPlugin)
         .add_plugins(GraphPlugin)
-        // .add_plugin(DataSubscriberPlugin)
         .add_plugins(ConfigMenuEventPlugin)
         .add_plugins(NnFeCameraPlugin)
-        .add_startup_systems(test::test_plugin::add_node_entities)
-        .add_system(calculate_picks)
+        .add_plugins(PickingAdapterPlugin)
+        .add_plugins(SelectionSetPlugin)
+        .add_plugins(AlertsPlugin)
+        .add_plugins(MenuInputPlugin)
+        .add_plugins(ContextMenuPlugin)
+        .add_plugins(TooltipPlugin)
+        .add_plugins(NotesPlugin)
+        .add_plugins(ShortcutsPlugin)
+        .add_plugins(SearchPlugin)
+        .add_plugins(ExportPlugin);
+    if let Some(workspace) = options.workspace {
+        app.insert_resource(Workspaces::new(workspace));
+    }
+    app.add_plugins(WorkspacePlugin);
+    if demo_network {
+        app.add_startup_systems(test::test_plugin::add_node_entities);
+    }
+    match source {
+        Some(DataSource::Kafka) => {
+            app.add_plugins(DataSubscriberPlugin)
+                .insert_resource(GraphConfigurationResource::<Layer>::default())
+                .insert_resource(GraphConfigurationResource::<Network>::default())
+                .insert_resource(GraphConfigurationResource::<MetricChildNodes>::default())
+                .add_system(read_metric_events::<LayerMetricEvent, Layer>)
+                .add_system(read_metric_events::<NetworkMetricEvent, Network>)
+                .add_system(read_metric_events::<NodeChildrenMetricEvent, MetricChildNodes>);
+        }
+        Some(DataSource::File) | Some(DataSource::Replay) => {
+            app.add_plugins(RecordingPlugin);
+        }
+        None => {}
+    }
+    if source.is_some() {
+        app.add_system(read_metric_events::<NodeMetricEvent, Node>);
+        add_derived_metrics(&mut app);
+    }
+    app.add_system(calculate_picks)
         .add_system(update_network)
         .add_system(draw_node_connections)
         .add_system(create_network)
         .add_system(draw_network_initial)
+        .add_system(color_nodes_by_metric)
         .add_event::<InteractionEvent<(With<PickableComponent>, With<GraphDimComponent>)>>()
         .run();
 }
-
-
@@ -191,11 +191,11 @@ fn create_add_events<T>(
     where
         T: Component + Send + Sync + Default + Clone + Debug + 'static
 {
-    let event_descriptor = create_graph_menu_event(entity, data_type, config);
+    let event_descriptor = create_config_option_event(entity, data_type, config);
     event_descriptors.push(event_descriptor);
 }
 
-fn create_graph_menu_event<T>(
+pub(crate) fn create_config_option_event<T>(
     entity: Entity,
     data_type: DataType,
     config: MetricsConfigurationOption<T>) -> EventDescriptor<DataType, ConfigurationOptionEventArgs<T>, MetricsConfigurationOption<T>>
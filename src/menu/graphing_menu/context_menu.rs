@@ -0,0 +1,521 @@
+use std::fmt::Debug;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use bevy_polyline::prelude::PolylineMaterial;
+use crate::config::ConfigurationProperties;
+use crate::config::export::ExportFormat;
+use crate::config::palette::{OKABE_ITO, Palette};
+use crate::config::theme::{DEFAULT_FONT, ThemeColor};
+use crate::data_subscriber::metric_event::MetricsState;
+use crate::graph::{GraphDimComponent, GraphingMetricsResource, GraphParent};
+use crate::graph::draw_graph_points::{SERIES_THICKNESS, SeriesAssets, SeriesPolylines};
+use crate::interactions::selection_set::{SelectionSet, update_selection_set};
+use crate::metrics::network_metrics::Metric;
+use crate::network::{Layer, Node};
+use crate::pickable_events::PickableEvent;
+use crate::theme::restyle::{ThemedBackground, ThemedText};
+
+pub const CONTEXT_MENU_WIDTH: f32 = 220.0;
+
+/// The colors of the default palette, which a series is cycled through when its color is changed
+/// from the context menu.
+pub const SERIES_COLORS: [Color; 8] = OKABE_ITO;
+
+/// The 3d entity the context menu was opened for. A series is the GraphDimComponent of a column
+/// of a metric.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContextMenuTarget {
+    Node(Entity),
+    Layer(Entity),
+    Series(Entity)
+}
+
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub enum ContextMenuAction {
+    GraphMetric(Entity),
+    Hide,
+    Isolate,
+    ChangeColor,
+    ExportMetric(ExportFormat),
+    ExportAllMetrics,
+    ShowRelated,
+    Notes,
+    SelectLayerNodes,
+    GraphSelection,
+    CompareSelection,
+    HideUnselected,
+    ShowAll,
+    ClearSelection
+}
+
+impl ContextMenuAction {
+    fn label(&self) -> &'static str {
+        match self {
+            ContextMenuAction::GraphMetric(_) => "Graph",
+            ContextMenuAction::Hide => "Hide",
+            ContextMenuAction::Isolate => "Isolate",
+            ContextMenuAction::ChangeColor => "Change Color",
+            ContextMenuAction::ExportMetric(ExportFormat::Csv) => "Export Metric as CSV",
+            ContextMenuAction::ExportMetric(ExportFormat::Parquet) => "Export Metric as Parquet",
+            ContextMenuAction::ExportMetric(ExportFormat::Arrow) => "Export Metric as Arrow",
+            ContextMenuAction::ExportMetric(ExportFormat::Json) => "Export Metric as JSON",
+            ContextMenuAction::ExportAllMetrics => "Export All Metrics",
+            ContextMenuAction::ShowRelated => "Show Related Metrics",
+            ContextMenuAction::Notes => "Notes",
+            ContextMenuAction::SelectLayerNodes => "Select Nodes in Layer",
+            ContextMenuAction::GraphSelection => "Graph Selection",
+            ContextMenuAction::CompareSelection => "Compare Selection",
+            ContextMenuAction::HideUnselected => "Hide Unselected",
+            ContextMenuAction::ShowAll => "Show All",
+            ContextMenuAction::ClearSelection => "Clear Selection"
+        }
+    }
+
+    fn series_actions() -> Vec<ContextMenuAction> {
+        let mut actions = vec![
+            ContextMenuAction::Hide,
+            ContextMenuAction::Isolate,
+            ContextMenuAction::ChangeColor
+        ];
+        actions.extend(ExportFormat::all().into_iter().map(ContextMenuAction::ExportMetric));
+        actions.extend([
+            ContextMenuAction::ExportAllMetrics,
+            ContextMenuAction::ShowRelated,
+            ContextMenuAction::Notes
+        ]);
+        actions
+    }
+
+    /// The actions for a node or layer, after its metrics. The actions on the selection are listed
+    /// once something is selected.
+    pub(crate) fn selection_actions(target: ContextMenuTarget, selection_set: &SelectionSet) -> Vec<ContextMenuAction> {
+        let mut actions = vec![];
+        if matches!(target, ContextMenuTarget::Layer(_)) {
+            actions.push(ContextMenuAction::SelectLayerNodes);
+        }
+        if !selection_set.is_empty() {
+            actions.extend([
+                ContextMenuAction::GraphSelection,
+                ContextMenuAction::CompareSelection,
+                ContextMenuAction::HideUnselected,
+                ContextMenuAction::ClearSelection
+            ]);
+        }
+        actions.push(ContextMenuAction::ShowAll);
+        actions
+    }
+}
+
+/// Sent when an entry of the context menu is clicked, to be read by the systems that apply the
+/// action to the target.
+#[derive(Event, Debug, Clone)]
+pub struct ContextMenuActionEvent {
+    pub(crate) target: ContextMenuTarget,
+    pub(crate) action: ContextMenuAction
+}
+
+/// The open context menu. There is at most one open at a time.
+#[derive(Resource, Default, Debug)]
+pub struct ContextMenuState {
+    pub(crate) menu: Option<Entity>,
+    pub(crate) target: Option<ContextMenuTarget>
+}
+
+#[derive(Component, Default, Debug)]
+pub struct ContextMenu;
+
+pub struct ContextMenuPlugin;
+
+impl Plugin for ContextMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ContextMenuActionEvent>()
+            .add_event::<PickableEvent>()
+            .init_resource::<ContextMenuState>()
+            .init_resource::<MetricsState>()
+            .add_system(select_context_menu_action)
+            .add_system(close_context_menu.after(select_context_menu_action))
+            .add_system(open_context_menu.after(close_context_menu).after(update_selection_set))
+            .add_system(apply_visibility_actions.after(select_context_menu_action))
+            .add_system(apply_series_actions::<Node>.after(select_context_menu_action));
+    }
+}
+
+/// The node, layer or series for the entity clicked. The lines of a series are children of the
+/// series, so the parent is checked when the entity itself is not a target.
+pub(crate) fn resolve_target(
+    entity: Entity,
+    nodes: &Query<&Node>,
+    layers: &Query<(Entity, &Layer)>,
+    series: &Query<&GraphDimComponent>,
+    parents: &Query<&Parent>,
+) -> Option<ContextMenuTarget> {
+    let target = |entity: Entity| {
+        if nodes.contains(entity) {
+            Some(ContextMenuTarget::Node(entity))
+        } else if layers.contains(entity) {
+            Some(ContextMenuTarget::Layer(entity))
+        } else if series.contains(entity) {
+            Some(ContextMenuTarget::Series(entity))
+        } else {
+            None
+        }
+    };
+    target(entity)
+        .or_else(|| parents.get(entity).ok().and_then(|parent| target(parent.get())))
+}
+
+/// The metric indices of the node or layer, which are the keys of the graphing indices.
+pub(crate) fn target_indices(
+    target: ContextMenuTarget,
+    nodes: &Query<&Node>,
+    layers: &Query<(Entity, &Layer)>,
+    parents: &Query<&Parent>,
+) -> Vec<String> {
+    match target {
+        ContextMenuTarget::Layer(entity) => layers.get(entity).ok()
+            .map(|(_, layer)| vec![layer.name.to_string()])
+            .or(Some(vec![]))
+            .unwrap(),
+        ContextMenuTarget::Node(entity) => nodes.get(entity).ok()
+            .and_then(|node| parents.get(entity).ok()
+                .and_then(|parent| layers.get(parent.get()).ok())
+                .map(|(_, layer)| vec![layer.node_id(node)])
+            )
+            .or(Some(vec![]))
+            .unwrap(),
+        ContextMenuTarget::Series(_) => vec![]
+    }
+}
+
+/// The name of a metric as it is listed in the menus, with the run when it is not the default.
+pub(crate) fn metric_label(metrics_state: &MetricsState, metric: Entity) -> String {
+    metrics_state.run_metric(metric)
+        .map(|(run_id, metric_name)| if metrics_state.runs.len() > 1 {
+            format!("{} ({})", metric_name, run_id)
+        } else {
+            metric_name.clone()
+        })
+        .or(Some(format!("{:?}", metric)))
+        .unwrap()
+}
+
+/// The metrics available for the indices, sorted by name.
+pub(crate) fn metric_entries(
+    indices: &Vec<String>,
+    graphing_metrics: &GraphingMetricsResource,
+    metrics_state: &MetricsState
+) -> Vec<(String, Entity)> {
+    let mut entries = graphing_metrics.metrics_for_indices(indices.iter())
+        .into_iter()
+        .map(|metric| (metric_label(metrics_state, metric), metric))
+        .collect::<Vec<(String, Entity)>>();
+    entries.sort();
+    entries
+}
+
+/// When a node, layer or series is clicked, a context menu is opened at the cursor. For a node or
+/// layer the menu lists the metrics indexed by it, or by the whole selection when it is part of a
+/// larger selection, followed by the actions on the selection. For a series it lists the actions
+/// for the series.
+pub(crate) fn open_context_menu(
+    mut commands: Commands,
+    mut picking_events: EventReader<PickableEvent>,
+    mut context_menu: ResMut<ContextMenuState>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    asset_server: Res<AssetServer>,
+    graphing_metrics: Res<GraphingMetricsResource>,
+    metrics_state: Res<MetricsState>,
+    selection_set: Res<SelectionSet>,
+    nodes: Query<&Node>,
+    layers: Query<(Entity, &Layer)>,
+    series: Query<&GraphDimComponent>,
+    parents: Query<&Parent>,
+) {
+    let clicked = picking_events.iter()
+        .flat_map(|event| match event {
+            PickableEvent::Clicked(entity) => Some(*entity),
+            _ => None
+        })
+        .last();
+    let target = clicked
+        .and_then(|entity| resolve_target(entity, &nodes, &layers, &series, &parents));
+    if target.is_none() {
+        return;
+    }
+    let target = target.unwrap();
+    let cursor = windows.get_single().ok()
+        .and_then(|window| window.cursor_position())
+        .or(Some(Vec2::ZERO))
+        .unwrap();
+
+    despawn_context_menu(&mut commands, &mut context_menu);
+
+    let mut has_metrics = true;
+    let (title, entries) = match target {
+        ContextMenuTarget::Series(entity) => {
+            let column = series.get(entity).ok()
+                .map(|dim| dim.name.clone())
+                .or(Some(String::new()))
+                .unwrap();
+            let metric = parents.get(entity).ok()
+                .map(|parent| metric_label(&metrics_state, parent.get()))
+                .or(Some(String::new()))
+                .unwrap();
+            let entries = ContextMenuAction::series_actions().into_iter()
+                .map(|action| (action.label().to_string(), action))
+                .collect::<Vec<(String, ContextMenuAction)>>();
+            (format!("{} {}", metric, column), entries)
+        }
+        ContextMenuTarget::Node(entity) | ContextMenuTarget::Layer(entity) => {
+            let in_selection = selection_set.len() > 1 && selection_set.contains(entity);
+            let indices = if in_selection {
+                selection_set.indices()
+            } else {
+                target_indices(target, &nodes, &layers, &parents)
+            };
+            let mut entries = metric_entries(&indices, &graphing_metrics, &metrics_state)
+                .into_iter()
+                .map(|(name, metric)| (name, ContextMenuAction::GraphMetric(metric)))
+                .collect::<Vec<(String, ContextMenuAction)>>();
+            has_metrics = !entries.is_empty();
+            entries.extend(ContextMenuAction::selection_actions(target, &selection_set).into_iter()
+                .map(|action| (action.label().to_string(), action)));
+            let kind = if in_selection {
+                format!("Selection ({})", selection_set.len())
+            } else if matches!(target, ContextMenuTarget::Node(_)) {
+                "Node".to_string()
+            } else {
+                "Layer".to_string()
+            };
+            (format!("{} {}", kind, indices.join(", ")), entries)
+        }
+    };
+
+    let menu = commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(cursor.x),
+                top: Val::Px(cursor.y),
+                width: Val::Px(CONTEXT_MENU_WIDTH),
+                display: Display::Flex,
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(2.0)),
+                ..default()
+            },
+            z_index: ZIndex::Global(100),
+            ..default()
+        },
+        ThemedBackground(ThemeColor::Panel),
+        ContextMenu::default()
+    )).id();
+
+    let title = spawn_text(&mut commands, &asset_server, title.as_str(), ThemeColor::PanelText);
+    commands.entity(menu).add_child(title);
+
+    if !has_metrics {
+        let empty = spawn_text(&mut commands, &asset_server, "No metrics", ThemeColor::PanelMutedText);
+        commands.entity(menu).add_child(empty);
+    }
+
+    for (name, action) in entries.into_iter() {
+        let text = spawn_text(&mut commands, &asset_server, name.as_str(), ThemeColor::PanelText);
+        let entry = commands.spawn((
+            ButtonBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    padding: UiRect::all(Val::Px(4.0)),
+                    ..default()
+                },
+                ..default()
+            },
+            ThemedBackground(ThemeColor::PanelEntry),
+            action
+        ))
+            .add_child(text)
+            .id();
+        commands.entity(menu).add_child(entry);
+    }
+
+    context_menu.menu = Some(menu);
+    context_menu.target = Some(target);
+}
+
+fn spawn_text(commands: &mut Commands, asset_server: &Res<AssetServer>, value: &str, color: ThemeColor) -> Entity {
+    commands.spawn((
+        TextBundle {
+            text: Text::from_section(value.to_string(), TextStyle {
+                font_size: 16.0,
+                font: asset_server.load(DEFAULT_FONT),
+                ..default()
+            }),
+            ..default()
+        },
+        ThemedText(color)
+    )).id()
+}
+
+fn despawn_context_menu(commands: &mut Commands, context_menu: &mut ResMut<ContextMenuState>) {
+    context_menu.menu.take()
+        .map(|menu| commands.get_entity(menu)
+            .map(|menu| menu.despawn_recursive())
+        );
+    context_menu.target = None;
+}
+
+/// Clicking an entry sends the action for the target, and closes the menu.
+pub(crate) fn select_context_menu_action(
+    mut commands: Commands,
+    mut context_menu: ResMut<ContextMenuState>,
+    mut entries: Query<(&Interaction, &ContextMenuAction, &mut ThemedBackground), Changed<Interaction>>,
+    mut action_writer: EventWriter<ContextMenuActionEvent>,
+) {
+    let mut selected = None;
+    for (interaction, action, mut background) in entries.iter_mut() {
+        match interaction {
+            Interaction::Pressed => selected = Some(action.clone()),
+            Interaction::Hovered => *background = ThemedBackground(ThemeColor::PanelHighlight),
+            Interaction::None => *background = ThemedBackground(ThemeColor::PanelEntry)
+        }
+    }
+    if let (Some(action), Some(target)) = (selected, context_menu.target) {
+        info!("Context menu action {:?} for {:?}.", action, target);
+        action_writer.send(ContextMenuActionEvent { target, action });
+        despawn_context_menu(&mut commands, &mut context_menu);
+    }
+}
+
+/// Pressing escape, or clicking outside of the menu, closes the menu.
+pub(crate) fn close_context_menu(
+    mut commands: Commands,
+    mut context_menu: ResMut<ContextMenuState>,
+    key_input: Res<Input<KeyCode>>,
+    mouse_button_input: Res<Input<MouseButton>>,
+    entries: Query<&Interaction, With<ContextMenuAction>>,
+) {
+    if context_menu.menu.is_none() {
+        return;
+    }
+    let clicked_outside = mouse_button_input.any_just_pressed([MouseButton::Left, MouseButton::Right])
+        && entries.iter().all(|interaction| matches!(interaction, Interaction::None));
+    if key_input.just_pressed(KeyCode::Escape) || clicked_outside {
+        despawn_context_menu(&mut commands, &mut context_menu);
+    }
+}
+
+/// Graphing a metric shows the metric and the graph. Hiding, isolating and showing the related
+/// metrics of a series change the visibility of the series and metrics.
+pub(crate) fn apply_visibility_actions(
+    mut commands: Commands,
+    mut action_events: EventReader<ContextMenuActionEvent>,
+    graphing_metrics: Res<GraphingMetricsResource>,
+    mut graph: Query<&mut Visibility, With<GraphParent>>,
+    series: Query<Entity, With<GraphDimComponent>>,
+    parents: Query<&Parent>,
+) {
+    let mut show_graph = false;
+    for event in action_events.iter() {
+        match (&event.action, event.target) {
+            (ContextMenuAction::GraphMetric(metric), _) => {
+                set_visibility(&mut commands, *metric, Visibility::Inherited);
+                show_graph = true;
+            }
+            (ContextMenuAction::Hide, ContextMenuTarget::Series(target)) => {
+                set_visibility(&mut commands, target, Visibility::Hidden);
+            }
+            (ContextMenuAction::Isolate, ContextMenuTarget::Series(target)) => {
+                series.iter()
+                    .for_each(|entity| set_visibility(
+                        &mut commands,
+                        entity,
+                        if entity == target { Visibility::Inherited } else { Visibility::Hidden }
+                    ));
+                let _ = parents.get(target)
+                    .map(|metric| set_visibility(&mut commands, metric.get(), Visibility::Inherited));
+            }
+            (ContextMenuAction::ShowRelated, ContextMenuTarget::Series(target)) => {
+                let _ = parents.get(target)
+                    .map(|metric| {
+                        let related = graphing_metrics.related_metrics(metric.get());
+                        info!("Showing {} metrics related to {:?}.", related.len(), metric.get());
+                        related.into_iter()
+                            .for_each(|related| set_visibility(&mut commands, related, Visibility::Inherited));
+                    });
+                show_graph = true;
+            }
+            _ => {}
+        }
+    }
+    if show_graph {
+        graph.iter_mut()
+            .for_each(|mut visibility| *visibility = Visibility::Visible);
+    }
+}
+
+pub(crate) fn set_visibility(commands: &mut Commands, entity: Entity, visibility: Visibility) {
+    commands.get_entity(entity)
+        .as_mut()
+        .map(|entity| entity.insert(visibility));
+}
+
+/// Changing the color of a series moves it to the next color of the palette of its graph.
+pub(crate) fn apply_series_actions<T>(
+    mut commands: Commands,
+    mut action_events: EventReader<ContextMenuActionEvent>,
+    metrics_state: Res<MetricsState>,
+    config_properties: Res<ConfigurationProperties>,
+    mut metrics: Query<&mut Metric<T>>,
+    series: Query<(&GraphDimComponent, &Parent, &SeriesPolylines)>,
+    mut series_assets: ResMut<SeriesAssets>,
+    mut polyline_materials: ResMut<Assets<PolylineMaterial>>,
+)
+    where T: Component + Debug
+{
+    for event in action_events.iter() {
+        if event.action != ContextMenuAction::ChangeColor {
+            continue;
+        }
+        let target = match event.target {
+            ContextMenuTarget::Series(target) => target,
+            _ => continue
+        };
+        let (graph_dim, metric_entity, series_polylines) = match series.get(target) {
+            Ok((graph_dim, parent, series_polylines)) => (graph_dim, parent.get(), series_polylines),
+            Err(_) => continue
+        };
+        let metric = metrics.get_mut(metric_entity);
+        if metric.is_err() {
+            continue;
+        }
+        let mut metric = metric.unwrap();
+        let palette = metrics_state.run_metric(metric_entity)
+            .map(|(_, metric_name)| config_properties.palette.graph_palette(metric_name))
+            .or(Some(config_properties.palette.graph))
+            .unwrap();
+        let color = metric.metric_dim_component_children.get_mut(&graph_dim.name)
+            .map(|(_, color)| {
+                *color = next_series_color(*color, palette);
+                *color
+            });
+        color.map(|color| {
+            let material = series_assets.get_material(color, SERIES_THICKNESS, &mut polyline_materials);
+            series_polylines.lines.iter()
+                .for_each(|(line, _)| {
+                    commands.get_entity(*line)
+                        .as_mut()
+                        .map(|line| line.insert(material.clone()));
+                });
+        });
+    }
+}
+
+/// The color of the palette after the color, or the first color of the palette when the color is
+/// not one of them.
+pub(crate) fn next_series_color(color: Color, palette: Palette) -> Color {
+    let swatches = palette.swatches();
+    swatches.iter()
+        .position(|series_color| series_color.as_rgba_u32() == color.as_rgba_u32())
+        .map(|index| swatches[(index + 1) % swatches.len()])
+        .or(Some(swatches[0]))
+        .unwrap()
+}
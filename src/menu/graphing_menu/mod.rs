@@ -1 +1,3 @@
-pub(crate) mod graph_menu;
\ No newline at end of file
+pub(crate) mod graph_menu;
+/// A pop-up menu at the cursor for the node, layer or graph series clicked.
+pub(crate) mod context_menu;
@@ -0,0 +1,385 @@
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::fs::read_to_string;
+use std::path::Path;
+use bevy::prelude::Color;
+use bevy::ui::Val;
+use serde::Deserialize;
+use crate::menu::{ConfigurationOptionEnum, DataType, MenuData, MenuInputType, MenuItemFont, MenuItemMetadata, MenuOption, MenuOptionInputType, MenuOptionType, MenuType, MetricsConfigurationOption, Position, SliderData, SliderScale};
+use crate::menu::menu_resource::{CONCAVITY, MENU, METRICS, VARIANCE};
+use crate::ui_components::Size;
+
+/// The menu tree as it is written in the menu file. The definitions are validated and converted
+/// into the MenuData used to build the menu.
+#[derive(Deserialize, Default, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct MenuDefinition {
+    #[serde(default)]
+    pub(crate) menus: Vec<MenuInputDefinition>
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(tag = "type")]
+pub enum MenuInputDefinition {
+    Dropdown {
+        metadata: MenuItemDefinition,
+        option: OptionBinding,
+        #[serde(default)]
+        options: Vec<MenuOptionDefinition>
+    },
+    CollapsableMenu {
+        metadata: MenuItemDefinition,
+        option: OptionBinding,
+        #[serde(default)]
+        options: Vec<MenuOptionDefinition>
+    },
+    ScrollableMenu {
+        metadata: MenuItemDefinition,
+        option: OptionBinding,
+        #[serde(default)]
+        options: Vec<MenuOptionDefinition>
+    },
+    Radial {
+        metadata: MenuItemDefinition,
+        option: OptionBinding,
+        #[serde(default)]
+        options: Vec<MenuOptionDefinition>
+    },
+    FormInput {
+        name: String,
+        metadata: MenuItemDefinition,
+        option: OptionBinding
+    },
+    Slider {
+        metadata: MenuItemDefinition,
+        option: OptionBinding,
+        start: f32,
+        end: f32,
+        #[serde(default)]
+        step: Option<f32>,
+        #[serde(default)]
+        scale: SliderScale
+    }
+}
+
+/// An option of a menu. When the input type opens another menu, the sub menu is required, and a
+/// slider option requires a Slider sub menu with the range.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct MenuOptionDefinition {
+    metadata: MenuItemDefinition,
+    option: OptionBinding,
+    #[serde(default)]
+    input_type: MenuOptionInputType,
+    #[serde(default)]
+    swing_out: bool,
+    #[serde(default)]
+    sub_menu: Option<Box<MenuInputDefinition>>
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct MenuItemDefinition {
+    id: f32,
+    name: String,
+    #[serde(default)]
+    icon: String,
+    #[serde(default)]
+    icon_pos: Position,
+    #[serde(default)]
+    initial_value: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    font: Option<String>,
+    /// The width and height, such as `100%`, `30px` or `auto`.
+    #[serde(default)]
+    size: Option<(String, String)>,
+    /// A hex color, such as `#FF0000` or `#FF000080`.
+    #[serde(default)]
+    color: Option<String>,
+    #[serde(default)]
+    swing_out: bool
+}
+
+/// Binds a menu item to a ConfigurationOptionEnum by the name of the variant, such as
+/// `NodeVariance`. The menu type is used for the `Menu` option to choose which part of the
+/// application is shown.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct OptionBinding {
+    name: String,
+    #[serde(default = "deselected")]
+    data_type: DataType,
+    #[serde(default)]
+    menu_type: Option<MenuType>
+}
+
+fn deselected() -> DataType {
+    DataType::Deselected
+}
+
+#[derive(Debug)]
+pub enum MenuDefinitionError {
+    Read {
+        path: String,
+        message: String
+    },
+    UnsupportedFormat(String),
+    Parse {
+        format: String,
+        message: String
+    },
+    UnknownOption {
+        id: f32,
+        option: String
+    },
+    DuplicateId {
+        id: f32,
+        first: String,
+        second: String
+    },
+    InvalidValue {
+        id: f32,
+        message: String
+    }
+}
+
+impl MenuDefinition {
+
+    /// Reads the menu file, with the format determined by the extension: toml, ron or json.
+    pub(crate) fn read(path: &str) -> Result<MenuDefinition, MenuDefinitionError> {
+        let format = Path::new(path).extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_lowercase())
+            .ok_or(MenuDefinitionError::UnsupportedFormat(path.to_string()))?;
+        let contents = read_to_string(Path::new(path))
+            .map_err(|e| MenuDefinitionError::Read { path: path.to_string(), message: e.to_string() })?;
+        Self::parse(&contents, &format)
+    }
+
+    pub(crate) fn parse(contents: &str, format: &str) -> Result<MenuDefinition, MenuDefinitionError> {
+        let parse_error = |message: String| MenuDefinitionError::Parse { format: format.to_string(), message };
+        match format {
+            "toml" => toml::from_str::<MenuDefinition>(contents)
+                .map_err(|e| parse_error(e.to_string())),
+            "ron" => ron::from_str::<MenuDefinition>(contents)
+                .map_err(|e| parse_error(e.to_string())),
+            "json" => serde_json::from_str::<MenuDefinition>(contents)
+                .map_err(|e| parse_error(e.to_string())),
+            _ => Err(MenuDefinitionError::UnsupportedFormat(format.to_string()))
+        }
+    }
+
+    /// Validates the ids and options of the menu tree and converts it into the MenuData.
+    pub(crate) fn to_menu_data(&self) -> Result<MenuData, MenuDefinitionError> {
+        let mut ids: HashMap<u32, String> = HashMap::new();
+        let selectables = self.menus.iter()
+            .map(|menu| menu.to_menu_input(&mut ids))
+            .collect::<Result<Vec<MenuInputType>, MenuDefinitionError>>()?;
+        Ok(MenuData {
+            sub_menus: vec![],
+            selectables,
+        })
+    }
+}
+
+impl MenuInputDefinition {
+    fn to_menu_input(&self, ids: &mut HashMap<u32, String>) -> Result<MenuInputType, MenuDefinitionError> {
+        match self {
+            MenuInputDefinition::Dropdown { metadata, option, options } => {
+                let (metadata, option, options) = to_menu(metadata, option, options, ids)?;
+                Ok(MenuInputType::Dropdown { options, metadata, option })
+            }
+            MenuInputDefinition::CollapsableMenu { metadata, option, options } => {
+                let (metadata, option, options) = to_menu(metadata, option, options, ids)?;
+                Ok(MenuInputType::CollapsableMenuInputType { options, metadata, option })
+            }
+            MenuInputDefinition::ScrollableMenu { metadata, option, options } => {
+                let (metadata, option, options) = to_menu(metadata, option, options, ids)?;
+                Ok(MenuInputType::ScrollableMenu { options, metadata, option })
+            }
+            MenuInputDefinition::Radial { metadata, option, options } => {
+                let (metadata, option, options) = to_menu(metadata, option, options, ids)?;
+                Ok(MenuInputType::Radial { options, metadata, option })
+            }
+            MenuInputDefinition::FormInput { name, metadata, option } => {
+                let metadata = metadata.to_metadata(ids)?;
+                let option = option.to_option(metadata.id)?;
+                Ok(MenuInputType::FormInput { name: name.clone(), metadata, option })
+            }
+            MenuInputDefinition::Slider { metadata, option, start, end, step, scale } => {
+                let metadata = metadata.to_metadata(ids)?;
+                let invalid = |message: String| Err(MenuDefinitionError::InvalidValue { id: metadata.id, message });
+                if start >= end {
+                    return invalid(format!("slider start {} is not less than end {}", start, end));
+                }
+                if *scale == SliderScale::Log && *start <= 0.0 {
+                    return invalid(format!("log scale slider start {} is not positive", start));
+                }
+                if step.filter(|step| *step <= 0.0).is_some() {
+                    return invalid(format!("slider step {:?} is not positive", step));
+                }
+                let option = option.to_option(metadata.id)?;
+                Ok(MenuInputType::Slider {
+                    metadata,
+                    slider_data: SliderData { start: *start, end: *end, step: *step, scale: scale.clone() },
+                    option
+                })
+            }
+        }
+    }
+}
+
+fn to_menu(
+    metadata: &MenuItemDefinition,
+    option: &OptionBinding,
+    options: &Vec<MenuOptionDefinition>,
+    ids: &mut HashMap<u32, String>
+) -> Result<(MenuItemMetadata, ConfigurationOptionEnum, Vec<MenuOption>), MenuDefinitionError> {
+    let metadata = metadata.to_metadata(ids)?;
+    let option = option.to_option(metadata.id)?;
+    let options = options.iter()
+        .enumerate()
+        .map(|(index, menu_option)| menu_option.to_menu_option(index, ids))
+        .collect::<Result<Vec<MenuOption>, MenuDefinitionError>>()?;
+    Ok((metadata, option, options))
+}
+
+impl MenuOptionDefinition {
+    fn to_menu_option(&self, index: usize, ids: &mut HashMap<u32, String>) -> Result<MenuOption, MenuDefinitionError> {
+        let metadata = self.metadata.to_metadata(ids)?;
+        let config_option = self.option.to_option(metadata.id)?;
+        let opens_menu = matches!(
+            self.input_type,
+            MenuOptionInputType::SubMenu | MenuOptionInputType::DropdownMenu | MenuOptionInputType::CollapsableMenu
+                | MenuOptionInputType::Slider | MenuOptionInputType::Radial | MenuOptionInputType::FormInput
+        );
+        let slider_sub_menu = self.sub_menu.as_ref()
+            .map(|sub_menu| matches!(**sub_menu, MenuInputDefinition::Slider { .. }))
+            .or(Some(false))
+            .unwrap();
+        if self.input_type == MenuOptionInputType::Slider && !slider_sub_menu {
+            return Err(MenuDefinitionError::InvalidValue {
+                id: metadata.id,
+                message: "slider option requires a Slider sub menu".to_string()
+            });
+        }
+        if self.input_type != MenuOptionInputType::Slider && slider_sub_menu {
+            return Err(MenuDefinitionError::InvalidValue {
+                id: metadata.id,
+                message: format!("option with input type {:?} cannot have a Slider sub menu", self.input_type)
+            });
+        }
+        let data_type = match (&self.sub_menu, opens_menu) {
+            (Some(sub_menu), true) => MenuOptionType::SubMenu {
+                sub_menu: sub_menu.to_menu_input(ids)?,
+                parent: metadata.clone(),
+                config_option,
+            },
+            (None, false) => MenuOptionType::Primitive(config_option),
+            (Some(_), false) => return Err(MenuDefinitionError::InvalidValue {
+                id: metadata.id,
+                message: format!("option with input type {:?} cannot have a sub menu", self.input_type)
+            }),
+            (None, true) => return Err(MenuDefinitionError::InvalidValue {
+                id: metadata.id,
+                message: format!("option with input type {:?} requires a sub menu", self.input_type)
+            }),
+        };
+        Ok(MenuOption {
+            data_type,
+            index,
+            metadata,
+            swing_out: self.swing_out,
+            ui_option_type: self.input_type.clone(),
+        })
+    }
+}
+
+impl MenuItemDefinition {
+    fn to_metadata(&self, ids: &mut HashMap<u32, String>) -> Result<MenuItemMetadata, MenuDefinitionError> {
+        if let Some(first) = ids.insert(self.id.to_bits(), self.name.clone()) {
+            return Err(MenuDefinitionError::DuplicateId {
+                id: self.id,
+                first,
+                second: self.name.clone()
+            });
+        }
+        let size = self.size.as_ref()
+            .map(|(width, height)| Ok::<Size, MenuDefinitionError>(Size::new(
+                parse_val(self.id, width)?,
+                parse_val(self.id, height)?
+            )))
+            .transpose()?;
+        let color = self.color.as_ref()
+            .map(|color| Color::hex(color.trim_start_matches('#'))
+                .map_err(|e| MenuDefinitionError::InvalidValue {
+                    id: self.id,
+                    message: format!("invalid color {}: {:?}", color, e)
+                })
+            )
+            .transpose()?;
+        Ok(MenuItemMetadata {
+            icon: self.icon.clone(),
+            name: self.name.clone(),
+            initial_value: self.initial_value.clone(),
+            icon_pos: self.icon_pos.clone(),
+            font: self.font.as_ref().map(|font| MenuItemFont { font: font.clone() }),
+            size,
+            color,
+            description: self.description.clone(),
+            id: self.id,
+            swing_out: self.swing_out,
+        })
+    }
+}
+
+fn parse_val(id: f32, value: &str) -> Result<Val, MenuDefinitionError> {
+    let value = value.trim();
+    let parsed = if value == "auto" {
+        Some(Val::Auto)
+    } else if let Some(percent) = value.strip_suffix('%') {
+        percent.trim().parse::<f32>().ok().map(Val::Percent)
+    } else if let Some(px) = value.strip_suffix("px") {
+        px.trim().parse::<f32>().ok().map(Val::Px)
+    } else {
+        None
+    };
+    parsed.ok_or(MenuDefinitionError::InvalidValue {
+        id,
+        message: format!("invalid size {}, expected a value such as 100%, 30px or auto", value)
+    })
+}
+
+impl OptionBinding {
+    fn to_option(&self, id: f32) -> Result<ConfigurationOptionEnum, MenuDefinitionError> {
+        let data_type = self.data_type.clone();
+        let option = match self.name.as_str() {
+            "Menu" => ConfigurationOptionEnum::Menu(
+                match self.menu_type.clone().or(Some(MenuType::Menu)).unwrap() {
+                    MenuType::Network => MetricsConfigurationOption::NetworkMenu(PhantomData::default(), data_type, MENU, MenuType::Network),
+                    menu_type => MetricsConfigurationOption::GraphMenu(PhantomData::default(), data_type, MENU, menu_type)
+                }
+            ),
+            "Metrics" => ConfigurationOptionEnum::Metrics(MetricsConfigurationOption::Metrics(PhantomData::default(), data_type, METRICS)),
+            "NetworkMetrics" => ConfigurationOptionEnum::NetworkMetrics(MetricsConfigurationOption::Metrics(PhantomData::default(), data_type, METRICS)),
+            "NetworkVariance" => ConfigurationOptionEnum::NetworkVariance(MetricsConfigurationOption::Variance(PhantomData::default(), data_type, VARIANCE)),
+            "NetworkConcavity" => ConfigurationOptionEnum::NetworkConcavity(MetricsConfigurationOption::Concavity(PhantomData::default(), data_type, CONCAVITY)),
+            "LayerMetrics" => ConfigurationOptionEnum::LayerMetrics(MetricsConfigurationOption::Metrics(PhantomData::default(), data_type, METRICS)),
+            "LayerVariance" => ConfigurationOptionEnum::LayerVariance(MetricsConfigurationOption::Variance(PhantomData::default(), data_type, VARIANCE)),
+            "LayerConcavity" => ConfigurationOptionEnum::LayerConcavity(MetricsConfigurationOption::Concavity(PhantomData::default(), data_type, CONCAVITY)),
+            "NodeMetrics" => ConfigurationOptionEnum::NodeMetrics(MetricsConfigurationOption::Metrics(PhantomData::default(), data_type, METRICS)),
+            "NodeVariance" => ConfigurationOptionEnum::NodeVariance(MetricsConfigurationOption::Variance(PhantomData::default(), data_type, VARIANCE)),
+            "NodeConcavity" => ConfigurationOptionEnum::NodeConcavity(MetricsConfigurationOption::Concavity(PhantomData::default(), data_type, CONCAVITY)),
+            _ => return Err(MenuDefinitionError::UnknownOption { id, option: self.name.clone() })
+        };
+        if self.menu_type.is_some() && self.name != "Menu" {
+            return Err(MenuDefinitionError::InvalidValue {
+                id,
+                message: format!("menu type can only be set for the Menu option, not {}", self.name)
+            });
+        }
+        Ok(option)
+    }
+}
@@ -1,8 +1,9 @@
 use std::fmt::Debug;
 use std::marker::PhantomData;
-use bevy::prelude::{Bundle, Color, Commands, Component, Display, FromReflect, Reflect, Visibility};
+use bevy::prelude::{Bundle, Color, Commands, Component, Display, FromReflect, Overflow, Reflect, Val, Visibility};
 use bevy::utils::petgraph::visit::Data;
 use serde::Deserialize;
+use crate::config::theme::DEFAULT_FONT;
 use crate::event::event_state::{Context, UpdateStateInPlace};
 use crate::menu::menu_resource::{MENU, VARIANCE};
 use ui_menu_event::transition_groups::PropagateVisible;
@@ -15,6 +16,10 @@ pub(crate) mod ui_menu_event;
 pub(crate) mod config_menu_event;
 pub(crate) mod menu_resource;
 pub(crate) mod graphing_menu;
+/// Menus defined in a TOML, RON or JSON file, validated and converted into the MenuData.
+pub(crate) mod menu_definition;
+
+pub const SCROLLABLE_MENU_MAX_HEIGHT: f32 = 300.0;
 
 pub struct MenuData {
     pub(crate) sub_menus: Vec<SubMenu>,
@@ -48,12 +53,12 @@ pub struct MenuItemFont {
 impl Default for MenuItemFont {
     fn default() -> Self {
         Self {
-            font: "fonts/FiraSans-Bold.ttf".to_string()
+            font: DEFAULT_FONT.to_string()
         }
     }
 }
 
-#[derive(Default, Clone, Debug)]
+#[derive(Default, Clone, Debug, Deserialize)]
 pub enum Position {
     Left,
     #[default]
@@ -68,8 +73,9 @@ pub enum SelectableType {
     NotSelectable
 }
 
-#[derive(Clone, Debug, Hash, Ord, PartialOrd, Eq, PartialEq)]
+#[derive(Default, Clone, Debug, Hash, Ord, PartialOrd, Eq, PartialEq, Deserialize)]
 pub enum MenuOptionInputType {
+    #[default]
     Activated,
     Radial,
     FormInput,
@@ -115,10 +121,74 @@ pub enum MenuInputType {
     }
 }
 
+/// The range of values of a slider. The position of the knob is mapped onto the range linearly
+/// or, for the log scale, by the exponent, and the value is snapped to the step.
 #[derive(Clone, Debug)]
 pub struct SliderData {
-    start: u32,
-    end: u32
+    pub(crate) start: f32,
+    pub(crate) end: f32,
+    pub(crate) step: Option<f32>,
+    pub(crate) scale: SliderScale
+}
+
+#[derive(Default, Clone, Debug, PartialEq, Deserialize)]
+pub enum SliderScale {
+    #[default]
+    Linear,
+    Log
+}
+
+impl Default for SliderData {
+    fn default() -> Self {
+        Self {
+            start: 0.0,
+            end: 1.0,
+            step: None,
+            scale: SliderScale::Linear,
+        }
+    }
+}
+
+impl SliderData {
+
+    /// The value at the fraction of the slider, from 0 at the start to 1 at the end.
+    pub(crate) fn value_at(&self, fraction: f32) -> f32 {
+        let fraction = fraction.clamp(0.0, 1.0);
+        let value = match self.scale {
+            SliderScale::Linear => self.start + fraction * (self.end - self.start),
+            SliderScale::Log => (self.start.ln() + fraction * (self.end.ln() - self.start.ln())).exp()
+        };
+        self.snap(value)
+    }
+
+    /// The fraction of the slider the value is at, so that the knob can be placed for the value.
+    pub(crate) fn fraction_of(&self, value: f32) -> f32 {
+        let fraction = match self.scale {
+            SliderScale::Linear => (value - self.start) / (self.end - self.start),
+            SliderScale::Log => (value.ln() - self.start.ln()) / (self.end.ln() - self.start.ln())
+        };
+        if fraction.is_finite() { fraction.clamp(0.0, 1.0) } else { 0.0 }
+    }
+
+    pub(crate) fn snap(&self, value: f32) -> f32 {
+        let (min, max) = if self.start <= self.end { (self.start, self.end) } else { (self.end, self.start) };
+        self.step
+            .filter(|step| *step > 0.0)
+            .map(|step| self.start + ((value - self.start) / step).round() * step)
+            .or(Some(value))
+            .unwrap()
+            .clamp(min, max)
+    }
+
+    /// The value formatted with as many decimals as the step has.
+    pub(crate) fn format(&self, value: f32) -> String {
+        let decimals = self.step
+            .filter(|step| *step > 0.0)
+            .map(|step| (-step.log10().floor()).max(0.0) as usize)
+            .or(Some(3))
+            .unwrap();
+        format!("{:.*}", decimals, value)
+    }
 }
 
 /// Query by the T in ConfigurationOption, and then query by the T component in order to apply
@@ -170,7 +240,7 @@ impl<T: ChangeVisible + Debug> StateAdviser<Visibility> for T {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize)]
 pub enum MenuType {
     Graph, Network, Metrics, Menu
 }
@@ -194,6 +264,37 @@ for MetricsConfigurationOption<T> {
     }
 }
 
+impl<T> MetricsConfigurationOption<T>
+where
+    T: Component + Send + Sync + Clone + Debug + Default + 'static
+{
+    pub(crate) fn data_type(&self) -> &DataType {
+        match self {
+            Self::Variance(_, data_type, _) => data_type,
+            Self::Concavity(_, data_type, _) => data_type,
+            Self::Metrics(_, data_type, _) => data_type,
+            Self::GraphMenu(_, data_type, ..) => data_type,
+            Self::NetworkMenu(_, data_type, ..) => data_type,
+        }
+    }
+
+    pub(crate) fn data_type_mut(&mut self) -> &mut DataType {
+        match self {
+            Self::Variance(_, data_type, _) => data_type,
+            Self::Concavity(_, data_type, _) => data_type,
+            Self::Metrics(_, data_type, _) => data_type,
+            Self::GraphMenu(_, data_type, ..) => data_type,
+            Self::NetworkMenu(_, data_type, ..) => data_type,
+        }
+    }
+
+    pub(crate) fn with_data_type(&self, data_type: DataType) -> Self {
+        let mut option = self.clone();
+        *option.data_type_mut() = data_type;
+        option
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct MenuOption {
     pub(crate) data_type: MenuOptionType,
@@ -231,6 +332,24 @@ impl ConfigurationOptionEnum {
     }
 }
 
+impl ConfigurationOptionEnum {
+    pub(crate) fn data_type(&self) -> &DataType {
+        match self {
+            Self::Menu(option) => option.data_type(),
+            Self::Metrics(option) => option.data_type(),
+            Self::NetworkMetrics(option) => option.data_type(),
+            Self::NetworkVariance(option) => option.data_type(),
+            Self::NetworkConcavity(option) => option.data_type(),
+            Self::LayerMetrics(option) => option.data_type(),
+            Self::LayerVariance(option) => option.data_type(),
+            Self::LayerConcavity(option) => option.data_type(),
+            Self::NodeMetrics(option) => option.data_type(),
+            Self::NodeVariance(option) => option.data_type(),
+            Self::NodeConcavity(option) => option.data_type(),
+        }
+    }
+}
+
 impl Default for ConfigurationOptionEnum {
     fn default() -> Self {
         ConfigurationOptionEnum::Menu(
@@ -251,7 +370,7 @@ pub enum MenuOptionType {
 }
 
 /// Contains the default value.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Deserialize)]
 pub enum DataType {
     Number(Option<f32>),
     String(Option<String>),
@@ -278,24 +397,40 @@ pub struct CollapsableMenuComponent {
 
 #[derive(Component, Clone, Debug, Default)]
 pub struct Slider {
+    pub(crate) name: String,
+    pub(crate) slider_data: SliderData,
+    pub(crate) value: f32
 }
 
 #[derive(Component, Clone, Debug, Default)]
 pub struct SliderKnob {
 }
 
+/// A group of radio buttons, of which one option is selected.
 #[derive(Component, Clone, Debug, Default)]
 pub struct Radial {
+    pub(crate) selected_index: usize
 }
 
 #[derive(Component, Clone, Debug, Default)]
 pub struct RadialButton {
+    pub(crate) index: usize
 }
 
 #[derive(Component, Clone, Debug, Default)]
 pub struct RadialButtonSelection {
 }
 
+/// A text or number input. The caret is the index of the character the next character is
+/// inserted before.
+#[derive(Component, Clone, Debug, Default)]
+pub struct FormInputComponent {
+    pub(crate) value: String,
+    pub(crate) caret: usize,
+    pub(crate) numeric: bool,
+    pub(crate) valid: bool
+}
+
 #[derive(Component, Default, Clone, Debug)]
 pub struct DropdownOption {
     pub(crate) index: usize,
@@ -349,15 +484,21 @@ pub enum UiComponent {
     Dropdown(Dropdown),
     MenuOption(DropdownOption),
     CollapsableMenu(CollapsableMenuComponent),
-    SlideComponent(Slider),
+    /// The value of the slider is the Slider component of the same entity, which is updated as
+    /// the knob is dragged.
+    SlideComponent,
     SliderKnob(SliderKnob),
-    RadialComponent(Radial),
-    RadialButton(RadialButton),
+    /// The selected index of the radial menu is the Radial component of the same entity.
+    RadialComponent,
+    /// The index of the button is the RadialButton component of the same entity.
+    RadialButton,
     RadialSelection(RadialButtonSelection),
     ScrollableMenuComponent(ScrollableMenuComponent),
     ScrollWheel(ScrollWheelComponent),
     ScrollingSidebar(ScrollingSidebarComponent),
     ScrollableMenuItemsBar(ScrollableMenuItemsBarComponent),
+    /// The value of the form input is the FormInputComponent of the input field in it.
+    FormInput,
     DropdownSelectable,
     NamedDropdownMenu,
     MenuOptionCheckmark,
@@ -367,8 +508,31 @@ pub enum UiComponent {
 impl UiComponent {
     pub(crate) fn starting_display(&self) -> Display {
         match self {
-            UiComponent::RadialComponent(_) => Display::Flex,
+            UiComponent::RadialComponent => Display::Flex,
+            UiComponent::FormInput => Display::Flex,
             _ => Display::None
         }
     }
+
+    pub(crate) fn starting_height(&self) -> Val {
+        match self {
+            UiComponent::ScrollableMenuComponent(_) => Val::Auto,
+            _ => Val::Px(40.0)
+        }
+    }
+
+    /// Scrollable menus are clipped at the max height, and the options are scrolled into view.
+    pub(crate) fn max_height(&self) -> Val {
+        match self {
+            UiComponent::ScrollableMenuComponent(_) => Val::Px(SCROLLABLE_MENU_MAX_HEIGHT),
+            _ => Val::Auto
+        }
+    }
+
+    pub(crate) fn overflow(&self) -> Overflow {
+        match self {
+            UiComponent::ScrollableMenuComponent(_) => Overflow::clip_y(),
+            _ => Overflow::visible()
+        }
+    }
 }
@@ -1,6 +1,7 @@
 use bevy::prelude::*;
 use bevy::app::{App, Plugin};
 use std::fmt::Debug;
+use crate::config::theme::Theme;
 use crate::cursor_adapter::{event_merge_propagate, propagate_drag_events, propagate_scroll_events};
 use crate::event::event_actions::{EventsSystem, InsertComponentInteractionEventReader, InteractionEventReader};
 use crate::event::event_descriptor::EventArgs;
@@ -32,6 +33,7 @@ impl Plugin for UiEventPlugin {
             .insert_resource(DraggableStateChangeRetriever::default())
             .insert_resource(ScrollableStateChangeRetriever::default())
             .insert_resource(UiContext::default())
+            .init_resource::<Theme>()
             .insert_resource(ClickSelectOptions::default())
             .insert_resource(ClickSelectionEventRetriever::default())
             .insert_resource(ChangeVisibleEventRetriever::<MetricsConfigurationOption<Menu>, Visibility>::default())
@@ -1,10 +1,11 @@
 use std::fmt::Debug;
 use bevy::ecs::component::ComponentStorage;
-use bevy::prelude::{BackgroundColor, Button, Changed, Color, Commands, Component, Display, Entity, info, Interaction, Query, ResMut, Resource, Style, Visibility, With};
+use bevy::prelude::{BackgroundColor, Button, Changed, Commands, Component, Display, Entity, info, Interaction, Query, Res, ResMut, Resource, Style, Visibility, With};
 use bevy::ui::UiRect;
 use bevy::ecs::query::ReadOnlyWorldQuery;
 use bevy::math::Vec2;
 use bevy::input::mouse::MouseScrollUnit;
+use crate::config::theme::{Theme, ThemeColor};
 use crate::event::event_descriptor::{EventArgs, EventData};
 use crate::event::event_state::{ComponentChangeEventData, Context, StyleStateChangeEventData, Update};
 use crate::menu::{UiComponent};
@@ -104,14 +105,15 @@ impl StateChangeMachine<Style, UiContext, UiEventArgs> for StyleStateChangeEvent
 
 pub fn hover_event(
     mut query: Query<(&mut Style, &mut BackgroundColor, &Interaction), (With<UiComponent>, With<Button>, Changed<Interaction>)>,
+    theme: Res<Theme>,
 ) {
     for (_, mut color, interaction) in query.iter_mut() {
         match *interaction {
             Interaction::Pressed => {
-                color.0 = Color::BLUE;
+                color.0 = theme.color(ThemeColor::MenuPressed);
             }
             Interaction::Hovered => {
-                color.0 = Color::YELLOW;
+                color.0 = theme.color(ThemeColor::MenuHovered);
             }
             Interaction::None => {
                 // color.0 = Color::GREEN;
@@ -0,0 +1,441 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::Duration;
+use bevy::prelude::*;
+use bevy::tasks::AsyncComputeTaskPool;
+use ndarray::{Array1, s};
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use rdkafka::util::Timeout;
+use serde::Serialize;
+use crate::config::ConfigurationProperties;
+use crate::config::alerts::{AlertKind, AlertRule};
+use crate::config::theme::{DEFAULT_FONT, ThemeColor};
+use crate::data_subscriber::kafka_data_subscriber::KafkaClientProvider;
+use crate::data_subscriber::metric_event::{MetricComponentType, MetricsState};
+use crate::graph::draw_graph_points::WithDataSeriesChangedHistorical;
+use crate::graph::radial::calculate_derivatives;
+use crate::metrics::network_metrics::Metric;
+use crate::network::{Layer, MetricChildNodes, Network, Node};
+use crate::theme::highlight::Highlight;
+use crate::theme::restyle::{ThemedBackground, ThemedText};
+use crate::util::{glob_match, run_blocking};
+
+pub const MAX_ALERTS_DISPLAYED: usize = 10;
+
+#[derive(Serialize, Clone, Debug)]
+pub struct Alert {
+    pub(crate) id: u64,
+    pub(crate) rule: String,
+    pub(crate) run_id: String,
+    pub(crate) metric_name: String,
+    pub(crate) column: String,
+    pub(crate) index: usize,
+    pub(crate) timestep: u64,
+    pub(crate) value: f32,
+    pub(crate) message: String,
+    /// The Node and Layer entities the metric is indexed by.
+    #[serde(skip)]
+    pub(crate) entities: Vec<Entity>,
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct AlertEvent(pub(crate) Alert);
+
+/// Sent with the id of an alert when its entry is clicked, or when its entry is dropped from the
+/// full alerts list, to remove the alert and its highlight.
+#[derive(Event, Clone, Debug, PartialEq)]
+pub struct AlertAcknowledged(pub(crate) u64);
+
+/// The alerts that have fired and have not been acknowledged. A rule fires once when it starts
+/// matching a value, and can fire again after it stops matching.
+#[derive(Resource, Default, Debug)]
+pub struct Alerts {
+    pub(crate) alerts: Vec<Alert>,
+    active: HashSet<(String, String, String, String, usize)>,
+    next_id: u64,
+}
+
+#[derive(Component, Default, Debug)]
+pub struct AlertsList;
+
+#[derive(Component, Debug)]
+pub struct AlertEntry {
+    pub(crate) id: u64
+}
+
+#[derive(Resource, Default)]
+pub struct AlertPublisher {
+    producer: Option<FutureProducer>,
+    topic: Option<String>
+}
+
+pub struct AlertsPlugin;
+
+impl Plugin for AlertsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<AlertEvent>()
+            .add_event::<AlertAcknowledged>()
+            .init_resource::<Alerts>()
+            .init_resource::<AlertPublisher>()
+            .init_resource::<MetricsState>()
+            .add_startup_system(setup_alerts_list)
+            .add_startup_system(setup_alert_publisher)
+            .add_system(add_alert_entries)
+            .add_system(publish_alerts)
+            .add_system(acknowledge_alerts)
+            .add_system(remove_acknowledged_alerts
+                .after(add_alert_entries)
+                .after(acknowledge_alerts))
+            .add_system(highlight_alerts.after(remove_acknowledged_alerts));
+        add_alert_detection::<Node>(app);
+        add_alert_detection::<Layer>(app);
+        add_alert_detection::<Network>(app);
+        add_alert_detection::<MetricChildNodes>(app);
+    }
+}
+
+fn add_alert_detection<T>(app: &mut App)
+    where T: Component
+{
+    app.add_system(detect_alerts::<T>
+        .before(add_alert_entries)
+        .before(publish_alerts)
+        .before(highlight_alerts));
+}
+
+impl Alerts {
+    /// Removes the alert, returning it if it had not already been acknowledged.
+    pub(crate) fn acknowledge(&mut self, id: u64) -> Option<Alert> {
+        self.alerts.iter()
+            .position(|alert| alert.id == id)
+            .map(|position| self.alerts.remove(position))
+    }
+
+    /// The Nodes and Layers of the alerts that have not been acknowledged.
+    pub(crate) fn highlighted(&self) -> HashSet<Entity> {
+        self.alerts.iter()
+            .flat_map(|alert| alert.entities.iter().cloned())
+            .collect()
+    }
+}
+
+/// Evaluates the rule for the latest value of the series. Returns the message for the alert if
+/// the rule matches.
+pub(crate) fn evaluate_rule(kind: &AlertKind, series: &Array1<f32>) -> Option<String> {
+    let n = series.len();
+    let latest = *series.last()?;
+    match kind {
+        AlertKind::NonFinite => {
+            (!latest.is_finite()).then(|| format!("value is {}", latest))
+        }
+        AlertKind::Spike { z_score, min_history } => {
+            if n < (*min_history).max(3) || !latest.is_finite() {
+                return None;
+            }
+            let first_deriv = calculate_derivatives(series, 1).pop()?;
+            let change = *first_deriv.last()?;
+            let prev = first_deriv.slice(s![..first_deriv.len() - 1])
+                .iter()
+                .filter(|d| d.is_finite())
+                .cloned()
+                .collect::<Array1<f32>>();
+            let mean = prev.mean()?;
+            let std = prev.std(1.0);
+            if std <= f32::EPSILON {
+                return None;
+            }
+            let found_z = (change - mean) / std;
+            (found_z > *z_score).then(|| format!("spike with z-score {:.2} > {:.2}", found_z, z_score))
+        }
+        AlertKind::Threshold { above, below } => {
+            if above.filter(|above| latest > *above).is_some() {
+                Some(format!("value {} above {}", latest, above.unwrap()))
+            } else if below.filter(|below| latest < *below).is_some() {
+                Some(format!("value {} below {}", latest, below.unwrap()))
+            } else {
+                None
+            }
+        }
+        AlertKind::Growth { factor, window } => {
+            if n <= *window {
+                return None;
+            }
+            let prev = series[n - 1 - window];
+            if prev.abs() <= f32::EPSILON {
+                return None;
+            }
+            let growth = latest / prev;
+            (growth > *factor).then(|| format!("grew {:.1}x over {} steps", growth, window))
+        }
+        AlertKind::Plateau { window, tolerance } => {
+            if n < *window || *window < 2 {
+                return None;
+            }
+            let recent = series.slice(s![n - window..]);
+            let max = recent.fold(f32::MIN, |a, b| a.max(*b));
+            let min = recent.fold(f32::MAX, |a, b| a.min(*b));
+            (max - min <= *tolerance).then(|| format!("changed by less than {} over {} steps", tolerance, window))
+        }
+    }
+}
+
+fn rule_matches(rule: &AlertRule, metric_name: &str, column: &str) -> bool {
+    glob_match(&rule.metric, metric_name)
+        && rule.column.as_ref()
+            .map(|c| glob_match(c, column))
+            .or(Some(true))
+            .unwrap()
+}
+
+/// The Node and Layer entities for the metric indices of the metric.
+fn indexed_entities<T: Component>(metric: &Metric<T>, layers: &Query<(Entity, &Layer)>) -> Vec<Entity> {
+    let layer_indices = metric.metric_indices.get(&MetricComponentType::Layer);
+    let node_indices = metric.metric_indices.get(&MetricComponentType::Node);
+    layers.iter()
+        .flat_map(|(layer_entity, layer)| {
+            let mut entities = vec![];
+            if layer_indices.filter(|indices| indices.iter().any(|i| i == layer.name)).is_some() {
+                entities.push(layer_entity);
+            }
+            node_indices.map(|indices| layer.nodes.iter()
+                .filter(|node| indices.contains(&layer.node_id(node)))
+                .flat_map(|node| node.entity)
+                .for_each(|node| entities.push(node))
+            );
+            entities
+        })
+        .collect()
+}
+
+pub(crate) fn detect_alerts<T>(
+    config_properties: Res<ConfigurationProperties>,
+    metrics_state: Res<MetricsState>,
+    mut alerts: ResMut<Alerts>,
+    metrics: Query<(Entity, &Metric<T>), WithDataSeriesChangedHistorical>,
+    layers: Query<(Entity, &Layer)>,
+    mut alert_writer: EventWriter<AlertEvent>
+)
+    where T: Component
+{
+    let names = metrics_state.entities.iter()
+        .map(|(run_metric, (entity, _))| (*entity, run_metric))
+        .collect::<HashMap<Entity, &(String, String)>>();
+
+    for (metric_entity, metric) in metrics.iter() {
+        let (run_id, metric_name) = match names.get(&metric_entity) {
+            Some(run_metric) => *run_metric,
+            None => continue
+        };
+        let timestep = metric.historical.timestep.keys().last().cloned().or(Some(0)).unwrap();
+        for column in metric.historical.labels.keys() {
+            let rules = config_properties.alerts.rules.iter()
+                .filter(|rule| rule_matches(rule, metric_name, column))
+                .collect::<Vec<&AlertRule>>();
+            if rules.is_empty() {
+                continue;
+            }
+            for (index, series) in metric.historical.retrieve_historical_1d(column).iter().enumerate() {
+                let series = series.slice(s![1..]).to_owned();
+                for rule in rules.iter() {
+                    let key = (rule.name.clone(), run_id.clone(), metric_name.clone(), column.clone(), index);
+                    match evaluate_rule(&rule.kind, &series) {
+                        Some(message) if !alerts.active.contains(&key) => {
+                            alerts.active.insert(key);
+                            let id = alerts.next_id;
+                            alerts.next_id += 1;
+                            let alert = Alert {
+                                id,
+                                rule: rule.name.clone(),
+                                run_id: run_id.clone(),
+                                metric_name: metric_name.clone(),
+                                column: column.clone(),
+                                index,
+                                timestep,
+                                value: series.last().cloned().or(Some(f32::NAN)).unwrap(),
+                                message: format!("{} {}[{}]: {}", metric_name, column, index, message),
+                                entities: indexed_entities(metric, &layers),
+                            };
+                            info!("Alert {} fired: {}.", rule.name, alert.message);
+                            alerts.alerts.push(alert.clone());
+                            alert_writer.send(AlertEvent(alert));
+                        }
+                        None => {
+                            alerts.active.remove(&key);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Highlights the Nodes and Layers of the alerts that have not been acknowledged, and clears the
+/// highlight of those whose alerts have all been acknowledged.
+pub(crate) fn highlight_alerts(
+    alerts: Res<Alerts>,
+    added: Query<(), Added<Highlight>>,
+    mut highlights: Query<(Entity, &mut Highlight)>,
+) {
+    if !alerts.is_changed() && added.is_empty() {
+        return;
+    }
+    let to_highlight = alerts.highlighted();
+    for (entity, mut highlight) in highlights.iter_mut() {
+        let alert = to_highlight.contains(&entity);
+        if highlight.alert != alert {
+            highlight.alert = alert;
+        }
+    }
+}
+
+pub(crate) fn setup_alerts_list(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(10.0),
+                bottom: Val::Px(10.0),
+                display: Display::Flex,
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            ..default()
+        },
+        AlertsList::default()
+    ));
+}
+
+/// Adds an entry for each new alert. When the list is full, the oldest entry is dropped and its
+/// alert is acknowledged, so that no highlight is left without an entry to clear it.
+pub(crate) fn add_alert_entries(
+    mut commands: Commands,
+    mut alert_events: EventReader<AlertEvent>,
+    mut acknowledged: EventWriter<AlertAcknowledged>,
+    asset_server: Res<AssetServer>,
+    alerts_list: Query<(Entity, Option<&Children>), With<AlertsList>>,
+    entries: Query<&AlertEntry>,
+) {
+    let _ = alerts_list.get_single()
+        .map(|(alerts_list, children)| {
+            let mut displayed = children.iter()
+                .flat_map(|c| c.iter())
+                .flat_map(|entry| entries.get(*entry).map(|alert_entry| (*entry, alert_entry.id)))
+                .collect::<VecDeque<(Entity, u64)>>();
+            for AlertEvent(alert) in alert_events.iter() {
+                while displayed.len() >= MAX_ALERTS_DISPLAYED {
+                    displayed.pop_front()
+                        .map(|(entry, id)| {
+                            commands.entity(entry).despawn_recursive();
+                            acknowledged.send(AlertAcknowledged(id));
+                        });
+                }
+                let entry = commands.spawn((
+                    ButtonBundle {
+                        style: Style {
+                            padding: UiRect::all(Val::Px(4.0)),
+                            margin: UiRect::all(Val::Px(2.0)),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    ThemedBackground(ThemeColor::Panel),
+                    AlertEntry { id: alert.id }
+                ))
+                    .with_children(|entry| {
+                        entry.spawn((
+                            TextBundle {
+                                text: Text::from_section(alert.message.clone(), TextStyle {
+                                    font_size: 14.0,
+                                    font: asset_server.load(DEFAULT_FONT),
+                                    ..default()
+                                }),
+                                ..default()
+                            },
+                            ThemedText(ThemeColor::Alert)
+                        ));
+                    })
+                    .id();
+                commands.entity(alerts_list).add_child(entry);
+                displayed.push_back((entry, alert.id));
+            }
+        });
+}
+
+/// Clicking an alert in the list acknowledges it.
+pub(crate) fn acknowledge_alerts(
+    mut commands: Commands,
+    entries: Query<(Entity, &Interaction, &AlertEntry), Changed<Interaction>>,
+    mut acknowledged: EventWriter<AlertAcknowledged>,
+) {
+    for (entry_entity, interaction, entry) in entries.iter() {
+        if !matches!(interaction, Interaction::Pressed) {
+            continue;
+        }
+        commands.entity(entry_entity).despawn_recursive();
+        acknowledged.send(AlertAcknowledged(entry.id));
+    }
+}
+
+/// Removes the acknowledged alerts, and the highlight of the Node or Layer is cleared in
+/// highlight_alerts when it has no other alerts.
+pub(crate) fn remove_acknowledged_alerts(
+    mut acknowledged: EventReader<AlertAcknowledged>,
+    mut alerts: ResMut<Alerts>,
+) {
+    for AlertAcknowledged(id) in acknowledged.iter() {
+        alerts.acknowledge(*id);
+    }
+}
+
+pub(crate) fn setup_alert_publisher(
+    config_properties: Res<ConfigurationProperties>,
+    kafka_client: Option<Res<KafkaClientProvider>>,
+    mut alert_publisher: ResMut<AlertPublisher>,
+) {
+    if config_properties.alerts.publish_topic.is_none() || kafka_client.is_none() {
+        return;
+    }
+    let _ = run_blocking(kafka_client.unwrap().get_producer())
+        .map(|producer| {
+            alert_publisher.producer = Some(producer);
+            alert_publisher.topic = config_properties.alerts.publish_topic.clone();
+        })
+        .or_else(|e| {
+            error!("Could not create producer to publish alerts: {:?}.", e);
+            Err(e)
+        });
+}
+
+pub(crate) fn publish_alerts(
+    mut alert_events: EventReader<AlertEvent>,
+    alert_publisher: Res<AlertPublisher>,
+) {
+    if alert_publisher.producer.is_none() {
+        alert_events.clear();
+        return;
+    }
+    let topic = alert_publisher.topic.clone().unwrap();
+    for AlertEvent(alert) in alert_events.iter() {
+        let _ = serde_json::to_string(alert)
+            .map(|payload| {
+                let producer = alert_publisher.producer.clone().unwrap();
+                let topic = topic.clone();
+                let key = alert.metric_name.clone();
+                AsyncComputeTaskPool::get().spawn(async move {
+                    let record = FutureRecord::to(&topic)
+                        .key(&key)
+                        .payload(&payload);
+                    let _ = producer.send(record, Timeout::After(Duration::from_secs(3)))
+                        .await
+                        .or_else(|(e, _)| {
+                            error!("Could not publish alert: {:?}.", e);
+                            Err(e)
+                        });
+                }).detach();
+            })
+            .or_else(|e| {
+                error!("Could not serialize alert: {:?}.", e);
+                Err(e)
+            });
+    }
+}
@@ -0,0 +1,510 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Debug;
+use bevy::log::{error, info};
+use bevy::prelude::{App, Component, Entity, EventWriter, IntoSystemConfigs, Query, Res, ResMut, Resource, With};
+use ndarray::{Array1, s};
+use crate::config::ConfigurationProperties;
+use crate::data_subscriber::metric_event::{MetricComponentType, MetricsState, NetworkMetricsServiceEvent, NodeMetricEvent};
+use crate::graph::draw_graph_points::WithDataSeriesChangedHistorical;
+use crate::graph::graph_data_event_reader::read_metric_events;
+use crate::graph::radial::calculate_derivatives;
+use crate::metrics::network_metrics::{HistoricalData, Metric};
+use crate::network::Node;
+
+/// The column that the value of a derived metric is written to.
+pub const DERIVED_COLUMN: &str = "value";
+
+/// An expression over the columns of existing metrics. Columns are referenced as
+/// `metric_name:column`, and the functions are
+///
+/// - `moving_average(expr, window)`
+/// - `ema(expr, alpha)`
+/// - `ratio(expr, expr)`
+/// - `norm_ratio(expr, expr)`, the ratio of the L2 norms, for instance gradient to weight norm.
+/// - `derivative(expr)`, the first derivative from calculate_derivatives.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DerivedExpression {
+    Column {
+        metric: String,
+        column: String
+    },
+    MovingAverage(Box<DerivedExpression>, usize),
+    Ema(Box<DerivedExpression>, f32),
+    Ratio(Box<DerivedExpression>, Box<DerivedExpression>),
+    NormRatio(Box<DerivedExpression>, Box<DerivedExpression>),
+    Derivative(Box<DerivedExpression>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DerivedMetricError {
+    Parse {
+        expression: String,
+        position: usize,
+        message: String
+    },
+    MissingMetric(String),
+    MissingColumn {
+        metric: String,
+        column: String
+    },
+    ShapeMismatch {
+        left: usize,
+        right: usize
+    }
+}
+
+/// The values of an element of a column, with the timestep of each value, so that series with
+/// gaps are combined at the timesteps they share.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DerivedSeries {
+    pub(crate) timesteps: Vec<u64>,
+    pub(crate) values: Array1<f32>
+}
+
+impl DerivedSeries {
+    fn map_values(&self, f: impl Fn(&Array1<f32>) -> Array1<f32>) -> DerivedSeries {
+        DerivedSeries {
+            timesteps: self.timesteps.clone(),
+            values: f(&self.values)
+        }
+    }
+
+    /// The first derivative, at the timestep of the later value of each difference. A series
+    /// with fewer than two values has no derivative.
+    fn derivative(&self) -> DerivedSeries {
+        if self.values.len() < 2 {
+            return DerivedSeries { timesteps: vec![], values: Array1::zeros(0) };
+        }
+        DerivedSeries {
+            timesteps: self.timesteps[1..].to_vec(),
+            values: calculate_derivatives(&self.values, 1)
+                .pop()
+                .or(Some(Array1::zeros(0)))
+                .unwrap()
+        }
+    }
+}
+
+/// The derived metrics by the name of the metric they are materialized as, and the latest timestep
+/// shared by the dependencies that each has been emitted for, by run and name.
+#[derive(Resource, Default, Debug)]
+pub struct DerivedMetrics {
+    pub(crate) metrics: HashMap<String, DerivedExpression>,
+    emitted: HashMap<(String, String), u64>,
+}
+
+impl DerivedMetrics {
+
+    pub(crate) fn add_metric(&mut self, name: &str, expression: &str) -> Result<(), DerivedMetricError> {
+        DerivedExpression::parse(expression)
+            .map(|parsed| {
+                self.metrics.insert(name.to_string(), parsed);
+            })
+    }
+
+}
+
+/// Records the timestep as emitted for the derived metric of the run, returning false when it is
+/// not after the last timestep emitted, so that each timestep is emitted once even when the
+/// dependencies are updated in different frames.
+pub(crate) fn advance(
+    emitted: &mut HashMap<(String, String), u64>,
+    run_id: &str,
+    name: &str,
+    timestep: u64
+) -> bool {
+    let emitted = emitted.entry((run_id.to_string(), name.to_string()))
+        .or_insert(0);
+    if *emitted >= timestep {
+        return false;
+    }
+    *emitted = timestep;
+    true
+}
+
+impl DerivedExpression {
+
+    pub(crate) fn parse(expression: &str) -> Result<DerivedExpression, DerivedMetricError> {
+        let mut parser = ExpressionParser {
+            expression,
+            chars: expression.chars().collect(),
+            position: 0,
+        };
+        let parsed = parser.parse_expression()?;
+        parser.skip_whitespace();
+        if parser.position != parser.chars.len() {
+            return Err(parser.error("unexpected trailing input"));
+        }
+        Ok(parsed)
+    }
+
+    /// The names of the metrics the expression reads from, in the order they appear in it.
+    pub(crate) fn dependencies(&self) -> Vec<&str> {
+        let mut dependencies = vec![];
+        self.add_dependencies(&mut dependencies);
+        dependencies
+    }
+
+    fn add_dependencies<'a>(&'a self, dependencies: &mut Vec<&'a str>) {
+        match self {
+            DerivedExpression::Column { metric, .. } => {
+                if !dependencies.contains(&metric.as_str()) {
+                    dependencies.push(metric.as_str());
+                }
+            }
+            DerivedExpression::MovingAverage(inner, _)
+            | DerivedExpression::Ema(inner, _)
+            | DerivedExpression::Derivative(inner) => inner.add_dependencies(dependencies),
+            DerivedExpression::Ratio(left, right)
+            | DerivedExpression::NormRatio(left, right) => {
+                left.add_dependencies(dependencies);
+                right.add_dependencies(dependencies);
+            }
+        }
+    }
+
+    /// Evaluates the expression over the history of the metrics. The result contains a series
+    /// over the timesteps for each value of the column.
+    pub(crate) fn evaluate<'a, F>(&self, historical: &F) -> Result<Vec<DerivedSeries>, DerivedMetricError>
+        where F: Fn(&str) -> Option<&'a HistoricalData>
+    {
+        match self {
+            DerivedExpression::Column { metric, column } => {
+                let historical_data = historical(metric)
+                    .ok_or(DerivedMetricError::MissingMetric(metric.clone()))?;
+                if !historical_data.labels.contains_key(column) {
+                    return Err(DerivedMetricError::MissingColumn {
+                        metric: metric.clone(),
+                        column: column.clone()
+                    });
+                }
+                let series = historical_data.retrieve_series(column);
+                let timesteps = series.iter()
+                    .map(|(timestep, _)| *timestep)
+                    .collect::<Vec<u64>>();
+                let num_values = series.iter()
+                    .map(|(_, values)| values.len())
+                    .min()
+                    .or(Some(0))
+                    .unwrap();
+                Ok((0..num_values)
+                    .map(|index| DerivedSeries {
+                        timesteps: timesteps.clone(),
+                        values: series.iter().map(|(_, values)| values[index]).collect()
+                    })
+                    .collect())
+            }
+            DerivedExpression::MovingAverage(inner, window) => {
+                Ok(inner.evaluate(historical)?
+                    .iter()
+                    .map(|series| series.map_values(|values| moving_average(values, *window)))
+                    .collect())
+            }
+            DerivedExpression::Ema(inner, alpha) => {
+                Ok(inner.evaluate(historical)?
+                    .iter()
+                    .map(|series| series.map_values(|values| ema(values, *alpha)))
+                    .collect())
+            }
+            DerivedExpression::Derivative(inner) => {
+                Ok(inner.evaluate(historical)?
+                    .iter()
+                    .map(|series| series.derivative())
+                    .collect())
+            }
+            DerivedExpression::Ratio(left, right) => {
+                let left = left.evaluate(historical)?;
+                let right = right.evaluate(historical)?;
+                if left.is_empty() || right.is_empty() {
+                    return Ok(vec![]);
+                }
+                if left.len() != right.len() && left.len() != 1 && right.len() != 1 {
+                    return Err(DerivedMetricError::ShapeMismatch { left: left.len(), right: right.len() });
+                }
+                Ok((0..left.len().max(right.len()))
+                    .map(|i| divide(&left[i.min(left.len() - 1)], &right[i.min(right.len() - 1)]))
+                    .collect())
+            }
+            DerivedExpression::NormRatio(left, right) => {
+                let left = norm(&left.evaluate(historical)?);
+                let right = norm(&right.evaluate(historical)?);
+                Ok(vec![divide(&left, &right)])
+            }
+        }
+    }
+}
+
+/// The values of the series at the timesteps they both have, so that a gap in either series
+/// does not shift the values of the other.
+pub(crate) fn align(left: &DerivedSeries, right: &DerivedSeries) -> (Vec<u64>, Array1<f32>, Array1<f32>) {
+    let right_values = right.timesteps.iter()
+        .zip(right.values.iter())
+        .collect::<HashMap<&u64, &f32>>();
+    let (timesteps, (left, right)): (Vec<u64>, (Vec<f32>, Vec<f32>)) = left.timesteps.iter()
+        .zip(left.values.iter())
+        .flat_map(|(timestep, value)| right_values.get(timestep)
+            .map(|right_value| (*timestep, (*value, **right_value))))
+        .unzip();
+    (timesteps, Array1::from_vec(left), Array1::from_vec(right))
+}
+
+fn divide(left: &DerivedSeries, right: &DerivedSeries) -> DerivedSeries {
+    let (timesteps, numerator, denominator) = align(left, right);
+    DerivedSeries {
+        timesteps,
+        values: &numerator / &denominator
+    }
+}
+
+/// The L2 norm over the values of the column at each timestep that all of the values have.
+fn norm(series: &Vec<DerivedSeries>) -> DerivedSeries {
+    let Some(first) = series.first() else {
+        return DerivedSeries { timesteps: vec![], values: Array1::zeros(0) };
+    };
+    let squares = first.map_values(|values| values.mapv(|value| value.powi(2)));
+    let sum = series.iter()
+        .skip(1)
+        .fold(squares, |sum, next| {
+            let (timesteps, sum, next) = align(&sum, next);
+            DerivedSeries {
+                timesteps,
+                values: sum + next.mapv(|value| value.powi(2))
+            }
+        });
+    sum.map_values(|values| values.mapv(f32::sqrt))
+}
+
+pub(crate) fn moving_average(series: &Array1<f32>, window: usize) -> Array1<f32> {
+    let window = window.max(1);
+    Array1::from_iter((0..series.len()).map(|i| {
+        let start = (i + 1).saturating_sub(window);
+        series.slice(s![start..i + 1]).mean().unwrap()
+    }))
+}
+
+pub(crate) fn ema(series: &Array1<f32>, alpha: f32) -> Array1<f32> {
+    let mut prev = None;
+    series.mapv(|value| {
+        let next = prev.map(|p| alpha * value + (1.0 - alpha) * p)
+            .or(Some(value))
+            .unwrap();
+        prev = Some(next);
+        next
+    })
+}
+
+struct ExpressionParser<'a> {
+    expression: &'a str,
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl<'a> ExpressionParser<'a> {
+
+    fn error(&self, message: &str) -> DerivedMetricError {
+        DerivedMetricError::Parse {
+            expression: self.expression.to_string(),
+            position: self.position,
+            message: message.to_string()
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.get(self.position).filter(|c| c.is_whitespace()).is_some() {
+            self.position += 1;
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), DerivedMetricError> {
+        self.skip_whitespace();
+        if self.chars.get(self.position) == Some(&expected) {
+            self.position += 1;
+            Ok(())
+        } else {
+            Err(self.error(format!("expected '{}'", expected).as_str()))
+        }
+    }
+
+    fn identifier(&mut self) -> String {
+        self.skip_whitespace();
+        let start = self.position;
+        while self.chars.get(self.position)
+            .filter(|c| c.is_alphanumeric() || ['_', '-', '.'].contains(c))
+            .is_some() {
+            self.position += 1;
+        }
+        self.chars[start..self.position].iter().collect()
+    }
+
+    fn number(&mut self) -> Result<f32, DerivedMetricError> {
+        let start = self.position;
+        self.identifier()
+            .parse::<f32>()
+            .map_err(|_| {
+                self.position = start;
+                self.error("expected a number")
+            })
+    }
+
+    fn parse_expression(&mut self) -> Result<DerivedExpression, DerivedMetricError> {
+        let name = self.identifier();
+        if name.is_empty() {
+            return Err(self.error("expected a function or metric column"));
+        }
+        self.skip_whitespace();
+        match self.chars.get(self.position) {
+            Some(':') => {
+                self.position += 1;
+                let column = self.identifier();
+                if column.is_empty() {
+                    return Err(self.error("expected a column name"));
+                }
+                Ok(DerivedExpression::Column { metric: name, column })
+            }
+            Some('(') => {
+                self.position += 1;
+                let parsed = self.parse_function(name.as_str())?;
+                self.expect(')')?;
+                Ok(parsed)
+            }
+            _ => Err(self.error("expected ':' or '('"))
+        }
+    }
+
+    fn parse_function(&mut self, name: &str) -> Result<DerivedExpression, DerivedMetricError> {
+        match name {
+            "moving_average" => {
+                let inner = self.parse_expression()?;
+                self.expect(',')?;
+                let window = self.number()?;
+                if window < 1.0 {
+                    return Err(self.error("moving average window must be at least 1"));
+                }
+                Ok(DerivedExpression::MovingAverage(Box::new(inner), window as usize))
+            }
+            "ema" => {
+                let inner = self.parse_expression()?;
+                self.expect(',')?;
+                let alpha = self.number()?;
+                if alpha <= 0.0 || alpha > 1.0 {
+                    return Err(self.error("ema alpha must be in (0, 1]"));
+                }
+                Ok(DerivedExpression::Ema(Box::new(inner), alpha))
+            }
+            "ratio" | "norm_ratio" => {
+                let left = self.parse_expression()?;
+                self.expect(',')?;
+                let right = self.parse_expression()?;
+                if name == "ratio" {
+                    Ok(DerivedExpression::Ratio(Box::new(left), Box::new(right)))
+                } else {
+                    Ok(DerivedExpression::NormRatio(Box::new(left), Box::new(right)))
+                }
+            }
+            "derivative" => {
+                Ok(DerivedExpression::Derivative(Box::new(self.parse_expression()?)))
+            }
+            _ => Err(self.error(format!("unknown function {}", name).as_str()))
+        }
+    }
+}
+
+/// Loads the derived metrics of the configuration and computes them from the metrics read in
+/// read_metric_events, for every data source the metrics are read from.
+pub(crate) fn add_derived_metrics(app: &mut App) {
+    app.init_resource::<DerivedMetrics>()
+        .add_startup_system(load_derived_metrics)
+        .add_system(compute_derived_metrics::<NodeMetricEvent, Node>
+            .after(read_metric_events::<NodeMetricEvent, Node>));
+}
+
+pub(crate) fn load_derived_metrics(
+    config_properties: Res<ConfigurationProperties>,
+    mut derived_metrics: ResMut<DerivedMetrics>
+) {
+    for (name, expression) in config_properties.metrics.derived.iter() {
+        let _ = derived_metrics.add_metric(name, expression)
+            .or_else(|e| {
+                error!("Could not add derived metric {}: {:?}.", name, e);
+                Err(e)
+            });
+    }
+}
+
+/// When any of the metrics a derived metric depends on are updated, the derived metric is
+/// evaluated and the latest value is written as a metric event, so that it is read in
+/// read_metric_events and materialized as a regular metric. A value is only written once the
+/// latest timestep shared by all of the dependencies advances, and the derived metric is indexed
+/// like the first of its dependencies in the expression.
+pub(crate) fn compute_derived_metrics<T, U>(
+    mut derived_metrics: ResMut<DerivedMetrics>,
+    metrics_state: Res<MetricsState>,
+    updated: Query<Entity, (WithDataSeriesChangedHistorical, With<Metric<U>>)>,
+    metrics: Query<&Metric<U>>,
+    mut event_writer: EventWriter<T>
+)
+    where
+        T: NetworkMetricsServiceEvent<U> + 'static + Debug,
+        U: Component + 'static
+{
+    let mut updated_names: HashMap<&str, HashSet<&str>> = HashMap::new();
+    metrics_state.entities.iter()
+        .filter(|(_, (entity, _))| updated.contains(*entity))
+        .for_each(|((run_id, name), _)| {
+            updated_names.entry(run_id.as_str())
+                .or_insert_with(HashSet::new)
+                .insert(name.as_str());
+        });
+
+    let derived_metrics = derived_metrics.as_mut();
+    for (run_id, updated_names) in updated_names.iter() {
+        let historical = |name: &str| metrics_state.get_entity(run_id, name)
+            .and_then(|(entity, _)| metrics.get(entity).ok())
+            .map(|metric| &metric.historical);
+
+        for (name, expression) in derived_metrics.metrics.iter() {
+            let dependencies = expression.dependencies();
+            if !dependencies.iter().any(|d| updated_names.contains(d)) {
+                continue;
+            }
+            let emitted = &mut derived_metrics.emitted;
+            let _ = expression.evaluate(&historical)
+                .map(|series| {
+                    let latest = series.iter()
+                        .flat_map(|s| s.values.last().cloned())
+                        .collect::<Vec<f32>>();
+                    if latest.len() != series.len() || latest.is_empty() {
+                        debug!("Not enough history to compute derived metric {} for run {}.", name, run_id);
+                        return;
+                    }
+                    let timestep = series.iter()
+                        .flat_map(|s| s.timesteps.last().cloned())
+                        .min()
+                        .or(Some(0))
+                        .unwrap();
+                    if !advance(emitted, run_id, name, timestep) {
+                        return;
+                    }
+                    let metric_indices = dependencies.iter()
+                        .flat_map(|d| metrics_state.get_entity(run_id, d))
+                        .flat_map(|(entity, _)| metrics.get(entity).ok())
+                        .map(|metric| metric.metric_indices.clone())
+                        .next()
+                        .or(Some(HashMap::new()))
+                        .unwrap();
+                    event_writer.send(T::new(
+                        run_id,
+                        name.clone(),
+                        vec![1, latest.len()],
+                        latest,
+                        HashMap::from([(DERIVED_COLUMN.to_string(), 0)]),
+                        metric_indices
+                    ));
+                })
+                .or_else(|e| {
+                    error!("Could not compute derived metric {} for run {}: {:?}.", name, run_id, e);
+                    Err(e)
+                });
+        }
+    }
+}
@@ -6,4 +6,8 @@ use crate::network::Node;
 /// Provide metrics for nodes and layers
 pub(crate) mod network_metrics;
 /// Retrieve the network loss and feed it into graph loss resource
-pub(crate) mod network_loss;
\ No newline at end of file
+pub(crate) mod network_loss;
+/// Metrics computed in-app as expressions over the columns of other metrics
+pub(crate) mod derived_metrics;
+/// Rules evaluated over the metric streams, highlighting the network and listing the alerts
+pub(crate) mod alerts;
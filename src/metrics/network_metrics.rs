@@ -7,6 +7,7 @@ use serde::{Deserialize, Deserializer};
 use serde::de::EnumAccess;
 use crate::data_subscriber::metric_event::MetricComponentType;
 use crate::graph::{GraphDim, GraphDimType, GridAxis};
+use crate::graph::run_comparison::RunAlignment;
 use crate::menu::Menu;
 use crate::network::{Layer, Network, Node};
 
@@ -16,7 +17,8 @@ where T: Component {
     pub(crate) historical: HistoricalData,
     pub(crate) metric_type: MetricType<T>,
     pub(crate) metric_indices: HashMap<MetricComponentType, Vec<String>>,
-    pub(crate) metric_dim_component_children: HashMap<String, (Entity, Color)>
+    pub(crate) metric_dim_component_children: HashMap<String, (Entity, Color)>,
+    pub(crate) run_id: String
 }
 
 #[derive(Clone, Debug, Ord, PartialOrd, Eq, PartialEq, Hash)]
@@ -40,13 +42,15 @@ impl <T> Metric<T> where T: Component {
         size: Vec<usize>,
         metric_type: MetricType<T>,
         labels: HashMap<String, usize>,
-        metric_indices: HashMap<MetricComponentType, Vec<String>>
+        metric_indices: HashMap<MetricComponentType, Vec<String>>,
+        run_id: &str
     )  -> Metric<T> {
         Self {
             historical: HistoricalData::new(size, labels),
             metric_type,
             metric_indices,
             metric_dim_component_children: Default::default(),
+            run_id: run_id.to_string(),
         }
     }
 }
@@ -81,6 +85,7 @@ pub(crate) struct HistoricalData {
     pub(crate) data: ArrayD<f32>,
     pub(crate) labels: HashMap<String, usize>,
     pub(crate) timestep: BTreeMap<u64, (usize, usize)>,
+    pub(crate) wall_clock: BTreeMap<u64, u64>,
     index_to_timestep: HashMap<usize, u64>,
     convergence: HashMap<String, HashMap<u64, f32>>,
     write_index: usize,
@@ -105,6 +110,7 @@ impl HistoricalData {
             prev_write_index: 1,
             convergence,
             timestep: BTreeMap::new(),
+            wall_clock: BTreeMap::new(),
             index_to_timestep: Default::default(),
         }
     }
@@ -193,6 +199,43 @@ impl HistoricalData {
         out
     }
 
+    /// The values of the column at each timestep received, in the order of the timesteps.
+    pub(crate) fn retrieve_series(&self, column_name: &str) -> Vec<(u64, Vec<f32>)> {
+        if !self.labels.contains_key(column_name) {
+            return vec![];
+        }
+        let historical_1d = self.retrieve_historical_1d(column_name);
+        self.timestep.iter()
+            .map(|(timestep, (_, index))| (
+                *timestep,
+                historical_1d.iter().map(|h| h[*index]).collect::<Vec<f32>>()
+            ))
+            .collect()
+    }
+
+    /// The value of the element of the column at the timestep.
+    pub(crate) fn value_at(&self, column_name: &str, index: usize, timestep: u64) -> Option<f32> {
+        let column = *self.labels.get(column_name)?;
+        let (_, row) = self.timestep.get(&timestep)?;
+        if self.data.ndim() > 2 {
+            self.get(&[*row, column, index])
+        } else if index == 0 {
+            self.get(&[*row, column])
+        } else {
+            None
+        }
+    }
+
+    /// The latest timestep and the values of each element of the column at that timestep.
+    pub(crate) fn latest_values(&self, column_name: &str) -> Option<(u64, Vec<f32>)> {
+        let timestep = *self.timestep.keys().last()?;
+        let num_elements = if self.data.ndim() > 2 { self.data.shape()[2] } else { 1 };
+        let values = (0..num_elements)
+            .flat_map(|index| self.value_at(column_name, index, timestep))
+            .collect::<Vec<f32>>();
+        Some((timestep, values))
+    }
+
     pub(crate) fn get_timestamp(&self, step: usize) -> Option<u64> {
         self.index_to_timestep.get(&step)
             .cloned()
@@ -210,6 +253,29 @@ impl HistoricalData {
             .unwrap()
     }
 
+    /// Records the wall-clock time, in milliseconds, that the timestep was produced.
+    pub(crate) fn add_wall_clock(&mut self, timestep: u64, wall_clock: u64) {
+        self.wall_clock.insert(timestep, wall_clock);
+    }
+
+    /// The time of the timestep used to line up runs, from the start of the run: the steps since
+    /// the first timestep, or the seconds since the wall-clock time of the first timestep, so runs
+    /// started at different steps or times are compared from their start.
+    pub(crate) fn aligned_time(&self, timestep: u64, alignment: &RunAlignment) -> Option<f32> {
+        match alignment {
+            RunAlignment::Step => {
+                let start = self.timestep.keys().next()?;
+                self.timestep.get(&timestep)
+                    .map(|_| timestep.saturating_sub(*start) as f32)
+            }
+            RunAlignment::WallClock => {
+                let start = self.wall_clock.values().next()?;
+                self.wall_clock.get(&timestep)
+                    .map(|wall_clock| wall_clock.saturating_sub(*start) as f32 / 1000.0)
+            }
+        }
+    }
+
     pub(crate) fn get(&self, index: &[usize]) -> Option<f32> {
         self.data.get(index)
             .cloned()
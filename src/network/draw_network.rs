@@ -1,5 +1,5 @@
 use std::collections::HashSet;
-use bevy::prelude::{Changed, ClearColor, Color, Commands, default, Entity, Mesh, Query, Res, ResMut, SpriteBundle, Visibility};
+use bevy::prelude::{Added, Changed, Color, Commands, default, Entity, Handle, Mesh, Query, Res, ResMut, SpriteBundle, Visibility, With};
 use bevy::asset::Assets;
 use bevy::pbr::{MaterialMeshBundle, PbrBundle, StandardMaterial};
 use bevy_transform::components::{GlobalTransform, Transform};
@@ -9,12 +9,20 @@ use std::marker::PhantomData;
 use bevy::hierarchy::{BuildChildren, Parent};
 use bevy::math::Vec3;
 use bevy_polyline::prelude::{Polyline, PolylineMaterial};
+use crate::config::ConfigurationProperties;
+use crate::config::palette::Palette;
+use crate::config::theme::{Theme, ThemeColor};
+use crate::data_subscriber::metric_event::MetricsState;
+use crate::graph::GraphingMetricsResource;
 use crate::lines::line_list::{create_3d_line, LineList};
 use crate::menu::{DataType, MetricsConfigurationOption};
 use crate::menu::config_menu_event::interaction_config_event_writer::NetworkMenuResultBuilder;
 use crate::menu::menu_resource::VARIANCE;
+use crate::metrics::network_metrics::Metric;
 use crate::menu::ui_menu_event::transition_groups::PropagateVisible;
 use crate::network::{Layer, Network, Node};
+use crate::theme::highlight::Highlight;
+use crate::theme::restyle::{ThemedMaterial, ThemedPolyline};
 use crate::util;
 
 pub const NODE_RADIUS: f32 = 1.0;
@@ -89,14 +97,14 @@ pub(crate) fn draw_network_initial(
     mut materials: ResMut<Assets<StandardMaterial>>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut layer_query: Query<(Entity, &mut Layer, &mut Transform), Changed<Layer>>,
-    color: Res<ClearColor>,
+    theme: Res<Theme>,
 ) {
     if layer_query.is_empty() {
         return;
     }
 
     for layer_tuple in layer_query.iter() {
-        draw_layers_and_nodes(&mut commands, &mut materials, &mut meshes, &(layer_tuple.1, layer_tuple.2, layer_tuple.0), &color);
+        draw_layers_and_nodes(&mut commands, &mut materials, &mut meshes, &(layer_tuple.1, layer_tuple.2, layer_tuple.0), &theme);
     }
 }
 
@@ -107,11 +115,11 @@ pub(crate) fn update_network(
     mut materials: ResMut<Assets<StandardMaterial>>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut layer_query: Query<(&mut Layer, &mut Transform, Entity), Changed<Layer>>,
-    color: Res<ClearColor>
+    theme: Res<Theme>,
 ) {
 
     for layer_tuple in layer_query.iter() {
-        draw_layers_and_nodes(&mut commands, &mut materials, &mut meshes, &layer_tuple, &color);
+        draw_layers_and_nodes(&mut commands, &mut materials, &mut meshes, &layer_tuple, &theme);
     }
 }
 
@@ -122,7 +130,8 @@ pub(crate) fn draw_node_connections(
     mut polylines: ResMut<Assets<Polyline>>,
     mut polyline_materials: ResMut<Assets<PolylineMaterial>>,
     mut meshes: ResMut<Assets<Mesh>>,
-    global_transform_query: Query<&GlobalTransform>
+    global_transform_query: Query<&GlobalTransform>,
+    theme: Res<Theme>
 ) {
     /// Track if dirty updated somewhere else, in which case do not set not dirty.
     let _ = layer_query.iter()
@@ -149,7 +158,7 @@ pub(crate) fn draw_node_connections(
                     let relative_pos = Transform::from_matrix(relative_pos);
 
                     let polyline_bundler = create_3d_line(LineList {
-                        color: Color::BLUE,
+                        color: theme.color(ThemeColor::Connection),
                         lines: vec![(
                             Vec3::new(0.0, layer.2.translation.y, 1.0),
                             Vec3::new(relative_pos.translation.x, connection_to_make.2.translation.y, 1.0)
@@ -158,7 +167,7 @@ pub(crate) fn draw_node_connections(
                     }, &mut polylines, &mut polyline_materials);
 
                     let line = commands.
-                        spawn(polyline_bundler);
+                        spawn((polyline_bundler, ThemedPolyline(ThemeColor::Connection)));
 
                     line.id()
                 })
@@ -175,21 +184,26 @@ fn draw_layers_and_nodes<'a>(
     mut materials: &mut ResMut<Assets<StandardMaterial>>,
     mut meshes: &mut ResMut<Assets<Mesh>>,
     layer_tuple: &(&'a Layer, &Transform, Entity),
-    color: &Res<ClearColor>
+    theme: &Res<Theme>,
 ) {
 
 
     let layer = layer_tuple.0;
     let layer_entity = layer_tuple.2;
 
+    let layer_mesh = layer_tuple.0.layer_type.create_mesh(layer_tuple.0, meshes, materials, theme);
+    let highlight = materials.get(&layer_mesh.material).map(Highlight::new);
     commands.entity(layer_entity)
-        .insert((layer_tuple.0.layer_type.create_mesh(layer_tuple.0, meshes, materials, color)));
+        .insert((layer_mesh, ThemedMaterial(ThemeColor::Layer)));
+    if let Some(highlight) = highlight {
+        commands.entity(layer_entity).insert(highlight);
+    }
 
 
     for node in layer_tuple.0.nodes.iter() {
         if node.entity.is_some() {
             let node_entity = node.entity.clone();
-            node_entity.map(|node_entity| draw_node(commands, materials, meshes, node, node_entity, layer));
+            node_entity.map(|node_entity| draw_node(commands, materials, meshes, node, node_entity, layer, theme));
             commands.entity(layer_entity)
                 .add_child(node_entity.clone().unwrap());
         }
@@ -203,15 +217,18 @@ fn draw_node(
     mut meshes: &mut ResMut<Assets<Mesh>>,
     node: &Node,
     node_entity: Entity,
-    layer: &Layer
+    layer: &Layer,
+    theme: &Res<Theme>,
 ) {
     info!("Drawing node!");
     let mut y = (node.node_pos as f32 * NODE_SPACING) - ((layer.sub_layers.len() as f32 * NODE_SPACING) / 2.0);
     let value = (NODE_RADIUS * 2.0 * layer.nodes.len() as f32 + NODE_SPACING * (layer.nodes.len() - 1) as f32) / 2 as f32;
+    let node_mesh = layer.layer_type.draw_node_mesh(y, meshes, materials, theme.color(ThemeColor::Node));
+    let highlight = materials.get(&node_mesh.material).map(Highlight::new);
     commands.entity(node_entity)
         .insert(node.clone())
         .insert((
-            layer.layer_type.draw_node_mesh(y, meshes, materials),
+            node_mesh,
             PickableBundle::default()
         ))
         .insert(PickableBundle::default())
@@ -225,4 +242,89 @@ fn draw_node(
         .with_children(|child| {
             child.spawn(MetricsConfigurationOption::Variance(PhantomData::<Node>::default(), DataType::Selected, VARIANCE));
         });
+    commands.entity(node_entity).insert(ThemedMaterial(ThemeColor::Node));
+    if let Some(highlight) = highlight {
+        commands.entity(node_entity).insert(highlight);
+    }
+}
+
+/// With a network palette, the nodes are colored by their latest metric value, and recolored as
+/// the metrics arrive. Nodes without a value keep the color of the theme.
+pub(crate) fn color_nodes_by_metric(
+    mut commands: Commands,
+    config_properties: Res<ConfigurationProperties>,
+    graphing_metrics: Res<GraphingMetricsResource>,
+    metrics_state: Res<MetricsState>,
+    updated: Query<(), Changed<Metric<Node>>>,
+    drawn: Query<(), (With<Node>, Added<Highlight>)>,
+    metrics: Query<&Metric<Node>>,
+    layers: Query<&Layer>,
+    mut highlights: Query<&mut Highlight, With<Node>>,
+) {
+    let Some(palette) = config_properties.palette.network_palette() else {
+        return;
+    };
+    if updated.is_empty() && drawn.is_empty() {
+        return;
+    }
+    for layer in layers.iter() {
+        let values = layer.nodes.iter()
+            .map(|node| node_value(&layer.node_id(node), &graphing_metrics, &metrics_state, &metrics))
+            .collect::<Vec<Option<f32>>>();
+        layer.nodes.iter()
+            .zip(node_colors(&values, palette))
+            .flat_map(|(node, color)| node.entity.zip(color))
+            .for_each(|(node_entity, color)| {
+                let _ = highlights.get_mut(node_entity)
+                    .map(|mut highlight| {
+                        highlight.base_color = color;
+                        highlight.emissive = color;
+                    });
+                commands.entity(node_entity).remove::<ThemedMaterial>();
+            });
+    }
+}
+
+/// The latest value of the node, the mean of the first column of the first of its metrics by
+/// name, so that the value of a node is taken from the same metric each time.
+fn node_value(
+    node_id: &String,
+    graphing_metrics: &GraphingMetricsResource,
+    metrics_state: &MetricsState,
+    metrics: &Query<&Metric<Node>>,
+) -> Option<f32> {
+    let mut node_metrics = graphing_metrics.metrics_for_indices(vec![node_id])
+        .into_iter()
+        .flat_map(|entity| metrics.get(entity).ok()
+            .zip(metrics_state.run_metric(entity))
+        )
+        .collect::<Vec<(&Metric<Node>, &(String, String))>>();
+    node_metrics.sort_by(|(_, first), (_, second)| first.cmp(second));
+    node_metrics.into_iter()
+        .flat_map(|(metric, _)| {
+            let mut columns = metric.historical.labels.keys().collect::<Vec<&String>>();
+            columns.sort();
+            columns.first()
+                .and_then(|column| metric.historical.latest_values(column))
+                .filter(|(_, values)| !values.is_empty())
+                .map(|(_, values)| values.iter().sum::<f32>() / values.len() as f32)
+        })
+        .find(|value| value.is_finite())
+}
+
+/// The values of the nodes of a layer are normalized over the layer and mapped through the palette.
+/// When the nodes all have the same value, they are the middle of the palette.
+pub(crate) fn node_colors(values: &Vec<Option<f32>>, palette: Palette) -> Vec<Option<Color>> {
+    let (min, max) = values.iter()
+        .flatten()
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), value| (min.min(*value), max.max(*value)));
+    values.iter()
+        .map(|value| value.map(|value| {
+            if max > min {
+                palette.sample((value - min) / (max - min))
+            } else {
+                palette.sample(0.5)
+            }
+        }))
+        .collect()
 }
@@ -1,6 +1,7 @@
 use std::collections::{HashMap, HashSet};
 use bevy::prelude::*;
 use draw_network::{LAYER_SPACING, NODE_RADIUS, NODE_SPACING};
+use crate::config::theme::{Theme, ThemeColor};
 
 pub mod draw_network;
 
@@ -29,6 +30,14 @@ pub struct Layer {
     pub(crate) network_id: NetworkId
 }
 
+impl Layer {
+    /// The identifier of a node of the layer in the metric indices, the layer name and the position
+    /// of the node in the layer, such as `encoder.3`.
+    pub(crate) fn node_id(&self, node: &Node) -> String {
+        format!("{}.{}", self.name, node.node_pos)
+    }
+}
+
 #[derive(Default, Ord, PartialOrd, Eq, PartialEq, Clone, Debug, Hash)]
 pub struct NetworkId {
     pub(crate) network_id: &'static str,
@@ -52,12 +61,21 @@ pub enum LayerType {
 
 /// Based on the different type of network, different display.
 impl LayerType {
+    /// The name the layer type is searched and filtered by.
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            LayerType::TFormer => "attention",
+            LayerType::FullyConnected => "fully_connected",
+            LayerType::Normalization => "normalization"
+        }
+    }
+
     pub(crate) fn create_mesh(
         &self,
         layer: &Layer,
         mut meshes: &mut ResMut<Assets<Mesh>>,
         mut materials: &mut ResMut<Assets<StandardMaterial>>,
-        color: &Res<ClearColor>
+        theme: &Res<Theme>
     ) -> PbrBundle {
         // match self {
         //     LayerType::TFormer => {}
@@ -67,7 +85,7 @@ impl LayerType {
         //     LayerType::Normalization => {}
         // }
         let y_length = NODE_RADIUS * 2.0 * layer.nodes.len() as f32 + NODE_SPACING * (layer.nodes.len() - 1) as f32;
-        let mut standard_material = StandardMaterial::from(color.0);
+        let mut standard_material = StandardMaterial::from(theme.color(ThemeColor::Layer));
         let materials = materials.add(standard_material).into();
 
         PbrBundle {
@@ -86,11 +104,12 @@ impl LayerType {
         &self,
         y: f32,
         mut meshes: &mut ResMut<Assets<Mesh>>,
-        mut materials: &mut ResMut<Assets<StandardMaterial>>
+        mut materials: &mut ResMut<Assets<StandardMaterial>>,
+        color: Color
     ) -> PbrBundle {
-        let mut material = StandardMaterial::from(Color::GREEN);
-        material.base_color = Color::GREEN;
-        material.emissive = Color::GREEN;
+        let mut material = StandardMaterial::from(color);
+        material.base_color = color;
+        material.emissive = color;
         PbrBundle {
             mesh: meshes.add(shape::UVSphere{
                 radius: NODE_RADIUS,
@@ -1,11 +1,33 @@
-use bevy::prelude::{Entity, Event};
+use bevy::prelude::*;
+use crate::camera::raycast_select::BevyPickingState;
 use crate::interactions::{HoverEvent, SelectionEvent};
+use crate::pickable_events::picking_adapter::{box_select, BoxSelection, bridge_pointer_events};
+use crate::ui_components::tooltip::HoveredEntity;
+
+/// Translates the pointer events of the picking backend into picking events, and selects the
+/// nodes inside a box dragged with shift held.
+pub(crate) mod picking_adapter;
 
 #[derive(Event)]
 pub enum PickableEvent {
     Selection(SelectionEvent),
     Hover(HoverEvent),
-    Clicked(Entity)
+    Clicked(Entity),
+    /// The entity is dragged by the pointer, by the distance in logical pixels.
+    Dragged(Entity, Vec2)
+}
+
+impl PickableEvent {
+    pub(crate) fn entity(&self) -> Entity {
+        match self {
+            PickableEvent::Selection(SelectionEvent::JustSelected(entity))
+            | PickableEvent::Selection(SelectionEvent::JustDeselected(entity))
+            | PickableEvent::Hover(HoverEvent::JustEntered(entity))
+            | PickableEvent::Hover(HoverEvent::JustLeft(entity))
+            | PickableEvent::Clicked(entity)
+            | PickableEvent::Dragged(entity, _) => *entity
+        }
+    }
 }
 
 pub enum PickableComponentState {
@@ -17,3 +39,16 @@ pub enum ComponentSpawned {
     ComponentNotSpawned,
     Any
 }
+
+pub struct PickingAdapterPlugin;
+
+impl Plugin for PickingAdapterPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PickableEvent>()
+            .init_resource::<BoxSelection>()
+            .init_resource::<HoveredEntity>()
+            .init_resource::<BevyPickingState>()
+            .add_system(bridge_pointer_events)
+            .add_system(box_select.after(bridge_pointer_events));
+    }
+}
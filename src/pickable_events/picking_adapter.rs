@@ -0,0 +1,151 @@
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+use bevy::ui::Node as UiNode;
+use bevy::window::PrimaryWindow;
+use bevy_mod_picking::prelude::{Click, Deselect, Drag, Out, Over, PickSelection, Pointer, Select};
+use crate::camera::ActiveCamera;
+use crate::camera::raycast_select::BevyPickingState;
+use crate::camera::viewports::viewport_offset;
+use crate::config::theme::{Theme, ThemeColor};
+use crate::interactions::{HoverEvent, SelectionEvent};
+use crate::network::Node;
+use crate::pickable_events::PickableEvent;
+use crate::ui_components::tooltip::HoveredEntity;
+
+/// Boxes narrower and shorter than this, in logical pixels, are taken as clicks rather than boxes.
+pub const MIN_BOX_SIZE: f32 = 4.0;
+
+/// The corner the box is dragged from, and the corner under the cursor, while a box is dragged.
+#[derive(Resource, Default, Debug)]
+pub struct BoxSelection {
+    pub(crate) start: Option<Vec2>,
+    pub(crate) end: Vec2
+}
+
+/// The outline of the box drawn while it is dragged.
+#[derive(Component, Default, Debug)]
+pub struct BoxSelectionRect;
+
+/// The box between the corners, or none when it is too small to be a box.
+pub(crate) fn selection_box(start: Vec2, end: Vec2) -> Option<Rect> {
+    let rect = Rect::from_corners(start, end);
+    if rect.width() < MIN_BOX_SIZE && rect.height() < MIN_BOX_SIZE {
+        return None;
+    }
+    Some(rect)
+}
+
+/// The pointer events of the entities in the scene are sent as picking events. The UI is left out,
+/// as its interactions are read from the UI components. Several entities are selected by holding
+/// ctrl or shift while clicking them, which the selection of the picking backend handles.
+pub(crate) fn bridge_pointer_events(
+    mut over_events: EventReader<Pointer<Over>>,
+    mut out_events: EventReader<Pointer<Out>>,
+    mut click_events: EventReader<Pointer<Click>>,
+    mut drag_events: EventReader<Pointer<Drag>>,
+    mut select_events: EventReader<Pointer<Select>>,
+    mut deselect_events: EventReader<Pointer<Deselect>>,
+    ui_nodes: Query<(), With<UiNode>>,
+    mut picking_writer: EventWriter<PickableEvent>,
+) {
+    let mut events = vec![];
+    out_events.iter()
+        .for_each(|out| events.push(PickableEvent::Hover(HoverEvent::JustLeft(out.target))));
+    over_events.iter()
+        .for_each(|over| events.push(PickableEvent::Hover(HoverEvent::JustEntered(over.target))));
+    deselect_events.iter()
+        .for_each(|deselect| events.push(PickableEvent::Selection(SelectionEvent::JustDeselected(deselect.target))));
+    select_events.iter()
+        .for_each(|select| events.push(PickableEvent::Selection(SelectionEvent::JustSelected(select.target))));
+    click_events.iter()
+        .for_each(|click| events.push(PickableEvent::Clicked(click.target)));
+    drag_events.iter()
+        .for_each(|drag| events.push(PickableEvent::Dragged(drag.target, drag.event.delta)));
+
+    events.into_iter()
+        .filter(|event| !ui_nodes.contains(event.entity()))
+        .for_each(|event| picking_writer.send(event));
+}
+
+/// Dragging with shift held, starting from where no entity is, draws a box, and the visible nodes
+/// inside the box are added to the selection when it is released. The camera is not rotated while
+/// the box is dragged.
+pub(crate) fn box_select(
+    mut commands: Commands,
+    mut box_selection: ResMut<BoxSelection>,
+    mouse_button_input: Res<Input<MouseButton>>,
+    key_input: Res<Input<KeyCode>>,
+    hovered: Res<HoveredEntity>,
+    mut pick_state: ResMut<BevyPickingState>,
+    theme: Res<Theme>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<ActiveCamera>>,
+    mut nodes: Query<(Entity, &GlobalTransform, &ComputedVisibility, &mut PickSelection), With<Node>>,
+    mut rects: Query<(Entity, &mut Style), With<BoxSelectionRect>>,
+    mut picking_writer: EventWriter<PickableEvent>,
+) {
+    let cursor = windows.get_single().ok()
+        .and_then(|window| window.cursor_position());
+    if let Some(cursor) = cursor {
+        box_selection.end = cursor;
+    }
+    let end = box_selection.end;
+
+    match box_selection.start {
+        None => {
+            let shift = key_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+            if !shift || !mouse_button_input.just_pressed(MouseButton::Left) || cursor.is_none()
+                || hovered.entity.is_some() || pick_state.picked_ui_flag {
+                return;
+            }
+            box_selection.start = cursor;
+            pick_state.picked_ui_flag = true;
+            commands.spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        left: Val::Px(end.x),
+                        top: Val::Px(end.y),
+                        border: UiRect::all(Val::Px(1.0)),
+                        ..default()
+                    },
+                    background_color: Color::NONE.into(),
+                    focus_policy: FocusPolicy::Pass,
+                    z_index: ZIndex::Global(150),
+                    ..default()
+                },
+                BorderColor(theme.color(ThemeColor::PanelHighlight)),
+                BoxSelectionRect::default()
+            ));
+        }
+        Some(start) if mouse_button_input.pressed(MouseButton::Left) => {
+            let rect = Rect::from_corners(start, end);
+            for (_, mut style) in rects.iter_mut() {
+                style.left = Val::Px(rect.min.x);
+                style.top = Val::Px(rect.min.y);
+                style.width = Val::Px(rect.width());
+                style.height = Val::Px(rect.height());
+            }
+        }
+        Some(start) => {
+            box_selection.start = None;
+            pick_state.picked_ui_flag = false;
+            rects.iter().for_each(|(rect, _)| commands.entity(rect).despawn_recursive());
+            let (rect, (camera, camera_transform)) = match (selection_box(start, end), cameras.get_single()) {
+                (Some(rect), Ok(camera)) => (rect, camera),
+                _ => return
+            };
+            let offset = viewport_offset(camera);
+            for (entity, transform, visibility, mut selection) in nodes.iter_mut() {
+                let inside = visibility.is_visible() && camera.world_to_viewport(camera_transform, transform.translation())
+                    .map(|point| rect.contains(point + offset))
+                    .or(Some(false))
+                    .unwrap();
+                if inside && !selection.is_selected {
+                    selection.is_selected = true;
+                    picking_writer.send(PickableEvent::Selection(SelectionEvent::JustSelected(entity)));
+                }
+            }
+        }
+    }
+}
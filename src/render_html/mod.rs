@@ -1,12 +1,156 @@
-use bevy::ecs::system::EntityCommands;
+use std::ops::Range;
+use bevy::asset::LoadState;
 use bevy::prelude::*;
-use pulldown_cmark::{CowStr, Event, HeadingLevel, Parser, Tag};
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, OffsetIter, Options, Parser, Tag};
+use crate::config::theme::{Theme, ThemeColor};
+use crate::theme::restyle::{ThemedSections, ThemeFont};
 
-#[derive(Component)]
+pub const TEXT_COLOR: Color = Color::BLACK;
+pub const LINK_COLOR: Color = Color::rgb(0.1, 0.3, 0.8);
+pub const CODE_COLOR: Color = Color::rgb(0.6, 0.1, 0.3);
+pub const EMPHASIS_COLOR: Color = Color::rgb(0.3, 0.3, 0.45);
+pub const STRIKETHROUGH_COLOR: Color = Color::GRAY;
+pub const CODE_BACKGROUND: Color = Color::rgb(0.93, 0.93, 0.93);
+pub const QUOTE_BACKGROUND: Color = Color::rgb(0.95, 0.95, 0.98);
+pub const TABLE_HEAD_BACKGROUND: Color = Color::rgb(0.88, 0.88, 0.9);
+pub const RULE_COLOR: Color = Color::GRAY;
+
+/// The kind of element a UI node was rendered from, which determines its styling.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
 pub enum HtmlTags {
+    Document,
     Paragraph,
     H1, H2, H3, H4, H5, H6,
-    Code
+    Code,
+    CodeBlock,
+    BlockQuote,
+    List,
+    ListItem,
+    Table,
+    TableHead,
+    TableRow,
+    TableCell,
+    Image,
+    Rule,
+    Footnote
+}
+
+impl HtmlTags {
+    fn heading(level: HeadingLevel) -> HtmlTags {
+        match level {
+            HeadingLevel::H1 => HtmlTags::H1,
+            HeadingLevel::H2 => HtmlTags::H2,
+            HeadingLevel::H3 => HtmlTags::H3,
+            HeadingLevel::H4 => HtmlTags::H4,
+            HeadingLevel::H5 => HtmlTags::H5,
+            HeadingLevel::H6 => HtmlTags::H6,
+        }
+    }
+
+    pub(crate) fn font_size(&self) -> f32 {
+        match self {
+            HtmlTags::H1 => 30.0,
+            HtmlTags::H2 => 26.0,
+            HtmlTags::H3 => 22.0,
+            HtmlTags::H4 => 20.0,
+            HtmlTags::H5 => 18.0,
+            HtmlTags::Code | HtmlTags::CodeBlock => 14.0,
+            _ => 16.0
+        }
+    }
+
+    pub(crate) fn background(&self, markdown_style: &MarkdownStyle) -> Color {
+        match self {
+            HtmlTags::CodeBlock => markdown_style.code_background,
+            HtmlTags::BlockQuote | HtmlTags::Footnote => markdown_style.quote_background,
+            HtmlTags::TableHead => markdown_style.table_head_background,
+            HtmlTags::Rule => markdown_style.rule,
+            _ => Color::NONE
+        }
+    }
+
+    pub(crate) fn style(&self) -> Style {
+        let block = Style {
+            width: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            margin: UiRect::bottom(Val::Px(6.0)),
+            ..default()
+        };
+        match self {
+            HtmlTags::CodeBlock => Style { padding: UiRect::all(Val::Px(6.0)), ..block },
+            HtmlTags::BlockQuote | HtmlTags::Footnote => Style {
+                padding: UiRect::left(Val::Px(8.0)),
+                border: UiRect::left(Val::Px(3.0)),
+                ..block
+            },
+            HtmlTags::List => Style { padding: UiRect::left(Val::Px(12.0)), ..block },
+            HtmlTags::ListItem | HtmlTags::TableHead | HtmlTags::TableRow => Style {
+                flex_direction: FlexDirection::Row,
+                margin: UiRect::bottom(Val::Px(2.0)),
+                ..block
+            },
+            HtmlTags::TableCell => Style {
+                flex_grow: 1.0,
+                flex_basis: Val::Px(0.0),
+                padding: UiRect::all(Val::Px(4.0)),
+                ..default()
+            },
+            HtmlTags::Rule => Style {
+                width: Val::Percent(100.0),
+                height: Val::Px(1.0),
+                margin: UiRect::vertical(Val::Px(6.0)),
+                ..default()
+            },
+            HtmlTags::H1 | HtmlTags::H2 | HtmlTags::H3 => Style { margin: UiRect::vertical(Val::Px(8.0)), ..block },
+            _ => block
+        }
+    }
+}
+
+/// The style of a run of text within a block.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct InlineStyle {
+    pub(crate) emphasis: bool,
+    pub(crate) strong: bool,
+    pub(crate) strikethrough: bool,
+    pub(crate) code: bool,
+    pub(crate) link: Option<String>
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct InlineSpan {
+    pub(crate) text: String,
+    pub(crate) style: InlineStyle
+}
+
+/// A Markdown document is parsed into blocks before it is rendered. Images are inline in Markdown,
+/// but are rendered as a block after the text they are in.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MarkdownBlock {
+    Paragraph(Vec<InlineSpan>),
+    Heading(HeadingLevel, Vec<InlineSpan>),
+    CodeBlock {
+        language: Option<String>,
+        code: String
+    },
+    BlockQuote(Vec<MarkdownBlock>),
+    List {
+        start: Option<u64>,
+        items: Vec<Vec<MarkdownBlock>>
+    },
+    Table {
+        head: Vec<Vec<InlineSpan>>,
+        rows: Vec<Vec<Vec<InlineSpan>>>
+    },
+    Image {
+        url: String,
+        alt: String
+    },
+    Footnote {
+        label: String,
+        blocks: Vec<MarkdownBlock>
+    },
+    Rule
 }
 
 #[derive(Clone)]
@@ -14,148 +158,643 @@ pub struct HtmlParsingResult {
     pub(crate) tree: HtmlTree
 }
 
-#[derive(Clone)]
+/// The UI nodes spawned for a document, with the element each was rendered from.
+#[derive(Clone, Debug)]
 pub struct HtmlTree {
-    pub(crate) html_tree: Option<Box<HtmlTree>>,
-    pub(crate) self_entity: Entity
+    pub(crate) self_entity: Entity,
+    pub(crate) tag: HtmlTags,
+    pub(crate) children: Vec<HtmlTree>
 }
 
-#[derive(Debug)]
-pub struct HtmlParsingError {
+/// The offset is the byte offset into the document of the event the error was found at.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HtmlParsingError {
+    Empty,
+    UnexpectedEnd {
+        tag: String,
+        expected: Option<String>,
+        offset: usize
+    },
+    UnexpectedStart {
+        tag: String,
+        within: String,
+        offset: usize
+    },
+    Unclosed {
+        tag: String
+    }
 }
 
-/// Will divide into sections to render
-pub(crate) fn render_html(mut commands: &mut Commands, mut parser: Parser) -> Result<HtmlTree, HtmlParsingError> {
-    let mut next_value = parser.next();
+/// The fonts and colors documents are rendered with. Strong text is bold and code is monospaced.
+#[derive(Resource, Clone, Debug)]
+pub struct MarkdownStyle {
+    pub(crate) regular: Handle<Font>,
+    pub(crate) bold: Handle<Font>,
+    pub(crate) mono: Handle<Font>,
+    pub(crate) text: Color,
+    pub(crate) link: Color,
+    pub(crate) code: Color,
+    pub(crate) emphasis: Color,
+    pub(crate) strikethrough: Color,
+    pub(crate) code_background: Color,
+    pub(crate) quote_background: Color,
+    pub(crate) table_head_background: Color,
+    pub(crate) rule: Color
+}
 
-    if next_value.as_ref().is_some() {
-        match next_value.unwrap() {
-            Event::Start(start) => {
-                let mut entity = commands.spawn(NodeBundle {
-                    ..default()
-                });
-                let mut html_tree = HtmlTree {
-                    html_tree: None,
-                    self_entity: entity.id()
-                };
-                html_child(&mut entity, &mut parser, &start, &mut html_tree);
-                let next = parser.next();
-                if !matches!(next, Some(Event::End(_))) {
-                    return Err(HtmlParsingError{});
-                }
-                return Ok(html_tree);
+impl Default for MarkdownStyle {
+    fn default() -> Self {
+        Self {
+            regular: Handle::default(),
+            bold: Handle::default(),
+            mono: Handle::default(),
+            text: TEXT_COLOR,
+            link: LINK_COLOR,
+            code: CODE_COLOR,
+            emphasis: EMPHASIS_COLOR,
+            strikethrough: STRIKETHROUGH_COLOR,
+            code_background: CODE_BACKGROUND,
+            quote_background: QUOTE_BACKGROUND,
+            table_head_background: TABLE_HEAD_BACKGROUND,
+            rule: RULE_COLOR
+        }
+    }
+}
+
+impl FromWorld for MarkdownStyle {
+    fn from_world(world: &mut World) -> Self {
+        let theme = world.get_resource::<Theme>()
+            .cloned()
+            .or(Some(Theme::default()))
+            .unwrap();
+        MarkdownStyle::themed(&theme, world.resource::<AssetServer>())
+    }
+}
+
+impl MarkdownStyle {
+    /// Documents are rendered on panels, so the text is the panel text of the theme.
+    pub(crate) fn themed(theme: &Theme, asset_server: &AssetServer) -> MarkdownStyle {
+        MarkdownStyle {
+            regular: Handle::default(),
+            bold: asset_server.load(theme.font.as_str()),
+            mono: asset_server.load(theme.mono_font.as_str()),
+            text: theme.color(ThemeColor::PanelText),
+            link: theme.color(ThemeColor::Link),
+            code: theme.color(ThemeColor::Code),
+            emphasis: theme.color(ThemeColor::PanelText),
+            strikethrough: theme.color(ThemeColor::PanelMutedText),
+            code_background: theme.color(ThemeColor::CodeBackground),
+            quote_background: theme.color(ThemeColor::PanelEntry),
+            table_head_background: theme.color(ThemeColor::PanelHighlight),
+            rule: theme.color(ThemeColor::PanelMutedText)
+        }
+    }
+}
+
+/// The link targets of the text of a block.
+#[derive(Component, Clone, Debug, Default)]
+pub struct MarkdownLinks(pub Vec<String>);
+
+/// An image of a document, loaded from the asset path once it is spawned.
+#[derive(Component, Clone, Debug)]
+pub struct MarkdownImage {
+    pub(crate) path: String,
+    pub(crate) alt: String
+}
+
+/// The image of a MarkdownImage while it is loading.
+#[derive(Component, Clone, Debug)]
+pub struct MarkdownImageLoading(pub(crate) Handle<Image>);
+
+/// A parser for the CommonMark document with tables, footnotes, strikethrough and task lists.
+pub(crate) fn markdown_parser(markdown: &str) -> Parser {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_FOOTNOTES);
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    options.insert(Options::ENABLE_TASKLISTS);
+    Parser::new_ext(markdown, options)
+}
+
+/// Renders the document into a column of UI nodes, one for each block, under a Document node.
+pub(crate) fn render_html(commands: &mut Commands, parser: Parser, markdown_style: &MarkdownStyle) -> Result<HtmlTree, HtmlParsingError> {
+    let blocks = parse_markdown(parser)?;
+    if blocks.is_empty() {
+        return Err(HtmlParsingError::Empty);
+    }
+    Ok(spawn_container(commands, HtmlTags::Document, &blocks, markdown_style))
+}
+
+pub(crate) fn parse_markdown(parser: Parser) -> Result<Vec<MarkdownBlock>, HtmlParsingError> {
+    let mut markdown_parser = MarkdownParser {
+        events: parser.into_offset_iter()
+    };
+    markdown_parser.parse_blocks(None)
+}
+
+fn tag_name(tag: &Tag) -> String {
+    match tag {
+        Tag::Heading(level, ..) => format!("{:?}", level),
+        Tag::CodeBlock(_) => "CodeBlock".to_string(),
+        Tag::List(_) => "List".to_string(),
+        Tag::Table(_) => "Table".to_string(),
+        Tag::Link(..) => "Link".to_string(),
+        Tag::Image(..) => "Image".to_string(),
+        Tag::FootnoteDefinition(_) => "FootnoteDefinition".to_string(),
+        tag => format!("{:?}", tag)
+    }
+}
+
+fn is_inline(tag: &Tag) -> bool {
+    matches!(tag, Tag::Emphasis | Tag::Strong | Tag::Strikethrough | Tag::Link(..) | Tag::Image(..))
+}
+
+/// The spans of text and the images of a block, styled by the inline elements that are open.
+#[derive(Default)]
+struct InlineBuilder<'a> {
+    open: Vec<Tag<'a>>,
+    spans: Vec<InlineSpan>,
+    alt: String,
+    images: Vec<MarkdownBlock>
+}
+
+impl<'a> InlineBuilder<'a> {
+    fn style(&self) -> InlineStyle {
+        let mut style = InlineStyle::default();
+        for tag in self.open.iter() {
+            match tag {
+                Tag::Emphasis => style.emphasis = true,
+                Tag::Strong => style.strong = true,
+                Tag::Strikethrough => style.strikethrough = true,
+                Tag::Link(_, url, _) => style.link = Some(url.to_string()),
+                _ => {}
             }
-            val => {
+        }
+        style
+    }
+
+    fn push(&mut self, text: &str, code: bool) {
+        if self.open.iter().any(|tag| matches!(tag, Tag::Image(..))) {
+            self.alt.push_str(text);
+            return;
+        }
+        let mut style = self.style();
+        style.code = code;
+        match self.spans.last_mut() {
+            Some(last) if last.style == style => last.text.push_str(text),
+            _ => self.spans.push(InlineSpan { text: text.to_string(), style })
+        }
+    }
+
+    fn start(&mut self, tag: Tag<'a>) {
+        self.open.push(tag);
+    }
+
+    fn end(&mut self, tag: Tag<'a>, offset: usize) -> Result<(), HtmlParsingError> {
+        match self.open.pop() {
+            Some(open) if open == tag => {
+                if let Tag::Image(_, url, _) = open {
+                    self.images.push(MarkdownBlock::Image { url: url.to_string(), alt: std::mem::take(&mut self.alt) });
+                }
+                Ok(())
             }
+            open => Err(HtmlParsingError::UnexpectedEnd {
+                tag: tag_name(&tag),
+                expected: open.map(|open| tag_name(&open)),
+                offset
+            })
         }
     }
 
-    Err(HtmlParsingError{})
-}
-
-pub(crate) fn html_child(mut commands: &mut EntityCommands, mut parser: &mut Parser, tag: &Tag, mut html_tree: &mut HtmlTree) {
-    let mut next = parser.next();
-    if next.is_some() {
-        commands.with_children(|children| {
-            match next.unwrap() {
-                Event::Start(start) => {
-                    let mut commands = children.spawn((
-                        NodeBundle {
-                            ..default()
-                        }
-                    ));
-                    let mut entity = commands.id();
-                    let mut next_html = HtmlTree {
-                        html_tree: None,
-                        self_entity: entity,
-                    };
-                    html_child(&mut commands, parser, &start, &mut next_html);
-                    html_tree.html_tree = Some(next_html.into());
+    fn take_spans(&mut self) -> Vec<InlineSpan> {
+        std::mem::take(&mut self.spans)
+    }
+
+    /// The text as a paragraph, followed by its images.
+    fn flush_into(&mut self, blocks: &mut Vec<MarkdownBlock>) {
+        let spans = self.take_spans();
+        if spans.iter().any(|span| !span.text.trim().is_empty()) {
+            blocks.push(MarkdownBlock::Paragraph(spans));
+        }
+        blocks.append(&mut self.images);
+    }
+
+    fn unclosed(&self) -> Option<HtmlParsingError> {
+        self.open.last().map(|tag| HtmlParsingError::Unclosed { tag: tag_name(tag) })
+    }
+}
+
+struct MarkdownParser<'a, 'b> {
+    events: OffsetIter<'a, 'b>
+}
+
+impl<'a, 'b> MarkdownParser<'a, 'b> {
+    /// Parses blocks until the end of the element, or of the document. Text that is not in a
+    /// paragraph, as in the items of a tight list, is collected into a paragraph.
+    fn parse_blocks(&mut self, until: Option<&Tag<'a>>) -> Result<Vec<MarkdownBlock>, HtmlParsingError> {
+        let mut blocks = vec![];
+        let mut inline = InlineBuilder::default();
+        loop {
+            let (event, range) = match self.events.next() {
+                Some(next) => next,
+                None => {
+                    if let Some(error) = inline.unclosed() {
+                        return Err(error);
+                    }
+                    if let Some(tag) = until {
+                        return Err(HtmlParsingError::Unclosed { tag: tag_name(tag) });
+                    }
+                    inline.flush_into(&mut blocks);
+                    return Ok(blocks);
                 }
-                Event::End(_) => {
-                    return;
+            };
+            match event {
+                Event::Start(tag) if is_inline(&tag) => inline.start(tag),
+                Event::End(tag) if is_inline(&tag) => inline.end(tag, range.start)?,
+                Event::Start(tag) => {
+                    inline.flush_into(&mut blocks);
+                    self.parse_block(tag, range, &mut blocks)?;
                 }
-                Event::Text(txt) => {
-                    match tag {
-                        Tag::Paragraph => {
-                            let next = spawn_text_value(children, txt, HtmlTags::Paragraph);
-                            html_tree.html_tree = Some(HtmlTree {
-                                html_tree: None,
-                                self_entity: next,
-                            }.into());
-                        }
-                        Tag::Heading(heading, _, _) => {
-                            match heading {
-                                HeadingLevel::H1 => {
-                                    spawn_text_value(children, txt, HtmlTags::H1);
-                                },
-                                HeadingLevel::H2 => {
-                                    spawn_text_value(children, txt, HtmlTags::H2);
-                                },
-                                HeadingLevel::H3 => {
-                                    spawn_text_value(children, txt, HtmlTags::H3);
-                                },
-                                HeadingLevel::H4 => {
-                                    spawn_text_value(children, txt, HtmlTags::H4);
-                                },
-                                HeadingLevel::H5 => {
-                                    spawn_text_value(children, txt, HtmlTags::H5);
-                                },
-                                HeadingLevel::H6 => {
-                                    spawn_text_value(children, txt, HtmlTags::H6);
-                                },
-                            }
-                        }
-                        Tag::BlockQuote => {}
-                        Tag::CodeBlock(_) => {}
-                        Tag::List(_) => {}
-                        Tag::Item => {}
-                        Tag::FootnoteDefinition(_) => {}
-                        Tag::Table(_) => {}
-                        Tag::TableHead => {}
-                        Tag::TableRow => {}
-                        Tag::TableCell => {}
-                        Tag::Emphasis => {}
-                        Tag::Strong => {}
-                        Tag::Strikethrough => {}
-                        Tag::Link(_, _, _) => {}
-                        Tag::Image(_, _, _) => {}
+                Event::End(tag) => {
+                    if until.filter(|until| **until == tag).is_none() {
+                        return Err(HtmlParsingError::UnexpectedEnd {
+                            tag: tag_name(&tag),
+                            expected: until.map(tag_name),
+                            offset: range.start
+                        });
+                    }
+                    if let Some(error) = inline.unclosed() {
+                        return Err(error);
                     }
+                    inline.flush_into(&mut blocks);
+                    return Ok(blocks);
                 }
-                Event::Code(code) => {
+                Event::Rule => {
+                    inline.flush_into(&mut blocks);
+                    blocks.push(MarkdownBlock::Rule);
                 }
-                Event::Html(html) => {
+                event => Self::inline_event(event, &mut inline)
+            }
+        }
+    }
+
+    fn inline_event(event: Event<'a>, inline: &mut InlineBuilder<'a>) {
+        match event {
+            Event::Text(text) | Event::Html(text) => inline.push(&text, false),
+            Event::Code(code) => inline.push(&code, true),
+            Event::SoftBreak => inline.push(" ", false),
+            Event::HardBreak => inline.push("\n", false),
+            Event::TaskListMarker(checked) => inline.push(if checked { "[x] " } else { "[ ] " }, false),
+            Event::FootnoteReference(label) => inline.push(&format!("[{}]", label), false),
+            _ => {}
+        }
+    }
+
+    fn parse_block(&mut self, tag: Tag<'a>, range: Range<usize>, blocks: &mut Vec<MarkdownBlock>) -> Result<(), HtmlParsingError> {
+        match &tag {
+            Tag::Paragraph => {
+                let (spans, mut images) = self.parse_inlines(&tag)?;
+                if !spans.is_empty() {
+                    blocks.push(MarkdownBlock::Paragraph(spans));
                 }
-                Event::FootnoteReference(_) => {}
-                Event::SoftBreak => {
+                blocks.append(&mut images);
+            }
+            Tag::Heading(level, ..) => {
+                let (spans, mut images) = self.parse_inlines(&tag)?;
+                blocks.push(MarkdownBlock::Heading(*level, spans));
+                blocks.append(&mut images);
+            }
+            Tag::CodeBlock(kind) => {
+                let language = match kind {
+                    CodeBlockKind::Fenced(language) if !language.is_empty() => Some(language.to_string()),
+                    _ => None
+                };
+                let mut code = String::new();
+                loop {
+                    match self.events.next() {
+                        Some((Event::Text(text), _)) => code.push_str(&text),
+                        Some((Event::End(end), _)) if end == tag => break,
+                        Some((event, range)) => return Err(self.unexpected(event, &tag, range)),
+                        None => return Err(HtmlParsingError::Unclosed { tag: tag_name(&tag) })
+                    }
                 }
-                Event::HardBreak => {
+                blocks.push(MarkdownBlock::CodeBlock { language, code: code.trim_end_matches('\n').to_string() });
+            }
+            Tag::BlockQuote => blocks.push(MarkdownBlock::BlockQuote(self.parse_blocks(Some(&tag))?)),
+            Tag::FootnoteDefinition(label) => blocks.push(MarkdownBlock::Footnote {
+                label: label.to_string(),
+                blocks: self.parse_blocks(Some(&tag))?
+            }),
+            Tag::List(start) => {
+                let mut items = vec![];
+                loop {
+                    match self.events.next() {
+                        Some((Event::Start(Tag::Item), _)) => items.push(self.parse_blocks(Some(&Tag::Item))?),
+                        Some((Event::End(end), _)) if end == tag => break,
+                        Some((event, range)) => return Err(self.unexpected(event, &tag, range)),
+                        None => return Err(HtmlParsingError::Unclosed { tag: tag_name(&tag) })
+                    }
                 }
-                Event::Rule => {
+                blocks.push(MarkdownBlock::List { start: *start, items });
+            }
+            Tag::Table(_) => {
+                let mut head = vec![];
+                let mut rows = vec![];
+                loop {
+                    match self.events.next() {
+                        Some((Event::Start(Tag::TableHead), _)) => head = self.parse_row(Tag::TableHead)?,
+                        Some((Event::Start(Tag::TableRow), _)) => rows.push(self.parse_row(Tag::TableRow)?),
+                        Some((Event::End(end), _)) if end == tag => break,
+                        Some((event, range)) => return Err(self.unexpected(event, &tag, range)),
+                        None => return Err(HtmlParsingError::Unclosed { tag: tag_name(&tag) })
+                    }
                 }
-                Event::TaskListMarker(_) => {
+                blocks.push(MarkdownBlock::Table { head, rows });
+            }
+            _ => return Err(HtmlParsingError::UnexpectedStart {
+                tag: tag_name(&tag),
+                within: "block".to_string(),
+                offset: range.start
+            })
+        }
+        Ok(())
+    }
+
+    fn parse_row(&mut self, row: Tag<'a>) -> Result<Vec<Vec<InlineSpan>>, HtmlParsingError> {
+        let mut cells = vec![];
+        loop {
+            match self.events.next() {
+                Some((Event::Start(Tag::TableCell), _)) => cells.push(self.parse_inlines(&Tag::TableCell)?.0),
+                Some((Event::End(end), _)) if end == row => return Ok(cells),
+                Some((event, range)) => return Err(self.unexpected(event, &row, range)),
+                None => return Err(HtmlParsingError::Unclosed { tag: tag_name(&row) })
+            }
+        }
+    }
+
+    /// Parses the text of an element that only contains inline elements.
+    fn parse_inlines(&mut self, until: &Tag<'a>) -> Result<(Vec<InlineSpan>, Vec<MarkdownBlock>), HtmlParsingError> {
+        let mut inline = InlineBuilder::default();
+        loop {
+            match self.events.next() {
+                Some((Event::Start(tag), _)) if is_inline(&tag) => inline.start(tag),
+                Some((Event::End(tag), range)) if is_inline(&tag) => inline.end(tag, range.start)?,
+                Some((Event::End(tag), range)) => {
+                    if &tag != until {
+                        return Err(HtmlParsingError::UnexpectedEnd {
+                            tag: tag_name(&tag),
+                            expected: Some(tag_name(until)),
+                            offset: range.start
+                        });
+                    }
+                    if let Some(error) = inline.unclosed() {
+                        return Err(error);
+                    }
+                    let spans = inline.take_spans();
+                    return Ok((spans, inline.images));
+                }
+                Some((event @ Event::Start(_), range)) | Some((event @ Event::Rule, range)) => {
+                    return Err(self.unexpected(event, until, range));
                 }
+                Some((event, _)) => Self::inline_event(event, &mut inline),
+                None => return Err(HtmlParsingError::Unclosed { tag: tag_name(until) })
             }
-        });
+        }
+    }
+
+    fn unexpected(&self, event: Event<'a>, within: &Tag<'a>, range: Range<usize>) -> HtmlParsingError {
+        match event {
+            Event::End(tag) => HtmlParsingError::UnexpectedEnd {
+                tag: tag_name(&tag),
+                expected: Some(tag_name(within)),
+                offset: range.start
+            },
+            Event::Start(tag) => HtmlParsingError::UnexpectedStart {
+                tag: tag_name(&tag),
+                within: tag_name(within),
+                offset: range.start
+            },
+            event => HtmlParsingError::UnexpectedStart {
+                tag: format!("{:?}", event),
+                within: tag_name(within),
+                offset: range.start
+            }
+        }
     }
 }
 
-fn spawn_text_value(children: &mut ChildBuilder, txt: CowStr, tags: HtmlTags) -> Entity {
-    let spawned = children.spawn((
+fn spawn_node(commands: &mut Commands, tag: HtmlTags, markdown_style: &MarkdownStyle) -> Entity {
+    commands.spawn((
+        NodeBundle {
+            style: tag.style(),
+            background_color: tag.background(markdown_style).into(),
+            border_color: markdown_style.rule.into(),
+            ..default()
+        },
+        tag
+    )).id()
+}
+
+fn spawn_container(commands: &mut Commands, tag: HtmlTags, blocks: &Vec<MarkdownBlock>, markdown_style: &MarkdownStyle) -> HtmlTree {
+    let entity = spawn_node(commands, tag, markdown_style);
+    let children = blocks.iter()
+        .map(|block| spawn_block(commands, block, markdown_style))
+        .collect::<Vec<HtmlTree>>();
+    add_children(commands, entity, &children);
+    HtmlTree { self_entity: entity, tag, children }
+}
+
+fn add_children(commands: &mut Commands, parent: Entity, children: &Vec<HtmlTree>) {
+    let children = children.iter()
+        .map(|child| child.self_entity)
+        .collect::<Vec<Entity>>();
+    commands.entity(parent).push_children(&children);
+}
+
+fn spawn_block(commands: &mut Commands, block: &MarkdownBlock, markdown_style: &MarkdownStyle) -> HtmlTree {
+    match block {
+        MarkdownBlock::Paragraph(spans) => spawn_text(commands, spans, HtmlTags::Paragraph, markdown_style),
+        MarkdownBlock::Heading(level, spans) => spawn_text(commands, spans, HtmlTags::heading(*level), markdown_style),
+        MarkdownBlock::CodeBlock { code, .. } => {
+            let code = vec![InlineSpan { text: code.clone(), style: InlineStyle { code: true, ..default() } }];
+            let text = spawn_text(commands, &code, HtmlTags::Code, markdown_style);
+            wrap(commands, HtmlTags::CodeBlock, vec![text], markdown_style)
+        }
+        MarkdownBlock::BlockQuote(blocks) => spawn_container(commands, HtmlTags::BlockQuote, blocks, markdown_style),
+        MarkdownBlock::Footnote { label, blocks } => {
+            let label = spawn_text(commands, &plain(format!("[{}]", label)), HtmlTags::Paragraph, markdown_style);
+            let mut footnote = spawn_container(commands, HtmlTags::Footnote, blocks, markdown_style);
+            commands.entity(footnote.self_entity).insert_children(0, &[label.self_entity]);
+            footnote.children.insert(0, label);
+            footnote
+        }
+        MarkdownBlock::List { start, items } => {
+            let items = items.iter()
+                .enumerate()
+                .map(|(index, blocks)| {
+                    let marker = start
+                        .map(|start| format!("{}. ", start + index as u64))
+                        .or(Some("• ".to_string()))
+                        .unwrap();
+                    let marker = spawn_text(commands, &plain(marker), HtmlTags::Paragraph, markdown_style);
+                    let content = spawn_container(commands, HtmlTags::Document, blocks, markdown_style);
+                    wrap(commands, HtmlTags::ListItem, vec![marker, content], markdown_style)
+                })
+                .collect::<Vec<HtmlTree>>();
+            wrap(commands, HtmlTags::List, items, markdown_style)
+        }
+        MarkdownBlock::Table { head, rows } => {
+            let mut table_rows = vec![];
+            if !head.is_empty() {
+                table_rows.push(spawn_row(commands, head, HtmlTags::TableHead, markdown_style));
+            }
+            rows.iter()
+                .for_each(|row| table_rows.push(spawn_row(commands, row, HtmlTags::TableRow, markdown_style)));
+            wrap(commands, HtmlTags::Table, table_rows, markdown_style)
+        }
+        MarkdownBlock::Image { url, alt } => {
+            let alt_text = spawn_text(commands, &plain(alt.clone()), HtmlTags::Paragraph, markdown_style);
+            let image = wrap(commands, HtmlTags::Image, vec![alt_text], markdown_style);
+            commands.entity(image.self_entity)
+                .insert(MarkdownImage { path: url.clone(), alt: alt.clone() });
+            image
+        }
+        MarkdownBlock::Rule => HtmlTree {
+            self_entity: spawn_node(commands, HtmlTags::Rule, markdown_style),
+            tag: HtmlTags::Rule,
+            children: vec![]
+        }
+    }
+}
+
+fn wrap(commands: &mut Commands, tag: HtmlTags, children: Vec<HtmlTree>, markdown_style: &MarkdownStyle) -> HtmlTree {
+    let entity = spawn_node(commands, tag, markdown_style);
+    add_children(commands, entity, &children);
+    HtmlTree { self_entity: entity, tag, children }
+}
+
+fn spawn_row(commands: &mut Commands, cells: &Vec<Vec<InlineSpan>>, tag: HtmlTags, markdown_style: &MarkdownStyle) -> HtmlTree {
+    let cells = cells.iter()
+        .map(|cell| {
+            let text = spawn_text(commands, cell, HtmlTags::Paragraph, markdown_style);
+            wrap(commands, HtmlTags::TableCell, vec![text], markdown_style)
+        })
+        .collect::<Vec<HtmlTree>>();
+    wrap(commands, tag, cells, markdown_style)
+}
+
+fn plain(text: String) -> Vec<InlineSpan> {
+    vec![InlineSpan { text, style: InlineStyle::default() }]
+}
+
+/// The role of a section in the theme, so that links and code keep their color and font when the
+/// theme changes.
+pub(crate) fn section_role(style: &InlineStyle, tag: HtmlTags) -> (ThemeColor, ThemeFont) {
+    let font = if style.code {
+        ThemeFont::Mono
+    } else if style.strong || matches!(tag, HtmlTags::H1 | HtmlTags::H2 | HtmlTags::H3 | HtmlTags::H4 | HtmlTags::H5 | HtmlTags::H6) {
+        ThemeFont::Bold
+    } else {
+        ThemeFont::Regular
+    };
+    let color = if style.link.is_some() {
+        ThemeColor::Link
+    } else if style.strikethrough {
+        ThemeColor::PanelMutedText
+    } else if style.code {
+        ThemeColor::Code
+    } else {
+        ThemeColor::PanelText
+    };
+    (color, font)
+}
+
+/// The sections of the text are styled by their inline style, and sized by the element.
+pub(crate) fn text_style(style: &InlineStyle, tag: HtmlTags, markdown_style: &MarkdownStyle) -> TextStyle {
+    let font = match section_role(style, tag).1 {
+        ThemeFont::Mono => markdown_style.mono.clone(),
+        ThemeFont::Bold => markdown_style.bold.clone(),
+        ThemeFont::Regular => markdown_style.regular.clone()
+    };
+    let color = if style.link.is_some() {
+        markdown_style.link
+    } else if style.strikethrough {
+        markdown_style.strikethrough
+    } else if style.code {
+        markdown_style.code
+    } else if style.emphasis {
+        markdown_style.emphasis
+    } else {
+        markdown_style.text
+    };
+    let font_size = if style.code && tag != HtmlTags::Code { HtmlTags::Code.font_size() } else { tag.font_size() };
+    TextStyle { font, font_size, color }
+}
+
+fn spawn_text(commands: &mut Commands, spans: &Vec<InlineSpan>, tag: HtmlTags, markdown_style: &MarkdownStyle) -> HtmlTree {
+    let sections = spans.iter()
+        .map(|span| TextSection::new(span.text.clone(), text_style(&span.style, tag, markdown_style)))
+        .collect::<Vec<TextSection>>();
+    let roles = spans.iter()
+        .map(|span| section_role(&span.style, tag))
+        .collect::<Vec<(ThemeColor, ThemeFont)>>();
+    let links = spans.iter()
+        .flat_map(|span| span.style.link.clone())
+        .collect::<Vec<String>>();
+    let mut text = commands.spawn((
         TextBundle {
-            text: Text {
-                sections: vec![
-                    TextSection::new(txt.into_string(), TextStyle {
-                        ..default()
-                    })
-                ],
-                ..default()
-            },
+            text: Text::from_sections(sections),
+            style: tag.style(),
             ..default()
         },
-        tags
+        ThemedSections(roles),
+        tag
     ));
+    if !links.is_empty() {
+        text.insert(MarkdownLinks(links));
+    }
+    HtmlTree { self_entity: text.id(), tag, children: vec![] }
+}
 
-    spawned.id()
-
-}
\ No newline at end of file
+/// Images are loaded from the assets once they are spawned. The alt text is shown until the image
+/// has loaded, and kept when it cannot be loaded, and the image is then shown in an ImageBundle so
+/// that it is sized by the image.
+pub(crate) fn load_markdown_images(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    added: Query<(Entity, &MarkdownImage), Added<MarkdownImage>>,
+    loading: Query<(Entity, &MarkdownImage, &MarkdownImageLoading, Option<&Children>)>,
+) {
+    for (entity, image) in added.iter() {
+        if image.path.contains("://") {
+            info!("Not loading remote image {}, showing {} instead.", &image.path, &image.alt);
+            continue;
+        }
+        commands.entity(entity).insert(MarkdownImageLoading(asset_server.load(image.path.as_str())));
+    }
+    for (entity, image, MarkdownImageLoading(handle), children) in loading.iter() {
+        match asset_server.get_load_state(handle) {
+            LoadState::Loaded => {
+                children.map(|children| children.iter()
+                    .for_each(|child| commands.entity(*child).despawn_recursive())
+                );
+                let image_node = commands.spawn(ImageBundle {
+                    style: Style {
+                        max_width: Val::Percent(100.0),
+                        ..default()
+                    },
+                    image: UiImage::new(handle.clone()),
+                    ..default()
+                }).id();
+                commands.entity(entity)
+                    .remove::<MarkdownImageLoading>()
+                    .add_child(image_node);
+            }
+            LoadState::Failed => {
+                error!("Could not load image {}, showing {} instead.", &image.path, &image.alt);
+                commands.entity(entity).remove::<MarkdownImageLoading>();
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+use bevy::prelude::Entity;
+use crate::util::fuzzy_score;
+
+/// The comparisons of a filter term, as they are written in the query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le
+}
+
+impl Comparison {
+    /// The operators in the order they are looked for, so that `>=` is not read as `>`.
+    fn all() -> Vec<(&'static str, Comparison)> {
+        vec![
+            (">=", Comparison::Ge),
+            ("<=", Comparison::Le),
+            ("!=", Comparison::Ne),
+            ("==", Comparison::Eq),
+            ("=", Comparison::Eq),
+            (">", Comparison::Gt),
+            ("<", Comparison::Lt)
+        ]
+    }
+
+    fn compare_values(&self, value: f32, to: f32) -> bool {
+        match self {
+            Comparison::Eq => value == to,
+            Comparison::Ne => value != to,
+            Comparison::Gt => value > to,
+            Comparison::Ge => value >= to,
+            Comparison::Lt => value < to,
+            Comparison::Le => value <= to
+        }
+    }
+}
+
+/// A clause of the query. Text is fuzzy-matched against the names of the candidate, and a field
+/// is compared with a text field of the candidate, such as `layer_type`, or with the latest value
+/// of a metric of the candidate, such as `weight variance`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterTerm {
+    Text(String),
+    Field {
+        field: String,
+        comparison: Comparison,
+        value: String
+    }
+}
+
+/// What is searched: a layer, a node of a layer, or a metric.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SearchKind {
+    Layer,
+    Node,
+    Metric
+}
+
+impl SearchKind {
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            SearchKind::Layer => "layer",
+            SearchKind::Node => "node",
+            SearchKind::Metric => "metric"
+        }
+    }
+}
+
+/// The names the text of the query is matched against, the text fields and the latest metric
+/// values the fields of the query are compared with. Field and metric names are normalized.
+#[derive(Debug, Clone)]
+pub struct SearchCandidate {
+    pub(crate) entity: Entity,
+    pub(crate) kind: SearchKind,
+    pub(crate) label: String,
+    pub(crate) names: Vec<String>,
+    pub(crate) fields: HashMap<String, String>,
+    pub(crate) values: HashMap<String, f32>
+}
+
+impl SearchCandidate {
+    pub(crate) fn new(entity: Entity, kind: SearchKind, label: &str) -> Self {
+        let mut fields = HashMap::new();
+        fields.insert("kind".to_string(), kind.name().to_string());
+        fields.insert("name".to_string(), label.to_string());
+        Self {
+            entity,
+            kind,
+            label: label.to_string(),
+            names: vec![label.to_string()],
+            fields,
+            values: HashMap::new()
+        }
+    }
+
+    pub(crate) fn with_name(mut self, name: &str) -> Self {
+        self.names.push(name.to_string());
+        self
+    }
+
+    pub(crate) fn with_field(mut self, field: &str, value: &str) -> Self {
+        self.fields.insert(normalize(field), value.to_string());
+        self
+    }
+
+    /// Keeps the largest value when several metrics of the candidate have the same name, such as
+    /// the same metric from more than one run.
+    pub(crate) fn with_value(mut self, metric_name: &str, value: f32) -> Self {
+        let value = self.values.get(&normalize(metric_name))
+            .map(|previous| previous.max(value))
+            .or(Some(value))
+            .unwrap();
+        self.values.insert(normalize(metric_name), value);
+        self
+    }
+}
+
+/// A query of terms that are all matched, separated by `and`, such as
+/// `layer_type = attention and weight variance > 0.5`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchFilter {
+    pub(crate) terms: Vec<FilterTerm>
+}
+
+impl SearchFilter {
+    pub(crate) fn parse(query: &str) -> SearchFilter {
+        let terms = split_and(query).into_iter()
+            .map(|clause| clause.trim())
+            .filter(|clause| !clause.is_empty())
+            .map(parse_term)
+            .collect();
+        SearchFilter { terms }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// The score of the candidate, the sum of the scores of the text terms, or None when any of
+    /// the terms does not match.
+    pub(crate) fn score(&self, candidate: &SearchCandidate) -> Option<i32> {
+        let mut score = 0;
+        for term in self.terms.iter() {
+            match term {
+                FilterTerm::Text(text) => {
+                    score += candidate.names.iter()
+                        .flat_map(|name| fuzzy_score(text, name))
+                        .max()?;
+                }
+                FilterTerm::Field { field, comparison, value } => {
+                    if !field_matches(candidate, field, *comparison, value) {
+                        return None;
+                    }
+                }
+            }
+        }
+        Some(score)
+    }
+}
+
+/// Field and metric names are compared lowercase, with spaces and dashes read as underscores, so
+/// that `weight variance` is the same as `weight_variance`.
+pub(crate) fn normalize(name: &str) -> String {
+    name.trim()
+        .to_lowercase()
+        .split(|c: char| c.is_whitespace() || c == '-' || c == '_')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<&str>>()
+        .join("_")
+}
+
+fn split_and(query: &str) -> Vec<&str> {
+    let mut clauses = vec![];
+    let mut start = 0;
+    let spaces = query.char_indices()
+        .filter(|(_, c)| c.is_whitespace())
+        .map(|(index, c)| (index, index + c.len_utf8()))
+        .collect::<Vec<(usize, usize)>>();
+    for window in spaces.windows(2) {
+        let ((before, word_start), (word_end, after)) = (window[0], window[1]);
+        if before >= start && query[word_start..word_end].eq_ignore_ascii_case("and") {
+            clauses.push(&query[start..before]);
+            start = after;
+        }
+    }
+    clauses.push(&query[start..]);
+    clauses
+}
+
+fn parse_term(clause: &str) -> FilterTerm {
+    Comparison::all().into_iter()
+        .flat_map(|(operator, comparison)| clause.find(operator)
+            .map(|found| (found, operator, comparison)))
+        .min_by_key(|(found, operator, _)| (*found, usize::MAX - operator.len()))
+        .map(|(found, operator, comparison)| (
+            clause[..found].trim(),
+            comparison,
+            clause[found + operator.len()..].trim()
+        ))
+        .filter(|(field, _, value)| !field.is_empty() && !value.is_empty())
+        .map(|(field, comparison, value)| FilterTerm::Field {
+            field: normalize(field),
+            comparison,
+            value: value.to_string()
+        })
+        .or(Some(FilterTerm::Text(clause.to_string())))
+        .unwrap()
+}
+
+/// A text field is compared by name, and anything else is compared with the value of the metric
+/// of the same name. A candidate without the field or metric does not match.
+fn field_matches(candidate: &SearchCandidate, field: &str, comparison: Comparison, value: &str) -> bool {
+    if let Some(text) = candidate.fields.get(field) {
+        return match comparison {
+            Comparison::Eq => normalize(text) == normalize(value),
+            Comparison::Ne => normalize(text) != normalize(value),
+            _ => false
+        };
+    }
+    match (candidate.values.get(field), value.parse::<f32>()) {
+        (Some(metric_value), Ok(to)) => comparison.compare_values(*metric_value, to),
+        _ => false
+    }
+}
@@ -0,0 +1,355 @@
+use std::collections::{HashMap, HashSet};
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+use crate::camera::FrameEntity;
+use crate::camera::orbit_camera::frame_target;
+use crate::config::keymap::KeyAction;
+use crate::config::theme::{DEFAULT_FONT, ThemeColor};
+use crate::data_subscriber::metric_event::MetricsState;
+use crate::graph::{GraphingMetricsResource, GraphParent};
+use crate::menu::graphing_menu::context_menu::set_visibility;
+use crate::metrics::network_metrics::Metric;
+use crate::network::{Layer, Network, Node};
+use crate::search::filter::{SearchCandidate, SearchFilter, SearchKind};
+use crate::shortcuts::keymap::{KeyActionEvent, Keymap};
+use crate::theme::highlight::Highlight;
+use crate::theme::restyle::{ThemedBackground, ThemedText};
+
+/// The query of the search, parsed into fuzzy-matched text and the fields compared with the
+/// candidates.
+pub(crate) mod filter;
+
+pub const SEARCH_MAX_RESULTS: usize = 10;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchResult {
+    pub(crate) entity: Entity,
+    pub(crate) kind: SearchKind,
+    pub(crate) label: String,
+    pub(crate) score: i32
+}
+
+/// The results are every layer, node and metric matching the query, best first, and are collected
+/// again whenever the query changes.
+#[derive(Resource, Default, Debug)]
+pub struct SearchState {
+    pub(crate) open: bool,
+    pub(crate) query: String,
+    pub(crate) selected: usize,
+    pub(crate) results: Vec<SearchResult>
+}
+
+impl SearchState {
+    /// The scene is only highlighted and dimmed while there is something to search for.
+    pub(crate) fn searching(&self) -> bool {
+        self.open && !self.query.trim().is_empty()
+    }
+}
+
+#[derive(Component, Default, Debug)]
+pub struct SearchPanel;
+
+pub struct SearchPlugin;
+
+impl Plugin for SearchPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<KeyActionEvent>()
+            .add_event::<FrameEntity>()
+            .init_resource::<SearchState>()
+            .init_resource::<MetricsState>()
+            .add_system(search_input)
+            .add_system(update_search_results::<Node>.after(search_input))
+            .add_system(highlight_search_results.after(update_search_results::<Node>))
+            .add_system(render_search.after(update_search_results::<Node>));
+    }
+}
+
+/// The candidates matching the query, best first. Candidates that score the same are sorted by
+/// label, and nothing matches an empty query.
+pub(crate) fn search_results(candidates: &Vec<SearchCandidate>, query: &str) -> Vec<SearchResult> {
+    let filter = SearchFilter::parse(query);
+    if filter.is_empty() {
+        return vec![];
+    }
+    let mut results = candidates.iter()
+        .flat_map(|candidate| filter.score(candidate).map(|score| SearchResult {
+            entity: candidate.entity,
+            kind: candidate.kind,
+            label: candidate.label.clone(),
+            score
+        }))
+        .collect::<Vec<SearchResult>>();
+    results.sort_by(|first, second| second.score.cmp(&first.score)
+        .then_with(|| first.label.cmp(&second.label))
+    );
+    results
+}
+
+/// The largest of the latest values of the columns of the metric.
+pub(crate) fn latest_value<T: Component>(metric: &Metric<T>) -> Option<f32> {
+    metric.historical.labels.keys()
+        .flat_map(|column| metric.historical.latest_values(column))
+        .flat_map(|(_, values)| values.into_iter())
+        .reduce(f32::max)
+}
+
+/// The layers and nodes, searched by their name and network, and filtered by their layer type and
+/// the latest values of the metrics indexed by them, and the metrics, searched by their name.
+fn search_candidates<T: Component>(
+    layers: &Query<(Entity, &Layer)>,
+    metrics: &Query<(Entity, &Metric<T>)>,
+    metrics_state: &MetricsState,
+    graphing_metrics: &GraphingMetricsResource,
+) -> Vec<SearchCandidate> {
+    let latest = metrics_state.entities.iter()
+        .flat_map(|((_, metric_name), (entity, _))| metrics.get(*entity).ok()
+            .and_then(|(_, metric)| latest_value(metric))
+            .map(|value| (*entity, (metric_name.as_str(), value)))
+        )
+        .collect::<HashMap<Entity, (&str, f32)>>();
+    let with_values = |candidate: SearchCandidate, index: &str| match graphing_metrics.graphing_indices.get(index) {
+        Some(indexed) => indexed.iter()
+            .flat_map(|metric| latest.get(metric))
+            .fold(candidate, |candidate, (metric_name, value)| candidate.with_value(metric_name, *value)),
+        None => candidate
+    };
+
+    let mut candidates = vec![];
+    for (layer_entity, layer) in layers.iter() {
+        let network = layer.network_id.network_id;
+        let layer_candidate = SearchCandidate::new(layer_entity, SearchKind::Layer, layer.name)
+            .with_name(network)
+            .with_field("layer_type", layer.layer_type.name())
+            .with_field("network", network);
+        candidates.push(with_values(layer_candidate, layer.name));
+        for node in layer.nodes.iter() {
+            let node_entity = match node.entity {
+                Some(node_entity) => node_entity,
+                None => continue
+            };
+            let node_id = layer.node_id(node);
+            let node_candidate = SearchCandidate::new(node_entity, SearchKind::Node, &node_id)
+                .with_name(network)
+                .with_field("layer_type", layer.layer_type.name())
+                .with_field("network", network)
+                .with_field("layer", layer.name);
+            candidates.push(with_values(node_candidate, &node_id));
+        }
+    }
+    for ((run_id, metric_name), (entity, _)) in metrics_state.entities.iter() {
+        let label = if metrics_state.runs.len() > 1 {
+            format!("{} ({})", metric_name, run_id)
+        } else {
+            metric_name.clone()
+        };
+        let metric_candidate = SearchCandidate::new(*entity, SearchKind::Metric, &label)
+            .with_field("run", run_id);
+        candidates.push(match latest.get(entity) {
+            Some((metric_name, value)) => metric_candidate.with_value(metric_name, *value),
+            None => metric_candidate
+        });
+    }
+    candidates
+}
+
+/// Opens the search from its key action. While it is open the characters typed edit the query,
+/// the arrows move the selection, return moves the camera to the selected result and escape
+/// closes it.
+pub(crate) fn search_input(
+    mut commands: Commands,
+    mut state: ResMut<SearchState>,
+    mut action_events: EventReader<KeyActionEvent>,
+    mut characters: EventReader<ReceivedCharacter>,
+    input: Res<Input<KeyCode>>,
+    keymap: Res<Keymap>,
+    mut graph: Query<&mut Visibility, With<GraphParent>>,
+    parents: Query<&Parent>,
+    framable: Query<(), Or<(With<Layer>, With<Network>, With<GraphParent>)>>,
+    mut frame_writer: EventWriter<FrameEntity>,
+) {
+    if !state.open {
+        if action_events.iter().any(|KeyActionEvent(action)| *action == KeyAction::Search) {
+            characters.clear();
+            state.open = true;
+            state.query.clear();
+            state.selected = 0;
+        }
+        return;
+    }
+    action_events.clear();
+
+    let closed = input.just_pressed(KeyCode::Escape)
+        || keymap.binding(&KeyAction::Search)
+            .filter(|binding| binding.just_pressed(&input))
+            .is_some();
+    if closed {
+        characters.clear();
+        state.open = false;
+        state.query.clear();
+        state.selected = 0;
+        return;
+    }
+
+    let typed = characters.iter()
+        .map(|character| character.char)
+        .filter(|c| !c.is_control())
+        .collect::<String>();
+    if !typed.is_empty() {
+        state.query.push_str(&typed);
+        state.selected = 0;
+    }
+    if input.just_pressed(KeyCode::Back) && state.query.pop().is_some() {
+        state.selected = 0;
+    }
+
+    let num_results = state.results.len().min(SEARCH_MAX_RESULTS);
+    if input.just_pressed(KeyCode::Down) && state.selected + 1 < num_results {
+        state.selected += 1;
+    }
+    if input.just_pressed(KeyCode::Up) && state.selected > 0 {
+        state.selected -= 1;
+    }
+    if input.just_pressed(KeyCode::Return) {
+        match state.results.get(state.selected) {
+            Some(SearchResult { entity, kind: SearchKind::Metric, .. }) => {
+                set_visibility(&mut commands, *entity, Visibility::Inherited);
+                graph.iter_mut()
+                    .for_each(|mut visibility| *visibility = Visibility::Visible);
+                frame_writer.send(FrameEntity(frame_target(*entity, &parents, &framable)));
+            }
+            Some(SearchResult { entity, .. }) => frame_writer.send(FrameEntity(*entity)),
+            None => {}
+        }
+    }
+}
+
+/// Collects the results again when the query changes, or clears them when the search is closed.
+pub(crate) fn update_search_results<T>(
+    mut state: ResMut<SearchState>,
+    metrics_state: Res<MetricsState>,
+    graphing_metrics: Res<GraphingMetricsResource>,
+    layers: Query<(Entity, &Layer)>,
+    metrics: Query<(Entity, &Metric<T>)>,
+    mut searched: Local<Option<String>>,
+)
+    where T: Component
+{
+    let query = Some(state.query.clone()).filter(|_| state.open);
+    if *searched == query {
+        return;
+    }
+    *searched = query;
+    state.results = searched.as_ref()
+        .map(|query| search_results(
+            &search_candidates(&layers, &metrics, &metrics_state, &graphing_metrics),
+            query
+        ))
+        .or(Some(vec![]))
+        .unwrap();
+}
+
+/// While searching, the layers and nodes that match are lit with the search color of the theme
+/// and the others are dimmed, in apply_highlights. The colors are restored when the results change
+/// or the search is closed.
+pub(crate) fn highlight_search_results(
+    state: Res<SearchState>,
+    mut network: Query<(Entity, &mut Highlight), Or<(With<Layer>, With<Node>)>>,
+) {
+    if !state.is_changed() {
+        return;
+    }
+    let matched = state.results.iter()
+        .map(|result| result.entity)
+        .collect::<HashSet<Entity>>();
+    for (entity, mut highlight) in network.iter_mut() {
+        let search_match = state.searching() && matched.contains(&entity);
+        let dimmed = state.searching() && !search_match;
+        if highlight.search_match != search_match || highlight.dimmed != dimmed {
+            highlight.search_match = search_match;
+            highlight.dimmed = dimmed;
+        }
+    }
+}
+
+/// The search panel is rebuilt whenever the query, the results or the selection change.
+pub(crate) fn render_search(
+    mut commands: Commands,
+    state: Res<SearchState>,
+    panels: Query<Entity, With<SearchPanel>>,
+    asset_server: Res<AssetServer>,
+) {
+    if !state.is_changed() {
+        return;
+    }
+    panels.iter().for_each(|panel| commands.entity(panel).despawn_recursive());
+    if !state.open {
+        return;
+    }
+
+    let panel = commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(30.0),
+                top: Val::Percent(15.0),
+                width: Val::Percent(40.0),
+                display: Display::Flex,
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(4.0)),
+                ..default()
+            },
+            focus_policy: FocusPolicy::Block,
+            z_index: ZIndex::Global(150),
+            ..default()
+        },
+        ThemedBackground(ThemeColor::Panel),
+        SearchPanel::default()
+    )).id();
+
+    let query = spawn_text(&mut commands, &asset_server, format!("Search: {}", state.query).as_str(), ThemeColor::PanelText);
+    commands.entity(panel).add_child(query);
+
+    let summary = if !state.searching() {
+        "Type a name, or filters such as layer_type = attention and weight variance > 0.5".to_string()
+    } else if state.results.is_empty() {
+        "No matches".to_string()
+    } else {
+        format!("{} matches", state.results.len())
+    };
+    let summary = spawn_text(&mut commands, &asset_server, summary.as_str(), ThemeColor::PanelMutedText);
+    commands.entity(panel).add_child(summary);
+
+    for (index, result) in state.results.iter().take(SEARCH_MAX_RESULTS).enumerate() {
+        let label = format!("{} ({})", result.label, result.kind.name());
+        let text = spawn_text(&mut commands, &asset_server, label.as_str(), ThemeColor::PanelText);
+        let color = if index == state.selected { ThemeColor::PanelHighlight } else { ThemeColor::PanelEntry };
+        let row = commands.spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    padding: UiRect::all(Val::Px(4.0)),
+                    ..default()
+                },
+                ..default()
+            },
+            ThemedBackground(color)
+        ))
+            .add_child(text)
+            .id();
+        commands.entity(panel).add_child(row);
+    }
+}
+
+fn spawn_text(commands: &mut Commands, asset_server: &Res<AssetServer>, value: &str, color: ThemeColor) -> Entity {
+    commands.spawn((
+        TextBundle {
+            text: Text::from_section(value.to_string(), TextStyle {
+                font_size: 16.0,
+                font: asset_server.load(DEFAULT_FONT),
+                ..default()
+            }),
+            ..default()
+        },
+        ThemedText(color)
+    )).id()
+}
@@ -0,0 +1,272 @@
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+use crate::config::keymap::KeyAction;
+use crate::config::theme::{DEFAULT_FONT, ThemeColor};
+use crate::menu::{MenuData, MenuInputType, MenuOptionType};
+use crate::menu::menu_resource::MenuResource;
+use crate::shortcuts::keymap::{KeyActionEvent, Keymap, PressUiEntity};
+use crate::theme::restyle::{ThemedBackground, ThemedText};
+use crate::ui_components::ui_menu_component::UiIdentifiableComponent;
+use crate::util::fuzzy_score;
+
+pub const PALETTE_MAX_RESULTS: usize = 8;
+
+/// What is done when an entry of the palette is chosen: the menu option with the id is pressed, or
+/// the action is sent as if its key had been pressed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PaletteTarget {
+    MenuOption(f32),
+    Action(KeyAction)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaletteEntry {
+    pub(crate) label: String,
+    pub(crate) target: PaletteTarget
+}
+
+/// The entries are collected when the palette is opened, and filtered by the query as it is typed.
+#[derive(Resource, Default, Debug)]
+pub struct CommandPaletteState {
+    pub(crate) open: bool,
+    pub(crate) query: String,
+    pub(crate) selected: usize,
+    pub(crate) entries: Vec<PaletteEntry>
+}
+
+#[derive(Component, Default, Debug)]
+pub struct CommandPalette;
+
+/// An action chosen in the palette. It is forwarded as a KeyActionEvent by
+/// forward_palette_actions, as the palette also reads the KeyActionEvents to open.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct PaletteAction(pub KeyAction);
+
+/// The menu options, labelled by their path through the menus, such as
+/// `Metrics > Layer Metrics > Variance`, followed by the actions of the keymap.
+pub(crate) fn palette_entries(menu_data: &MenuData, keymap: &Keymap) -> Vec<PaletteEntry> {
+    let mut entries = vec![];
+    menu_data.selectables.iter()
+        .chain(menu_data.sub_menus.iter().flat_map(|sub_menu| sub_menu.selectables.iter()))
+        .for_each(|selectable| collect_menu_entries(selectable, &vec![], &mut entries));
+    KeyAction::all().into_iter()
+        .filter(|action| *action != KeyAction::CommandPalette)
+        .for_each(|action| {
+            let label = keymap.binding(&action)
+                .map(|binding| format!("{} ({})", action.label(), binding.label()))
+                .or(Some(action.label().to_string()))
+                .unwrap();
+            entries.push(PaletteEntry { label, target: PaletteTarget::Action(action) });
+        });
+    entries
+}
+
+fn collect_menu_entries(input: &MenuInputType, path: &Vec<String>, entries: &mut Vec<PaletteEntry>) {
+    let with_name = |name: &String| {
+        let mut path = path.clone();
+        path.push(name.clone());
+        path
+    };
+    let menu_entry = |path: &Vec<String>, id: f32| PaletteEntry {
+        label: path.join(" > "),
+        target: PaletteTarget::MenuOption(id)
+    };
+    match input {
+        MenuInputType::Dropdown { options, metadata, .. }
+        | MenuInputType::CollapsableMenuInputType { options, metadata, .. }
+        | MenuInputType::ScrollableMenu { options, metadata, .. }
+        | MenuInputType::Radial { options, metadata, .. } => {
+            let path = with_name(&metadata.name);
+            entries.push(menu_entry(&path, metadata.id));
+            for option in options.iter() {
+                match &option.data_type {
+                    MenuOptionType::Primitive(_) => {
+                        let mut option_path = path.clone();
+                        option_path.push(option.metadata.name.clone());
+                        entries.push(menu_entry(&option_path, option.metadata.id));
+                    }
+                    MenuOptionType::SubMenu { sub_menu, .. } => collect_menu_entries(sub_menu, &path, entries)
+                }
+            }
+        }
+        MenuInputType::FormInput { metadata, .. } | MenuInputType::Slider { metadata, .. } => {
+            entries.push(menu_entry(&with_name(&metadata.name), metadata.id));
+        }
+    }
+}
+
+/// The entries matching the query, best first. Entries that score the same are sorted by label.
+pub(crate) fn matching_entries<'a>(entries: &'a Vec<PaletteEntry>, query: &str) -> Vec<&'a PaletteEntry> {
+    let mut matching = entries.iter()
+        .flat_map(|entry| fuzzy_score(query, &entry.label).map(|score| (score, entry)))
+        .collect::<Vec<(i32, &PaletteEntry)>>();
+    matching.sort_by(|(first_score, first), (second_score, second)| second_score.cmp(first_score)
+        .then_with(|| first.label.cmp(&second.label))
+    );
+    matching.into_iter()
+        .take(PALETTE_MAX_RESULTS)
+        .map(|(_, entry)| entry)
+        .collect()
+}
+
+/// Opens the palette from its key action. While it is open the characters typed edit the query,
+/// the arrows move the selection, return runs the selected entry and escape closes it.
+pub(crate) fn command_palette_input(
+    mut state: ResMut<CommandPaletteState>,
+    mut action_events: EventReader<KeyActionEvent>,
+    mut characters: EventReader<ReceivedCharacter>,
+    input: Res<Input<KeyCode>>,
+    keymap: Res<Keymap>,
+    menu_resource: Res<MenuResource>,
+    buttons: Query<(Entity, &UiIdentifiableComponent), With<Button>>,
+    mut action_writer: EventWriter<PaletteAction>,
+    mut press_writer: EventWriter<PressUiEntity>,
+) {
+    if !state.open {
+        if action_events.iter().any(|KeyActionEvent(action)| *action == KeyAction::CommandPalette) {
+            characters.clear();
+            state.open = true;
+            state.query.clear();
+            state.selected = 0;
+            state.entries = palette_entries(&menu_resource.menu_data, &keymap);
+        }
+        return;
+    }
+    action_events.clear();
+
+    let closed = input.just_pressed(KeyCode::Escape)
+        || keymap.binding(&KeyAction::CommandPalette)
+            .filter(|binding| binding.just_pressed(&input))
+            .is_some();
+    if closed {
+        characters.clear();
+        close_palette(&mut state);
+        return;
+    }
+
+    let typed = characters.iter()
+        .map(|character| character.char)
+        .filter(|c| !c.is_control())
+        .collect::<String>();
+    if !typed.is_empty() {
+        state.query.push_str(&typed);
+        state.selected = 0;
+    }
+    if input.just_pressed(KeyCode::Back) && state.query.pop().is_some() {
+        state.selected = 0;
+    }
+
+    let matching = matching_entries(&state.entries, &state.query).into_iter()
+        .map(|entry| entry.target.clone())
+        .collect::<Vec<PaletteTarget>>();
+    if input.just_pressed(KeyCode::Down) && state.selected + 1 < matching.len() {
+        state.selected += 1;
+    }
+    if input.just_pressed(KeyCode::Up) && state.selected > 0 {
+        state.selected -= 1;
+    }
+    if input.just_pressed(KeyCode::Return) {
+        match matching.get(state.selected) {
+            Some(PaletteTarget::MenuOption(id)) => {
+                buttons.iter()
+                    .find(|(_, identifiable)| identifiable.0 == *id)
+                    .map(|(entity, _)| press_writer.send(PressUiEntity(entity)));
+            }
+            Some(PaletteTarget::Action(action)) => action_writer.send(PaletteAction(*action)),
+            None => {}
+        }
+        close_palette(&mut state);
+    }
+}
+
+pub(crate) fn forward_palette_actions(
+    mut palette_actions: EventReader<PaletteAction>,
+    mut action_writer: EventWriter<KeyActionEvent>,
+) {
+    palette_actions.iter()
+        .for_each(|PaletteAction(action)| action_writer.send(KeyActionEvent(*action)));
+}
+
+fn close_palette(state: &mut ResMut<CommandPaletteState>) {
+    state.open = false;
+    state.query.clear();
+    state.selected = 0;
+    state.entries.clear();
+}
+
+/// The palette is rebuilt whenever the query or the selection changes.
+pub(crate) fn render_command_palette(
+    mut commands: Commands,
+    state: Res<CommandPaletteState>,
+    palettes: Query<Entity, With<CommandPalette>>,
+    asset_server: Res<AssetServer>,
+) {
+    if !state.is_changed() {
+        return;
+    }
+    palettes.iter().for_each(|palette| commands.entity(palette).despawn_recursive());
+    if !state.open {
+        return;
+    }
+
+    let palette = commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(30.0),
+                top: Val::Percent(15.0),
+                width: Val::Percent(40.0),
+                display: Display::Flex,
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(4.0)),
+                ..default()
+            },
+            focus_policy: FocusPolicy::Block,
+            z_index: ZIndex::Global(150),
+            ..default()
+        },
+        ThemedBackground(ThemeColor::Panel),
+        CommandPalette::default()
+    )).id();
+
+    let query = spawn_text(&mut commands, &asset_server, format!("> {}", state.query).as_str(), ThemeColor::PanelText);
+    commands.entity(palette).add_child(query);
+
+    let matching = matching_entries(&state.entries, &state.query);
+    if matching.is_empty() {
+        let empty = spawn_text(&mut commands, &asset_server, "No matches", ThemeColor::PanelMutedText);
+        commands.entity(palette).add_child(empty);
+    }
+    for (index, entry) in matching.into_iter().enumerate() {
+        let text = spawn_text(&mut commands, &asset_server, entry.label.as_str(), ThemeColor::PanelText);
+        let color = if index == state.selected { ThemeColor::PanelHighlight } else { ThemeColor::PanelEntry };
+        let row = commands.spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    padding: UiRect::all(Val::Px(4.0)),
+                    ..default()
+                },
+                ..default()
+            },
+            ThemedBackground(color)
+        ))
+            .add_child(text)
+            .id();
+        commands.entity(palette).add_child(row);
+    }
+}
+
+fn spawn_text(commands: &mut Commands, asset_server: &Res<AssetServer>, value: &str, color: ThemeColor) -> Entity {
+    commands.spawn((
+        TextBundle {
+            text: Text::from_section(value.to_string(), TextStyle {
+                font_size: 16.0,
+                font: asset_server.load(DEFAULT_FONT),
+                ..default()
+            }),
+            ..default()
+        },
+        ThemedText(color)
+    )).id()
+}
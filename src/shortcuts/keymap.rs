@@ -0,0 +1,210 @@
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use bevy::prelude::*;
+use bevy::render::view::screenshot::ScreenshotManager;
+use bevy::window::PrimaryWindow;
+use crate::camera::{ActiveCamera, FrameEntity, initial_camera_transform, INITIAL_CAMERA_POSITION, reset_pitch_yaw, ZoomableDraggableCamera};
+use crate::camera::orbit_camera::frame_target;
+use crate::config::ConfigurationProperties;
+use crate::config::keymap::{KeyAction, KeyBinding, KeymapConfiguration};
+use crate::data_subscriber::kafka_data_subscriber::StreamState;
+use crate::graph::GraphParent;
+use crate::interactions::SelectionEvent;
+use crate::menu::config_menu_event::interaction_config_event_writer::{GraphMenuResultBuilder, NetworkMenuResultBuilder};
+use crate::network::{Layer, Network};
+use crate::pickable_events::PickableEvent;
+use crate::search::SearchState;
+use crate::shortcuts::command_palette::CommandPaletteState;
+use crate::ui_components::menu_components::menu_types::form_input::FormInputFocus;
+
+pub const SCREENSHOT_DIR: &str = "screenshots";
+
+/// The key bindings parsed from the configuration.
+#[derive(Resource, Default, Debug)]
+pub struct Keymap {
+    pub(crate) bindings: Vec<(KeyAction, KeyBinding)>
+}
+
+impl Keymap {
+    /// Parses the binding of each action. A binding that cannot be parsed is logged and the
+    /// default binding of the action is used.
+    pub(crate) fn from_config(config: &KeymapConfiguration) -> Keymap {
+        let defaults = KeymapConfiguration::default();
+        let bindings = KeyAction::all().into_iter()
+            .flat_map(|action| KeyBinding::parse(config.binding(&action))
+                .or_else(|e| {
+                    error!("Invalid key binding for {:?}, using the default: {}", action, e);
+                    KeyBinding::parse(defaults.binding(&action))
+                })
+                .ok()
+                .map(|binding| (action, binding))
+            )
+            .collect();
+        Keymap { bindings }
+    }
+
+    pub(crate) fn binding(&self, action: &KeyAction) -> Option<&KeyBinding> {
+        self.bindings.iter()
+            .find(|(bound, _)| bound == action)
+            .map(|(_, binding)| binding)
+    }
+}
+
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct KeyActionEvent(pub KeyAction);
+
+/// Presses a menu option as if it had been clicked, so that the change goes through the same
+/// state transitions as a click.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct PressUiEntity(pub Entity);
+
+/// The entities pressed by a PressUiEntity, which are released the next frame.
+#[derive(Resource, Default, Debug)]
+pub struct PressedUiEntities {
+    pub(crate) entities: Vec<Entity>
+}
+
+/// The last 3d entity selected or clicked, which is focused by the camera.
+#[derive(Resource, Default, Debug)]
+pub struct LastSelected {
+    pub(crate) entity: Option<Entity>
+}
+
+pub(crate) fn load_keymap(config: Res<ConfigurationProperties>, mut keymap: ResMut<Keymap>) {
+    *keymap = Keymap::from_config(&config.keymap);
+}
+
+/// The actions are not read while text is being typed into a form input, the command palette or
+/// the search.
+pub(crate) fn read_keymap(
+    input: Res<Input<KeyCode>>,
+    keymap: Res<Keymap>,
+    focus: Res<FormInputFocus>,
+    palette: Res<CommandPaletteState>,
+    search: Res<SearchState>,
+    mut action_writer: EventWriter<KeyActionEvent>,
+) {
+    if focus.focused.is_some() || palette.open || search.open {
+        return;
+    }
+    keymap.bindings.iter()
+        .filter(|(_, binding)| binding.just_pressed(&input))
+        .for_each(|(action, _)| action_writer.send(KeyActionEvent(*action)));
+}
+
+pub(crate) fn press_ui_entities(
+    mut press_events: EventReader<PressUiEntity>,
+    mut pressed: ResMut<PressedUiEntities>,
+    mut interactions: Query<&mut Interaction>,
+) {
+    for entity in pressed.entities.drain(..) {
+        let _ = interactions.get_mut(entity)
+            .map(|mut interaction| *interaction = Interaction::None);
+    }
+    for PressUiEntity(entity) in press_events.iter() {
+        if let Ok(mut interaction) = interactions.get_mut(*entity) {
+            *interaction = Interaction::Pressed;
+            pressed.entities.push(*entity);
+        } else {
+            error!("Could not press {:?}, it is not a ui component.", entity);
+        }
+    }
+}
+
+pub(crate) fn track_last_selected(
+    mut picking_events: EventReader<PickableEvent>,
+    mut last_selected: ResMut<LastSelected>,
+) {
+    for event in picking_events.iter() {
+        match event {
+            PickableEvent::Selection(SelectionEvent::JustSelected(entity))
+            | PickableEvent::Clicked(entity) => last_selected.entity = Some(*entity),
+            PickableEvent::Selection(SelectionEvent::JustDeselected(entity))
+                if last_selected.entity == Some(*entity) => last_selected.entity = None,
+            _ => {}
+        }
+    }
+}
+
+/// Toggling the network or graph presses the menu option for it, pausing sets the stream state,
+/// and the screenshot is saved to the screenshots directory.
+pub(crate) fn apply_key_actions(
+    mut action_events: EventReader<KeyActionEvent>,
+    mut press_writer: EventWriter<PressUiEntity>,
+    network_menu: Res<NetworkMenuResultBuilder>,
+    graph_menu: Res<GraphMenuResultBuilder>,
+    mut stream_state: ResMut<StreamState>,
+    mut screenshots: ResMut<ScreenshotManager>,
+    windows: Query<Entity, With<PrimaryWindow>>,
+) {
+    for KeyActionEvent(action) in action_events.iter() {
+        match action {
+            KeyAction::ToggleNetwork => {
+                network_menu.network_menu_config_option
+                    .map(|entity| press_writer.send(PressUiEntity(entity)));
+            }
+            KeyAction::ToggleGraph => {
+                graph_menu.graph_menu_config_option
+                    .map(|entity| press_writer.send(PressUiEntity(entity)));
+            }
+            KeyAction::PauseStream => {
+                stream_state.paused = !stream_state.paused;
+                info!("Stream paused: {}.", stream_state.paused);
+            }
+            KeyAction::Screenshot => {
+                let _ = windows.get_single()
+                    .map(|window| save_screenshot(window, &mut screenshots));
+            }
+            _ => {}
+        }
+    }
+}
+
+fn save_screenshot(window: Entity, screenshots: &mut ScreenshotManager) {
+    let millis = SystemTime::now().duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis())
+        .or(Ok::<u128, ()>(0))
+        .unwrap();
+    let path = Path::new(SCREENSHOT_DIR).join(format!("screenshot-{}.png", millis));
+    let _ = fs::create_dir_all(SCREENSHOT_DIR)
+        .map_err(|e| e.to_string())
+        .and_then(|_| screenshots.save_screenshot_to_disk(window, &path).map_err(|e| e.to_string()))
+        .or_else(|e| {
+            error!("Could not save screenshot {:?}: {}", path, e);
+            Err(e)
+        });
+}
+
+/// Resetting moves the camera back to its initial position. Focusing frames the layer, network or
+/// graph of the last selected entity.
+pub(crate) fn apply_camera_actions(
+    mut action_events: EventReader<KeyActionEvent>,
+    mut camera: ResMut<ZoomableDraggableCamera>,
+    mut camera_query: Query<&mut Transform, With<ActiveCamera>>,
+    last_selected: Res<LastSelected>,
+    mut frame_writer: EventWriter<FrameEntity>,
+    parents: Query<&Parent>,
+    framable: Query<(), Or<(With<Layer>, With<Network>, With<GraphParent>)>>,
+) {
+    for KeyActionEvent(action) in action_events.iter() {
+        match action {
+            KeyAction::ResetCamera => {
+                let initial = initial_camera_transform();
+                for mut transform in camera_query.iter_mut() {
+                    transform.rotation = initial.rotation;
+                }
+                reset_pitch_yaw(&mut camera, &initial);
+                camera.target_rotation = None;
+                camera.target_translation = Some(initial.translation);
+                camera.target_focus = Some(Vec3::ZERO);
+                camera.zoom = INITIAL_CAMERA_POSITION.length();
+            }
+            KeyAction::FocusSelected => {
+                last_selected.entity
+                    .map(|entity| frame_writer.send(FrameEntity(frame_target(entity, &parents, &framable))));
+            }
+            _ => {}
+        }
+    }
+}
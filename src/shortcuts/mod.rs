@@ -0,0 +1,41 @@
+use bevy::prelude::*;
+use crate::camera::FrameEntity;
+use crate::data_subscriber::kafka_data_subscriber::StreamState;
+use crate::pickable_events::PickableEvent;
+use crate::search::SearchState;
+use crate::shortcuts::command_palette::{command_palette_input, CommandPaletteState, forward_palette_actions, PaletteAction, render_command_palette};
+use crate::shortcuts::keymap::{apply_camera_actions, apply_key_actions, KeyActionEvent, Keymap, LastSelected, load_keymap, press_ui_entities, PressedUiEntities, PressUiEntity, read_keymap, track_last_selected};
+use crate::ui_components::menu_components::menu_types::form_input::FormInputFocus;
+
+/// The keys bound to actions in the configuration, and the systems that apply the actions.
+pub(crate) mod keymap;
+/// A fuzzy search over the menu options and the key actions.
+pub(crate) mod command_palette;
+
+pub struct ShortcutsPlugin;
+
+impl Plugin for ShortcutsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<KeyActionEvent>()
+            .add_event::<PressUiEntity>()
+            .add_event::<PaletteAction>()
+            .add_event::<PickableEvent>()
+            .add_event::<FrameEntity>()
+            .init_resource::<Keymap>()
+            .init_resource::<PressedUiEntities>()
+            .init_resource::<LastSelected>()
+            .init_resource::<StreamState>()
+            .init_resource::<FormInputFocus>()
+            .init_resource::<CommandPaletteState>()
+            .init_resource::<SearchState>()
+            .add_startup_system(load_keymap)
+            .add_system(read_keymap)
+            .add_system(command_palette_input.after(read_keymap))
+            .add_system(render_command_palette.after(command_palette_input))
+            .add_system(forward_palette_actions.after(command_palette_input))
+            .add_system(track_last_selected)
+            .add_system(apply_key_actions.after(forward_palette_actions))
+            .add_system(apply_camera_actions.after(forward_palette_actions).after(track_last_selected))
+            .add_system(press_ui_entities.after(apply_key_actions).after(command_palette_input));
+    }
+}
@@ -34,9 +34,10 @@ use crate::config::ConfigurationProperties;
 use crate::config::kafka::KafkaConfiguration;
 use crate::data_subscriber::data_subscriber::DataSubscriber;
 use crate::data_subscriber::kafka_data_subscriber::{EventReceiver, KafkaClientProvider, KafkaMessageSubscriber, write_events};
-use crate::data_subscriber::metric_event::{MetricsState, NetworkMetricEvent, NodeMetricEvent};
+use crate::data_subscriber::metric_event::{DEFAULT_RUN_ID, MetricsState, NetworkMetricEvent, NodeMetricEvent};
 use crate::data_subscriber::network_metadata_event::NetworkMetadataEvent;
-use crate::graph::draw_graph_points::draw_graph_points;
+use crate::graph::draw_graph_points::{draw_graph_points, SeriesAssets};
+use crate::graph::run_comparison::RunComparison;
 use crate::graph::{GraphConfigurationResource, SeriesStep};
 use crate::graph::graph_data_event_reader::read_metric_events;
 use crate::graph::radial::RadialGraphPoints;
@@ -45,7 +46,7 @@ use crate::lines::line_list::LineMaterial;
 use crate::menu::config_menu_event::interaction_config_event_writer::NetworkMenuResultBuilder;
 use crate::metrics::network_metrics::Metric;
 use crate::network::Node;
-use crate::test::bevy_tests::default_plugins::NoRenderBevyIntegrationTestPlugin;
+use crate::headless::default_plugins::NoRenderBevyIntegrationTestPlugin;
 use crate::test::bevy_tests::graphing_tests::mock_metric_data_test_system::{TestEventGeneratingResource, write_fake_metric_network_events};
 use crate::util::run_blocking;
 
@@ -55,8 +56,8 @@ async fn test_draw_graph_points_1_x_1() {
     let mut app = App::new();
     let app = create_app(1, &mut app);
 
-    let metric = app.world.resource::<MetricsState>()
-        .entities.get("metric").unwrap();
+    let metric = &app.world.resource::<MetricsState>()
+        .get_entity(DEFAULT_RUN_ID, "metric").unwrap();
 
     let node_metric = app.world.get::<Metric<Node>>(metric.0).unwrap();
     let first_column = node_metric.historical.retrieve_historical_1d("0");
@@ -78,8 +79,8 @@ async fn test_draw_graph_points_1_x_20() {
 
     let app = create_app(20, &mut app);
 
-    let metric = app.world.resource::<MetricsState>()
-        .entities.get("metric").unwrap();
+    let metric = &app.world.resource::<MetricsState>()
+        .get_entity(DEFAULT_RUN_ID, "metric").unwrap();
 
     let node_metric = app.world.get::<Metric<Node>>(metric.0).unwrap();
     let first_column = node_metric.historical.retrieve_historical_1d("0");
@@ -108,6 +109,8 @@ fn create_app<'a>(dim: usize, app: &'a mut App) -> &'a mut App {
         .insert_resource(NetworkMenuResultBuilder::default())
         .insert_resource(TestEventGeneratingResource::new(dim))
         .insert_resource(GraphConfigurationResource::<Node>::default())
+        .insert_resource(SeriesAssets::default())
+        .insert_resource(RunComparison::default())
         .add_plugins(NoRenderBevyIntegrationTestPlugin)
         .add_plugin(MaterialPlugin::<LineMaterial>::default())
         .add_startup_system(setup_graph)
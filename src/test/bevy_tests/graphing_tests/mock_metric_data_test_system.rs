@@ -34,6 +34,8 @@ impl TestEventGeneratingResource {
             included: vec![],
             columns: Some(columns),
             metric_indices: None,
+            run_id: None,
+            wall_clock: None,
         };
         metric
     }
@@ -6,7 +6,7 @@ use testcontainers::clients::Cli;
 use crate::config::ConfigurationProperties;
 use crate::data_subscriber::data_subscriber::DataSubscriber;
 use crate::data_subscriber::kafka_data_subscriber::{EventReceiver, KafkaClientProvider, KafkaMessageSubscriber, write_events};
-use crate::data_subscriber::metric_event::{MetricsState, NodeMetricEvent};
+use crate::data_subscriber::metric_event::{DEFAULT_RUN_ID, MetricsState, NodeMetricEvent};
 use crate::data_subscriber::network_metadata_event::NetworkMetadataEvent;
 use crate::graph::graph_data_event_reader::read_metric_events;
 use crate::graph::GraphConfigurationResource;
@@ -59,18 +59,17 @@ async fn test_load_metric_from_kafka() {
 
     assert!(wait_for::wait_for::wait_async::WaitFor::wait_for(Duration::new(3, 0), &|| {
         let metrics = &app.world.resource::<MetricsState>()
-            .entities
-            .get("metric")
+            .get_entity(DEFAULT_RUN_ID, "metric")
             .unwrap()
             .1;
-        *metrics
+        metrics
     }, &|len| {
         println!("{}", len);
         len != 0
     }));
 
-    let metric = app.world.resource::<MetricsState>()
-        .entities.get("metric").unwrap();
+    let metric = &app.world.resource::<MetricsState>()
+        .get_entity(DEFAULT_RUN_ID, "metric").unwrap();
 
     let node_metric = app.world.get::<Metric<Node>>(metric.0).unwrap();
     let first_column = node_metric.historical.retrieve_historical_1d("first");
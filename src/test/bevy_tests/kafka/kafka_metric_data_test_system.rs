@@ -65,6 +65,8 @@ fn send_message(producer: &mut FutureProducer, vec1: Vec<f32>) -> OwnedDeliveryR
             ("fourth".to_string(), 2)
         ])),
         metric_indices: None,
+        run_id: None,
+        wall_clock: None,
     };
 
     let json_str_result = serde_json::to_string(&network_metric_event);
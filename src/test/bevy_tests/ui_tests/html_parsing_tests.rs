@@ -1,17 +1,64 @@
 use bevy::ecs::system::CommandQueue;
 use bevy::prelude::{Commands, World};
-use pulldown_cmark::{html, Parser};
-use crate::render_html::render_html;
+use pulldown_cmark::{HeadingLevel, Parser};
+use crate::render_html::{HtmlParsingError, HtmlTags, markdown_parser, MarkdownBlock, MarkdownStyle, parse_markdown, render_html};
 
 #[test]
 pub(crate) fn test_html() {
     let mut queue = CommandQueue::default();
     let world = World::default();
     let mut commands: Commands = Commands::new(&mut queue, &world);
-    let mut parser = Parser::new(&"###little header");
-    let out = render_html(&mut commands, parser);
+    let parser = Parser::new(&"###little header");
+    let out = render_html(&mut commands, parser, &MarkdownStyle::default());
 
     assert!(out.is_ok());
-    assert!(out.as_ref().unwrap().html_tree.is_some());
+    let tree = out.unwrap();
+    assert_eq!(tree.tag, HtmlTags::Document);
+    assert_eq!(tree.children.len(), 1);
+    assert_eq!(tree.children[0].tag, HtmlTags::Paragraph);
 
-}
\ No newline at end of file
+    let out = render_html(&mut commands, markdown_parser("# Title\n\n- one\n- two\n\n---\n"), &MarkdownStyle::default());
+    let tags = out.unwrap().children.iter()
+        .map(|child| child.tag)
+        .collect::<Vec<HtmlTags>>();
+    assert_eq!(tags, vec![HtmlTags::H1, HtmlTags::List, HtmlTags::Rule]);
+
+    let out = render_html(&mut commands, markdown_parser(""), &MarkdownStyle::default());
+    assert_eq!(out.unwrap_err(), HtmlParsingError::Empty);
+}
+
+#[test]
+fn test_parse_inline_styles() {
+    let blocks = parse_markdown(markdown_parser("Some *emphasis*, **strong**, `code` and a [link](https://example.com).")).unwrap();
+    assert_eq!(blocks.len(), 1);
+    let spans = match &blocks[0] {
+        MarkdownBlock::Paragraph(spans) => spans,
+        block => panic!("Expected a paragraph, found {:?}", block)
+    };
+    let text = spans.iter().map(|span| span.text.as_str()).collect::<String>();
+    assert_eq!(text, "Some emphasis, strong, code and a link.");
+    assert!(spans.iter().any(|span| span.text == "emphasis" && span.style.emphasis));
+    assert!(spans.iter().any(|span| span.text == "strong" && span.style.strong));
+    assert!(spans.iter().any(|span| span.text == "code" && span.style.code));
+    assert!(spans.iter().any(|span| span.text == "link" && span.style.link == Some("https://example.com".to_string())));
+}
+
+#[test]
+fn test_parse_blocks() {
+    let markdown = "## Header\n\n\
+        3. first\n4. second\n\n\
+        > quoted\n\n\
+        ```rust\nlet x = 1;\n```\n\n\
+        | a | b |\n| --- | --- |\n| 1 | 2 |\n\n\
+        ![diagram](images/diagram.png)\n";
+    let blocks = parse_markdown(markdown_parser(markdown)).unwrap();
+    assert_eq!(blocks.len(), 6);
+    assert!(matches!(&blocks[0], MarkdownBlock::Heading(HeadingLevel::H2, spans) if spans[0].text == "Header"));
+    assert!(matches!(&blocks[1], MarkdownBlock::List { start: Some(3), items } if items.len() == 2
+        && matches!(&items[1][0], MarkdownBlock::Paragraph(spans) if spans[0].text == "second")));
+    assert!(matches!(&blocks[2], MarkdownBlock::BlockQuote(quoted) if quoted.len() == 1));
+    assert_eq!(blocks[3], MarkdownBlock::CodeBlock { language: Some("rust".to_string()), code: "let x = 1;".to_string() });
+    assert!(matches!(&blocks[4], MarkdownBlock::Table { head, rows } if head.len() == 2 && rows.len() == 1
+        && rows[0][1][0].text == "2"));
+    assert_eq!(blocks[5], MarkdownBlock::Image { url: "images/diagram.png".to_string(), alt: "diagram".to_string() });
+}
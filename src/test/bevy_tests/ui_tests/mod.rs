@@ -8,7 +8,7 @@ use crate::menu::ui_menu_event::ui_state_change::GlobalState;
 use crate::menu::ui_menu_event::next_action::Matches;
 use crate::menu::ui_menu_event::type_alias::state_transitions::UiStyleEntityComponentStateTransitions;
 use crate::menu::ui_menu_event::ui_menu_event_plugin::{UiEventPlugin};
-use crate::test::bevy_tests::default_plugins::NoRenderBevyIntegrationTestPlugin;
+use crate::headless::default_plugins::NoRenderBevyIntegrationTestPlugin;
 use crate::ui_components::menu_components::BuildMenuResult;
 
 pub(crate) mod html_parsing_tests;
@@ -57,7 +57,7 @@ fn create_app<'a>(app: &'a mut App) -> &'a mut App {
 
 
     let mut app = app
-        .insert_resource(MenuResource::default())
+        .insert_resource(MenuResource::read("resources/menu.toml").unwrap())
         .insert_resource(GlobalState::default())
         .insert_resource(BevyPickingState::default())
         .add_event::<SideEffectWriter>()
@@ -22,6 +22,54 @@ mod config_test;
 mod test_interpolate;
 #[cfg(test)]
 mod test_util;
+#[cfg(test)]
+mod test_forecast;
+#[cfg(test)]
+mod test_derived_metrics;
+#[cfg(test)]
+mod test_alerts;
+#[cfg(test)]
+mod test_menu_definition;
+#[cfg(test)]
+mod test_form_input;
+#[cfg(test)]
+mod test_slider;
+#[cfg(test)]
+mod test_context_menu;
+#[cfg(test)]
+mod test_tooltip;
+#[cfg(test)]
+mod test_shortcuts;
+#[cfg(test)]
+mod test_workspace;
+#[cfg(test)]
+mod test_notes;
+#[cfg(test)]
+mod test_theme;
+#[cfg(test)]
+mod test_palette;
+#[cfg(test)]
+mod test_camera;
+#[cfg(test)]
+mod test_bookmarks;
+#[cfg(test)]
+mod test_viewports;
+#[cfg(test)]
+mod test_picking_adapter;
+#[cfg(test)]
+mod test_selection_set;
+#[cfg(test)]
+mod test_search;
+#[cfg(test)]
+mod test_headless;
+#[cfg(test)]
+mod test_export;
+#[cfg(test)]
+mod test_gltf;
+#[cfg(test)]
+mod test_cli;
+#[cfg(test)]
+mod test_draw_graph_points;
 
 
 pub(crate) mod test_plugin;
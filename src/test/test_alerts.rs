@@ -0,0 +1,103 @@
+use bevy::prelude::*;
+use ndarray::array;
+use crate::config::alerts::AlertKind;
+use crate::config::theme::Theme;
+use crate::metrics::alerts::{add_alert_entries, Alert, AlertAcknowledged, AlertEvent, Alerts, AlertsList, evaluate_rule, highlight_alerts, MAX_ALERTS_DISPLAYED, remove_acknowledged_alerts};
+use crate::theme::highlight::{apply_highlights, Highlight};
+
+#[test]
+fn test_non_finite_alert() {
+    let kind = AlertKind::NonFinite;
+    assert!(evaluate_rule(&kind, &array![1.0, 0.9, f32::NAN]).is_some());
+    assert!(evaluate_rule(&kind, &array![1.0, 0.9, f32::INFINITY]).is_some());
+    assert!(evaluate_rule(&kind, &array![1.0, 0.9, 0.8]).is_none());
+}
+
+#[test]
+fn test_spike_alert() {
+    let kind = AlertKind::Spike { z_score: 4.0, min_history: 5 };
+    let steady = array![1.0, 0.95, 0.91, 0.86, 0.82, 0.79, 0.75];
+    assert!(evaluate_rule(&kind, &steady).is_none());
+    let spike = array![1.0, 0.95, 0.91, 0.86, 0.82, 0.79, 3.0];
+    assert!(evaluate_rule(&kind, &spike).is_some());
+    let short = array![1.0, 0.95, 3.0];
+    assert!(evaluate_rule(&kind, &short).is_none());
+}
+
+#[test]
+fn test_threshold_growth_plateau_alerts() {
+    let threshold = AlertKind::Threshold { above: Some(2.0), below: Some(0.1) };
+    assert!(evaluate_rule(&threshold, &array![1.0, 2.5]).is_some());
+    assert!(evaluate_rule(&threshold, &array![1.0, 0.05]).is_some());
+    assert!(evaluate_rule(&threshold, &array![1.0, 1.5]).is_none());
+
+    let growth = AlertKind::Growth { factor: 10.0, window: 2 };
+    assert!(evaluate_rule(&growth, &array![0.1, 0.5, 2.0]).is_some());
+    assert!(evaluate_rule(&growth, &array![0.1, 0.2, 0.3]).is_none());
+
+    let plateau = AlertKind::Plateau { window: 3, tolerance: 1e-3 };
+    assert!(evaluate_rule(&plateau, &array![1.0, 0.5, 0.5, 0.5]).is_some());
+    assert!(evaluate_rule(&plateau, &array![1.0, 0.8, 0.6, 0.4]).is_none());
+}
+
+fn alert(id: u64, entity: Entity) -> Alert {
+    Alert {
+        id,
+        rule: "spike".to_string(),
+        run_id: "run".to_string(),
+        metric_name: "loss".to_string(),
+        column: "value".to_string(),
+        index: 0,
+        timestep: id,
+        value: 1.0,
+        message: format!("alert {}", id),
+        entities: vec![entity],
+    }
+}
+
+#[test]
+fn test_dropped_alert_entries_are_acknowledged() {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugins(AssetPlugin::default())
+        .add_asset::<StandardMaterial>()
+        .add_asset::<Font>()
+        .add_event::<AlertEvent>()
+        .add_event::<AlertAcknowledged>()
+        .init_resource::<Alerts>()
+        .init_resource::<Theme>()
+        .add_system(add_alert_entries)
+        .add_system(remove_acknowledged_alerts.after(add_alert_entries))
+        .add_system(highlight_alerts.after(remove_acknowledged_alerts))
+        .add_system(apply_highlights.after(highlight_alerts));
+    let alerts_list = app.world.spawn(AlertsList::default()).id();
+    let emissive = Color::rgb(0.1, 0.2, 0.3);
+    let material = app.world.resource_mut::<Assets<StandardMaterial>>()
+        .add(StandardMaterial { emissive, ..default() });
+    let highlight = Highlight::new(app.world.resource::<Assets<StandardMaterial>>().get(&material).unwrap());
+    let node = app.world.spawn((material.clone(), highlight)).id();
+
+    let num_alerts = MAX_ALERTS_DISPLAYED as u64 + 2;
+    for id in 0..num_alerts {
+        app.world.resource_mut::<Alerts>().alerts.push(alert(id, node));
+        app.world.resource_mut::<Events<AlertEvent>>().send(AlertEvent(alert(id, node)));
+    }
+    app.update();
+
+    let remaining = app.world.resource::<Alerts>().alerts.iter()
+        .map(|alert| alert.id)
+        .collect::<Vec<u64>>();
+    assert_eq!(remaining, (2..num_alerts).collect::<Vec<u64>>());
+    assert_eq!(app.world.get::<Children>(alerts_list).unwrap().len(), MAX_ALERTS_DISPLAYED);
+    assert!(app.world.get::<Highlight>(node).unwrap().alert);
+    assert_ne!(app.world.resource::<Assets<StandardMaterial>>().get(&material).unwrap().emissive, emissive);
+
+    for id in 2..num_alerts {
+        app.world.resource_mut::<Events<AlertAcknowledged>>().send(AlertAcknowledged(id));
+    }
+    app.update();
+
+    assert!(app.world.resource::<Alerts>().alerts.is_empty());
+    assert!(!app.world.get::<Highlight>(node).unwrap().alert);
+    assert_eq!(app.world.resource::<Assets<StandardMaterial>>().get(&material).unwrap().emissive, emissive);
+}
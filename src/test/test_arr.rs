@@ -1,9 +1,11 @@
 use std::collections::HashMap;
 use bevy::ecs::system::CommandQueue;
-use bevy::prelude::{Commands, World};
-use ndarray::{arr1, arr2, arr3, array, Array2, Array3, Ix2, s};
-use crate::data_subscriber::metric_event::MetricsState;
-use crate::metrics::network_metrics::HistoricalData;
+use bevy::prelude::{Color, Commands, Entity, Vec3, World};
+use ndarray::{arr1, arr2, arr3, array, Array2, Array3, ArrayD, Ix2, s};
+use crate::data_subscriber::metric_event::{DEFAULT_RUN_ID, MetricsState};
+use crate::graph::draw_graph_points::SeriesPoint;
+use crate::graph::run_comparison::{ALIGNED_TIME_SCALE, RunAlignment, RunComparison};
+use crate::metrics::network_metrics::{HistoricalData, Metric};
 use crate::ndarray::{get_arr, get_arr_from_vec, get_metric_message};
 use crate::test::config_test::get_metric_message_test;
 use crate::test::TestComponent;
@@ -28,9 +30,9 @@ fn test_mutable() {
     let mut commands =
          Commands::new(&mut queue, &world);
     let e = commands.spawn(TestComponent::default()).id();
-    metrics.entities.insert("test".to_string(), (e, 1));
-    metrics.increment_entity("test");
-    assert_eq!(metrics.entities.get("test").unwrap().1, 2);
+    metrics.insert_entity(DEFAULT_RUN_ID, "test", e, 1);
+    metrics.increment_entity(DEFAULT_RUN_ID, "test");
+    assert_eq!(metrics.get_entity(DEFAULT_RUN_ID, "test").unwrap().1, 2);
 }
 
 #[test]
@@ -120,3 +122,52 @@ fn test_historical_data1d() {
     assert_eq!(out.get(0).unwrap().as_slice().unwrap(), out_assert.as_slice().unwrap());
 
 }
+
+#[test]
+fn test_aligned_time() {
+    let mut historical = HistoricalData::new(vec![1], HashMap::from([("loss".to_string(), 0)]));
+    historical.add_wall_clock(1, 5_000);
+    historical.extend(ArrayD::zeros(vec![1]), 1);
+    historical.add_wall_clock(2, 7_500);
+    historical.extend(ArrayD::zeros(vec![1]), 2);
+    assert_eq!(historical.aligned_time(2, &RunAlignment::Step), Some(1.0));
+    assert_eq!(historical.aligned_time(1, &RunAlignment::Step), Some(0.0));
+    assert_eq!(historical.aligned_time(2, &RunAlignment::WallClock), Some(2.5));
+    assert_eq!(historical.aligned_time(3, &RunAlignment::Step), None);
+}
+
+#[test]
+fn test_align_points() {
+    let mut metric = Metric::<TestComponent>::default();
+    metric.historical = HistoricalData::new(vec![1], HashMap::from([("loss".to_string(), 0)]));
+    [(3, 1_000), (5, 3_000)].iter()
+        .for_each(|(timestep, wall_clock)| {
+            metric.historical.add_wall_clock(*timestep, *wall_clock);
+            metric.historical.extend(ArrayD::zeros(vec![1]), *timestep);
+        });
+    let (compared, other) = (Entity::from_raw(1), Entity::from_raw(2));
+    let points = || vec![SeriesPoint {
+        graph_dim_entity: compared,
+        color: Color::RED,
+        index: 0,
+        start: Vec3::new(0.5, 0.0, 0.0),
+        end: Vec3::new(0.25, 0.0, 0.0),
+        step: 5,
+    }];
+    let mut run_comparison = RunComparison::default();
+    run_comparison.horizons.insert(compared, 2.0);
+
+    let mut aligned = points();
+    run_comparison.align_points(compared, &metric, &mut aligned);
+    assert_eq!(aligned[0].start.x, 1.0);
+    assert_eq!(aligned[0].end.x, 1.0 - 2.0 * ALIGNED_TIME_SCALE);
+
+    run_comparison.alignment = RunAlignment::WallClock;
+    let mut aligned = points();
+    run_comparison.align_points(compared, &metric, &mut aligned);
+    assert_eq!(aligned[0].end.x, 1.0 - 2.0 * ALIGNED_TIME_SCALE);
+
+    let mut not_compared = points();
+    run_comparison.align_points(other, &metric, &mut not_compared);
+    assert_eq!(not_compared[0].start.x, 0.5);
+}
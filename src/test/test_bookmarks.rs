@@ -0,0 +1,67 @@
+use std::env;
+use std::fs;
+use bevy::prelude::{Quat, Vec3};
+use crate::camera::bookmarks::{BOOKMARK_FILE, CameraBookmark, CameraBookmarks, ease_in_out, eased_pose};
+use crate::workspace::CameraPose;
+
+fn bookmark(name: &str, key: Option<&str>) -> CameraBookmark {
+    CameraBookmark {
+        name: name.to_string(),
+        pose: CameraPose { translation: [50.0, 50.0, 100.0], rotation: [0.0, 0.0, 0.0, 1.0], pitch: -20.0, yaw: 30.0 },
+        distance: 120.0,
+        key: key.map(|key| key.to_string())
+    }
+}
+
+#[test]
+fn test_ease_in_out() {
+    assert_eq!(ease_in_out(0.0), 0.0);
+    assert_eq!(ease_in_out(0.5), 0.5);
+    assert_eq!(ease_in_out(1.0), 1.0);
+    assert_eq!(ease_in_out(2.0), 1.0);
+    assert!(ease_in_out(0.1) < 0.1);
+    assert!(ease_in_out(0.9) > 0.9);
+    let from = (Vec3::ZERO, Quat::IDENTITY);
+    let to = (Vec3::new(10.0, 0.0, 0.0), Quat::from_rotation_y(1.0));
+    let (translation, rotation) = eased_pose(from, to, 0.0);
+    assert_eq!(translation, from.0);
+    assert!(rotation.angle_between(from.1) < 0.001);
+    let (translation, rotation) = eased_pose(from, to, 1.0);
+    assert!(translation.distance(to.0) < 0.001);
+    assert!(rotation.angle_between(to.1) < 0.001);
+    assert!((eased_pose(from, to, 0.5).0.x - 5.0).abs() < 0.001);
+}
+
+#[test]
+fn test_bookmark_names_and_keys() {
+    let mut bookmarks = CameraBookmarks { path: String::new(), bookmarks: vec![], current: None };
+    assert_eq!(bookmarks.next_index(), None);
+    assert_eq!(bookmarks.next_name(), "view-1");
+    assert_eq!(bookmarks.next_key(), Some("1".to_string()));
+    assert_eq!(bookmarks.save(bookmark("view-1", Some("1"))), 0);
+    assert_eq!(bookmarks.save(bookmark("loss", Some("3"))), 1);
+    assert_eq!(bookmarks.next_name(), "view-2");
+    assert_eq!(bookmarks.next_key(), Some("2".to_string()));
+    assert_eq!(bookmarks.save(bookmark("view-1", None)), 0);
+    assert_eq!(bookmarks.bookmarks.len(), 2);
+    assert_eq!(bookmarks.next_key(), Some("1".to_string()));
+    assert_eq!(bookmarks.next_index(), Some(0));
+    bookmarks.current = Some(1);
+    assert_eq!(bookmarks.next_index(), Some(0));
+}
+
+#[test]
+fn test_write_read_bookmarks() {
+    let dir = env::temp_dir().join("nn-fe-test-bookmarks");
+    let _ = fs::remove_dir_all(&dir);
+    let path = dir.join(BOOKMARK_FILE).to_str().unwrap().to_string();
+    let bookmarks = CameraBookmarks {
+        path: path.clone(),
+        bookmarks: vec![bookmark("network", Some("1")), bookmark("loss", None)],
+        current: None
+    };
+    assert_eq!(bookmarks.write().unwrap(), path);
+    assert_eq!(CameraBookmarks::read(&path).unwrap(), bookmarks.bookmarks);
+    assert!(CameraBookmarks::read(dir.join("missing.json").to_str().unwrap()).is_err());
+    let _ = fs::remove_dir_all(&dir);
+}
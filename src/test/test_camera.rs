@@ -0,0 +1,48 @@
+use std::f32::consts::FRAC_PI_2;
+use bevy::prelude::{Quat, Vec3};
+use crate::camera::{initial_camera_transform, INITIAL_CAMERA_POSITION, sync_orbit, ZoomableDraggableCamera};
+use crate::camera::orbit_camera::{enclosing_sphere, fit_distance, FRAME_MARGIN, MIN_FRAME_RADIUS, zoom_distance};
+
+fn camera() -> ZoomableDraggableCamera {
+    ZoomableDraggableCamera {
+        min_distance: 5.0,
+        max_distance: 500.0,
+        current_distance: INITIAL_CAMERA_POSITION.length(),
+        zoom_sensitivity: 0.1,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_zoom_distance() {
+    let cam = camera();
+    assert!((zoom_distance(100.0, 1.0, &cam) - 90.0).abs() < 0.001);
+    assert!((zoom_distance(90.0, -1.0, &cam) - 100.0).abs() < 0.001);
+    assert_eq!(zoom_distance(10.0, 100.0, &cam), 5.0);
+    assert_eq!(zoom_distance(400.0, -100.0, &cam), 500.0);
+}
+
+#[test]
+fn test_sync_orbit() {
+    let mut cam = camera();
+    let initial = initial_camera_transform();
+    sync_orbit(&mut cam, &initial);
+    assert!(cam.focus.length() < 0.001);
+    assert_eq!(cam.zoom, cam.current_distance);
+    let rotation = Quat::from_axis_angle(Vec3::Y, cam.yaw.to_radians())
+        * Quat::from_axis_angle(Vec3::X, cam.pitch.to_radians());
+    let translation = cam.focus + rotation * Vec3::Z * cam.current_distance;
+    assert!(translation.distance(INITIAL_CAMERA_POSITION) < 0.01);
+}
+
+#[test]
+fn test_frame_bounds() {
+    assert_eq!(enclosing_sphere(&vec![]), None);
+    let (center, radius) = enclosing_sphere(&vec![(Vec3::new(-10.0, 0.0, 0.0), 0.0), (Vec3::new(10.0, 0.0, 0.0), 0.0)]).unwrap();
+    assert_eq!(center, Vec3::ZERO);
+    assert_eq!(radius, 10.0);
+    let (center, radius) = enclosing_sphere(&vec![(Vec3::new(5.0, 5.0, 5.0), 0.0)]).unwrap();
+    assert_eq!(center, Vec3::splat(5.0));
+    assert_eq!(radius, MIN_FRAME_RADIUS);
+    assert!((fit_distance(10.0, FRAC_PI_2) - 10.0 * FRAME_MARGIN * 2.0_f32.sqrt()).abs() < 0.001);
+}
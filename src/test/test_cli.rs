@@ -0,0 +1,119 @@
+use std::fs;
+use bevy::log::Level;
+use crate::cli::{CliError, CliOptions};
+use crate::config::ConfigurationProperties;
+use crate::config::data_source::DataSource;
+use crate::data_subscriber::kafka_data_subscriber::topic_matches;
+
+fn parse(args: &[&str]) -> Result<CliOptions, CliError> {
+    CliOptions::parse(args.iter().map(|arg| arg.to_string()))
+}
+
+fn read_config() -> ConfigurationProperties {
+    ConfigurationProperties::read_config_file("resources/config.toml").unwrap()
+}
+
+fn recording(name: &str) -> String {
+    let path = std::env::temp_dir().join(name);
+    fs::write(&path, "").unwrap();
+    path.display().to_string()
+}
+
+#[test]
+fn test_parse() {
+    let options = parse(&[
+        "--source", "kafka", "--brokers=a:9092, b:9092", "--topics", "node_metric_gpt",
+        "--workspace", "training", "--log-level", "debug", "--no-demo"
+    ]).unwrap();
+    assert_eq!(options.source, Some(DataSource::Kafka));
+    assert_eq!(options.brokers, vec!["a:9092".to_string(), "b:9092".to_string()]);
+    assert_eq!(options.topics, vec!["node_metric_gpt".to_string()]);
+    assert_eq!(options.workspace, Some("training".to_string()));
+    assert_eq!(options.log_level, Some(Level::DEBUG));
+    assert!(options.no_demo && !options.demo && !options.headless_mode().any());
+    assert!(parse(&["-h"]).unwrap().help);
+    let mode = parse(&["--export", "--gltf"]).unwrap().headless_mode();
+    assert!(!mode.snapshots && mode.export && mode.scene);
+}
+
+#[test]
+fn test_parse_errors() {
+    assert_eq!(parse(&["--verbose"]), Err(CliError::UnknownArgument("--verbose".to_string())));
+    assert_eq!(parse(&["--headless=yes"]), Err(CliError::UnknownArgument("--headless=yes".to_string())));
+    assert_eq!(parse(&["--recording"]), Err(CliError::MissingValue("--recording".to_string())));
+    assert!(matches!(parse(&["--source", "s3"]), Err(CliError::InvalidValue { value, .. }) if value == "s3"));
+    assert!(matches!(parse(&["--log-level=loud"]), Err(CliError::InvalidValue { flag, .. }) if flag == "--log-level"));
+}
+
+#[test]
+fn test_validate() {
+    assert!(parse(&["--source", "replay", "--recording", "run.jsonl", "--demo"]).unwrap().validate().is_ok());
+    let conflicts = [
+        vec!["--demo", "--no-demo"],
+        vec!["--source", "kafka", "--recording", "run.jsonl"],
+        vec!["--source", "file", "--brokers", "a:9092"],
+        vec!["--source", "replay", "--topics", "node_metric_gpt"],
+        vec!["--headless", "--workspace", "training"],
+        vec!["--export", "--no-demo"]
+    ];
+    for args in conflicts.iter() {
+        assert!(matches!(parse(args).unwrap().validate(), Err(CliError::Conflict(_))), "{:?}", args);
+    }
+}
+
+#[test]
+fn test_apply() {
+    let recording = recording("nn-fe-cli-apply.jsonl");
+    let options = parse(&["--recording", &recording, "--no-demo"]).unwrap();
+    let mut config = read_config();
+    options.apply(&mut config);
+    assert_eq!(config.data_source.source, Some(DataSource::Replay));
+    assert_eq!(config.data_source.recording, Some(recording));
+    assert!(!config.data_source.demo_network);
+    assert!(options.check_data_source(&config).is_ok());
+
+    let options = parse(&["--source", "kafka", "--brokers", "a:9092", "--topics", "t1,t2"]).unwrap();
+    let mut config = read_config();
+    options.apply(&mut config);
+    assert_eq!(config.kafka.hosts, vec!["a:9092".to_string()]);
+    assert_eq!(config.kafka.topics, vec!["t1".to_string(), "t2".to_string()]);
+    assert!(config.data_source.live());
+}
+
+#[test]
+fn test_check_data_source() {
+    let check_source = |source: Option<DataSource>, args: &[&str]| {
+        let options = parse(args).unwrap();
+        let mut config = read_config();
+        config.data_source.source = source;
+        config.data_source.recording = None;
+        options.apply(&mut config);
+        options.check_data_source(&config)
+    };
+    let check = |args: &[&str]| check_source(None, args);
+    assert!(check(&[]).is_ok());
+    assert!(matches!(check(&["--source", "file"]), Err(CliError::Config(_))));
+    assert!(matches!(check(&["--recording", "does-not-exist.jsonl"]), Err(CliError::Config(_))));
+    assert!(matches!(check(&["--headless"]), Err(CliError::Config(_))));
+    assert!(matches!(check(&["--brokers", "a:9092"]), Err(CliError::Conflict(_))));
+    assert!(check(&["--headless", "--recording", &recording("nn-fe-cli-check.jsonl")]).is_ok());
+    assert!(check_source(Some(DataSource::Kafka), &["--brokers", "a:9092"]).is_ok());
+    assert!(matches!(
+        check_source(Some(DataSource::Kafka), &["--recording", &recording("nn-fe-cli-check-kafka.jsonl")]),
+        Err(CliError::Conflict(_))
+    ));
+}
+
+#[test]
+fn test_configuration_file() {
+    let options = parse(&["--config", "does-not-exist.toml"]).unwrap();
+    assert!(matches!(options.configuration(), Err(CliError::Config(message)) if message.contains("does-not-exist.toml")));
+}
+
+#[test]
+fn test_topic_matches() {
+    assert!(topic_matches("node_metric_*", "node_metric_gpt"));
+    assert!(topic_matches("node_metric_gpt", "node_metric_gpt"));
+    assert!(!topic_matches("node_metric_gpt", "node_metric_bert"));
+    assert!(!topic_matches("layer_metric_*", "node_metric_gpt"));
+}
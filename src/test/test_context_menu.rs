@@ -0,0 +1,48 @@
+use std::collections::HashSet;
+use bevy::prelude::Entity;
+use crate::config::palette::Palette;
+use crate::data_subscriber::metric_event::MetricsState;
+use crate::graph::GraphingMetricsResource;
+use crate::menu::graphing_menu::context_menu::{metric_entries, next_series_color, SERIES_COLORS};
+
+fn graphing_metrics() -> (GraphingMetricsResource, MetricsState) {
+    let loss = Entity::from_raw(1);
+    let variance = Entity::from_raw(2);
+    let concavity = Entity::from_raw(3);
+    let mut graphing_metrics = GraphingMetricsResource::default();
+    graphing_metrics.graphing_indices.insert("encoder".to_string(), HashSet::from([loss, variance]));
+    graphing_metrics.graphing_indices.insert("encoder.1".to_string(), HashSet::from([concavity]));
+    graphing_metrics.graphing_indices.insert("decoder".to_string(), HashSet::from([concavity]));
+    let mut metrics_state = MetricsState::default();
+    metrics_state.insert_entity("default", "loss", loss, 0);
+    metrics_state.insert_entity("default", "variance", variance, 0);
+    metrics_state.insert_entity("default", "concavity", concavity, 0);
+    (graphing_metrics, metrics_state)
+}
+
+#[test]
+fn test_metric_entries() {
+    let (graphing_metrics, metrics_state) = graphing_metrics();
+    let entries = metric_entries(&vec!["encoder".to_string()], &graphing_metrics, &metrics_state);
+    assert_eq!(entries, vec![
+        ("loss".to_string(), Entity::from_raw(1)),
+        ("variance".to_string(), Entity::from_raw(2))
+    ]);
+    assert!(metric_entries(&vec!["attention".to_string()], &graphing_metrics, &metrics_state).is_empty());
+}
+
+#[test]
+fn test_related_metrics() {
+    let (graphing_metrics, _) = graphing_metrics();
+    assert_eq!(graphing_metrics.related_metrics(Entity::from_raw(1)), HashSet::from([Entity::from_raw(2)]));
+    assert!(graphing_metrics.related_metrics(Entity::from_raw(3)).is_empty());
+}
+
+#[test]
+fn test_next_series_color() {
+    assert_eq!(next_series_color(SERIES_COLORS[0], Palette::OkabeIto), SERIES_COLORS[1]);
+    assert_eq!(next_series_color(SERIES_COLORS[SERIES_COLORS.len() - 1], Palette::OkabeIto), SERIES_COLORS[0]);
+    assert_eq!(next_series_color(bevy::prelude::Color::PINK, Palette::OkabeIto), SERIES_COLORS[0]);
+    let viridis = Palette::Viridis.swatches();
+    assert_eq!(next_series_color(viridis[0], Palette::Viridis), viridis[1]);
+}
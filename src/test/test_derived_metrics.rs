@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+use ndarray::{arr1, arr2, array, Array1};
+use crate::metrics::derived_metrics::{advance, DerivedExpression, DerivedMetricError, DerivedSeries, ema, moving_average};
+use crate::metrics::network_metrics::HistoricalData;
+
+fn history(values: &[(u64, f32)]) -> HistoricalData {
+    let mut historical = HistoricalData::new(vec![1], HashMap::from([("value".to_string(), 0)]));
+    values.iter()
+        .for_each(|(timestep, value)| historical.extend(arr1(&[*value]).into_dyn(), *timestep));
+    historical
+}
+
+fn evaluate(expression: &str, histories: &HashMap<String, HistoricalData>) -> Result<Vec<DerivedSeries>, DerivedMetricError> {
+    DerivedExpression::parse(expression).unwrap()
+        .evaluate(&|name: &str| histories.get(name))
+}
+
+fn series(timesteps: Vec<u64>, values: Vec<f32>) -> DerivedSeries {
+    DerivedSeries { timesteps, values: Array1::from_vec(values) }
+}
+
+#[test]
+fn test_parse_derived_expression() {
+    let parsed = DerivedExpression::parse("ema(ratio(grad-norm:value, weight-norm:value), 0.5)");
+    assert_eq!(parsed, Ok(DerivedExpression::Ema(
+        Box::new(DerivedExpression::Ratio(
+            Box::new(DerivedExpression::Column { metric: "grad-norm".to_string(), column: "value".to_string() }),
+            Box::new(DerivedExpression::Column { metric: "weight-norm".to_string(), column: "value".to_string() }),
+        )),
+        0.5
+    )));
+    let dependencies = parsed.unwrap().dependencies().len();
+    assert_eq!(dependencies, 2);
+}
+
+#[test]
+fn test_dependencies_in_expression_order() {
+    let parsed = DerivedExpression::parse("ratio(b:value, ratio(a:value, b:value))").unwrap();
+    assert_eq!(parsed.dependencies(), vec!["b", "a"]);
+}
+
+#[test]
+fn test_advance_emits_each_timestep_once() {
+    let mut emitted = HashMap::new();
+    assert!(advance(&mut emitted, "run", "ratio", 1));
+    assert!(!advance(&mut emitted, "run", "ratio", 1));
+    assert!(advance(&mut emitted, "other", "ratio", 1));
+    assert!(advance(&mut emitted, "run", "ratio", 3));
+    assert!(!advance(&mut emitted, "run", "ratio", 2));
+}
+
+#[test]
+fn test_parse_derived_expression_errors() {
+    assert!(matches!(DerivedExpression::parse("smooth(loss:value)"), Err(DerivedMetricError::Parse { .. })));
+    assert!(matches!(DerivedExpression::parse("ema(loss:value, 2.0)"), Err(DerivedMetricError::Parse { .. })));
+    assert!(matches!(DerivedExpression::parse("derivative(loss:value"), Err(DerivedMetricError::Parse { .. })));
+}
+
+#[test]
+fn test_moving_average_ema() {
+    let series = array![1.0, 2.0, 3.0, 4.0];
+    assert_eq!(moving_average(&series, 2), array![1.0, 1.5, 2.5, 3.5]);
+    assert_eq!(ema(&series, 0.5), array![1.0, 1.5, 2.25, 3.125]);
+}
+
+#[test]
+fn test_parse_rejects_wildcard() {
+    assert!(matches!(DerivedExpression::parse("ema(loss*:value, 0.5)"), Err(DerivedMetricError::Parse { .. })));
+}
+
+#[test]
+fn test_evaluate() {
+    let histories = HashMap::from([
+        ("loss".to_string(), history(&[(1, 4.0), (2, 2.0), (3, 1.0)])),
+    ]);
+    assert_eq!(evaluate("loss:value", &histories), Ok(vec![series(vec![1, 2, 3], vec![4.0, 2.0, 1.0])]));
+    assert_eq!(evaluate("derivative(loss:value)", &histories), Ok(vec![series(vec![2, 3], vec![-2.0, -1.0])]));
+    assert_eq!(evaluate("moving_average(loss:value, 2)", &histories), Ok(vec![series(vec![1, 2, 3], vec![4.0, 3.0, 1.5])]));
+    assert_eq!(evaluate("missing:value", &histories), Err(DerivedMetricError::MissingMetric("missing".to_string())));
+    assert!(matches!(evaluate("loss:variance", &histories), Err(DerivedMetricError::MissingColumn { .. })));
+}
+
+#[test]
+fn test_evaluate_nested_derivative() {
+    let histories = HashMap::from([
+        ("short".to_string(), history(&[(1, 4.0), (2, 2.0)])),
+        ("empty".to_string(), history(&[])),
+    ]);
+    assert_eq!(evaluate("derivative(derivative(short:value))", &histories), Ok(vec![series(vec![], vec![])]));
+    assert_eq!(evaluate("derivative(derivative(derivative(short:value)))", &histories), Ok(vec![series(vec![], vec![])]));
+    assert_eq!(evaluate("derivative(empty:value)", &histories), Ok(vec![]));
+}
+
+#[test]
+fn test_evaluate_ratio_aligns_timesteps() {
+    let histories = HashMap::from([
+        ("grad".to_string(), history(&[(1, 2.0), (2, 4.0), (3, 6.0), (4, 8.0)])),
+        ("weight".to_string(), history(&[(1, 1.0), (3, 2.0), (4, 4.0)])),
+    ]);
+    assert_eq!(evaluate("ratio(grad:value, weight:value)", &histories), Ok(vec![series(vec![1, 3, 4], vec![2.0, 3.0, 2.0])]));
+}
+
+#[test]
+fn test_evaluate_norm_ratio() {
+    let mut grad = HistoricalData::new(vec![1, 2], HashMap::from([("value".to_string(), 0)]));
+    grad.extend(arr2(&[[3.0, 4.0]]).into_dyn(), 1);
+    grad.extend(arr2(&[[6.0, 8.0]]).into_dyn(), 2);
+    let histories = HashMap::from([
+        ("grad".to_string(), grad),
+        ("weight".to_string(), history(&[(2, 2.0)])),
+    ]);
+    assert_eq!(evaluate("norm_ratio(grad:value, weight:value)", &histories), Ok(vec![series(vec![2], vec![5.0])]));
+}
@@ -0,0 +1,66 @@
+use bevy::prelude::*;
+use bevy_polyline::prelude::{Polyline, PolylineMaterial};
+use crate::graph::GraphDimComponent;
+use crate::graph::draw_graph_points::{append_series_point, SeriesAssets, SeriesPoint, SeriesPolylines};
+
+/// The points appended to the series each update.
+#[derive(Resource, Default)]
+struct PendingPoints(Vec<SeriesPoint>);
+
+fn append_pending_points(
+    mut commands: Commands,
+    mut pending: ResMut<PendingPoints>,
+    mut metric_dims: Query<&mut SeriesPolylines, With<GraphDimComponent>>,
+    mut series_assets: ResMut<SeriesAssets>,
+    mut polylines: ResMut<Assets<Polyline>>,
+    mut materials: ResMut<Assets<PolylineMaterial>>,
+) {
+    for point in pending.0.drain(..) {
+        append_series_point(&mut commands, &mut metric_dims, &mut series_assets, &mut polylines, &mut materials, point);
+    }
+}
+
+fn point(graph_dim_entity: Entity, index: usize, x: f32) -> SeriesPoint {
+    SeriesPoint {
+        graph_dim_entity,
+        color: Color::RED,
+        index,
+        start: Vec3::new(x, 0.0, 0.0),
+        end: Vec3::new(x + 1.0, 0.0, 0.0),
+        step: x as u64 + 1,
+    }
+}
+
+#[test]
+fn test_append_series_point() {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugins(AssetPlugin::default())
+        .add_asset::<Polyline>()
+        .add_asset::<PolylineMaterial>()
+        .init_resource::<SeriesAssets>()
+        .init_resource::<PendingPoints>()
+        .add_system(append_pending_points);
+    let graph_dim = app.world.spawn((GraphDimComponent { name: "dim".to_string() }, SeriesPolylines::default())).id();
+
+    app.world.resource_mut::<PendingPoints>().0 = vec![point(graph_dim, 0, 0.0), point(graph_dim, 1, 0.0)];
+    app.update();
+    let lines = app.world.get::<SeriesPolylines>(graph_dim).unwrap().lines.clone();
+    assert_eq!(lines.len(), 2);
+    let polyline_count = app.world.resource::<Assets<Polyline>>().len();
+    let material_count = app.world.resource::<Assets<PolylineMaterial>>().len();
+    let entity_count = app.world.entities().len();
+
+    app.world.resource_mut::<PendingPoints>().0 = vec![point(graph_dim, 0, 1.0), point(graph_dim, 0, 2.0)];
+    app.update();
+
+    assert_eq!(app.world.get::<SeriesPolylines>(graph_dim).unwrap().lines, lines);
+    assert_eq!(app.world.resource::<Assets<Polyline>>().len(), polyline_count);
+    assert_eq!(app.world.resource::<Assets<PolylineMaterial>>().len(), material_count);
+    assert_eq!(app.world.entities().len(), entity_count);
+    let vertices = &app.world.resource::<Assets<Polyline>>().get(&lines[0].1).unwrap().vertices;
+    assert_eq!(vertices, &vec![Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0), Vec3::new(2.0, 0.0, 0.0), Vec3::new(3.0, 0.0, 0.0)]);
+    assert_eq!(app.world.resource::<Assets<Polyline>>().get(&lines[1].1).unwrap().vertices.len(), 2);
+    assert_eq!(app.world.get::<Children>(graph_dim).unwrap().len(), 2);
+    assert_eq!(app.world.get::<SeriesPolylines>(graph_dim).unwrap().steps, vec![vec![1, 1, 2, 3], vec![1, 1]]);
+}
@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+use std::fs;
+use std::fs::File;
+use std::marker::PhantomData;
+use bevy::prelude::Entity;
+use ndarray::{ArrayD, IxDyn};
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use crate::config::export::{ExportConfiguration, ExportFormat};
+use crate::data_subscriber::metric_event::{MetricComponentType, MetricsState};
+use crate::export::{export_metrics, export_rows, metric_rows, write_metric_rows};
+use crate::export::history::{has_component_type, history_rows, record_batch};
+use crate::metrics::network_metrics::{Metric, MetricType};
+use crate::network::{Layer, Node};
+
+fn metric() -> Metric<Node> {
+    let labels = HashMap::from([("variance".to_string(), 1), ("loss".to_string(), 0)]);
+    let indices = HashMap::from([(MetricComponentType::Node, vec!["0".to_string(), "1".to_string()])]);
+    let mut metric = Metric::<Node>::new(vec![2, 2], MetricType::Loss(PhantomData), labels, indices, "run-1");
+    metric.historical.extend(ArrayD::from_shape_vec(IxDyn(&[2, 2]), vec![1.0, 2.0, 3.0, 4.0]).unwrap(), 1);
+    metric.historical.extend(ArrayD::from_shape_vec(IxDyn(&[2, 2]), vec![0.5, 1.5, 2.5, 3.5]).unwrap(), 2);
+    metric.historical.add_wall_clock(2, 1000);
+    metric
+}
+
+fn export_config(name: &str) -> ExportConfiguration {
+    ExportConfiguration {
+        output_dir: std::env::temp_dir().join(name).display().to_string(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_history_rows() {
+    let rows = history_rows("run-1", "loss", &metric());
+    assert_eq!(rows.len(), 8);
+    assert_eq!(
+        rows.iter().map(|row| (row.label.as_str(), row.timestep, row.index, row.value)).collect::<Vec<_>>(),
+        vec![
+            ("loss", 1, 0, 1.0), ("loss", 1, 1, 2.0), ("loss", 2, 0, 0.5), ("loss", 2, 1, 1.5),
+            ("variance", 1, 0, 3.0), ("variance", 1, 1, 4.0), ("variance", 2, 0, 2.5), ("variance", 2, 1, 3.5)
+        ]
+    );
+    assert_eq!(rows[0].wall_clock, None);
+    assert_eq!(rows[2].wall_clock, Some(1000));
+    assert_eq!(rows[0].node_indices, "0;1");
+    assert_eq!(rows[0].layer_indices, "");
+    assert!(rows.iter().all(|row| row.run_id == "run-1" && row.metric_name == "loss"));
+}
+
+#[test]
+fn test_has_component_type() {
+    let metric = metric();
+    assert!(has_component_type(&metric, &None));
+    assert!(has_component_type(&metric, &Some(MetricComponentType::Node)));
+    assert!(!has_component_type(&metric, &Some(MetricComponentType::Layer)));
+}
+
+#[test]
+fn test_record_batch() {
+    let batch = record_batch(&history_rows("run-1", "loss", &metric())).unwrap();
+    assert_eq!(batch.num_rows(), 8);
+    assert_eq!(batch.num_columns(), 10);
+    assert_eq!(batch.schema().field(6).name(), "wall_clock");
+    assert_eq!(batch.column(6).null_count(), 4);
+}
+
+#[test]
+fn test_export_rows() {
+    let export = export_config("nn-fe-test-export-rows");
+    let rows = history_rows("run-1", "loss", &metric());
+    for format in ExportFormat::all() {
+        let path = export_rows(&export, "run-1/loss", &rows, format).unwrap();
+        assert!(path.ends_with(&format!("run-1_loss.{}", format.extension())));
+        match format {
+            ExportFormat::Csv => {
+                let csv = fs::read_to_string(&path).unwrap();
+                assert!(csv.starts_with("run_id,metric_name,label,timestep,index,value,wall_clock"));
+                assert_eq!(csv.lines().count(), 9);
+            }
+            ExportFormat::Json => {
+                let json = serde_json::from_str::<serde_json::Value>(&fs::read_to_string(&path).unwrap()).unwrap();
+                assert_eq!(json.as_array().unwrap().len(), 8);
+                assert_eq!(json[2]["wall_clock"], 1000);
+            }
+            ExportFormat::Arrow => {
+                let reader = arrow::ipc::reader::FileReader::try_new(File::open(&path).unwrap(), None).unwrap();
+                assert_eq!(reader.map(|batch| batch.unwrap().num_rows()).sum::<usize>(), 8);
+            }
+            ExportFormat::Parquet => {
+                let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+                assert_eq!(reader.metadata().file_metadata().num_rows(), 8);
+            }
+        }
+    }
+    let _ = fs::remove_dir_all(&export.output_dir);
+}
+
+#[test]
+fn test_export_metrics() {
+    let export = ExportConfiguration {
+        component_type: Some(MetricComponentType::Node),
+        ..export_config("nn-fe-test-export-metrics")
+    };
+    let mut metrics_state = MetricsState::default();
+    metrics_state.insert_entity("run-1", "loss", Entity::from_raw(1), 2);
+    let (first, second) = (metric(), metric());
+    let path = export_metrics(&export, &metrics_state, vec![(Entity::from_raw(1), &first), (Entity::from_raw(2), &second)].into_iter())
+        .unwrap();
+    assert!(path.ends_with("node_metrics.csv"));
+    let csv = fs::read_to_string(&path).unwrap();
+    assert_eq!(csv.lines().count(), 17);
+    assert_eq!(csv.lines().filter(|line| line.starts_with("run-1,loss,")).count(), 8);
+    let _ = fs::remove_dir_all(&export.output_dir);
+}
+
+#[test]
+fn test_export_metrics_of_all_component_types() {
+    let export = export_config("nn-fe-test-export-all-metrics");
+    let mut metrics_state = MetricsState::default();
+    metrics_state.insert_entity("run-1", "loss", Entity::from_raw(1), 2);
+    metrics_state.insert_entity("run-1", "attention", Entity::from_raw(2), 2);
+    let node_metric = metric();
+    let labels = HashMap::from([("entropy".to_string(), 0)]);
+    let indices = HashMap::from([(MetricComponentType::Layer, vec!["0".to_string()])]);
+    let mut layer_metric = Metric::<Layer>::new(vec![1, 1], MetricType::Loss(PhantomData), labels, indices, "run-1");
+    layer_metric.historical.extend(ArrayD::from_shape_vec(IxDyn(&[1, 1]), vec![0.25]).unwrap(), 1);
+
+    let mut rows = metric_rows(&export, &metrics_state, vec![(Entity::from_raw(1), &node_metric)].into_iter());
+    rows.extend(metric_rows(&export, &metrics_state, vec![(Entity::from_raw(2), &layer_metric)].into_iter()));
+    let path = write_metric_rows(&export, rows).unwrap();
+    assert!(path.ends_with("metrics.csv"));
+    let csv = fs::read_to_string(&path).unwrap();
+    assert_eq!(csv.lines().count(), 10);
+    assert!(csv.lines().nth(1).unwrap().starts_with("run-1,attention,entropy,"));
+    let _ = fs::remove_dir_all(&export.output_dir);
+}
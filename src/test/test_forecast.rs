@@ -0,0 +1,45 @@
+use ndarray::array;
+use crate::graph::forecast::{forecast_series, ForecastConfiguration};
+
+#[test]
+fn test_forecast_decreasing_loss() {
+    let loss_values = array![1.0, 0.8, 0.65, 0.55, 0.48, 0.43];
+    let config = ForecastConfiguration {
+        target_loss: 0.3,
+        ..ForecastConfiguration::default()
+    };
+
+    let forecast = forecast_series(&loss_values, &config);
+
+    assert!(forecast.is_some());
+    let forecast = forecast.unwrap();
+    assert_eq!(forecast.predicted.len(), config.horizon);
+    assert!(forecast.predicted[0] < 0.43);
+    assert!(forecast.predicted.windows(2).all(|w| w[1] <= w[0]));
+
+    let first_width = forecast.upper[0] - forecast.lower[0];
+    let last_width = forecast.upper[config.horizon - 1] - forecast.lower[config.horizon - 1];
+    assert!(last_width > first_width);
+    assert!((0..config.horizon).all(|i| forecast.lower[i] <= forecast.predicted[i] && forecast.predicted[i] <= forecast.upper[i]));
+    assert!(forecast.predicted.iter().all(|value| value.is_finite()));
+    assert!(forecast.predicted[config.horizon - 1] < forecast.predicted[0]);
+}
+
+#[test]
+fn test_forecast_steps_to_target() {
+    let loss_values = array![1.0, 0.9, 0.8, 0.7, 0.6];
+    let config = ForecastConfiguration {
+        target_loss: 0.35,
+        ..ForecastConfiguration::default()
+    };
+
+    let forecast = forecast_series(&loss_values, &config).unwrap();
+
+    assert_eq!(forecast.steps_to_target, Some(3));
+}
+
+#[test]
+fn test_forecast_too_short() {
+    let loss_values = array![1.0, 0.9];
+    assert!(forecast_series(&loss_values, &ForecastConfiguration::default()).is_none());
+}
@@ -0,0 +1,88 @@
+use std::marker::PhantomData;
+use bevy::prelude::*;
+use crate::event::event_descriptor::EventDescriptor;
+use crate::menu::{DataType, FormInputComponent, MetricsConfigurationOption, Radial, RadialButton};
+use crate::menu::config_menu_event::config_event::ConfigurationOptionEventArgs;
+use crate::network::Node;
+use crate::ui_components::menu_components::menu_types::radial_menu::{select_radial_button, update_radial_options};
+use crate::ui_components::menu_components::menu_types::scrollable_menu::{scroll_offset, SCROLL_LINE_HEIGHT};
+
+#[test]
+fn test_form_input_number() {
+    let mut form_input = FormInputComponent {
+        numeric: true,
+        valid: true,
+        ..FormInputComponent::default()
+    };
+    "1a.5".chars().for_each(|c| form_input.insert_char(c));
+    assert_eq!(form_input.value, "1.5");
+    assert_eq!(form_input.caret, 3);
+    assert!(matches!(form_input.validate(), Some(DataType::Number(Some(n))) if n == 1.5));
+
+    form_input.move_caret(-3);
+    form_input.insert_char('-');
+    assert_eq!(form_input.value, "-1.5");
+    assert_eq!(form_input.display_value(true), "-|1.5");
+
+    form_input.insert_char('.');
+    assert!(!form_input.valid);
+    assert!(form_input.validate().is_none());
+    form_input.backspace();
+    assert!(form_input.valid);
+
+    form_input.move_caret(10);
+    assert_eq!(form_input.caret, 4);
+    form_input.delete();
+    assert_eq!(form_input.value, "-1.5");
+}
+
+#[test]
+fn test_form_input_string() {
+    let mut form_input = FormInputComponent::default();
+    assert!(matches!(form_input.validate(), Some(DataType::String(None))));
+    "loss".chars().for_each(|c| form_input.insert_char(c));
+    form_input.move_caret(-1);
+    form_input.backspace();
+    assert_eq!(form_input.value, "los");
+    assert_eq!(form_input.display_value(false), "los");
+    assert!(matches!(form_input.validate(), Some(DataType::String(Some(value))) if value == "los"));
+}
+
+#[test]
+fn test_scroll_offset() {
+    assert_eq!(scroll_offset(0.0, -SCROLL_LINE_HEIGHT, 500.0, 300.0), SCROLL_LINE_HEIGHT);
+    assert_eq!(scroll_offset(190.0, -SCROLL_LINE_HEIGHT, 500.0, 300.0), 200.0);
+    assert_eq!(scroll_offset(10.0, SCROLL_LINE_HEIGHT, 500.0, 300.0), 0.0);
+    assert_eq!(scroll_offset(0.0, -SCROLL_LINE_HEIGHT, 200.0, 300.0), 0.0);
+}
+
+#[test]
+fn test_select_radial_button() {
+    let mut app = App::new();
+    app.add_event::<EventDescriptor<DataType, ConfigurationOptionEventArgs<Node>, MetricsConfigurationOption<Node>>>()
+        .add_system(select_radial_button)
+        .add_system(update_radial_options::<Node>.after(select_radial_button));
+
+    let option = |data_type: DataType| MetricsConfigurationOption::<Node>::Metrics(PhantomData, data_type, "");
+    let buttons = (0..3)
+        .map(|index| app.world.spawn((
+            RadialButton { index },
+            Interaction::None,
+            option(if index == 0 { DataType::Selected } else { DataType::Deselected })
+        )).id())
+        .collect::<Vec<Entity>>();
+    let radial = app.world.spawn(Radial { selected_index: 0 }).push_children(&buttons).id();
+    app.update();
+    app.world.resource_mut::<Events<EventDescriptor<DataType, ConfigurationOptionEventArgs<Node>, MetricsConfigurationOption<Node>>>>().clear();
+
+    *app.world.get_mut::<Interaction>(buttons[2]).unwrap() = Interaction::Pressed;
+    app.update();
+
+    assert_eq!(app.world.get::<Radial>(radial).unwrap().selected_index, 2);
+    let selected = buttons.iter()
+        .map(|button| matches!(app.world.get::<MetricsConfigurationOption<Node>>(*button).unwrap().data_type(), DataType::Selected))
+        .collect::<Vec<bool>>();
+    assert_eq!(selected, vec![false, false, true]);
+    let events = app.world.resource::<Events<EventDescriptor<DataType, ConfigurationOptionEventArgs<Node>, MetricsConfigurationOption<Node>>>>();
+    assert_eq!(events.len(), 2);
+}
@@ -0,0 +1,104 @@
+use bevy::prelude::{Color, Entity, Mat4, Mesh, shape, StandardMaterial, Vec3};
+use bevy::render::mesh::PrimitiveTopology;
+use serde_json::Value;
+use crate::export::gltf::{GLB_BIN_CHUNK, GLB_JSON_CHUNK, GLB_MAGIC, GltfGeometry, GltfMaterial, GltfScene};
+use crate::graph::draw_graph_points::SeriesPoint;
+use crate::headless::HeadlessSeries;
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+fn scene() -> GltfScene {
+    let mut scene = GltfScene::default();
+    let cube = GltfGeometry::from_mesh(&Mesh::from(shape::Cube { size: 1.0 })).unwrap();
+    let material = GltfMaterial::from_standard("layer", &StandardMaterial::from(Color::RED));
+    scene.add("dense_0", Mat4::from_translation(Vec3::X), cube.clone(), material.clone());
+    scene.add("dense_1", Mat4::IDENTITY, cube, material);
+    scene.add(
+        "loss 0 0",
+        Mat4::IDENTITY,
+        GltfGeometry::LineStrip { positions: vec![Vec3::ZERO, Vec3::ONE, Vec3::new(2.0, 0.0, 1.0)] },
+        GltfMaterial::unlit("series", Color::BLUE)
+    );
+    scene.add("empty", Mat4::IDENTITY, GltfGeometry::LineStrip { positions: vec![] }, GltfMaterial::unlit("series", Color::BLUE));
+    scene
+}
+
+#[test]
+fn test_geometry_from_mesh() {
+    let Some(GltfGeometry::Triangles { positions, normals, indices }) = GltfGeometry::from_mesh(&Mesh::from(shape::Cube { size: 1.0 })) else {
+        panic!("The cube is a list of triangles.");
+    };
+    assert_eq!(positions.len(), 24);
+    assert_eq!(normals.len(), 24);
+    assert_eq!(indices.len(), 36);
+    let mut strip = Mesh::new(PrimitiveTopology::LineStrip);
+    strip.insert_attribute(Mesh::ATTRIBUTE_POSITION, vec![[0.0, 0.0, 0.0], [1.0, 1.0, 1.0]]);
+    assert_eq!(GltfGeometry::from_mesh(&strip), Some(GltfGeometry::LineStrip { positions: vec![Vec3::ZERO, Vec3::ONE] }));
+    let mut points = Mesh::new(PrimitiveTopology::PointList);
+    points.insert_attribute(Mesh::ATTRIBUTE_POSITION, vec![[0.0, 0.0, 0.0]]);
+    assert_eq!(GltfGeometry::from_mesh(&points), None);
+}
+
+#[test]
+fn test_scene_shares_materials() {
+    let scene = scene();
+    assert_eq!(scene.objects.len(), 3);
+    assert_eq!(scene.materials.len(), 2);
+    assert_eq!(scene.objects.iter().map(|object| object.material).collect::<Vec<usize>>(), vec![0, 0, 1]);
+    assert!(!scene.materials[0].unlit);
+    assert!(scene.materials[1].unlit);
+}
+
+#[test]
+fn test_to_gltf() {
+    let (gltf, bin) = scene().to_gltf();
+    assert_eq!(gltf["asset"]["version"], "2.0");
+    assert_eq!(gltf["scenes"][0]["nodes"].as_array().unwrap().len(), 3);
+    assert_eq!(gltf["nodes"][0]["name"], "dense_0");
+    assert_eq!(gltf["nodes"][0]["matrix"][12], 1.0);
+    assert_eq!(gltf["meshes"][0]["primitives"][0]["mode"], 4);
+    assert_eq!(gltf["meshes"][2]["primitives"][0]["mode"], 3);
+    assert!(gltf["meshes"][2]["primitives"][0].get("indices").is_none());
+    assert_eq!(gltf["materials"][1]["extensions"]["KHR_materials_unlit"], Value::Object(Default::default()));
+    assert_eq!(gltf["extensionsUsed"][0], "KHR_materials_unlit");
+    let line_positions = &gltf["accessors"][gltf["meshes"][2]["primitives"][0]["attributes"]["POSITION"].as_u64().unwrap() as usize];
+    assert_eq!(line_positions["count"], 3);
+    assert_eq!(line_positions["min"], serde_json::json!([0.0, 0.0, 0.0]));
+    assert_eq!(line_positions["max"], serde_json::json!([2.0, 1.0, 1.0]));
+    assert_eq!(gltf["buffers"][0]["byteLength"], bin.len());
+    assert!(gltf["bufferViews"].as_array().unwrap().iter()
+        .all(|view| view["byteOffset"].as_u64().unwrap() % 4 == 0));
+}
+
+#[test]
+fn test_to_glb() {
+    let glb = scene().to_glb();
+    assert_eq!(read_u32(&glb, 0), GLB_MAGIC);
+    assert_eq!(read_u32(&glb, 4), 2);
+    assert_eq!(read_u32(&glb, 8) as usize, glb.len());
+    let json_length = read_u32(&glb, 12) as usize;
+    assert_eq!(json_length % 4, 0);
+    assert_eq!(read_u32(&glb, 16), GLB_JSON_CHUNK);
+    let gltf = serde_json::from_slice::<Value>(&glb[20..20 + json_length]).unwrap();
+    let bin_length = read_u32(&glb, 20 + json_length) as usize;
+    assert_eq!(read_u32(&glb, 24 + json_length), GLB_BIN_CHUNK);
+    assert_eq!(28 + json_length + bin_length, glb.len());
+    assert_eq!(gltf["buffers"][0]["byteLength"].as_u64().unwrap() as usize, bin_length);
+    let empty = GltfScene::default().to_glb();
+    assert_eq!(read_u32(&empty, 8) as usize, empty.len());
+    assert_eq!(20 + read_u32(&empty, 12) as usize, empty.len());
+}
+
+#[test]
+fn test_headless_series_gltf_scene() {
+    let mut series = HeadlessSeries::default();
+    let (metric, graph_dim) = (Entity::from_raw(1), Entity::from_raw(2));
+    series.append(metric, SeriesPoint { graph_dim_entity: graph_dim, color: Color::RED, index: 0, start: Vec3::ZERO, end: Vec3::X, step: 1 });
+    series.append(metric, SeriesPoint { graph_dim_entity: graph_dim, color: Color::RED, index: 1, start: Vec3::ZERO, end: Vec3::Y, step: 1 });
+    let scene = series.gltf_scene(|_| "run-1/loss".to_string(), |_| "0".to_string());
+    assert_eq!(scene.objects.iter().map(|object| object.name.as_str()).collect::<Vec<&str>>(), vec!["run-1/loss 0 0", "run-1/loss 0 1"]);
+    assert_eq!(scene.objects[1].geometry, GltfGeometry::LineStrip { positions: vec![Vec3::ZERO, Vec3::Y] });
+    assert_eq!(scene.materials.len(), 1);
+}
@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+use bevy::prelude::{Color, Entity, Vec2, Vec3};
+use crate::config::headless::{HeadlessConfiguration, Projection};
+use crate::data_subscriber::metric_event::{NetworkMetricsServiceEvent, NodeMetricEvent};
+use crate::graph::draw_graph_points::SeriesPoint;
+use crate::data_subscriber::recording::RecordedStream;
+use crate::headless::{HeadlessProgress, HeadlessSeries};
+use crate::headless::raster::{fit_lines, project, Raster, SNAPSHOT_MARGIN, SnapshotLine, to_svg};
+
+fn line(points: Vec<Vec2>) -> SnapshotLine {
+    SnapshotLine { color: Color::RED, points }
+}
+
+#[test]
+fn test_project() {
+    let point = Vec3::new(1.0, 2.0, 3.0);
+    assert_eq!(project(point, Projection::Front), Vec2::new(1.0, 2.0));
+    assert_eq!(project(point, Projection::Side), Vec2::new(3.0, 2.0));
+    assert_eq!(project(point, Projection::Top), Vec2::new(1.0, 3.0));
+}
+
+#[test]
+fn test_fit_lines() {
+    let lines = vec![line(vec![Vec2::new(-1.0, -1.0), Vec2::new(1.0, 1.0)])];
+    let fitted = fit_lines(&lines, 140, 100);
+    let scale = (100.0 - 2.0 * SNAPSHOT_MARGIN) / 2.0;
+    assert_eq!(fitted[0].points, vec![
+        Vec2::new(70.0 - scale, 50.0 + scale),
+        Vec2::new(70.0 + scale, 50.0 - scale)
+    ]);
+    assert!(fit_lines(&vec![], 140, 100).is_empty());
+    let flat = fit_lines(&vec![line(vec![Vec2::new(2.0, 5.0), Vec2::new(4.0, 5.0)])], 140, 100);
+    assert!(flat[0].points.iter().all(|point| point.is_finite() && (point.y - 50.0).abs() < 0.01));
+}
+
+#[test]
+fn test_raster_draw_line() {
+    let mut raster = Raster::new(10, 10, Color::WHITE);
+    raster.draw_lines(&vec![line(vec![Vec2::new(0.0, 0.0), Vec2::new(9.0, 9.0), Vec2::new(20.0, 9.0)])]);
+    let red = Color::RED.as_rgba_u8();
+    assert!((0..10).all(|i| raster.pixel(i, i) == Some(red)));
+    assert_eq!(raster.pixel(9, 0), Some(Color::WHITE.as_rgba_u8()));
+    assert_eq!(raster.pixel(0, 9), Some(Color::WHITE.as_rgba_u8()));
+    assert_eq!(raster.pixel(10, 0), None);
+}
+
+#[test]
+fn test_to_svg() {
+    let svg = to_svg(
+        &vec![line(vec![Vec2::new(0.0, 0.0), Vec2::new(1.5, 2.0)]), line(vec![Vec2::ZERO])],
+        80,
+        60,
+        Color::WHITE,
+        "loss <run>"
+    );
+    assert!(svg.starts_with("<svg"));
+    assert!(svg.contains("width=\"80\" height=\"60\""));
+    assert!(svg.contains("fill=\"#ffffff\""));
+    assert!(svg.contains("loss &lt;run&gt;"));
+    assert_eq!(svg.matches("<polyline").count(), 1);
+    assert!(svg.contains("stroke=\"#ff0000\" stroke-width=\"1\" points=\"0.00,0.00 1.50,2.00\""));
+}
+
+#[test]
+fn test_headless_series_append() {
+    let mut series = HeadlessSeries::default();
+    let metric = Entity::from_raw(1);
+    let dim = Entity::from_raw(2);
+    let point = |index, start: f32, end: f32| SeriesPoint {
+        graph_dim_entity: dim,
+        color: Color::BLUE,
+        index,
+        start: Vec3::splat(start),
+        end: Vec3::splat(end),
+        step: 1
+    };
+    series.append(metric, point(0, 0.0, 1.0));
+    series.append(metric, point(0, 1.0, 2.0));
+    series.append(metric, point(1, 0.0, 3.0));
+    let headless = HeadlessConfiguration { projection: Projection::Top, ..Default::default() };
+    let lines = series.snapshot_lines(metric, &headless);
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0].points, vec![Vec2::splat(0.0), Vec2::splat(1.0), Vec2::splat(2.0)]);
+    assert_eq!(lines[1].points, vec![Vec2::splat(0.0), Vec2::splat(3.0)]);
+    assert!(series.snapshot_lines(Entity::from_raw(3), &headless).is_empty());
+}
+
+#[test]
+fn test_recorded_stream_parse() {
+    let event = NodeMetricEvent::new(
+        "run-1",
+        "loss".to_string(),
+        vec![2],
+        vec![0.5, 0.25],
+        HashMap::new(),
+        HashMap::new()
+    );
+    let line = serde_json::to_string(&event).unwrap();
+    let recording = format!("{}\n\nnot an event\n{}\n", line, line);
+    let recorded = RecordedStream::parse(&recording);
+    assert_eq!(recorded.events.len(), 2);
+    assert_eq!(recorded.events[0].metric_name, "loss");
+    assert_eq!(*recorded.events[1].data.lock().unwrap(), Some(vec![0.5, 0.25]));
+}
+
+#[test]
+fn test_headless_progress_finished() {
+    let headless = HeadlessConfiguration { idle_frames: 2, max_frames: 5, ..Default::default() };
+    assert!(!HeadlessProgress { frames: 3, idle_frames: 1, done: false }.finished(&headless, false, 0));
+    assert!(!HeadlessProgress { frames: 3, idle_frames: 2, done: false }.finished(&headless, false, 1));
+    assert!(HeadlessProgress { frames: 3, idle_frames: 2, done: false }.finished(&headless, false, 0));
+    assert!(HeadlessProgress { frames: 5, idle_frames: 0, done: false }.finished(&headless, false, 1));
+    assert!(!HeadlessProgress { frames: 3, idle_frames: 2, done: false }.finished(&headless, true, 0));
+    assert!(HeadlessProgress { frames: 5, idle_frames: 0, done: false }.finished(&headless, true, 0));
+}
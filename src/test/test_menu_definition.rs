@@ -0,0 +1,169 @@
+use crate::menu::{ConfigurationOptionEnum, MenuInputType, MenuOptionType, SliderScale};
+use crate::menu::menu_definition::{MenuDefinition, MenuDefinitionError};
+use crate::menu::menu_resource::MenuResource;
+
+#[test]
+fn test_read_menu_file() {
+    let menu_data = MenuDefinition::read("resources/menu.toml")
+        .and_then(|menu_definition| menu_definition.to_menu_data());
+    assert!(menu_data.is_ok());
+    let menu_data = menu_data.unwrap();
+    assert_eq!(menu_data.selectables.len(), 2);
+    if let MenuInputType::CollapsableMenuInputType { options, metadata, .. } = &menu_data.selectables[0] {
+        assert_eq!(metadata.name, "Metrics");
+        assert!(matches!(options[0].data_type, MenuOptionType::SubMenu { .. }));
+    } else {
+        panic!("First menu was not collapsable.");
+    }
+}
+
+#[test]
+fn test_parse_json_menu() {
+    let json = r#"{"menus": [{
+        "type": "Dropdown",
+        "metadata": {"id": 1.0, "name": "Layers", "size": ["100%", "30px"], "color": "FF0000"},
+        "option": {"name": "LayerMetrics"},
+        "options": [{"metadata": {"id": 2.0, "name": "Layer Variance"}, "option": {"name": "LayerVariance", "data_type": "Selected"}}]
+    }]}"#;
+    let menu_data = MenuDefinition::parse(json, "json")
+        .and_then(|menu_definition| menu_definition.to_menu_data())
+        .unwrap();
+    if let MenuInputType::Dropdown { options, option, metadata } = &menu_data.selectables[0] {
+        assert!(matches!(option, ConfigurationOptionEnum::LayerMetrics(_)));
+        assert!(metadata.size.is_some());
+        assert!(metadata.color.is_some());
+        assert_eq!(options[0].index, 0);
+        assert!(matches!(options[0].data_type, MenuOptionType::Primitive(ConfigurationOptionEnum::LayerVariance(_))));
+    } else {
+        panic!("Menu was not a dropdown.");
+    }
+}
+
+#[test]
+fn test_menu_validation_errors() {
+    let duplicate = r#"
+        [[menus]]
+        type = "Dropdown"
+        metadata = { id = 1.0, name = "Layers" }
+        option = { name = "LayerMetrics" }
+
+        [[menus.options]]
+        metadata = { id = 1.0, name = "Layer Variance" }
+        option = { name = "LayerVariance" }
+    "#;
+    let result = MenuDefinition::parse(duplicate, "toml")
+        .and_then(|menu_definition| menu_definition.to_menu_data());
+    assert!(matches!(result, Err(MenuDefinitionError::DuplicateId { .. })));
+
+    let unknown = r#"
+        [[menus]]
+        type = "Dropdown"
+        metadata = { id = 1.0, name = "Layers" }
+        option = { name = "LayerSparsity" }
+    "#;
+    let result = MenuDefinition::parse(unknown, "toml")
+        .and_then(|menu_definition| menu_definition.to_menu_data());
+    assert!(matches!(result, Err(MenuDefinitionError::UnknownOption { .. })));
+
+    let missing_sub_menu = r#"
+        [[menus]]
+        type = "Dropdown"
+        metadata = { id = 1.0, name = "Layers" }
+        option = { name = "LayerMetrics" }
+
+        [[menus.options]]
+        input_type = "SubMenu"
+        metadata = { id = 2.0, name = "Layer Variance" }
+        option = { name = "LayerVariance" }
+    "#;
+    let result = MenuDefinition::parse(missing_sub_menu, "toml")
+        .and_then(|menu_definition| menu_definition.to_menu_data());
+    assert!(matches!(result, Err(MenuDefinitionError::InvalidValue { .. })));
+
+    assert!(matches!(MenuDefinition::parse("", "yaml"), Err(MenuDefinitionError::UnsupportedFormat(_))));
+}
+
+#[test]
+fn test_slider_menu_option() {
+    let slider = r#"
+        [[menus]]
+        type = "Dropdown"
+        metadata = { id = 1.0, name = "Layers" }
+        option = { name = "LayerMetrics" }
+
+        [[menus.options]]
+        input_type = "Slider"
+        metadata = { id = 2.0, name = "Learning Rate" }
+        option = { name = "LayerVariance" }
+        sub_menu = { type = "Slider", metadata = { id = 3.0, name = "Learning Rate" }, option = { name = "LayerVariance" }, start = 0.0001, end = 1.0, scale = "Log" }
+    "#;
+    let menu_data = MenuDefinition::parse(slider, "toml")
+        .and_then(|menu_definition| menu_definition.to_menu_data())
+        .unwrap();
+    if let MenuInputType::Dropdown { options, .. } = &menu_data.selectables[0] {
+        assert!(matches!(
+            &options[0].data_type,
+            MenuOptionType::SubMenu { sub_menu: MenuInputType::Slider { slider_data, .. }, .. } if slider_data.scale == SliderScale::Log
+        ));
+    } else {
+        panic!("Menu was not a dropdown.");
+    }
+
+    let invalid_range = slider.replace("start = 0.0001", "start = 0.0");
+    let result = MenuDefinition::parse(&invalid_range, "toml")
+        .and_then(|menu_definition| menu_definition.to_menu_data());
+    assert!(matches!(result, Err(MenuDefinitionError::InvalidValue { .. })));
+
+    for input_type in ["SubMenu", "DropdownMenu", "CollapsableMenu", "Radial", "FormInput"] {
+        let slider_sub_menu = slider.replace("input_type = \"Slider\"", &format!("input_type = \"{}\"", input_type));
+        let result = MenuDefinition::parse(&slider_sub_menu, "toml")
+            .and_then(|menu_definition| menu_definition.to_menu_data());
+        assert!(matches!(result, Err(MenuDefinitionError::InvalidValue { .. })), "{}", input_type);
+    }
+}
+
+#[test]
+fn test_invalid_menu_file() {
+    let path = std::env::temp_dir().join("nn-fe-invalid-menu.toml");
+    std::fs::write(&path, "[[menus]]\ntype = \"Dropdown\"\n").unwrap();
+    let result = MenuResource::read(&path.display().to_string());
+    assert!(matches!(result, Err(MenuDefinitionError::Parse { .. })));
+    assert!(matches!(MenuResource::read("does-not-exist.toml"), Err(MenuDefinitionError::Read { path, .. }) if path == "does-not-exist.toml"));
+}
+
+#[test]
+fn test_radial_and_form_input_sub_menus() {
+    let menus = r#"
+        [[menus]]
+        type = "Dropdown"
+        metadata = { id = 1.0, name = "Layers" }
+        option = { name = "LayerMetrics" }
+
+        [[menus.options]]
+        input_type = "Radial"
+        metadata = { id = 2.0, name = "Layer Statistic" }
+        option = { name = "LayerMetrics" }
+        sub_menu = { type = "Radial", metadata = { id = 3.0, name = "Layer Statistic" }, option = { name = "LayerMetrics" }, options = [
+            { metadata = { id = 4.0, name = "Variance" }, option = { name = "LayerVariance", data_type = "Selected" } },
+            { metadata = { id = 5.0, name = "Concavity" }, option = { name = "LayerConcavity" } }
+        ] }
+
+        [[menus.options]]
+        input_type = "FormInput"
+        metadata = { id = 6.0, name = "Window" }
+        option = { name = "LayerMetrics" }
+        sub_menu = { type = "FormInput", name = "window", metadata = { id = 7.0, name = "Window" }, option = { name = "LayerMetrics" } }
+    "#;
+    let menu_data = MenuDefinition::parse(menus, "toml")
+        .and_then(|menu_definition| menu_definition.to_menu_data())
+        .unwrap();
+    if let MenuInputType::Dropdown { options, .. } = &menu_data.selectables[0] {
+        assert!(matches!(
+            &options[0].data_type,
+            MenuOptionType::SubMenu { sub_menu: MenuInputType::Radial { options, .. }, .. } if options.len() == 2
+        ));
+        assert!(matches!(&options[1].data_type, MenuOptionType::SubMenu { sub_menu: MenuInputType::FormInput { .. }, .. }));
+    } else {
+        panic!("Menu was not a dropdown.");
+    }
+}
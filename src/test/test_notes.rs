@@ -0,0 +1,44 @@
+use std::env;
+use std::fs;
+use bevy::prelude::*;
+use crate::render_html::{load_markdown_images, MarkdownImage, MarkdownImageLoading};
+use crate::ui_components::notes_panel::{Notes, NotesSubject};
+
+#[test]
+fn test_attach_notes() {
+    let dir = env::temp_dir().join("nn-fe-test-notes");
+    let _ = fs::remove_dir_all(&dir);
+    let notes = Notes { dir: dir.to_str().unwrap().to_string() };
+    let metric = NotesSubject::Metric { run_id: "run/1".to_string(), metric_name: "loss".to_string() };
+
+    assert_eq!(notes.read(&metric), "");
+    assert!(notes.attach(&metric, "Diverges after *epoch 3*.").is_ok());
+    assert!(notes.attach(&metric, "  Fixed by lowering the learning rate.  ").is_ok());
+    assert_eq!(notes.read(&metric), "Diverges after *epoch 3*.\n\nFixed by lowering the learning rate.\n");
+    assert!(metric.path(&notes.dir).ends_with("metrics/run_1/loss.md"));
+    assert_eq!(notes.read(&NotesSubject::Run("run/1".to_string())), "");
+
+    assert!(notes.attach(&NotesSubject::Help, "Not allowed.").is_err());
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_image_keeps_alt_text_until_loaded() {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugins(AssetPlugin::default())
+        .add_asset::<Image>()
+        .add_system(load_markdown_images);
+    let alt_text = app.world.spawn(TextBundle::from_section("diagram", TextStyle::default())).id();
+    let image = app.world.spawn((NodeBundle::default(), MarkdownImage { path: "does-not-exist.png".to_string(), alt: "diagram".to_string() }))
+        .push_children(&[alt_text])
+        .id();
+
+    app.update();
+    assert!(app.world.get::<MarkdownImageLoading>(image).is_some());
+    for _ in 0..10 {
+        app.update();
+    }
+    assert_eq!(app.world.get::<Children>(image).unwrap().to_vec(), vec![alt_text]);
+    assert!(app.world.get::<UiImage>(image).is_none());
+}
@@ -0,0 +1,119 @@
+use bevy::prelude::Color;
+use crate::config::palette::{color_distance, ColorVision, contrast_ratio, lightness, min_distance, Palette, PaletteConfiguration, PaletteKind};
+use crate::data_subscriber::metric_event::{MetricsState, RunState};
+use crate::network::draw_network::node_colors;
+use crate::util::gen_color_from_list;
+
+#[test]
+fn test_gen_color_from_list() {
+    let colors = gen_color_from_list(4.0);
+    assert_eq!(colors.len(), 4);
+    colors.iter().for_each(|color| assert_eq!(color.a(), 1.0));
+    let hues = colors.iter()
+        .map(|color| color.as_hsla_f32()[0].round())
+        .collect::<Vec<f32>>();
+    assert_eq!(hues, vec![0.0, 90.0, 180.0, 270.0]);
+}
+
+#[test]
+fn test_palette_colors() {
+    for palette in Palette::all() {
+        assert_eq!(palette.colors(12).len(), 12);
+        assert!(!palette.swatches().is_empty());
+        palette.colors(12).iter().for_each(|color| assert_eq!(color.a(), 1.0));
+    }
+    assert_eq!(Palette::OkabeIto.color_at(8, 12), Palette::OkabeIto.color_at(0, 12));
+    assert_eq!(Palette::Viridis.color_at(0, 3), Palette::Viridis.sample(0.0));
+    assert_eq!(Palette::Viridis.color_at(2, 3), Palette::Viridis.sample(1.0));
+    assert_eq!(Palette::RdBu.color_at(0, 1), Palette::RdBu.sample(0.5));
+}
+
+#[test]
+fn test_categorical_contrast() {
+    assert!(Palette::OkabeIto.distinguishable(8));
+    assert!(!Palette::OkabeIto.distinguishable(9));
+    for vision in ColorVision::all() {
+        assert!(min_distance(&Palette::OkabeIto.colors(8), vision) > 5.0, "{:?}", vision);
+    }
+    // Tableau and evenly spaced hues are distinct with normal color vision only.
+    assert!(min_distance(&Palette::Tableau10.colors(10), ColorVision::Normal) > 20.0);
+    assert!(!Palette::Tableau10.distinguishable(10));
+    assert!(min_distance(&Palette::Hues.colors(8), ColorVision::Normal) > 20.0);
+    assert!(!Palette::Hues.distinguishable(8));
+}
+
+#[test]
+fn test_colormap_contrast() {
+    for palette in [Palette::Viridis, Palette::Cividis] {
+        assert_eq!(palette.kind(), PaletteKind::Sequential);
+        let lightness = palette.colors(9).into_iter()
+            .map(lightness)
+            .collect::<Vec<f32>>();
+        assert!(lightness.windows(2).all(|pair| pair[1] - pair[0] > 5.0), "{:?} {:?}", palette, lightness);
+        assert!(contrast_ratio(palette.sample(0.0), palette.sample(1.0)) > 10.0);
+        assert!(palette.distinguishable(5));
+    }
+    let lightness = Palette::RdBu.colors(9).into_iter()
+        .map(lightness)
+        .collect::<Vec<f32>>();
+    assert!(lightness[..5].windows(2).all(|pair| pair[1] > pair[0]));
+    assert!(lightness[4..].windows(2).all(|pair| pair[1] < pair[0]));
+    assert!(color_distance(Palette::RdBu.sample(0.0), Palette::RdBu.sample(1.0), ColorVision::Deuteranopia) > 20.0);
+}
+
+#[test]
+fn test_contrast_ratio() {
+    assert!((contrast_ratio(Color::BLACK, Color::WHITE) - 21.0).abs() < 0.01);
+    assert_eq!(contrast_ratio(Color::GRAY, Color::GRAY), 1.0);
+    assert_eq!(contrast_ratio(Color::BLACK, Color::WHITE), contrast_ratio(Color::WHITE, Color::BLACK));
+}
+
+#[test]
+fn test_palette_configuration() {
+    let config = toml::from_str::<PaletteConfiguration>("graph = \"Tableau10\"\nnetwork = \"Viridis\"\n[graphs]\nloss = \"Cividis\"\n").unwrap();
+    assert_eq!(config.graph_palette("loss"), Palette::Cividis);
+    assert_eq!(config.graph_palette("variance"), Palette::Tableau10);
+    assert_eq!(config.network, Some(Palette::Viridis));
+    let config = toml::from_str::<PaletteConfiguration>("").unwrap();
+    assert_eq!(config.graph_palette("loss"), Palette::OkabeIto);
+    assert_eq!(config.network, None);
+    let config = toml::from_str::<PaletteConfiguration>("network = \"Hues\"").unwrap();
+    assert!(config.check().is_err());
+    assert_eq!(config.network_palette(), None);
+}
+
+#[test]
+fn test_sample_categorical_palettes() {
+    assert_eq!(Palette::Hues.sample(0.5), Color::hsl(180.0, 0.5, 0.5));
+    assert_eq!(Palette::OkabeIto.sample(0.0), Palette::OkabeIto.color_at(0, 8));
+    assert_eq!(Palette::OkabeIto.sample(1.0), Palette::OkabeIto.color_at(7, 8));
+}
+
+#[test]
+fn test_node_colors() {
+    let colors = node_colors(&vec![Some(2.0), None, Some(4.0), Some(3.0)], Palette::Viridis);
+    assert_eq!(colors, vec![Some(Palette::Viridis.sample(0.0)), None, Some(Palette::Viridis.sample(1.0)), Some(Palette::Viridis.sample(0.5))]);
+    let same = node_colors(&vec![Some(1.0), Some(1.0)], Palette::Cividis);
+    assert_eq!(same, vec![Some(Palette::Cividis.sample(0.5)); 2]);
+    assert!(node_colors(&vec![None, None], Palette::Viridis).iter().all(|color| color.is_none()));
+}
+
+#[test]
+fn test_runs_shade_the_graph_palette() {
+    let mut metrics_state = MetricsState::default();
+    let first = metrics_state.add_run("first", Palette::OkabeIto).clone();
+    let second = metrics_state.add_run("second", Palette::OkabeIto).clone();
+    let third = metrics_state.add_run("third", Palette::OkabeIto).clone();
+    assert_eq!(first.column_colors(Palette::OkabeIto, 3), Palette::OkabeIto.colors(3));
+    let lightness_of = |run: &RunState| run.column_colors(Palette::OkabeIto, 3).iter()
+        .map(|color| color.as_hsla_f32()[2])
+        .collect::<Vec<f32>>();
+    let base = lightness_of(&first);
+    lightness_of(&second).iter().zip(base.iter()).for_each(|(shaded, base)| assert!(shaded < base));
+    lightness_of(&third).iter().zip(base.iter()).for_each(|(shaded, base)| assert!(shaded > base));
+    let hues = |run: &RunState| run.column_colors(Palette::OkabeIto, 3).iter()
+        .map(|color| color.as_hsla_f32()[0].round())
+        .collect::<Vec<f32>>();
+    assert_eq!(hues(&second), hues(&first));
+    assert_eq!(metrics_state.add_run("second", Palette::Viridis).index, 1);
+}
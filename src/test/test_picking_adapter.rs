@@ -0,0 +1,33 @@
+use std::marker::PhantomData;
+use bevy::prelude::{Entity, Vec2, With};
+use crate::interactions::{HoverEvent, PickingEvent, SelectionEvent};
+use crate::network::Node;
+use crate::pickable_events::PickableEvent;
+use crate::pickable_events::picking_adapter::{MIN_BOX_SIZE, selection_box};
+
+#[test]
+fn test_selection_box() {
+    let rect = selection_box(Vec2::new(100.0, 50.0), Vec2::new(20.0, 80.0)).unwrap();
+    assert_eq!(rect.min, Vec2::new(20.0, 50.0));
+    assert_eq!(rect.max, Vec2::new(100.0, 80.0));
+    assert!(rect.contains(Vec2::new(50.0, 60.0)));
+    assert!(!rect.contains(Vec2::new(10.0, 60.0)));
+    assert!(selection_box(Vec2::ZERO, Vec2::splat(MIN_BOX_SIZE / 2.0)).is_none());
+    assert!(selection_box(Vec2::ZERO, Vec2::new(MIN_BOX_SIZE, 0.0)).is_some());
+}
+
+#[test]
+fn test_pickable_event_entity() {
+    let entity = Entity::from_raw(7);
+    let events = vec![
+        PickableEvent::Selection(SelectionEvent::JustSelected(entity)),
+        PickableEvent::Selection(SelectionEvent::JustDeselected(entity)),
+        PickableEvent::Hover(HoverEvent::JustEntered(entity)),
+        PickableEvent::Hover(HoverEvent::JustLeft(entity)),
+        PickableEvent::Clicked(entity),
+        PickableEvent::Dragged(entity, Vec2::new(1.0, 2.0)),
+    ];
+    events.iter().for_each(|event| assert_eq!(event.entity(), entity));
+    let dragged = PickingEvent::<With<Node>>::from(&PickableEvent::Dragged(entity, Vec2::X));
+    assert!(matches!(dragged, PickingEvent::Dragged(dragged, delta, PhantomData) if dragged == entity && delta == Vec2::X));
+}
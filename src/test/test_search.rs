@@ -0,0 +1,64 @@
+use bevy::prelude::{Color, Entity};
+use crate::search::search_results;
+use crate::search::filter::{Comparison, FilterTerm, normalize, SearchCandidate, SearchFilter, SearchKind};
+use crate::theme::highlight::{dimmed, DIM_FACTOR};
+
+fn candidates() -> Vec<SearchCandidate> {
+    vec![
+        SearchCandidate::new(Entity::from_raw(1), SearchKind::Layer, "attention_0")
+            .with_name("gpt")
+            .with_field("layer_type", "attention")
+            .with_value("weight_variance", 0.8),
+        SearchCandidate::new(Entity::from_raw(2), SearchKind::Layer, "attention_1")
+            .with_name("gpt")
+            .with_field("layer_type", "attention")
+            .with_value("weight_variance", 0.2),
+        SearchCandidate::new(Entity::from_raw(3), SearchKind::Layer, "dense_0")
+            .with_name("gpt")
+            .with_field("layer_type", "fully_connected")
+            .with_value("weight_variance", 0.9),
+        SearchCandidate::new(Entity::from_raw(4), SearchKind::Metric, "weight_variance")
+            .with_field("run", "run-1")
+    ]
+}
+
+#[test]
+fn test_parse_filter() {
+    let filter = SearchFilter::parse("layer_type = attention AND weight variance >= 0.5 and attn");
+    assert_eq!(filter.terms, vec![
+        FilterTerm::Field { field: "layer_type".to_string(), comparison: Comparison::Eq, value: "attention".to_string() },
+        FilterTerm::Field { field: "weight_variance".to_string(), comparison: Comparison::Ge, value: "0.5".to_string() },
+        FilterTerm::Text("attn".to_string()),
+    ]);
+    assert_eq!(SearchFilter::parse("brand new").terms, vec![FilterTerm::Text("brand new".to_string())]);
+    assert_eq!(SearchFilter::parse("loss != 0").terms[0], FilterTerm::Field {
+        field: "loss".to_string(),
+        comparison: Comparison::Ne,
+        value: "0".to_string()
+    });
+    assert_eq!(SearchFilter::parse("> 1").terms, vec![FilterTerm::Text("> 1".to_string())]);
+    assert!(SearchFilter::parse("  ").is_empty());
+    assert_eq!(normalize(" Weight-Variance  x"), "weight_variance_x");
+}
+
+#[test]
+fn test_search_results() {
+    let candidates = candidates();
+    let entities = |query: &str| search_results(&candidates, query).into_iter()
+        .map(|result| result.entity)
+        .collect::<Vec<Entity>>();
+
+    assert_eq!(entities("attn")[..2], [Entity::from_raw(1), Entity::from_raw(2)]);
+    assert_eq!(entities("layer_type = attention and weight variance > 0.5"), vec![Entity::from_raw(1)]);
+    assert_eq!(entities("weight_variance > 0.5"), vec![Entity::from_raw(1), Entity::from_raw(3)]);
+    assert_eq!(entities("kind = metric"), vec![Entity::from_raw(4)]);
+    assert_eq!(entities("gpt").len(), 3);
+    assert!(entities("layer_type > attention").is_empty());
+    assert!(entities("").is_empty());
+}
+
+#[test]
+fn test_dimmed() {
+    let color = dimmed(Color::rgba(1.0, 0.5, 0.0, 0.8));
+    assert_eq!(color.as_rgba_f32(), [DIM_FACTOR, 0.5 * DIM_FACTOR, 0.0, 0.8]);
+}
@@ -0,0 +1,61 @@
+use std::collections::{HashMap, HashSet};
+use bevy::prelude::Entity;
+use crate::graph::GraphingMetricsResource;
+use crate::interactions::selection_set::SelectionSet;
+use crate::menu::graphing_menu::context_menu::{ContextMenuAction, ContextMenuTarget};
+
+#[test]
+fn test_selection_set() {
+    let node = Entity::from_raw(1);
+    let layer = Entity::from_raw(2);
+    let mut selection_set = SelectionSet::default();
+    assert!(selection_set.is_empty());
+    assert!(selection_set.insert(node, vec!["encoder.3".to_string()]));
+    assert!(selection_set.insert(layer, vec!["encoder".to_string(), "encoder.3".to_string()]));
+    assert!(!selection_set.insert(node, vec![]));
+    assert_eq!(selection_set.len(), 2);
+    assert_eq!(selection_set.entities(), vec![node, layer]);
+    assert_eq!(selection_set.indices(), vec!["encoder".to_string(), "encoder.3".to_string()]);
+    assert!(selection_set.remove(node));
+    assert!(!selection_set.remove(node));
+    assert!(!selection_set.contains(node));
+    assert!(selection_set.contains(layer));
+}
+
+#[test]
+fn test_selection_metrics() {
+    let first = Entity::from_raw(10);
+    let second = Entity::from_raw(11);
+    let other = Entity::from_raw(12);
+    let graphing_metrics = GraphingMetricsResource {
+        graphing_indices: HashMap::from([
+            ("encoder.0".to_string(), HashSet::from([first])),
+            ("encoder.1".to_string(), HashSet::from([first, second])),
+            ("decoder".to_string(), HashSet::from([other])),
+        ]),
+        ..Default::default()
+    };
+    let mut selection_set = SelectionSet::default();
+    selection_set.insert(Entity::from_raw(1), vec!["encoder.0".to_string()]);
+    selection_set.insert(Entity::from_raw(2), vec!["encoder.1".to_string()]);
+    assert_eq!(selection_set.metrics(&graphing_metrics), HashSet::from([first, second]));
+}
+
+#[test]
+fn test_selection_actions() {
+    let mut selection_set = SelectionSet::default();
+    let layer = ContextMenuTarget::Layer(Entity::from_raw(2));
+    let node = ContextMenuTarget::Node(Entity::from_raw(1));
+    assert_eq!(ContextMenuAction::selection_actions(node, &selection_set), vec![ContextMenuAction::ShowAll]);
+    assert_eq!(
+        ContextMenuAction::selection_actions(layer, &selection_set),
+        vec![ContextMenuAction::SelectLayerNodes, ContextMenuAction::ShowAll]
+    );
+    selection_set.insert(Entity::from_raw(1), vec![]);
+    let actions = ContextMenuAction::selection_actions(node, &selection_set);
+    assert!(actions.contains(&ContextMenuAction::GraphSelection));
+    assert!(actions.contains(&ContextMenuAction::CompareSelection));
+    assert!(actions.contains(&ContextMenuAction::HideUnselected));
+    assert!(actions.contains(&ContextMenuAction::ClearSelection));
+    assert!(!actions.contains(&ContextMenuAction::SelectLayerNodes));
+}
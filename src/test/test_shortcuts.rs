@@ -0,0 +1,86 @@
+use bevy::prelude::*;
+use crate::config::keymap::{KeyAction, KeyBinding, KeymapConfiguration};
+use crate::menu::MenuData;
+use crate::menu::menu_definition::MenuDefinition;
+use crate::menu::menu_resource::MenuResource;
+use crate::shortcuts::command_palette::{command_palette_input, CommandPaletteState, forward_palette_actions, matching_entries, PaletteAction, palette_entries, PaletteEntry, PaletteTarget};
+use crate::shortcuts::keymap::{KeyActionEvent, Keymap, PressUiEntity};
+
+#[test]
+fn test_parse_key_binding() {
+    assert_eq!(KeyBinding::parse("g"), Ok(KeyBinding { key: KeyCode::G, ctrl: false, shift: false, alt: false }));
+    assert_eq!(KeyBinding::parse("Ctrl+Shift+P"), Ok(KeyBinding { key: KeyCode::P, ctrl: true, shift: true, alt: false }));
+    assert_eq!(KeyBinding::parse("F12").unwrap().key, KeyCode::F12);
+    assert_eq!(KeyBinding::parse("space").unwrap().key, KeyCode::Space);
+    assert_eq!(KeyBinding::parse("Alt + 1").unwrap(), KeyBinding { key: KeyCode::Key1, ctrl: false, shift: false, alt: true });
+    assert_eq!(KeyBinding::parse("Ctrl+Shift+P").unwrap().label(), "Ctrl+Shift+P");
+    assert!(KeyBinding::parse("F13").is_err());
+    assert!(KeyBinding::parse("Super+P").is_err());
+    assert!(KeyBinding::parse("Ctrl+").is_err());
+}
+
+#[test]
+fn test_keymap_falls_back_to_default() {
+    let mut config = KeymapConfiguration::default();
+    config.bindings.insert(KeyAction::ToggleGraph, "Shift+G".to_string());
+    config.bindings.insert(KeyAction::Screenshot, "NotAKey".to_string());
+    let keymap = Keymap::from_config(&config);
+    assert_eq!(keymap.bindings.len(), KeyAction::all().len());
+    assert!(keymap.binding(&KeyAction::ToggleGraph).unwrap().shift);
+    assert_eq!(keymap.binding(&KeyAction::Screenshot).unwrap().key, KeyCode::F12);
+}
+
+#[test]
+fn test_palette_entries() {
+    let json = r#"{"menus": [{
+        "type": "Dropdown",
+        "metadata": {"id": 1.0, "name": "Layers"},
+        "option": {"name": "LayerMetrics"},
+        "options": [
+            {"metadata": {"id": 2.0, "name": "Layer Variance"}, "option": {"name": "LayerVariance", "data_type": "Selected"}},
+            {"metadata": {"id": 3.0, "name": "Layer Concavity"}, "option": {"name": "LayerConcavity", "data_type": "Selected"}}
+        ]
+    }]}"#;
+    let menu_data = MenuDefinition::parse(json, "json")
+        .and_then(|menu_definition| menu_definition.to_menu_data())
+        .unwrap();
+    let keymap = Keymap::from_config(&KeymapConfiguration::default());
+    let entries = palette_entries(&menu_data, &keymap);
+
+    let labels = entries.iter().map(|entry| entry.label.as_str()).collect::<Vec<&str>>();
+    assert!(labels.contains(&"Layers > Layer Variance"));
+    assert!(labels.contains(&"Toggle Graph (G)"));
+    assert!(!entries.iter().any(|entry| entry.target == PaletteTarget::Action(KeyAction::CommandPalette)));
+
+    let matching = matching_entries(&entries, "lconc");
+    assert_eq!(matching[0].target, PaletteTarget::MenuOption(3.0));
+    assert_eq!(matching_entries(&entries, "").len(), 8);
+    assert!(matching_entries(&entries, "zzz").is_empty());
+}
+
+#[test]
+fn test_palette_action_is_sent_as_key_action() {
+    let mut app = App::new();
+    app.add_event::<KeyActionEvent>()
+        .add_event::<PaletteAction>()
+        .add_event::<PressUiEntity>()
+        .add_event::<ReceivedCharacter>()
+        .init_resource::<Input<KeyCode>>()
+        .init_resource::<Keymap>()
+        .insert_resource(MenuResource { menu_data: MenuData { sub_menus: vec![], selectables: vec![] } })
+        .insert_resource(CommandPaletteState {
+            open: true,
+            entries: vec![PaletteEntry { label: "Cycle Theme".to_string(), target: PaletteTarget::Action(KeyAction::CycleTheme) }],
+            ..default()
+        })
+        .add_system(command_palette_input)
+        .add_system(forward_palette_actions.after(command_palette_input));
+
+    app.world.resource_mut::<Input<KeyCode>>().press(KeyCode::Return);
+    app.update();
+
+    assert!(!app.world.resource::<CommandPaletteState>().open);
+    let events = app.world.resource::<Events<KeyActionEvent>>();
+    let actions = events.get_reader().iter(events).copied().collect::<Vec<KeyActionEvent>>();
+    assert_eq!(actions, vec![KeyActionEvent(KeyAction::CycleTheme)]);
+}
@@ -0,0 +1,52 @@
+use crate::menu::{SliderData, SliderScale};
+
+#[test]
+fn test_slider_linear() {
+    let slider = SliderData {
+        start: -1.0,
+        end: 1.0,
+        step: None,
+        scale: SliderScale::Linear,
+    };
+    assert_eq!(slider.value_at(0.0), -1.0);
+    assert_eq!(slider.value_at(0.75), 0.5);
+    assert_eq!(slider.value_at(2.0), 1.0);
+    assert_eq!(slider.fraction_of(0.5), 0.75);
+    assert_eq!(slider.fraction_of(-5.0), 0.0);
+    assert_eq!(slider.format(0.5), "0.500");
+}
+
+#[test]
+fn test_slider_step() {
+    let slider = SliderData {
+        start: 0.0,
+        end: 10.0,
+        step: Some(0.5),
+        scale: SliderScale::Linear,
+    };
+    assert_eq!(slider.value_at(0.33), 3.5);
+    assert_eq!(slider.snap(7.2), 7.0);
+    assert_eq!(slider.snap(12.0), 10.0);
+    assert_eq!(slider.format(3.5), "3.5");
+
+    let slider = SliderData {
+        step: Some(2.0),
+        ..slider
+    };
+    assert_eq!(slider.format(4.0), "4");
+}
+
+#[test]
+fn test_slider_log() {
+    let slider = SliderData {
+        start: 0.001,
+        end: 1.0,
+        step: None,
+        scale: SliderScale::Log,
+    };
+    assert!((slider.value_at(0.0) - 0.001).abs() < 1e-6);
+    assert!((slider.value_at(1.0 / 3.0) - 0.01).abs() < 1e-5);
+    assert!((slider.value_at(2.0 / 3.0) - 0.1).abs() < 1e-4);
+    assert!((slider.fraction_of(0.1) - 2.0 / 3.0).abs() < 1e-4);
+    assert_eq!(slider.fraction_of(0.0), 0.0);
+}
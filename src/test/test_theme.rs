@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+use std::path::Path;
+use bevy::prelude::*;
+use crate::config::theme::{Theme, ThemeColor, ThemeConfiguration, ThemeDefinition, ThemePreset};
+use crate::theme::{read_themes, Themes};
+use crate::theme::highlight::{apply_highlights, dimmed, Highlight};
+use crate::theme::restyle::{restyle_materials, restyle_text, ThemedMaterial, ThemedSections, ThemedText, ThemeFont, ThemeFonts};
+
+#[test]
+fn test_presets() {
+    for preset in ThemePreset::all() {
+        let theme = Theme::preset(preset);
+        assert_eq!(theme.colors.len(), ThemeColor::all().len());
+        assert_ne!(theme.color(ThemeColor::PanelText), theme.color(ThemeColor::Panel));
+        assert_ne!(theme.color(ThemeColor::MenuSelected), theme.color(ThemeColor::MenuDeselected));
+    }
+    assert_eq!(Theme::default().color(ThemeColor::Background), Color::rgb(0.4, 0.4, 0.4));
+}
+
+#[test]
+fn test_theme_definition() {
+    let definition = ThemeDefinition {
+        name: "Custom".to_string(),
+        base: ThemePreset::Dark,
+        font: Some("fonts/Custom.ttf".to_string()),
+        mono_font: None,
+        colors: HashMap::from([(ThemeColor::Node, "#ff0000".to_string())])
+    };
+    let theme = Theme::from_definition(definition.clone()).unwrap();
+    let dark = Theme::preset(ThemePreset::Dark);
+    assert_eq!(theme.color(ThemeColor::Node), Color::rgb(1.0, 0.0, 0.0));
+    assert_eq!(theme.color(ThemeColor::Layer), dark.color(ThemeColor::Layer));
+    assert_eq!(theme.font, "fonts/Custom.ttf");
+    assert_eq!(theme.mono_font, dark.mono_font);
+
+    let invalid = ThemeDefinition {
+        colors: HashMap::from([(ThemeColor::Node, "not a color".to_string())]),
+        ..definition
+    };
+    assert!(Theme::from_definition(invalid).is_err());
+}
+
+#[test]
+fn test_read_themes() {
+    let theme = Theme::read(Path::new("resources/themes/solarized-dark.toml")).unwrap();
+    assert_eq!(theme.name, "Solarized Dark");
+    assert!(theme.matches("solarized-dark"));
+    assert!(!theme.matches("Dark"));
+    assert!(read_themes("resources/themes").iter().any(|read| read == &theme));
+    assert!(read_themes("does/not/exist").is_empty());
+
+    let themes = Themes::load(&ThemeConfiguration { name: "high_contrast".to_string(), ..Default::default() });
+    assert_eq!(themes.current().name, ThemePreset::HighContrast.name());
+    let themes = Themes::load(&ThemeConfiguration { name: "Missing".to_string(), ..Default::default() });
+    assert_eq!(themes.current().name, ThemePreset::Light.name());
+}
+
+#[test]
+fn test_restyle_text_sections() {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugins(AssetPlugin::default())
+        .add_asset::<Font>()
+        .insert_resource(Theme::preset(ThemePreset::Light))
+        .init_resource::<ThemeFonts>()
+        .add_system(restyle_text);
+    let document = app.world.spawn((
+        Text::from_sections(vec![TextSection::new("see ", TextStyle::default()), TextSection::new("docs", TextStyle::default())]),
+        ThemedSections(vec![(ThemeColor::PanelText, ThemeFont::Regular), (ThemeColor::Link, ThemeFont::Mono)])
+    )).id();
+    let label = app.world.spawn((Text::from_section("label", TextStyle::default()), ThemedText(ThemeColor::MenuText))).id();
+    let unthemed = app.world.spawn(Text::from_section("unthemed", TextStyle::default())).id();
+
+    for preset in [ThemePreset::Light, ThemePreset::Dark] {
+        *app.world.resource_mut::<Theme>() = Theme::preset(preset);
+        app.update();
+        let theme = Theme::preset(preset);
+        let mono_font = app.world.resource::<ThemeFonts>().mono_font.clone();
+        let sections = &app.world.get::<Text>(document).unwrap().sections;
+        assert_eq!(sections[0].style.color, theme.color(ThemeColor::PanelText));
+        assert_eq!(sections[1].style.color, theme.color(ThemeColor::Link));
+        assert_eq!(sections[1].style.font, mono_font);
+        assert_eq!(app.world.get::<Text>(label).unwrap().sections[0].style.color, theme.color(ThemeColor::MenuText));
+        assert_eq!(app.world.get::<Text>(unthemed).unwrap().sections[0].style.color, TextStyle::default().color);
+    }
+}
+
+#[test]
+fn test_highlights_by_priority() {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugins(AssetPlugin::default())
+        .add_asset::<StandardMaterial>()
+        .insert_resource(Theme::preset(ThemePreset::Light))
+        .add_system(restyle_materials)
+        .add_system(apply_highlights.after(restyle_materials));
+    let light = Theme::preset(ThemePreset::Light);
+    let color = light.color(ThemeColor::Node);
+    let material = app.world.resource_mut::<Assets<StandardMaterial>>()
+        .add(StandardMaterial { base_color: color, emissive: color, ..default() });
+    let highlight = Highlight::new(app.world.resource::<Assets<StandardMaterial>>().get(&material).unwrap());
+    let node = app.world.spawn((material.clone(), ThemedMaterial(ThemeColor::Node), highlight)).id();
+    let emissive = |app: &App| app.world.resource::<Assets<StandardMaterial>>().get(&material).unwrap().emissive;
+
+    app.world.get_mut::<Highlight>(node).unwrap().search_match = true;
+    app.update();
+    assert_eq!(emissive(&app), light.color(ThemeColor::SearchMatch));
+
+    app.world.get_mut::<Highlight>(node).unwrap().selected = true;
+    app.world.get_mut::<Highlight>(node).unwrap().alert = true;
+    app.update();
+    assert_eq!(emissive(&app), light.color(ThemeColor::Alert));
+
+    app.world.get_mut::<Highlight>(node).unwrap().alert = false;
+    app.update();
+    assert_eq!(emissive(&app), light.color(ThemeColor::Selection));
+
+    let dark = Theme::preset(ThemePreset::Dark);
+    *app.world.resource_mut::<Theme>() = dark.clone();
+    app.update();
+    assert_eq!(emissive(&app), dark.color(ThemeColor::Selection));
+
+    app.world.get_mut::<Highlight>(node).unwrap().selected = false;
+    app.world.get_mut::<Highlight>(node).unwrap().search_match = false;
+    app.update();
+    let restored = app.world.resource::<Assets<StandardMaterial>>().get(&material).unwrap();
+    assert_eq!(restored.base_color, dark.color(ThemeColor::Node));
+    assert_eq!(restored.emissive, dark.color(ThemeColor::Node));
+}
+
+#[test]
+fn test_dimmed_highlight_is_restyled() {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugins(AssetPlugin::default())
+        .add_asset::<StandardMaterial>()
+        .insert_resource(Theme::preset(ThemePreset::Light))
+        .add_system(restyle_materials)
+        .add_system(apply_highlights.after(restyle_materials));
+    let color = Theme::preset(ThemePreset::Light).color(ThemeColor::Layer);
+    let material = app.world.resource_mut::<Assets<StandardMaterial>>()
+        .add(StandardMaterial::from(color));
+    let highlight = Highlight::new(app.world.resource::<Assets<StandardMaterial>>().get(&material).unwrap());
+    let layer = app.world.spawn((material.clone(), ThemedMaterial(ThemeColor::Layer), highlight)).id();
+    let base_color = |app: &App| app.world.resource::<Assets<StandardMaterial>>().get(&material).unwrap().base_color;
+
+    app.world.get_mut::<Highlight>(layer).unwrap().dimmed = true;
+    app.update();
+    assert_eq!(base_color(&app), dimmed(color));
+
+    let dark = Theme::preset(ThemePreset::Dark);
+    *app.world.resource_mut::<Theme>() = dark.clone();
+    app.update();
+    assert_eq!(base_color(&app), dimmed(dark.color(ThemeColor::Layer)));
+
+    app.world.get_mut::<Highlight>(layer).unwrap().dimmed = false;
+    app.update();
+    assert_eq!(base_color(&app), dark.color(ThemeColor::Layer));
+}
@@ -0,0 +1,32 @@
+use std::collections::HashMap;
+use bevy::prelude::Vec2;
+use ndarray::{ArrayD, IxDyn};
+use crate::metrics::network_metrics::HistoricalData;
+use crate::ui_components::tooltip::{format_values, nearest_point};
+
+#[test]
+fn test_nearest_point() {
+    let points = vec![Some(Vec2::new(0.0, 0.0)), None, Some(Vec2::new(10.0, 10.0)), Some(Vec2::new(4.0, 5.0))];
+    assert_eq!(nearest_point(&points, Vec2::new(6.0, 6.0)).map(|(index, _)| index), Some(3));
+    assert_eq!(nearest_point(&points, Vec2::new(-1.0, 0.0)).map(|(index, _)| index), Some(0));
+    assert!(nearest_point(&vec![None], Vec2::ZERO).is_none());
+}
+
+#[test]
+fn test_format_values() {
+    assert_eq!(format_values(&vec![1.0, 0.5]), "1.0000, 0.5000");
+    assert_eq!(format_values(&vec![1.0; 6]), "1.0000, 1.0000, 1.0000, 1.0000, ...");
+}
+
+#[test]
+fn test_historical_values() {
+    let labels = HashMap::from([("loss".to_string(), 0), ("variance".to_string(), 1)]);
+    let mut historical = HistoricalData::new(vec![2, 3], labels);
+    historical.extend(ArrayD::from_shape_vec(IxDyn(&[2, 3]), vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]).unwrap(), 1);
+    historical.extend(ArrayD::from_shape_vec(IxDyn(&[2, 3]), vec![0.5, 1.5, 2.5, 3.5, 4.5, 5.5]).unwrap(), 2);
+    assert_eq!(historical.value_at("loss", 1, 1), Some(2.0));
+    assert_eq!(historical.value_at("variance", 2, 2), Some(5.5));
+    assert_eq!(historical.value_at("variance", 2, 3), None);
+    assert_eq!(historical.latest_values("loss"), Some((2, vec![0.5, 1.5, 2.5])));
+    assert_eq!(historical.retrieve_series("variance"), vec![(1, vec![4.0, 5.0, 6.0]), (2, vec![3.5, 4.5, 5.5])]);
+}
@@ -1,5 +1,5 @@
 use std::collections::{HashMap, HashSet};
-use crate::util::add_or_insert;
+use crate::util::{add_or_insert, file_name, fuzzy_score, glob_match};
 
 #[test]
 fn test_utils() {
@@ -10,4 +10,34 @@ fn test_utils() {
     this_hashset.insert("another".to_string());
     let compare_hashset = grouped.get("key").unwrap().clone();
     assert_eq!(compare_hashset, this_hashset);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_glob_match() {
+    assert!(glob_match("*-loss-*", "node-loss-1"));
+    assert!(glob_match("*", "anything"));
+    assert!(glob_match("loss", "loss"));
+    assert!(!glob_match("loss-*", "node-loss-1"));
+    assert!(!glob_match("a*a", "a"));
+}
+
+#[test]
+fn test_fuzzy_score() {
+    assert_eq!(fuzzy_score("", "Variance"), Some(0));
+    assert!(fuzzy_score("lv", "Layer Metrics > Variance").is_some());
+    assert!(fuzzy_score("vl", "Layer Metrics > Variance").is_none());
+    assert!(fuzzy_score("VAR", "variance").is_some());
+    assert!(fuzzy_score("var", "Variance") > fuzzy_score("var", "Layer Metrics > Variance"));
+    assert!(fuzzy_score("var", "Variance") > fuzzy_score("var", "Very Area Ratio"));
+    assert!(fuzzy_score("lm", "Layer Metrics") > fuzzy_score("lm", "Lamp"));
+}
+
+#[test]
+fn test_file_name() {
+    assert_eq!(file_name("run 1/loss"), "run_1_loss");
+    assert_eq!(file_name("run_1.loss-2"), "run_1.loss-2");
+    assert_eq!(file_name("."), "_");
+    assert_eq!(file_name(".."), "__");
+    assert_eq!(file_name(""), "_");
+    assert_eq!(file_name("../notes"), ".._notes");
+}
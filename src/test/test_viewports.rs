@@ -0,0 +1,98 @@
+use bevy::math::{Rect, UVec2, Vec2};
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+use crate::camera::viewports::{assign_render_layers, graph_layers, NETWORK_LAYER, physical_viewport, subject_layers, viewport_at};
+use crate::menu::config_menu_event::interaction_config_event_writer::{GraphMenuResultBuilder, NetworkMenuResultBuilder};
+use crate::config::viewport::{MAX_GRAPH_VIEWPORTS, ViewportConfiguration, ViewportLayout, ViewportSubject};
+
+#[test]
+fn test_layouts_cover_window() {
+    for layout in ViewportLayout::all() {
+        let viewports = layout.viewports();
+        let area = viewports.iter()
+            .map(|(rect, _)| rect.width() * rect.height())
+            .sum::<f32>();
+        assert!((area - 1.0).abs() < 0.001, "{:?}", layout);
+        for (i, (first, _)) in viewports.iter().enumerate() {
+            for (second, _) in viewports.iter().skip(i + 1) {
+                assert!(first.intersect(*second).is_empty(), "{:?}", layout);
+            }
+        }
+    }
+    assert_eq!(ViewportLayout::GraphGrid.viewports().len(), MAX_GRAPH_VIEWPORTS);
+    assert_eq!(ViewportLayout::GraphGrid.next(), ViewportLayout::Single);
+}
+
+#[test]
+fn test_viewport_at() {
+    let window = Vec2::new(800.0, 600.0);
+    assert_eq!(viewport_at(ViewportLayout::Single, Vec2::new(700.0, 500.0), window), Some(0));
+    assert_eq!(viewport_at(ViewportLayout::NetworkAndGraphs, Vec2::new(100.0, 300.0), window), Some(0));
+    assert_eq!(viewport_at(ViewportLayout::NetworkAndGraphs, Vec2::new(700.0, 300.0), window), Some(1));
+    assert_eq!(viewport_at(ViewportLayout::GraphGrid, Vec2::new(100.0, 500.0), window), Some(2));
+    assert_eq!(viewport_at(ViewportLayout::GraphGrid, Vec2::new(900.0, 500.0), window), None);
+    assert_eq!(viewport_at(ViewportLayout::GraphGrid, Vec2::ZERO, Vec2::ZERO), None);
+}
+
+#[test]
+fn test_physical_viewport() {
+    let viewport = physical_viewport(Rect::new(0.5, 0.0, 1.0, 1.0), UVec2::new(1601, 900));
+    assert_eq!(viewport.physical_position, UVec2::new(801, 0));
+    assert_eq!(viewport.physical_size, UVec2::new(800, 900));
+    assert_eq!(physical_viewport(Rect::new(0.0, 0.0, 0.5, 0.5), UVec2::ZERO).physical_size, UVec2::ONE);
+}
+
+#[test]
+fn test_render_layers() {
+    let network = subject_layers(ViewportSubject::Network);
+    let graphs = subject_layers(ViewportSubject::Graphs);
+    let first_graph = subject_layers(ViewportSubject::Graph(0));
+    let second_graph = subject_layers(ViewportSubject::Graph(1));
+    assert!(!network.intersects(&graph_layers(None)));
+    assert!(graphs.intersects(&graph_layers(Some(0))));
+    assert!(graphs.intersects(&graph_layers(Some(MAX_GRAPH_VIEWPORTS))));
+    assert!(first_graph.intersects(&graph_layers(Some(0))));
+    assert!(!first_graph.intersects(&graph_layers(Some(1))));
+    assert!(second_graph.intersects(&graph_layers(None)));
+    assert!(subject_layers(ViewportSubject::All).intersects(&graph_layers(Some(MAX_GRAPH_VIEWPORTS))));
+}
+
+#[test]
+fn test_viewport_configuration() {
+    let config = toml::from_str::<ViewportConfiguration>("layout = \"GraphGrid\"").unwrap();
+    assert_eq!(config.layout, ViewportLayout::GraphGrid);
+    assert_eq!(toml::from_str::<ViewportConfiguration>("").unwrap().layout, ViewportLayout::Single);
+}
+
+#[test]
+fn test_assign_render_layers_to_added_subtrees() {
+    let mut app = App::new();
+    app.init_resource::<NetworkMenuResultBuilder>()
+        .init_resource::<GraphMenuResultBuilder>()
+        .add_system(assign_render_layers);
+    let node = app.world.spawn_empty().id();
+    let layer = app.world.spawn_empty().push_children(&[node]).id();
+    let network = app.world.spawn_empty().push_children(&[layer]).id();
+    let first_series = app.world.spawn_empty().id();
+    let first_graph = app.world.spawn_empty().push_children(&[first_series]).id();
+    let second_series = app.world.spawn_empty().id();
+    let second_graph = app.world.spawn_empty().push_children(&[second_series]).id();
+    let graph_parent = app.world.spawn_empty().push_children(&[first_graph, second_graph]).id();
+    app.world.resource_mut::<NetworkMenuResultBuilder>().network_parent_entity = Some(network);
+    app.world.resource_mut::<GraphMenuResultBuilder>().graph_parent_entity = Some(graph_parent);
+    app.update();
+
+    let layers = |app: &App, entity: Entity| app.world.get::<RenderLayers>(entity).copied();
+    assert_eq!(layers(&app, node), Some(RenderLayers::layer(NETWORK_LAYER)));
+    assert_eq!(layers(&app, graph_parent), Some(graph_layers(None)));
+    assert_eq!(layers(&app, first_series), Some(graph_layers(Some(0))));
+    assert_eq!(layers(&app, second_series), Some(graph_layers(Some(1))));
+
+    app.world.entity_mut(second_series).remove::<RenderLayers>();
+    let added = app.world.spawn_empty().id();
+    app.world.entity_mut(layer).push_children(&[added]);
+    app.update();
+
+    assert_eq!(layers(&app, added), Some(RenderLayers::layer(NETWORK_LAYER)));
+    assert_eq!(layers(&app, second_series), None);
+}
@@ -0,0 +1,46 @@
+use std::env;
+use std::fs;
+use crate::menu::menu_definition::MenuDefinition;
+use crate::workspace::{CameraPose, GraphedMetric, list_workspaces, menu_options, next_workspace_name, OptionState, Workspace};
+
+#[test]
+fn test_write_read_workspace() {
+    let dir = env::temp_dir().join("nn-fe-test-workspaces");
+    let _ = fs::remove_dir_all(&dir);
+    let dir = dir.to_str().unwrap();
+    let workspace = Workspace {
+        expanded_menus: vec![1.0],
+        options: vec![OptionState { id: 2.0, selected: true }],
+        graph_visible: Some(true),
+        network_visible: None,
+        camera: Some(CameraPose { translation: [50.0, 50.0, 100.0], rotation: [0.0, 0.0, 0.0, 1.0], pitch: -20.0, yaw: 30.0 }),
+        graphed_metrics: vec![GraphedMetric {
+            run_id: "default".to_string(),
+            metric_name: "loss".to_string(),
+            strategy: "Radial".to_string(),
+            visible: false,
+        }],
+    };
+    assert!(workspace.write(dir, "analysis").is_ok());
+    assert!(Workspace::default().write(dir, "empty").is_ok());
+    assert_eq!(Workspace::read(dir, "analysis").unwrap(), workspace);
+    assert!(Workspace::read(dir, "missing").is_err());
+    assert_eq!(list_workspaces(dir), vec!["analysis".to_string(), "empty".to_string()]);
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn test_next_workspace_name() {
+    assert_eq!(next_workspace_name(&vec![]), "workspace-1");
+    assert_eq!(next_workspace_name(&vec!["default".to_string(), "workspace-1".to_string()]), "workspace-2");
+}
+
+#[test]
+fn test_menu_options() {
+    let menu_data = MenuDefinition::read("resources/menu.toml")
+        .and_then(|menu_definition| menu_definition.to_menu_data())
+        .unwrap();
+    let menus = menu_options(&menu_data);
+    assert!(!menus.is_empty());
+    assert!(menus.iter().all(|(menu_id, option_ids)| !option_ids.contains(menu_id)));
+}
@@ -0,0 +1,80 @@
+use bevy::prelude::*;
+use crate::config::theme::{Theme, ThemeColor};
+
+/// The colors of the layers and nodes that do not match are scaled by this while searching.
+pub const DIM_FACTOR: f32 = 0.25;
+
+/// What a Layer or Node is highlighted for, and the colors of its material when it is not. The
+/// alerts, the selection set and the search each only set their own flag, and the material is
+/// colored from all of them in apply_highlights, so that they can be set and cleared in any order.
+#[derive(Component, Clone, Debug, PartialEq)]
+pub struct Highlight {
+    pub(crate) base_color: Color,
+    pub(crate) emissive: Color,
+    pub(crate) alert: bool,
+    pub(crate) selected: bool,
+    pub(crate) search_match: bool,
+    pub(crate) dimmed: bool
+}
+
+impl Highlight {
+    pub(crate) fn new(material: &StandardMaterial) -> Self {
+        Self {
+            base_color: material.base_color,
+            emissive: material.emissive,
+            alert: false,
+            selected: false,
+            search_match: false,
+            dimmed: false
+        }
+    }
+
+    /// The base color, dimmed while the entity does not match the search.
+    pub(crate) fn highlighted_base_color(&self) -> Color {
+        if self.dimmed {
+            dimmed(self.base_color)
+        } else {
+            self.base_color
+        }
+    }
+
+    /// The emissive color of the highest priority highlight: an alert, then the selection, then a
+    /// search match, or the base emissive color, dimmed like the base color, when there is none.
+    pub(crate) fn highlighted_emissive(&self, theme: &Theme) -> Color {
+        if self.alert {
+            theme.color(ThemeColor::Alert)
+        } else if self.selected {
+            theme.color(ThemeColor::Selection)
+        } else if self.search_match {
+            theme.color(ThemeColor::SearchMatch)
+        } else if self.dimmed {
+            dimmed(self.emissive)
+        } else {
+            self.emissive
+        }
+    }
+}
+
+/// The color scaled towards black, keeping the alpha.
+pub(crate) fn dimmed(color: Color) -> Color {
+    let [r, g, b, a] = color.as_rgba_f32();
+    Color::rgba(r * DIM_FACTOR, g * DIM_FACTOR, b * DIM_FACTOR, a)
+}
+
+/// Colors the material of each Layer and Node whose highlight or theme changed.
+pub(crate) fn apply_highlights(
+    theme: Res<Theme>,
+    highlights: Query<(Ref<Highlight>, &Handle<StandardMaterial>)>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for (highlight, handle) in highlights.iter() {
+        if !theme.is_changed() && !highlight.is_changed() {
+            continue;
+        }
+        materials.get_mut(handle)
+            .map(|material| {
+                material.base_color = highlight.highlighted_base_color();
+                material.emissive = highlight.highlighted_emissive(&theme);
+            });
+    }
+}
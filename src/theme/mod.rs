@@ -0,0 +1,146 @@
+use std::fs;
+use std::path::Path;
+use bevy::prelude::*;
+use crate::config::ConfigurationProperties;
+use crate::config::keymap::KeyAction;
+use crate::config::theme::{Theme, ThemeConfiguration, ThemePreset};
+use crate::shortcuts::keymap::KeyActionEvent;
+use crate::theme::highlight::apply_highlights;
+use crate::theme::restyle::{restyle_backgrounds, restyle_clear_color, restyle_materials, restyle_polylines, restyle_text, ThemeFonts};
+
+/// The highlights of the Layers and Nodes, and the system coloring their materials from them.
+pub(crate) mod highlight;
+/// The components marking the role an entity is colored by, and the systems that restyle the
+/// entities when the theme changes.
+pub(crate) mod restyle;
+
+/// The presets, followed by the themes read from the theme directory, and the one in use.
+#[derive(Resource, Debug)]
+pub struct Themes {
+    pub(crate) themes: Vec<Theme>,
+    pub(crate) current: usize
+}
+
+impl Default for Themes {
+    fn default() -> Self {
+        Self {
+            themes: ThemePreset::all().into_iter().map(Theme::preset).collect(),
+            current: 0
+        }
+    }
+}
+
+impl Themes {
+    /// The theme files that cannot be read are logged and skipped, and the configured theme is
+    /// the current theme, or the first preset when there is no theme with its name.
+    pub(crate) fn load(config: &ThemeConfiguration) -> Themes {
+        let mut themes = Themes::default();
+        themes.themes.extend(read_themes(&config.dir));
+        themes.current = themes.find(&config.name)
+            .or_else(|| {
+                error!("No theme named {}, using {}.", &config.name, ThemePreset::Light.name());
+                Some(0)
+            })
+            .unwrap();
+        themes
+    }
+
+    pub(crate) fn find(&self, name: &str) -> Option<usize> {
+        self.themes.iter().position(|theme| theme.matches(name))
+    }
+
+    pub(crate) fn current(&self) -> &Theme {
+        &self.themes[self.current]
+    }
+
+    pub(crate) fn names(&self) -> Vec<String> {
+        self.themes.iter().map(|theme| theme.name.clone()).collect()
+    }
+}
+
+/// The themes in the `.toml` files of the directory, sorted by file name.
+pub(crate) fn read_themes(dir: &str) -> Vec<Theme> {
+    let mut paths = fs::read_dir(dir)
+        .map(|entries| entries
+            .flat_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().map(|extension| extension == "toml").or(Some(false)).unwrap())
+            .collect::<Vec<_>>()
+        )
+        .or(Ok::<Vec<_>, std::io::Error>(vec![]))
+        .unwrap();
+    paths.sort();
+    paths.iter()
+        .flat_map(|path| Theme::read(Path::new(path))
+            .or_else(|e| {
+                error!("{}", e);
+                Err(e)
+            })
+            .ok()
+        )
+        .collect()
+}
+
+/// Sent to switch to the theme with the name.
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct SetTheme(pub String);
+
+pub struct ThemePlugin;
+
+impl Plugin for ThemePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SetTheme>()
+            .add_event::<KeyActionEvent>()
+            .init_resource::<Themes>()
+            .init_resource::<Theme>()
+            .init_resource::<ThemeFonts>()
+            .init_resource::<ClearColor>()
+            .add_startup_system(load_themes)
+            .add_system(switch_theme)
+            .add_systems(PostUpdate, (
+                restyle_clear_color,
+                restyle_backgrounds,
+                restyle_text,
+                restyle_materials,
+                restyle_polylines,
+                apply_highlights.after(restyle_materials)
+            ));
+    }
+}
+
+pub(crate) fn load_themes(
+    config: Res<ConfigurationProperties>,
+    mut themes: ResMut<Themes>,
+    mut theme: ResMut<Theme>,
+) {
+    *themes = Themes::load(&config.theme);
+    if theme.as_ref() != themes.current() {
+        *theme = themes.current().clone();
+    }
+    info!("Using theme {} of {:?}.", &theme.name, themes.names());
+}
+
+/// The cycle theme key switches to the next theme, and a SetTheme event to the theme named.
+pub(crate) fn switch_theme(
+    mut key_events: EventReader<KeyActionEvent>,
+    mut set_events: EventReader<SetTheme>,
+    mut themes: ResMut<Themes>,
+    mut theme: ResMut<Theme>,
+) {
+    let mut next = None;
+    if key_events.iter().any(|KeyActionEvent(action)| *action == KeyAction::CycleTheme) {
+        next = Some((themes.current + 1) % themes.themes.len());
+    }
+    for SetTheme(name) in set_events.iter() {
+        next = themes.find(name)
+            .or_else(|| {
+                error!("No theme named {}.", name);
+                next
+            });
+    }
+    if let Some(next) = next.filter(|next| *next != themes.current) {
+        themes.current = next;
+        *theme = themes.current().clone();
+        info!("Switched to theme {}.", &theme.name);
+    }
+}
@@ -0,0 +1,160 @@
+use bevy::prelude::*;
+use bevy_polyline::prelude::PolylineMaterial;
+use crate::config::theme::{Theme, ThemeColor};
+use crate::theme::highlight::Highlight;
+
+/// The background of a UI node is the color of the role. Systems that change the state of a node,
+/// such as selecting it, change the role rather than the color.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct ThemedBackground(pub ThemeColor);
+
+/// The sections of the text are the color of the role, in the font of the theme.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct ThemedText(pub ThemeColor);
+
+/// The font of a section of text: the default font, the font of the theme, or its monospace font.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ThemeFont {
+    Regular,
+    Bold,
+    Mono
+}
+
+/// The role of each section of a text whose sections are styled differently, such as a document
+/// with links and code, so that each section keeps its own color and font when restyled.
+#[derive(Component, Clone, Debug, PartialEq)]
+pub struct ThemedSections(pub Vec<(ThemeColor, ThemeFont)>);
+
+/// The base color of the material of a mesh is the color of the role.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct ThemedMaterial(pub ThemeColor);
+
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct ThemedPolyline(pub ThemeColor);
+
+/// The fonts of the current theme.
+#[derive(Resource, Default, Debug)]
+pub struct ThemeFonts {
+    pub(crate) font: Handle<Font>,
+    pub(crate) mono_font: Handle<Font>
+}
+
+impl ThemeFonts {
+    pub(crate) fn font(&self, font: ThemeFont) -> Handle<Font> {
+        match font {
+            ThemeFont::Regular => Handle::default(),
+            ThemeFont::Bold => self.font.clone(),
+            ThemeFont::Mono => self.mono_font.clone()
+        }
+    }
+}
+
+pub(crate) fn restyle_clear_color(theme: Res<Theme>, mut clear_color: ResMut<ClearColor>) {
+    if theme.is_changed() {
+        clear_color.0 = theme.color(ThemeColor::Background);
+    }
+}
+
+/// The entities are restyled when the theme changes, and when they are spawned or their role
+/// changes. This runs after the update, so entities spawned in the update are styled before they
+/// are first rendered.
+pub(crate) fn restyle_backgrounds(
+    theme: Res<Theme>,
+    mut backgrounds: Query<(Ref<ThemedBackground>, &mut BackgroundColor)>,
+) {
+    for (themed, mut background) in backgrounds.iter_mut() {
+        if theme.is_changed() || themed.is_changed() {
+            *background = BackgroundColor(theme.color(themed.0));
+        }
+    }
+}
+
+/// Text with a ThemedText role is restyled as a whole, and text with ThemedSections section by
+/// section. Sections without a role are left as they were styled.
+pub(crate) fn restyle_text(
+    theme: Res<Theme>,
+    asset_server: Res<AssetServer>,
+    mut fonts: ResMut<ThemeFonts>,
+    mut texts: Query<(Option<Ref<ThemedText>>, Option<Ref<ThemedSections>>, &mut Text), Or<(With<ThemedText>, With<ThemedSections>)>>,
+) {
+    if theme.is_changed() {
+        fonts.font = asset_server.load(theme.font.as_str());
+        fonts.mono_font = asset_server.load(theme.mono_font.as_str());
+    }
+    for (themed, sections, mut text) in texts.iter_mut() {
+        let changed = themed.as_ref().filter(|themed| themed.is_changed()).is_some()
+            || sections.as_ref().filter(|sections| sections.is_changed()).is_some();
+        if !theme.is_changed() && !changed {
+            continue;
+        }
+        match (sections, themed) {
+            (Some(sections), _) => {
+                text.sections.iter_mut()
+                    .zip(sections.0.iter())
+                    .for_each(|(section, (color, font))| {
+                        section.style.color = theme.color(*color);
+                        section.style.font = fonts.font(*font);
+                    });
+            }
+            (None, Some(themed)) => {
+                let color = theme.color(themed.0);
+                text.sections.iter_mut()
+                    .for_each(|section| {
+                        section.style.color = color;
+                        section.style.font = fonts.font.clone();
+                    });
+            }
+            (None, None) => {}
+        }
+    }
+}
+
+/// The emissive color is only restyled when it is the color of the previous theme. The colors of
+/// a Layer or Node are restyled in its Highlight, and its material colored from it in
+/// apply_highlights.
+pub(crate) fn restyle_materials(
+    theme: Res<Theme>,
+    mut previous: Local<Option<Theme>>,
+    mut themed: Query<(Ref<ThemedMaterial>, &Handle<StandardMaterial>, Option<&mut Highlight>)>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for (themed, handle, highlight) in themed.iter_mut() {
+        if !theme.is_changed() && !themed.is_changed() {
+            continue;
+        }
+        let color = theme.color(themed.0);
+        let previous_color = previous.as_ref().map(|previous| previous.color(themed.0));
+        let restyle = |base_color: &mut Color, emissive: &mut Color| {
+            *base_color = color;
+            if previous_color.filter(|previous_color| *previous_color == *emissive).is_some() {
+                *emissive = color;
+            }
+        };
+        match highlight {
+            Some(mut highlight) => {
+                let highlight = &mut *highlight;
+                restyle(&mut highlight.base_color, &mut highlight.emissive);
+            }
+            None => {
+                materials.get_mut(handle)
+                    .map(|material| restyle(&mut material.base_color, &mut material.emissive));
+            }
+        }
+    }
+    if theme.is_changed() {
+        *previous = Some(theme.clone());
+    }
+}
+
+pub(crate) fn restyle_polylines(
+    theme: Res<Theme>,
+    themed: Query<(Ref<ThemedPolyline>, &Handle<PolylineMaterial>)>,
+    mut materials: ResMut<Assets<PolylineMaterial>>,
+) {
+    for (themed, handle) in themed.iter() {
+        if theme.is_changed() || themed.is_changed() {
+            materials.get_mut(handle)
+                .map(|material| material.color = theme.color(themed.0));
+        }
+    }
+}
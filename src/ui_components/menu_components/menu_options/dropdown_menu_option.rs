@@ -1,4 +1,6 @@
 use bevy::prelude::*;
+use crate::config::theme::{DEFAULT_FONT, ThemeColor};
+use crate::theme::restyle::{ThemedBackground, ThemedText};
 use crate::menu::{ConfigurationOptionEnum, MenuItemMetadata, MenuOption, SelectableType, UiComponent};
 use crate::menu::ui_menu_event::transition_groups::{PropagateDisplay, PropagateSelect, PropagateVisible};
 use crate::ui_components::menu_components::{add_config_opt, BuilderResult, get_parent_entity, get_swing_out};
@@ -114,9 +116,9 @@ impl <'a> DropdownMenuOptionBuilder<'a> {
                     ..default()
                 },
                 z_index: ZIndex::Global(100),
-                background_color: BackgroundColor(Color::BLACK),
                 ..default()
             },
+            ThemedBackground(ThemeColor::MenuOption),
             PropagateSelect::default(),
             PropagateDisplay::default(),
             self.id_component.clone(),
@@ -134,9 +136,9 @@ impl <'a> DropdownMenuOptionBuilder<'a> {
                     width: Val::Percent(10.0),
                     ..default()
                 },
-                background_color: BackgroundColor(Color::GREEN),
                 ..default()
             },
+            ThemedBackground(ThemeColor::MenuCheckmark),
             UiComponent::MenuOptionCheckmark,
             PropagateSelect::default(),
             self.id_component.clone()
@@ -154,12 +156,12 @@ impl <'a> DropdownMenuOptionBuilder<'a> {
                 },
                 text: Text::from_section(self.menu_option.metadata.name.to_string(), TextStyle {
                     font_size: 16.0,
-                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
-                    color: Color::BLACK,
+                    font: asset_server.load(DEFAULT_FONT),
                     ..default()
                 }),
                 ..default()
             },
+            ThemedText(ThemeColor::MenuText),
             Label,
             PropagateDisplay::default(),
             self.id_component.clone()
@@ -176,9 +178,9 @@ impl <'a> DropdownMenuOptionBuilder<'a> {
                     width: height_width,
                     ..default()
                 },
-                background_color: BackgroundColor(Color::BLUE),
                 ..default()
             },
+            ThemedBackground(ThemeColor::MenuButton),
             Label,
             PropagateDisplay::default(),
             self.id_component.clone()
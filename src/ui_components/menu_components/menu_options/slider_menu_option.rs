@@ -1,21 +1,26 @@
-use std::marker::PhantomData;
+use std::fmt::Debug;
 use bevy::prelude::*;
-use crate::event::event_state::StyleStateChangeEventData::ChangeComponentStyle;
-use crate::menu::{ConfigurationOptionEnum, DraggableComponent, MenuInputType, MenuItemMetadata, MenuOption, Slider, SliderKnob, UiComponent};
-use crate::menu::ui_menu_event::change_style::UiChangeTypes;
-use crate::menu::ui_menu_event::next_action::UiComponentState;
+use bevy::ui::{FocusPolicy, RelativeCursorPosition};
+use crate::config::theme::{DEFAULT_FONT, ThemeColor};
+use crate::theme::restyle::{ThemedBackground, ThemedText};
+use crate::event::event_descriptor::EventDescriptor;
+use crate::menu::{ConfigurationOptionEnum, DataType, DraggableComponent, MenuInputType, MenuItemMetadata, MenuOption, MetricsConfigurationOption, Slider, SliderData, SliderKnob, UiComponent};
+use crate::menu::config_menu_event::config_event::ConfigurationOptionEventArgs;
+use crate::menu::config_menu_event::interaction_config_event_writer::create_config_option_event;
 use crate::ui_components::menu_components::BuilderResult;
 use crate::ui_components::ui_menu_component::{insert_config_option, UiIdentifiableComponent};
 
+pub const SLIDER_KNOB_WIDTH: f32 = 30.0;
+
 pub struct SliderMenuOptionBuilder<'a> {
     pub(crate) parent: Option<Entity>,
-    menu_option: &'a MenuOption,
-    config_option: &'a ConfigurationOptionEnum,
-    parents: &'a Vec<MenuItemMetadata>,
-    menu_input_type: Option<&'a MenuInputType>,
-    menu_option_component: UiComponent,
-    metadata: MenuItemMetadata,
-    id_component: UiIdentifiableComponent,
+    pub(crate) menu_option: Option<&'a MenuOption>,
+    pub(crate) config_option: &'a ConfigurationOptionEnum,
+    pub(crate) parents: Vec<MenuItemMetadata>,
+    pub(crate) menu_input_type: Option<&'a MenuInputType>,
+    pub(crate) slider_data: SliderData,
+    pub(crate) metadata: MenuItemMetadata,
+    pub(crate) id_component: UiIdentifiableComponent,
 }
 
 #[derive(Clone, Debug)]
@@ -53,47 +58,79 @@ impl <'a> SliderMenuOptionBuilder<'a> {
 
         let mut slider_entity = commands.spawn(self.spawn_base());
 
+        insert_config_option(self.config_option, &mut slider_entity);
+
         slider_entity.push_children(vec![text_entity, slider_knob].as_slice());
 
         let slider_entity = slider_entity.id();
 
+        self.parent.map(|parent| commands.get_entity(parent)
+            .as_mut()
+            .map(|parent| parent.add_child(slider_entity))
+        );
+
         SliderMenuOptionResult::new(slider_knob, text_entity, slider_entity)
     }
 
+    /// The initial value is the value of the configuration option, or the initial value of the
+    /// metadata, or the start of the range.
+    fn initial_value(&self) -> f32 {
+        match self.config_option.data_type() {
+            DataType::Number(Some(value)) => *value,
+            _ => self.metadata.initial_value.parse::<f32>().ok()
+                .or(Some(self.slider_data.start))
+                .unwrap()
+        }
+    }
+
+    fn slider_component(&self) -> Slider {
+        Slider {
+            name: self.metadata.name.clone(),
+            slider_data: self.slider_data.clone(),
+            value: self.slider_data.snap(self.initial_value()),
+        }
+    }
+
     pub(crate) fn spawn_base(&self) -> impl Bundle {
         (
             NodeBundle {
                 style: Style {
                     display: Display::Flex,
+                    width: Val::Percent(100.0),
+                    height: Val::Px(SLIDER_KNOB_WIDTH),
                     ..default()
                 },
-                background_color: BackgroundColor(Color::GREEN),
                 ..default()
             },
-            UiIdentifiableComponent(20.0),
+            ThemedBackground(ThemeColor::SliderTrack),
+            self.id_component.clone(),
             DraggableComponent::default(),
-            self.menu_option_component.clone(),
+            Interaction::default(),
+            RelativeCursorPosition::default(),
+            UiComponent::SlideComponent,
+            self.slider_component(),
             Label,
         )
     }
 
     pub(crate) fn spawn_text_value(&self, mut asset_server: &mut Res<AssetServer>) -> impl Bundle {
+        let slider = self.slider_component();
         (
             TextBundle {
                 style: Style {
                     display: Display::Flex,
-                    width: Val::Percent(100.0),
-                    height: Val::Px(50.0),
+                    position_type: PositionType::Absolute,
                     ..default()
                 },
-                text: Text::from_section(self.metadata.initial_value.clone(), TextStyle {
+                text: Text::from_section(slider_text(&slider), TextStyle {
                     font_size: 16.0,
-                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
-                    color: Color::BLACK,
+                    font: asset_server.load(DEFAULT_FONT),
                     ..default()
                 }),
+                focus_policy: FocusPolicy::Pass,
                 ..default()
             },
+            ThemedText(ThemeColor::MenuText),
             Label,
             self.id_component.clone()
         )
@@ -104,18 +141,77 @@ impl <'a> SliderMenuOptionBuilder<'a> {
             ButtonBundle {
                 style: Style {
                     display: Display::Flex,
-                    left: Val::Px(30.0),
-                    height: Val::Px(30.0),
-                    width: Val::Px(30.0),
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(0.0),
+                    height: Val::Px(SLIDER_KNOB_WIDTH),
+                    width: Val::Px(SLIDER_KNOB_WIDTH),
                     ..default()
                 },
-                background_color: BackgroundColor(Color::ORANGE),
+                focus_policy: FocusPolicy::Pass,
                 ..default()
             },
+            ThemedBackground(ThemeColor::SliderHandle),
             UiComponent::SliderKnob(SliderKnob::default()),
             DraggableComponent::default(),
-            UiIdentifiableComponent(20.0)
+            self.id_component.clone()
         )
     }
 
 }
+
+pub(crate) fn slider_text(slider: &Slider) -> String {
+    format!("{}: {}", slider.name, slider.slider_data.format(slider.value))
+}
+
+/// Pressing or dragging on the slider sets the value at the cursor.
+pub(crate) fn drag_slider(
+    mut sliders: Query<(&Interaction, &RelativeCursorPosition, &mut Slider)>,
+) {
+    for (interaction, cursor, mut slider) in sliders.iter_mut() {
+        if !matches!(interaction, Interaction::Pressed) {
+            continue;
+        }
+        if let Some(value) = cursor.normalized.map(|cursor| slider.slider_data.value_at(cursor.x)) {
+            if value != slider.value {
+                slider.value = value;
+            }
+        }
+    }
+}
+
+/// Places the knob and shows the value when the value of the slider changes.
+pub(crate) fn update_slider(
+    sliders: Query<(&Slider, &Node, &Children), Changed<Slider>>,
+    mut knobs: Query<&mut Style, With<DraggableComponent>>,
+    mut text: Query<&mut Text>,
+) {
+    for (slider, node, children) in sliders.iter() {
+        let fraction = slider.slider_data.fraction_of(slider.value);
+        let left = fraction * (node.size().x - SLIDER_KNOB_WIDTH).max(0.0);
+        children.iter()
+            .for_each(|child| {
+                let _ = knobs.get_mut(*child)
+                    .map(|mut style| style.left = Val::Px(left));
+                let _ = text.get_mut(*child)
+                    .map(|mut text| text.sections[0].value = slider_text(slider));
+            });
+    }
+}
+
+/// The value of the slider replaces the data type of its configuration option, and the change is
+/// sent as a configuration option event.
+pub(crate) fn emit_slider_changes<T>(
+    mut sliders: Query<(Entity, Ref<Slider>, &mut MetricsConfigurationOption<T>)>,
+    mut event_writer: EventWriter<EventDescriptor<DataType, ConfigurationOptionEventArgs<T>, MetricsConfigurationOption<T>>>,
+)
+    where T: Component + Send + Sync + Default + Clone + Debug + 'static
+{
+    for (entity, slider, mut config_option) in sliders.iter_mut() {
+        if !slider.is_changed() || slider.is_added() {
+            continue;
+        }
+        let value = DataType::Number(Some(slider.value));
+        *config_option.data_type_mut() = value.clone();
+        event_writer.send(create_config_option_event(entity, value, config_option.clone()));
+    }
+}
@@ -1,5 +1,7 @@
 use bevy::ecs::system::EntityCommands;
 use bevy::prelude::*;
+use crate::config::theme::{DEFAULT_FONT, ThemeColor};
+use crate::theme::restyle::{ThemedBackground, ThemedText};
 use crate::menu::{ConfigurationOptionEnum, DropdownName, DropdownSelected, MenuItemMetadata, ScrollableComponent, SelectableType, UiComponent};
 use crate::menu::ui_menu_event::transition_groups::PropagateDisplay;
 use crate::ui_components::menu_components::BuilderResult;
@@ -99,12 +101,12 @@ impl<'a> BaseMenu<'a> {
                 },
                 text: Text::from_section(self.menu_metadata.name.clone(), TextStyle {
                     font_size: 16.0,
-                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
-                    color: Color::BLACK,
+                    font: asset_server.load(DEFAULT_FONT),
                     ..default()
                 }),
                 ..default()
             },
+            ThemedText(ThemeColor::MenuText),
             Label,
             UiIdentifiableComponent(self.menu_metadata.id),
         )
@@ -139,16 +141,18 @@ impl<'a> BaseMenu<'a> {
                     flex_direction: FlexDirection::Column,
                     align_self: AlignSelf::Start,
                     width: Val::Percent(100.0),
-                    height: Val::Px(40.0),
+                    height: self.component.starting_height(),
+                    max_height: self.component.max_height(),
+                    overflow: self.component.overflow(),
                     left: pos.left,
                     right: pos.right,
                     top: pos.top,
                     bottom: pos.bottom,
                     ..default()
                 },
-                background_color: BackgroundColor(Color::BLUE),
                 ..default()
             },
+            ThemedBackground(ThemeColor::MenuButton),
             ScrollableComponent::default(),
             PropagateDisplay::default(),
             UiIdentifiableComponent(self.menu_metadata.id),
@@ -1,5 +1,7 @@
 use bevy::prelude::*;
 use bevy::utils::HashMap;
+use crate::config::theme::{DEFAULT_FONT, ThemeColor};
+use crate::theme::restyle::{ThemedBackground, ThemedText};
 use crate::menu::{ConfigurationOptionEnum, MenuItemMetadata, MenuOption, MenuOptionType, UiComponent};
 use crate::menu::ui_menu_event::transition_groups::PropagateDisplay;
 use crate::menu::UiComponent::CollapsableMenu;
@@ -96,9 +98,9 @@ impl <'a> CollapsableMenuBuilder<'a> {
                     justify_content: JustifyContent::Start,
                     ..default()
                 },
-                background_color: BackgroundColor(Color::BLACK),
                 ..default()
             },
+            ThemedBackground(ThemeColor::MenuBackground),
             PropagateDisplay::default(),
             self.menu_component.clone(),
             UiIdentifiableComponent(self.menu_metadata.id),
@@ -115,12 +117,12 @@ impl <'a> CollapsableMenuBuilder<'a> {
                 },
                 text: Text::from_section(self.menu_metadata.name.to_string(), TextStyle {
                     font_size: 16.0,
-                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
-                    color: Color::BLACK,
+                    font: asset_server.load(DEFAULT_FONT),
                     ..default()
                 }),
                 ..default()
             },
+            ThemedText(ThemeColor::MenuText),
             PropagateDisplay::default()
         )
     }
@@ -31,7 +31,7 @@ impl DrawDropdownMenuResult {
             .flat_map(|e| e.menu_option_entity.into_iter())
             .collect::<Vec<Entity>>();
         self.submenu_results.iter()
-            .map(|submenu| submenu.dropdown_menu_result.base_menu_result.base_menu_parent)
+            .flat_map(|submenu| submenu.submenu_entity())
             .for_each(|submenu| menu_options.push(submenu));
         self.slider.iter()
             .for_each(|slider| menu_options.push(slider.slider_entity));
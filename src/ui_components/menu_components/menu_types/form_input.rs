@@ -0,0 +1,330 @@
+use std::fmt::Debug;
+use bevy::prelude::*;
+use crate::event::event_descriptor::EventDescriptor;
+use crate::menu::{ConfigurationOptionEnum, DataType, FormInputComponent, MenuItemMetadata, MetricsConfigurationOption, UiComponent};
+use crate::menu::config_menu_event::config_event::ConfigurationOptionEventArgs;
+use crate::menu::config_menu_event::interaction_config_event_writer::create_config_option_event;
+use crate::menu::ui_menu_event::transition_groups::PropagateDisplay;
+use crate::config::theme::{DEFAULT_FONT, ThemeColor};
+use crate::theme::restyle::{ThemedBackground, ThemedText};
+use crate::ui_components::menu_components::BuilderResult;
+use crate::ui_components::ui_menu_component::{insert_config_option, UiIdentifiableComponent};
+
+pub const FORM_INPUT_CARET: char = '|';
+
+pub struct FormInputBuilder<'a> {
+    pub(crate) parent: Option<Entity>,
+    pub(crate) menu_metadata: &'a MenuItemMetadata,
+    pub(crate) config_option: &'a ConfigurationOptionEnum,
+}
+
+#[derive(Clone, Debug)]
+pub struct DrawFormInputResult {
+    pub(crate) form_input_parent: Entity,
+    pub(crate) label_entity: Entity,
+    pub(crate) form_input_entity: Entity,
+    pub(crate) text_entity: Entity
+}
+
+impl BuilderResult for DrawFormInputResult {}
+
+/// The form input that has keyboard focus, which receives the characters typed.
+#[derive(Resource, Default, Debug)]
+pub struct FormInputFocus {
+    pub(crate) focused: Option<Entity>
+}
+
+/// Sent when enter is pressed in a form input with a valid value.
+#[derive(Event, Debug, Clone)]
+pub struct FormInputCommitted {
+    pub(crate) entity: Entity,
+    pub(crate) value: DataType
+}
+
+impl <'a> FormInputBuilder<'a> {
+    pub(crate) fn build(
+        &self,
+        mut commands: &mut Commands,
+        mut materials: &mut ResMut<Assets<ColorMaterial>>,
+        mut meshes: &mut ResMut<Assets<Mesh>>,
+        mut asset_server: &mut Res<AssetServer>,
+    ) -> DrawFormInputResult {
+        let form_input_parent = commands.spawn(self.form_input_parent()).id();
+        let label_entity = commands.spawn(self.label(&mut asset_server, self.menu_metadata.name.as_str())).id();
+        let text_entity = commands.spawn(self.label(&mut asset_server, self.menu_metadata.initial_value.as_str())).id();
+
+        let mut form_input = commands.spawn(self.form_input_field());
+        insert_config_option(self.config_option, &mut form_input);
+        form_input.add_child(text_entity);
+        let form_input_entity = form_input.id();
+
+        commands.entity(form_input_parent)
+            .push_children(&[label_entity, form_input_entity]);
+
+        self.parent.map(|parent| commands.get_entity(parent)
+            .as_mut()
+            .map(|parent| parent.add_child(form_input_parent))
+        );
+
+        DrawFormInputResult {
+            form_input_parent,
+            label_entity,
+            form_input_entity,
+            text_entity,
+        }
+    }
+
+    fn form_input_component(&self) -> FormInputComponent {
+        let numeric = matches!(self.config_option.data_type(), DataType::Number(_));
+        let value = match self.config_option.data_type() {
+            DataType::Number(Some(number)) => number.to_string(),
+            DataType::String(Some(value)) => value.clone(),
+            _ => self.menu_metadata.initial_value.clone()
+        };
+        let mut form_input = FormInputComponent {
+            caret: value.chars().count(),
+            value,
+            numeric,
+            valid: true,
+        };
+        form_input.valid = form_input.validate().is_some();
+        form_input
+    }
+
+    pub(crate) fn form_input_parent(&self) -> impl Bundle {
+        let component = UiComponent::FormInput;
+        (
+            NodeBundle {
+                style: Style {
+                    display: component.starting_display(),
+                    flex_direction: FlexDirection::Column,
+                    width: Val::Percent(100.0),
+                    ..default()
+                },
+                ..default()
+            },
+            PropagateDisplay::default(),
+            UiIdentifiableComponent(self.menu_metadata.id),
+            component
+        )
+    }
+
+    pub(crate) fn form_input_field(&self) -> impl Bundle {
+        (
+            ButtonBundle {
+                style: Style {
+                    display: Display::Flex,
+                    width: Val::Percent(100.0),
+                    height: Val::Px(30.0),
+                    padding: UiRect::all(Val::Px(4.0)),
+                    ..default()
+                },
+                ..default()
+            },
+            self.form_input_component(),
+            ThemedBackground(ThemeColor::Input),
+            UiIdentifiableComponent(self.menu_metadata.id),
+        )
+    }
+
+    pub(crate) fn label(&self, asset_server: &mut Res<AssetServer>, value: &str) -> impl Bundle {
+        (
+            TextBundle {
+                text: Text::from_section(value.to_string(), TextStyle {
+                    font_size: 16.0,
+                    font: asset_server.load(DEFAULT_FONT),
+                    ..default()
+                }),
+                ..default()
+            },
+            ThemedText(ThemeColor::MenuText),
+            Label
+        )
+    }
+}
+
+impl FormInputComponent {
+
+    /// The value of the input as the data type of the configuration option, if it is valid.
+    pub(crate) fn validate(&self) -> Option<DataType> {
+        let value = self.value.trim();
+        if self.numeric {
+            if value.is_empty() {
+                return Some(DataType::Number(None));
+            }
+            value.parse::<f32>().ok()
+                .filter(|number| number.is_finite())
+                .map(|number| DataType::Number(Some(number)))
+        } else if value.is_empty() {
+            Some(DataType::String(None))
+        } else {
+            Some(DataType::String(Some(value.to_string())))
+        }
+    }
+
+    pub(crate) fn insert_char(&mut self, character: char) {
+        if character.is_control() {
+            return;
+        }
+        if self.numeric && !(character.is_ascii_digit() || matches!(character, '.' | '-' | '+' | 'e' | 'E')) {
+            return;
+        }
+        let index = self.byte_index(self.caret);
+        self.value.insert(index, character);
+        self.caret += 1;
+        self.valid = self.validate().is_some();
+    }
+
+    pub(crate) fn backspace(&mut self) {
+        if self.caret == 0 {
+            return;
+        }
+        self.caret -= 1;
+        let index = self.byte_index(self.caret);
+        self.value.remove(index);
+        self.valid = self.validate().is_some();
+    }
+
+    pub(crate) fn delete(&mut self) {
+        if self.caret >= self.value.chars().count() {
+            return;
+        }
+        let index = self.byte_index(self.caret);
+        self.value.remove(index);
+        self.valid = self.validate().is_some();
+    }
+
+    pub(crate) fn move_caret(&mut self, offset: i32) {
+        let len = self.value.chars().count() as i32;
+        self.caret = (self.caret as i32 + offset).clamp(0, len) as usize;
+    }
+
+    /// The value with the caret inserted, as it is shown when the input has focus.
+    pub(crate) fn display_value(&self, focused: bool) -> String {
+        let mut value = self.value.clone();
+        if focused {
+            value.insert(self.byte_index(self.caret), FORM_INPUT_CARET);
+        }
+        value
+    }
+
+    fn byte_index(&self, caret: usize) -> usize {
+        self.value.char_indices()
+            .nth(caret)
+            .map(|(index, _)| index)
+            .or(Some(self.value.len()))
+            .unwrap()
+    }
+}
+
+/// Clicking a form input focuses it, and clicking anywhere else removes the focus.
+pub(crate) fn focus_form_input(
+    mut focus: ResMut<FormInputFocus>,
+    mouse_button_input: Res<Input<MouseButton>>,
+    form_inputs: Query<(Entity, &Interaction), (Changed<Interaction>, With<FormInputComponent>)>,
+) {
+    let pressed = form_inputs.iter()
+        .find(|(_, interaction)| matches!(interaction, Interaction::Pressed))
+        .map(|(entity, _)| entity);
+    if pressed.is_some() {
+        focus.focused = pressed;
+    } else if mouse_button_input.just_pressed(MouseButton::Left) && focus.focused.is_some() {
+        focus.focused = None;
+    }
+}
+
+pub(crate) fn edit_form_input(
+    mut focus: ResMut<FormInputFocus>,
+    mut characters: EventReader<ReceivedCharacter>,
+    key_input: Res<Input<KeyCode>>,
+    mut form_inputs: Query<&mut FormInputComponent>,
+    mut committed: EventWriter<FormInputCommitted>,
+) {
+    let focused = match focus.focused {
+        Some(focused) => focused,
+        None => {
+            characters.clear();
+            return;
+        }
+    };
+    let form_input = form_inputs.get_mut(focused);
+    if form_input.is_err() {
+        focus.focused = None;
+        return;
+    }
+    let mut form_input = form_input.unwrap();
+
+    for character in characters.iter() {
+        form_input.insert_char(character.char);
+    }
+    if key_input.just_pressed(KeyCode::Back) {
+        form_input.backspace();
+    }
+    if key_input.just_pressed(KeyCode::Delete) {
+        form_input.delete();
+    }
+    if key_input.just_pressed(KeyCode::Left) {
+        form_input.move_caret(-1);
+    }
+    if key_input.just_pressed(KeyCode::Right) {
+        form_input.move_caret(1);
+    }
+    if key_input.just_pressed(KeyCode::Home) {
+        form_input.caret = 0;
+    }
+    if key_input.just_pressed(KeyCode::End) {
+        form_input.caret = form_input.value.chars().count();
+    }
+    if key_input.just_pressed(KeyCode::Return) {
+        let _ = form_input.validate()
+            .map(|value| committed.send(FormInputCommitted { entity: focused, value }))
+            .ok_or_else(|| {
+                error!("Form input value {} is not valid.", form_input.value);
+            });
+    }
+    if key_input.just_pressed(KeyCode::Escape) {
+        focus.focused = None;
+    }
+}
+
+/// The input is styled by the theme as focused or not, and its text as valid or not.
+pub(crate) fn render_form_input(
+    focus: Res<FormInputFocus>,
+    mut form_inputs: Query<(Entity, Ref<FormInputComponent>, &Children, &mut ThemedBackground)>,
+    mut text: Query<(&mut Text, &mut ThemedText)>,
+) {
+    for (entity, form_input, children, mut background) in form_inputs.iter_mut() {
+        if !form_input.is_changed() && !focus.is_changed() {
+            continue;
+        }
+        let focused = focus.focused == Some(entity);
+        background.set_if_neq(ThemedBackground(if focused { ThemeColor::InputFocused } else { ThemeColor::Input }));
+        children.iter()
+            .for_each(|child| {
+                let _ = text.get_mut(*child)
+                    .map(|(mut text, mut themed)| {
+                        text.sections[0].value = form_input.display_value(focused);
+                        themed.set_if_neq(ThemedText(if form_input.valid { ThemeColor::MenuText } else { ThemeColor::InputInvalid }));
+                    });
+            });
+    }
+}
+
+/// The committed value replaces the data type of the configuration option of the form input, and
+/// the change is sent as a configuration option event.
+pub(crate) fn commit_form_input<T>(
+    mut committed: EventReader<FormInputCommitted>,
+    mut config_options: Query<&mut MetricsConfigurationOption<T>, With<FormInputComponent>>,
+    mut event_writer: EventWriter<EventDescriptor<DataType, ConfigurationOptionEventArgs<T>, MetricsConfigurationOption<T>>>,
+)
+    where T: Component + Send + Sync + Default + Clone + Debug + 'static
+{
+    for commit in committed.iter() {
+        let _ = config_options.get_mut(commit.entity)
+            .map(|mut config_option| {
+                *config_option.data_type_mut() = commit.value.clone();
+                info!("Form input committed {:?}.", config_option);
+                event_writer.send(create_config_option_event(commit.entity, commit.value.clone(), config_option.clone()));
+            });
+    }
+}
@@ -2,4 +2,7 @@ pub(crate) mod root_collapsable;
 pub(crate) mod collapsable_menu;
 pub(crate) mod submenu_builder;
 pub(crate) mod base_menu;
-pub(crate) mod dropdown_menu;
\ No newline at end of file
+pub(crate) mod dropdown_menu;
+pub(crate) mod scrollable_menu;
+pub(crate) mod radial_menu;
+pub(crate) mod form_input;
@@ -0,0 +1,222 @@
+use std::fmt::Debug;
+use bevy::prelude::*;
+use crate::event::event_descriptor::EventDescriptor;
+use crate::menu::{ConfigurationOptionEnum, DataType, MenuItemMetadata, MenuOption, MenuOptionType, MetricsConfigurationOption, Radial, RadialButton, RadialButtonSelection, UiComponent};
+use crate::menu::config_menu_event::config_event::ConfigurationOptionEventArgs;
+use crate::menu::config_menu_event::interaction_config_event_writer::create_config_option_event;
+use crate::menu::ui_menu_event::transition_groups::PropagateDisplay;
+use crate::config::theme::{DEFAULT_FONT, ThemeColor};
+use crate::theme::restyle::{ThemedBackground, ThemedText};
+use crate::ui_components::menu_components::{add_config_opt, BuilderResult};
+use crate::ui_components::ui_menu_component::UiIdentifiableComponent;
+
+/// A radio button group. Each option is a button, and selecting one deselects the others.
+pub struct RadialMenuBuilder<'a> {
+    pub(crate) parent: Option<Entity>,
+    pub(crate) menu_metadata: &'a MenuItemMetadata,
+    pub(crate) config_option: &'a ConfigurationOptionEnum,
+    pub(crate) options: &'a Vec<MenuOption>,
+}
+
+#[derive(Clone, Debug)]
+pub struct DrawRadialMenuResult {
+    pub(crate) radial_entity: Entity,
+    pub(crate) text_entity: Entity,
+    pub(crate) radial_buttons: Vec<Entity>,
+    pub(crate) radial_selections: Vec<Entity>
+}
+
+impl BuilderResult for DrawRadialMenuResult {}
+
+impl <'a> RadialMenuBuilder<'a> {
+    pub(crate) fn build(
+        &self,
+        mut commands: &mut Commands,
+        mut materials: &mut ResMut<Assets<ColorMaterial>>,
+        mut meshes: &mut ResMut<Assets<Mesh>>,
+        mut asset_server: &mut Res<AssetServer>,
+    ) -> DrawRadialMenuResult {
+        let selected_index = self.selected_index();
+        let radial_entity = commands.spawn(self.radial_parent(selected_index)).id();
+        let text_entity = commands.spawn(self.text(&mut asset_server, self.menu_metadata.name.as_str())).id();
+        commands.entity(radial_entity).add_child(text_entity);
+        add_config_opt(commands, Some(radial_entity), self.config_option);
+
+        let mut radial_buttons = vec![];
+        let mut radial_selections = vec![];
+
+        for (index, option) in self.options.iter().enumerate() {
+            if let MenuOptionType::Primitive(config_option) = &option.data_type {
+                let selection = commands.spawn(self.radial_selection(index == selected_index)).id();
+                let text = commands.spawn(self.text(&mut asset_server, option.metadata.name.as_str())).id();
+                let button = commands.spawn(self.radial_button(index, option))
+                    .push_children(&[selection, text])
+                    .id();
+                add_config_opt(commands, Some(button), config_option);
+                commands.entity(radial_entity).add_child(button);
+                radial_buttons.push(button);
+                radial_selections.push(selection);
+            } else {
+                error!("Radial option {} must be a primitive option.", option.metadata.name);
+            }
+        }
+
+        self.parent.map(|parent| commands.get_entity(parent)
+            .as_mut()
+            .map(|parent| parent.add_child(radial_entity))
+        );
+
+        DrawRadialMenuResult {
+            radial_entity,
+            text_entity,
+            radial_buttons,
+            radial_selections,
+        }
+    }
+
+    /// The first option that is selected in the menu definition.
+    fn selected_index(&self) -> usize {
+        self.options.iter()
+            .position(|option| matches!(
+                &option.data_type,
+                MenuOptionType::Primitive(config_option) if matches!(config_option.data_type(), DataType::Selected)
+            ))
+            .or(Some(0))
+            .unwrap()
+    }
+
+    pub(crate) fn radial_parent(&self, selected_index: usize) -> impl Bundle {
+        let component = UiComponent::RadialComponent;
+        (
+            NodeBundle {
+                style: Style {
+                    display: component.starting_display(),
+                    flex_direction: FlexDirection::Column,
+                    width: Val::Percent(100.0),
+                    ..default()
+                },
+                ..default()
+            },
+            Radial { selected_index },
+            PropagateDisplay::default(),
+            UiIdentifiableComponent(self.menu_metadata.id),
+            component
+        )
+    }
+
+    pub(crate) fn radial_button(&self, index: usize, option: &MenuOption) -> impl Bundle {
+        (
+            ButtonBundle {
+                style: Style {
+                    display: Display::Flex,
+                    flex_direction: FlexDirection::Row,
+                    align_items: AlignItems::Center,
+                    width: Val::Percent(100.0),
+                    height: Val::Px(30.0),
+                    ..default()
+                },
+                ..default()
+            },
+            ThemedBackground(ThemeColor::Input),
+            RadialButton { index },
+            UiComponent::RadialButton,
+            UiIdentifiableComponent(option.metadata.id),
+        )
+    }
+
+    pub(crate) fn radial_selection(&self, selected: bool) -> impl Bundle {
+        (
+            NodeBundle {
+                style: Style {
+                    width: Val::Px(12.0),
+                    height: Val::Px(12.0),
+                    margin: UiRect::all(Val::Px(6.0)),
+                    ..default()
+                },
+                ..default()
+            },
+            ThemedBackground(selection_color(selected)),
+            RadialButtonSelection::default(),
+            UiComponent::RadialSelection(RadialButtonSelection::default()),
+        )
+    }
+
+    pub(crate) fn text(&self, asset_server: &mut Res<AssetServer>, value: &str) -> impl Bundle {
+        (
+            TextBundle {
+                text: Text::from_section(value.to_string(), TextStyle {
+                    font_size: 16.0,
+                    font: asset_server.load(DEFAULT_FONT),
+                    ..default()
+                }),
+                ..default()
+            },
+            ThemedText(ThemeColor::MenuText),
+            Label
+        )
+    }
+}
+
+/// Clicking a radial button selects it in the group.
+pub(crate) fn select_radial_button(
+    buttons: Query<(&Interaction, &RadialButton, &Parent), Changed<Interaction>>,
+    mut radials: Query<&mut Radial>,
+) {
+    for (interaction, button, parent) in buttons.iter() {
+        if !matches!(interaction, Interaction::Pressed) {
+            continue;
+        }
+        let _ = radials.get_mut(parent.get())
+            .map(|mut radial| {
+                if radial.selected_index != button.index {
+                    radial.selected_index = button.index;
+                }
+            });
+    }
+}
+
+/// When the selection of the group changes, the selected option is set to Selected and the others
+/// to Deselected, and the changes are sent as configuration option events.
+pub(crate) fn update_radial_options<T>(
+    radials: Query<(&Radial, &Children), Changed<Radial>>,
+    mut buttons: Query<(&RadialButton, &mut MetricsConfigurationOption<T>)>,
+    mut event_writer: EventWriter<EventDescriptor<DataType, ConfigurationOptionEventArgs<T>, MetricsConfigurationOption<T>>>,
+)
+    where T: Component + Send + Sync + Default + Clone + Debug + 'static
+{
+    for (radial, children) in radials.iter() {
+        for child in children.iter() {
+            let _ = buttons.get_mut(*child)
+                .map(|(button, mut config_option)| {
+                    let data_type = if button.index == radial.selected_index { DataType::Selected } else { DataType::Deselected };
+                    if std::mem::discriminant(config_option.data_type()) != std::mem::discriminant(&data_type) {
+                        *config_option.data_type_mut() = data_type.clone();
+                        event_writer.send(create_config_option_event(*child, data_type, config_option.clone()));
+                    }
+                });
+        }
+    }
+}
+
+pub(crate) fn show_radial_selection(
+    radials: Query<(&Radial, &Children), Changed<Radial>>,
+    buttons: Query<(&RadialButton, &Children)>,
+    mut selections: Query<&mut ThemedBackground, With<RadialButtonSelection>>,
+) {
+    for (radial, children) in radials.iter() {
+        children.iter()
+            .flat_map(|child| buttons.get(*child).ok())
+            .for_each(|(button, button_children)| {
+                let color = selection_color(button.index == radial.selected_index);
+                button_children.iter()
+                    .for_each(|selection| {
+                        let _ = selections.get_mut(*selection)
+                            .map(|mut background| *background = ThemedBackground(color));
+                    });
+            });
+    }
+}
+
+fn selection_color(selected: bool) -> ThemeColor {
+    if selected { ThemeColor::MenuSelected } else { ThemeColor::MenuDeselected }
+}
@@ -0,0 +1,106 @@
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+use bevy::prelude::*;
+use bevy::ui::RelativeCursorPosition;
+use crate::menu::{ConfigurationOptionEnum, MenuItemMetadata, MenuOption};
+use crate::ui_components::menu_components::BuilderResult;
+use crate::ui_components::menu_components::menu_options::MenuOptionBuilder;
+use crate::ui_components::menu_components::menu_types::base_menu::BaseMenu;
+use crate::ui_components::menu_components::menu_types::dropdown_menu::{DrawDropdownMenuResult, DropdownMenuBuilder};
+
+pub const SCROLL_LINE_HEIGHT: f32 = 20.0;
+
+/// A dropdown menu with a max height, where the options that do not fit are scrolled into view
+/// with the mouse wheel.
+pub struct ScrollableMenuBuilder<'a> {
+    pub(crate) menu_metadata: &'a MenuItemMetadata,
+    pub(crate) config_option: &'a ConfigurationOptionEnum,
+    pub(crate) parent_menus: Vec<MenuItemMetadata>,
+    pub(crate) base_menu: BaseMenu<'a>,
+    pub(crate) menu_option_builders: Vec<(MenuOption, MenuOptionBuilder<'a>)>,
+}
+
+#[derive(Clone, Debug)]
+pub struct DrawScrollableMenuResult {
+    pub(crate) dropdown_menu_result: DrawDropdownMenuResult,
+    pub(crate) scrollable_entity: Entity
+}
+
+impl BuilderResult for DrawScrollableMenuResult {}
+
+/// The distance the options of the scrollable menu are scrolled. The header stays in place.
+#[derive(Component, Default, Debug)]
+pub struct ScrollableMenuState {
+    pub(crate) offset: f32,
+    pub(crate) header: Option<Entity>
+}
+
+impl <'a> ScrollableMenuBuilder<'a> {
+    pub(crate) fn build(
+        self,
+        mut commands: &mut Commands,
+        mut materials: &mut ResMut<Assets<ColorMaterial>>,
+        mut meshes: &mut ResMut<Assets<Mesh>>,
+        mut asset_server: &mut Res<AssetServer>,
+    ) -> DrawScrollableMenuResult {
+        let mut dropdown_builder = DropdownMenuBuilder {
+            menu_metadata: self.menu_metadata,
+            config_option: self.config_option,
+            parent_menus: self.parent_menus,
+            base_menu: self.base_menu,
+            menu_option_builders: self.menu_option_builders,
+        };
+        let dropdown_menu_result = dropdown_builder.build(&mut commands, &mut materials, &mut meshes, &mut asset_server);
+        let scrollable_entity = dropdown_menu_result.base_menu_result.base_menu_parent;
+        let header = dropdown_menu_result.base_menu_result.base_menu_child_text;
+        commands.entity(scrollable_entity)
+            .insert((
+                ScrollableMenuState { offset: 0.0, header: Some(header) },
+                RelativeCursorPosition::default()
+            ));
+        DrawScrollableMenuResult {
+            dropdown_menu_result,
+            scrollable_entity,
+        }
+    }
+}
+
+/// Scrolls the menu under the cursor by offsetting its children other than the header, up to the
+/// height of the options that do not fit in the menu.
+pub(crate) fn scroll_menus(
+    mut scroll_events: EventReader<MouseWheel>,
+    mut menus: Query<(&mut ScrollableMenuState, &RelativeCursorPosition, &Node, &Children)>,
+    mut children_style: Query<(&mut Style, &Node), Without<ScrollableMenuState>>,
+) {
+    let scrolled = scroll_events.iter()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y * SCROLL_LINE_HEIGHT,
+            MouseScrollUnit::Pixel => event.y
+        })
+        .sum::<f32>();
+    if scrolled == 0.0 {
+        return;
+    }
+    for (mut state, cursor, menu_node, children) in menus.iter_mut() {
+        if !cursor.mouse_over() {
+            continue;
+        }
+        let content_height = children.iter()
+            .flat_map(|child| children_style.get(*child).ok())
+            .map(|(_, node)| node.size().y)
+            .sum::<f32>();
+        state.offset = scroll_offset(state.offset, scrolled, content_height, menu_node.size().y);
+        children.iter()
+            .filter(|child| state.header != Some(**child))
+            .for_each(|child| {
+                let _ = children_style.get_mut(*child)
+                    .map(|(mut style, _)| style.top = Val::Px(-state.offset));
+            });
+    }
+}
+
+/// The offset after scrolling, where scrolling up is positive, clamped so that the options are
+/// not scrolled past the top or past the last option.
+pub(crate) fn scroll_offset(offset: f32, scrolled: f32, content_height: f32, menu_height: f32) -> f32 {
+    let max_offset = (content_height - menu_height).max(0.0);
+    (offset - scrolled).clamp(0.0, max_offset)
+}
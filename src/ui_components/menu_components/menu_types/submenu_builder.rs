@@ -1,10 +1,13 @@
 use bevy::prelude::*;
 use bevy::utils::HashMap;
-use crate::menu::{ConfigurationOptionEnum, Dropdown, MenuInputType, MenuItemMetadata, MenuOption, MenuOptionType, SelectableType, UiComponent};
+use crate::menu::{ConfigurationOptionEnum, Dropdown, MenuInputType, MenuItemMetadata, MenuOption, MenuOptionType, ScrollableMenuComponent, SelectableType, UiComponent};
 use crate::ui_components::get_menu_option_names;
 use crate::ui_components::menu_components::BuilderResult;
 use crate::ui_components::menu_components::menu_types::base_menu::BaseMenu;
 use crate::ui_components::menu_components::menu_types::dropdown_menu::{DrawDropdownMenuResult, DropdownMenuBuilder};
+use crate::ui_components::menu_components::menu_types::form_input::{DrawFormInputResult, FormInputBuilder};
+use crate::ui_components::menu_components::menu_types::radial_menu::{DrawRadialMenuResult, RadialMenuBuilder};
+use crate::ui_components::menu_components::menu_types::scrollable_menu::ScrollableMenuBuilder;
 use crate::ui_components::ui_menu_component::{dropdown_component, menu_options};
 
 pub struct SubmenuBuilder<'a> {
@@ -18,10 +21,31 @@ pub struct SubmenuBuilder<'a> {
 
 #[derive(Clone, Debug)]
 pub struct DrawSubmenuResult {
-    pub(crate) dropdown_menu_result: DrawDropdownMenuResult,
+    pub(crate) dropdown_menu_result: Option<DrawDropdownMenuResult>,
+    pub(crate) radial: Option<DrawRadialMenuResult>,
+    pub(crate) form_input: Option<DrawFormInputResult>,
     with_submenu_added: Vec<MenuItemMetadata>
 }
 
+impl DrawSubmenuResult {
+    fn new(with_submenu_added: Vec<MenuItemMetadata>) -> Self {
+        Self {
+            dropdown_menu_result: None,
+            radial: None,
+            form_input: None,
+            with_submenu_added,
+        }
+    }
+
+    /// The entity of the submenu that is added as a child of the parent menu.
+    pub(crate) fn submenu_entity(&self) -> Option<Entity> {
+        self.dropdown_menu_result.as_ref()
+            .map(|dropdown| dropdown.base_menu_result.base_menu_parent)
+            .or_else(|| self.radial.as_ref().map(|radial| radial.radial_entity))
+            .or_else(|| self.form_input.as_ref().map(|form_input| form_input.form_input_parent))
+    }
+}
+
 impl BuilderResult for DrawSubmenuResult {}
 
 impl <'a> SubmenuBuilder<'a> {
@@ -61,8 +85,66 @@ impl <'a> SubmenuBuilder<'a> {
 
 
                     Some(DrawSubmenuResult {
-                        dropdown_menu_result: result,
-                        with_submenu_added: self.parent_menus.clone(),
+                        dropdown_menu_result: Some(result),
+                        ..DrawSubmenuResult::new(self.parent_menus.clone())
+                    })
+                }
+                MenuInputType::ScrollableMenu { options, metadata: menu_metadata, option } => {
+                    self.parent_menus.push(menu_metadata.clone());
+                    let base_menu = BaseMenu {
+                        menu_metadata,
+                        config_option: option,
+                        parent_menus: self.parent_menus.clone(),
+                        component: UiComponent::ScrollableMenuComponent(ScrollableMenuComponent::default()),
+                        parent: self.parent.unwrap(),
+                    };
+                    let scrollable_menu_builder = ScrollableMenuBuilder {
+                        menu_metadata,
+                        config_option: option,
+                        parent_menus: self.parent_menus.clone(),
+                        base_menu,
+                        menu_option_builders: menu_options(options, &self.parent_menus),
+                    };
+
+                    let result = scrollable_menu_builder
+                        .build(&mut commands, &mut materials, &mut meshes, &mut asset_server);
+
+                    Some(DrawSubmenuResult {
+                        dropdown_menu_result: Some(result.dropdown_menu_result),
+                        ..DrawSubmenuResult::new(self.parent_menus.clone())
+                    })
+                }
+                MenuInputType::Radial { options, metadata: menu_metadata, option } => {
+                    self.parent_menus.push(menu_metadata.clone());
+                    let radial_menu_builder = RadialMenuBuilder {
+                        parent: self.parent,
+                        menu_metadata,
+                        config_option: option,
+                        options,
+                    };
+
+                    let result = radial_menu_builder
+                        .build(&mut commands, &mut materials, &mut meshes, &mut asset_server);
+
+                    Some(DrawSubmenuResult {
+                        radial: Some(result),
+                        ..DrawSubmenuResult::new(self.parent_menus.clone())
+                    })
+                }
+                MenuInputType::FormInput { metadata: menu_metadata, option, .. } => {
+                    self.parent_menus.push(menu_metadata.clone());
+                    let form_input_builder = FormInputBuilder {
+                        parent: self.parent,
+                        menu_metadata,
+                        config_option: option,
+                    };
+
+                    let result = form_input_builder
+                        .build(&mut commands, &mut materials, &mut meshes, &mut asset_server);
+
+                    Some(DrawSubmenuResult {
+                        form_input: Some(result),
+                        ..DrawSubmenuResult::new(self.parent_menus.clone())
                     })
                 }
                 _ => {
@@ -4,11 +4,20 @@ use crate::menu::{ConfigurationOptionEnum, MenuOption, MenuOptionInputType, Menu
 use menu_types::dropdown_menu::{DrawDropdownMenuResult, DropdownMenuBuilder};
 use crate::ui_components::menu_components::menu_options::dropdown_menu_option::{DropdownMenuOptionBuilder, DropdownMenuOptionResult};
 use crate::ui_components::menu_components::menu_options::MenuOptionBuilder;
-use crate::ui_components::menu_components::menu_options::slider_menu_option::{SliderMenuOptionBuilder, SliderMenuOptionResult};
+use crate::ui_components::menu_components::menu_options::slider_menu_option::{drag_slider, emit_slider_changes, SliderMenuOptionBuilder, SliderMenuOptionResult, update_slider};
 use menu_types::submenu_builder::{DrawSubmenuResult, SubmenuBuilder};
 use crate::ui_components::menu_components::menu_types::base_menu::BuildBaseMenuResult;
 use crate::ui_components::menu_components::menu_types::collapsable_menu::{CollapsableMenuBuilder, DrawCollapsableMenuResult};
 use crate::ui_components::menu_components::menu_types::root_collapsable::{DrawRootNodeResult, RootNodeBuilder};
+use std::fmt::Debug;
+use crate::event::event_descriptor::EventDescriptor;
+use crate::menu::{DataType, Menu, MetricsConfigurationOption};
+use crate::menu::config_menu_event::config_event::ConfigurationOptionEventArgs;
+use crate::metrics::network_metrics::Metric;
+use crate::network::{Layer, Network, Node};
+use crate::ui_components::menu_components::menu_types::form_input::{commit_form_input, DrawFormInputResult, edit_form_input, focus_form_input, FormInputCommitted, FormInputFocus, render_form_input};
+use crate::ui_components::menu_components::menu_types::radial_menu::{DrawRadialMenuResult, select_radial_button, show_radial_selection, update_radial_options};
+use crate::ui_components::menu_components::menu_types::scrollable_menu::{DrawScrollableMenuResult, scroll_menus};
 use crate::ui_components::ui_menu_component::insert_config_option;
 
 pub(crate) mod menu_options;
@@ -26,6 +35,9 @@ pub struct BuildMenuResult {
     pub(crate) submenu_results:  Vec<DrawSubmenuResult>,
     pub(crate) base_menu_results: HashMap<Entity, BuildBaseMenuResult>,
     pub(crate) slider: HashMap<Entity, SliderMenuOptionResult>,
+    pub(crate) scrollable: HashMap<Entity, DrawScrollableMenuResult>,
+    pub(crate) radial: HashMap<Entity, DrawRadialMenuResult>,
+    pub(crate) form_input: HashMap<Entity, DrawFormInputResult>,
 }
 
 pub struct MenuBuilder<'a> {
@@ -37,6 +49,39 @@ pub struct MenuBuilder<'a> {
     pub(crate) slider: Vec<SliderMenuOptionBuilder<'a>>,
 }
 
+/// The systems for the inputs of the menus, form inputs, radio button groups, scrollable menus and
+/// sliders.
+pub struct MenuInputPlugin;
+
+impl Plugin for MenuInputPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FormInputFocus>()
+            .add_event::<FormInputCommitted>()
+            .add_system(focus_form_input)
+            .add_system(edit_form_input.after(focus_form_input))
+            .add_system(render_form_input.after(edit_form_input))
+            .add_system(select_radial_button)
+            .add_system(show_radial_selection.after(select_radial_button))
+            .add_system(scroll_menus)
+            .add_system(drag_slider)
+            .add_system(update_slider.after(drag_slider));
+        add_config_option_systems::<Menu>(app);
+        add_config_option_systems::<Network>(app);
+        add_config_option_systems::<Layer>(app);
+        add_config_option_systems::<Node>(app);
+        add_config_option_systems::<Metric<Network>>(app);
+    }
+}
+
+fn add_config_option_systems<T>(app: &mut App)
+    where T: Component + Send + Sync + Default + Clone + Debug + 'static
+{
+    app.add_event::<EventDescriptor<DataType, ConfigurationOptionEventArgs<T>, MetricsConfigurationOption<T>>>()
+        .add_system(commit_form_input::<T>.after(edit_form_input))
+        .add_system(update_radial_options::<T>.after(select_radial_button))
+        .add_system(emit_slider_changes::<T>.after(drag_slider));
+}
+
 fn add_config_opt(mut commands: &mut Commands, base_menu_result: Option<Entity>, config_option: &ConfigurationOptionEnum) {
     base_menu_result.map(|parent|
         commands.get_entity(parent)
@@ -93,6 +138,10 @@ fn do_submenu_menu_building<'a>(
                     builder.parent = get_parent_entity(&base_menu_parent, &collapsable_result);
                     builder.build(&mut commands, &mut materials, &mut meshes, &mut asset_server)
                         .map(|submenu| draw_submenu.push(submenu));
+                } else if let MenuOptionBuilder::SliderMenuOptionBuilder(slider) = builder {
+                    slider.parent = get_parent_entity(&base_menu_parent, &collapsable_result);
+                    let menu_option = slider.build(&mut commands, &mut materials, &mut meshes, &mut asset_server);
+                    slider_menu.push(menu_option);
                 }
             }
         };
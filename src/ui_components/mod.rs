@@ -3,6 +3,10 @@ use crate::menu::MenuOption;
 
 pub(crate) mod ui_menu_component;
 pub(crate) mod menu_components;
+/// The tooltip that follows the cursor with the values of the hovered node, layer or series.
+pub(crate) mod tooltip;
+/// The panel showing the help, and the Markdown notes attached to a run or a metric.
+pub(crate) mod notes_panel;
 
 #[derive(Debug, Clone, Default)]
 pub struct Size {
@@ -0,0 +1,391 @@
+use std::fs;
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+use bevy::prelude::*;
+use crate::config::keymap::KeyAction;
+use crate::config::theme::{Theme, ThemeColor};
+use crate::data_subscriber::metric_event::MetricsState;
+use crate::menu::{DataType, FormInputComponent};
+use crate::menu::graphing_menu::context_menu::{ContextMenuAction, ContextMenuActionEvent, ContextMenuTarget};
+use crate::render_html::{HtmlParsingError, HtmlTags, InlineSpan, InlineStyle, load_markdown_images, markdown_parser, MarkdownStyle, render_html, text_style};
+use crate::shortcuts::keymap::KeyActionEvent;
+use crate::theme::restyle::{ThemedBackground, ThemedText};
+use crate::ui_components::menu_components::menu_types::form_input::FormInputCommitted;
+use crate::util::file_name;
+
+pub const NOTES_DIR: &str = "notes";
+pub const HELP_PATH: &str = "resources/help.md";
+pub const NOTES_PANEL_WIDTH: f32 = 420.0;
+
+/// What the notes shown in the panel are about. The help is read only, and notes can be added to
+/// a run or a metric of a run.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NotesSubject {
+    Help,
+    Run(String),
+    Metric {
+        run_id: String,
+        metric_name: String
+    }
+}
+
+impl NotesSubject {
+    pub(crate) fn title(&self) -> String {
+        match self {
+            NotesSubject::Help => "Help".to_string(),
+            NotesSubject::Run(run_id) => format!("Notes for run {}", run_id),
+            NotesSubject::Metric { run_id, metric_name } => format!("Notes for {} ({})", metric_name, run_id)
+        }
+    }
+
+    /// The notes of a run are in `runs/<run>.md`, and of a metric in `metrics/<run>/<metric>.md`,
+    /// under the notes directory.
+    pub(crate) fn path(&self, dir: &str) -> PathBuf {
+        match self {
+            NotesSubject::Help => PathBuf::from(HELP_PATH),
+            NotesSubject::Run(run_id) => Path::new(dir).join("runs")
+                .join(format!("{}.md", file_name(run_id))),
+            NotesSubject::Metric { run_id, metric_name } => Path::new(dir).join("metrics")
+                .join(file_name(run_id))
+                .join(format!("{}.md", file_name(metric_name)))
+        }
+    }
+
+    pub(crate) fn editable(&self) -> bool {
+        !matches!(self, NotesSubject::Help)
+    }
+}
+
+/// The Markdown notes, read from and written to the notes directory.
+#[derive(Resource, Debug)]
+pub struct Notes {
+    pub(crate) dir: String
+}
+
+impl Default for Notes {
+    fn default() -> Self {
+        Self { dir: NOTES_DIR.to_string() }
+    }
+}
+
+impl Notes {
+    /// The notes for the subject, which are empty when none have been attached.
+    pub(crate) fn read(&self, subject: &NotesSubject) -> String {
+        read_to_string(subject.path(&self.dir))
+            .or(Ok::<String, std::io::Error>(String::new()))
+            .unwrap()
+    }
+
+    /// Appends the note as a paragraph to the notes of the subject, and returns the path written.
+    pub(crate) fn attach(&self, subject: &NotesSubject, note: &str) -> Result<String, String> {
+        if !subject.editable() {
+            return Err(format!("{} cannot be edited.", subject.title()));
+        }
+        let path = subject.path(&self.dir);
+        let mut contents = self.read(subject);
+        if !contents.is_empty() && !contents.ends_with("\n\n") {
+            contents.push_str(if contents.ends_with('\n') { "\n" } else { "\n\n" });
+        }
+        contents.push_str(note.trim());
+        contents.push('\n');
+        path.parent()
+            .map(|dir| fs::create_dir_all(dir))
+            .or(Some(Ok(())))
+            .unwrap()
+            .map_err(|e| e.to_string())?;
+        fs::write(&path, contents)
+            .map_err(|e| e.to_string())?;
+        Ok(path.display().to_string())
+    }
+}
+
+/// Whether the panel is open and what it shows. The revision is incremented when the notes shown
+/// are changed, so that the panel is rebuilt.
+#[derive(Resource, Debug)]
+pub struct NotesPanelState {
+    pub(crate) open: bool,
+    pub(crate) subject: NotesSubject,
+    pub(crate) revision: usize
+}
+
+impl Default for NotesPanelState {
+    fn default() -> Self {
+        Self {
+            open: false,
+            subject: NotesSubject::Help,
+            revision: 0
+        }
+    }
+}
+
+/// Sent to open the panel with the notes for the subject.
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct ShowNotes(pub NotesSubject);
+
+#[derive(Component, Default, Debug)]
+pub struct NotesPanel;
+
+/// The input the notes are added with.
+#[derive(Component, Default, Debug)]
+pub struct NotesInput;
+
+#[derive(Component, Debug, Clone, PartialEq)]
+pub enum NotesPanelButton {
+    Show(NotesSubject),
+    Close
+}
+
+pub struct NotesPlugin;
+
+impl Plugin for NotesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ShowNotes>()
+            .add_event::<KeyActionEvent>()
+            .add_event::<ContextMenuActionEvent>()
+            .add_event::<FormInputCommitted>()
+            .init_resource::<Notes>()
+            .init_resource::<NotesPanelState>()
+            .init_resource::<Theme>()
+            .init_resource::<MarkdownStyle>()
+            .init_resource::<MetricsState>()
+            .add_system(request_notes)
+            .add_system(select_notes_button)
+            .add_system(show_notes.after(request_notes).after(select_notes_button))
+            .add_system(commit_note)
+            .add_system(restyle_notes)
+            .add_system(render_notes_panel.after(show_notes).after(commit_note).after(restyle_notes))
+            .add_system(load_markdown_images.after(render_notes_panel));
+    }
+}
+
+/// The help key toggles the help, and the notes action of the context menu of a series shows the
+/// notes of its metric.
+pub(crate) fn request_notes(
+    mut key_events: EventReader<KeyActionEvent>,
+    mut context_menu_events: EventReader<ContextMenuActionEvent>,
+    mut show_writer: EventWriter<ShowNotes>,
+    mut state: ResMut<NotesPanelState>,
+    metrics_state: Res<MetricsState>,
+    parents: Query<&Parent>,
+) {
+    if key_events.iter().any(|KeyActionEvent(action)| *action == KeyAction::Help) {
+        if state.open && state.subject == NotesSubject::Help {
+            state.open = false;
+        } else {
+            show_writer.send(ShowNotes(NotesSubject::Help));
+        }
+    }
+    for event in context_menu_events.iter() {
+        if let (ContextMenuAction::Notes, ContextMenuTarget::Series(series)) = (&event.action, event.target) {
+            let _ = parents.get(series).ok()
+                .and_then(|metric| metrics_state.run_metric(metric.get()))
+                .map(|(run_id, metric_name)| show_writer.send(ShowNotes(NotesSubject::Metric {
+                    run_id: run_id.clone(),
+                    metric_name: metric_name.clone()
+                })))
+                .ok_or_else(|| error!("No metric found for the notes of series {:?}.", series));
+        }
+    }
+}
+
+pub(crate) fn select_notes_button(
+    interactions: Query<(&Interaction, &NotesPanelButton), Changed<Interaction>>,
+    mut show_writer: EventWriter<ShowNotes>,
+    mut state: ResMut<NotesPanelState>,
+) {
+    for (interaction, button) in interactions.iter() {
+        if !matches!(interaction, Interaction::Pressed) {
+            continue;
+        }
+        match button {
+            NotesPanelButton::Show(subject) => show_writer.send(ShowNotes(subject.clone())),
+            NotesPanelButton::Close => state.open = false
+        }
+    }
+}
+
+pub(crate) fn show_notes(mut show_events: EventReader<ShowNotes>, mut state: ResMut<NotesPanelState>) {
+    if let Some(ShowNotes(subject)) = show_events.iter().last() {
+        state.open = true;
+        state.subject = subject.clone();
+    }
+}
+
+/// A note entered in the panel is appended to the notes of the subject, and the input is cleared.
+pub(crate) fn commit_note(
+    mut committed: EventReader<FormInputCommitted>,
+    mut inputs: Query<&mut FormInputComponent, With<NotesInput>>,
+    mut state: ResMut<NotesPanelState>,
+    notes: Res<Notes>,
+) {
+    for commit in committed.iter() {
+        let mut input = match inputs.get_mut(commit.entity) {
+            Ok(input) => input,
+            Err(_) => continue
+        };
+        let note = match &commit.value {
+            DataType::String(Some(note)) if !note.trim().is_empty() => note.clone(),
+            _ => continue
+        };
+        let _ = notes.attach(&state.subject, &note)
+            .map(|path| {
+                info!("Added note to {}.", path);
+                input.value.clear();
+                input.caret = 0;
+                state.revision += 1;
+            })
+            .or_else(|e| {
+                error!("Could not add note to {}: {}", state.subject.title(), e);
+                Err(e)
+            });
+    }
+}
+
+/// The documents are rendered in the fonts and colors of the theme, so the panel is rebuilt when
+/// the theme changes.
+pub(crate) fn restyle_notes(
+    theme: Res<Theme>,
+    asset_server: Res<AssetServer>,
+    mut markdown_style: ResMut<MarkdownStyle>,
+    mut state: ResMut<NotesPanelState>,
+) {
+    if theme.is_changed() {
+        *markdown_style = MarkdownStyle::themed(&theme, &asset_server);
+        state.set_changed();
+    }
+}
+
+/// The panel is rebuilt when it is opened, its subject changes or a note is added. The notes are
+/// rendered from Markdown, with buttons to switch to the help or the notes of the run of a metric.
+pub(crate) fn render_notes_panel(
+    mut commands: Commands,
+    state: Res<NotesPanelState>,
+    notes: Res<Notes>,
+    markdown_style: Res<MarkdownStyle>,
+    panels: Query<Entity, With<NotesPanel>>,
+) {
+    if !state.is_changed() {
+        return;
+    }
+    panels.iter().for_each(|panel| commands.entity(panel).despawn_recursive());
+    if !state.open {
+        return;
+    }
+
+    let panel = commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                right: Val::Px(10.0),
+                top: Val::Px(60.0),
+                width: Val::Px(NOTES_PANEL_WIDTH),
+                max_height: Val::Percent(80.0),
+                display: Display::Flex,
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(8.0)),
+                overflow: Overflow::clip(),
+                ..default()
+            },
+            z_index: ZIndex::Global(90),
+            ..default()
+        },
+        ThemedBackground(ThemeColor::Panel),
+        NotesPanel::default()
+    )).id();
+
+    let header = spawn_header(&mut commands, &state.subject, &markdown_style);
+    commands.entity(panel).add_child(header);
+
+    let markdown = notes.read(&state.subject);
+    let document = match render_html(&mut commands, markdown_parser(&markdown), &markdown_style) {
+        Ok(tree) => tree.self_entity,
+        Err(HtmlParsingError::Empty) => spawn_label(&mut commands, "No notes yet.", ThemeColor::PanelMutedText, &markdown_style),
+        Err(e) => {
+            error!("Could not render the notes for {}: {:?}", state.subject.title(), e);
+            spawn_label(&mut commands, &format!("Could not render the notes: {:?}", e), ThemeColor::Alert, &markdown_style)
+        }
+    };
+    commands.entity(panel).add_child(document);
+
+    if state.subject.editable() {
+        let text = spawn_label(&mut commands, "", ThemeColor::MenuText, &markdown_style);
+        let input = commands.spawn((
+            ButtonBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    min_height: Val::Px(30.0),
+                    padding: UiRect::all(Val::Px(4.0)),
+                    margin: UiRect::top(Val::Px(6.0)),
+                    ..default()
+                },
+                ..default()
+            },
+            ThemedBackground(ThemeColor::Input),
+            FormInputComponent { value: String::new(), caret: 0, numeric: false, valid: true },
+            NotesInput::default()
+        ))
+            .add_child(text)
+            .id();
+        let label = spawn_label(&mut commands, "Add a note (Markdown), enter to save:", ThemeColor::PanelMutedText, &markdown_style);
+        commands.entity(panel).push_children(&[label, input]);
+    }
+}
+
+fn spawn_header(commands: &mut Commands, subject: &NotesSubject, markdown_style: &MarkdownStyle) -> Entity {
+    let title = commands.spawn(TextBundle {
+        text: Text::from_section(subject.title(), text_style(&InlineStyle { strong: true, ..default() }, HtmlTags::H4, markdown_style)),
+        style: Style { flex_grow: 1.0, ..default() },
+        ..default()
+    }).id();
+    let header = commands.spawn(NodeBundle {
+        style: Style {
+            width: Val::Percent(100.0),
+            flex_direction: FlexDirection::Row,
+            align_items: AlignItems::Center,
+            margin: UiRect::bottom(Val::Px(6.0)),
+            ..default()
+        },
+        ..default()
+    })
+        .add_child(title)
+        .id();
+
+    let mut buttons = vec![];
+    if let NotesSubject::Metric { run_id, .. } = subject {
+        buttons.push(("Run".to_string(), NotesPanelButton::Show(NotesSubject::Run(run_id.clone()))));
+    }
+    if subject != &NotesSubject::Help {
+        buttons.push(("Help".to_string(), NotesPanelButton::Show(NotesSubject::Help)));
+    }
+    buttons.push(("Close".to_string(), NotesPanelButton::Close));
+    for (name, button) in buttons.into_iter() {
+        let text = spawn_label(commands, &name, ThemeColor::PanelText, markdown_style);
+        let button = commands.spawn((
+            ButtonBundle {
+                style: Style {
+                    padding: UiRect::all(Val::Px(4.0)),
+                    margin: UiRect::left(Val::Px(4.0)),
+                    ..default()
+                },
+                ..default()
+            },
+            ThemedBackground(ThemeColor::PanelEntry),
+            button
+        ))
+            .add_child(text)
+            .id();
+        commands.entity(header).add_child(button);
+    }
+    header
+}
+
+fn spawn_label(commands: &mut Commands, value: &str, color: ThemeColor, markdown_style: &MarkdownStyle) -> Entity {
+    let span = InlineSpan { text: value.to_string(), style: InlineStyle::default() };
+    commands.spawn((
+        TextBundle {
+            text: Text::from_section(span.text, text_style(&span.style, HtmlTags::Paragraph, markdown_style)),
+            ..default()
+        },
+        ThemedText(color)
+    )).id()
+}
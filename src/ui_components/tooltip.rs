@@ -0,0 +1,268 @@
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+use bevy::window::PrimaryWindow;
+use bevy_polyline::prelude::Polyline;
+use crate::camera::ActiveCamera;
+use crate::camera::viewports::viewport_offset;
+use crate::config::theme::{DEFAULT_FONT, ThemeColor};
+use crate::data_subscriber::metric_event::MetricsState;
+use crate::graph::{GraphDimComponent, GraphingMetricsResource};
+use crate::graph::draw_graph_points::SeriesPolylines;
+use crate::menu::graphing_menu::context_menu::metric_label;
+use crate::metrics::network_metrics::Metric;
+use crate::network::{Layer, MetricChildNodes, Network, Node};
+use crate::pickable_events::PickableEvent;
+use crate::interactions::HoverEvent;
+use crate::theme::restyle::{ThemedBackground, ThemedText};
+
+pub const TOOLTIP_OFFSET: f32 = 16.0;
+pub const TOOLTIP_MAX_VALUES: usize = 4;
+
+/// The 3d entity under the cursor, from the hover picking events.
+#[derive(Resource, Default, Debug)]
+pub struct HoveredEntity {
+    pub(crate) entity: Option<Entity>
+}
+
+#[derive(Component, Default, Debug)]
+pub struct Tooltip;
+
+pub struct TooltipPlugin;
+
+impl Plugin for TooltipPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PickableEvent>()
+            .init_resource::<HoveredEntity>()
+            .init_resource::<MetricsState>()
+            .add_startup_system(setup_tooltip)
+            .add_system(track_hovered)
+            .add_system(update_tooltip.after(track_hovered));
+    }
+}
+
+pub(crate) fn setup_tooltip(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                display: Display::None,
+                padding: UiRect::all(Val::Px(4.0)),
+                ..default()
+            },
+            focus_policy: FocusPolicy::Pass,
+            z_index: ZIndex::Global(200),
+            ..default()
+        },
+        ThemedBackground(ThemeColor::Panel),
+        Tooltip::default()
+    ))
+        .with_children(|tooltip| {
+            tooltip.spawn((
+                TextBundle {
+                    text: Text::from_section(String::new(), TextStyle {
+                        font_size: 14.0,
+                        font: asset_server.load(DEFAULT_FONT),
+                        ..default()
+                    }),
+                    focus_policy: FocusPolicy::Pass,
+                    ..default()
+                },
+                ThemedText(ThemeColor::PanelText)
+            ));
+        });
+}
+
+pub(crate) fn track_hovered(
+    mut picking_events: EventReader<PickableEvent>,
+    mut hovered: ResMut<HoveredEntity>,
+) {
+    for event in picking_events.iter() {
+        match event {
+            PickableEvent::Hover(HoverEvent::JustEntered(entity)) => hovered.entity = Some(*entity),
+            PickableEvent::Hover(HoverEvent::JustLeft(entity)) if hovered.entity == Some(*entity) => {
+                hovered.entity = None;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The index of the point nearest to the cursor.
+pub(crate) fn nearest_point(points: &Vec<Option<Vec2>>, cursor: Vec2) -> Option<(usize, f32)> {
+    points.iter()
+        .enumerate()
+        .flat_map(|(index, point)| point.map(|point| (index, point.distance(cursor))))
+        .min_by(|(_, first), (_, second)| first.total_cmp(second))
+}
+
+pub(crate) fn format_values(values: &Vec<f32>) -> String {
+    let mut formatted = values.iter()
+        .take(TOOLTIP_MAX_VALUES)
+        .map(|value| format!("{:.4}", value))
+        .collect::<Vec<String>>()
+        .join(", ");
+    if values.len() > TOOLTIP_MAX_VALUES {
+        formatted.push_str(", ...");
+    }
+    formatted
+}
+
+/// The latest values of each column of the metrics indexed by the node or layer.
+fn latest_metric_values<T: Component>(
+    indices: &Vec<String>,
+    graphing_metrics: &GraphingMetricsResource,
+    metrics_state: &MetricsState,
+    metrics: &Query<&Metric<T>>,
+) -> Vec<String> {
+    let mut lines = graphing_metrics.metrics_for_indices(indices.iter())
+        .into_iter()
+        .flat_map(|metric_entity| metrics.get(metric_entity).ok()
+            .map(|metric| (metric_label(metrics_state, metric_entity), metric))
+        )
+        .flat_map(|(name, metric)| {
+            let mut columns = metric.historical.labels.keys().collect::<Vec<&String>>();
+            columns.sort();
+            columns.into_iter()
+                .flat_map(|column| metric.historical.latest_values(column)
+                    .map(|(_, values)| format!("{} {}: {}", name, column, format_values(&values)))
+                )
+                .collect::<Vec<String>>()
+        })
+        .collect::<Vec<String>>();
+    lines.sort();
+    lines
+}
+
+/// The tooltip is placed next to the cursor while an entity is hovered. It is recomputed each frame
+/// so that the values are updated as the data arrives. The metrics of the nodes, layers, networks
+/// and child nodes are all looked up, as a node, layer or series may be of any of them.
+pub(crate) fn update_tooltip(
+    hovered: Res<HoveredEntity>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<ActiveCamera>>,
+    mut tooltip: Query<(&mut Style, &Children), With<Tooltip>>,
+    mut text: Query<&mut Text>,
+    graphing_metrics: Res<GraphingMetricsResource>,
+    metrics_state: Res<MetricsState>,
+    metrics: (
+        Query<&Metric<Node>>,
+        Query<&Metric<Layer>>,
+        Query<&Metric<Network>>,
+        Query<&Metric<MetricChildNodes>>
+    ),
+    nodes: Query<&Node>,
+    layers: Query<&Layer>,
+    series: Query<(&GraphDimComponent, &Parent, &SeriesPolylines)>,
+    transforms: Query<&GlobalTransform>,
+    parents: Query<&Parent>,
+    polylines: Res<Assets<Polyline>>,
+) {
+    let cursor = windows.get_single().ok()
+        .and_then(|window| window.cursor_position());
+    let latest = |indices: Vec<String>| {
+        let mut lines = latest_metric_values(&indices, &graphing_metrics, &metrics_state, &metrics.0);
+        lines.extend(latest_metric_values(&indices, &graphing_metrics, &metrics_state, &metrics.1));
+        lines.extend(latest_metric_values(&indices, &graphing_metrics, &metrics_state, &metrics.2));
+        lines.extend(latest_metric_values(&indices, &graphing_metrics, &metrics_state, &metrics.3));
+        lines.sort();
+        lines
+    };
+    let series_content = |graph_dim: Entity, line: Option<Entity>, cursor: Vec2| {
+        series_tooltip(graph_dim, line, cursor, &series, &metrics.0, &metrics_state, &cameras, &transforms, &polylines)
+            .or_else(|| series_tooltip(graph_dim, line, cursor, &series, &metrics.1, &metrics_state, &cameras, &transforms, &polylines))
+            .or_else(|| series_tooltip(graph_dim, line, cursor, &series, &metrics.2, &metrics_state, &cameras, &transforms, &polylines))
+            .or_else(|| series_tooltip(graph_dim, line, cursor, &series, &metrics.3, &metrics_state, &cameras, &transforms, &polylines))
+    };
+
+    let content = hovered.entity
+        .zip(cursor)
+        .and_then(|(entity, cursor)| {
+            if let Ok(node) = nodes.get(entity) {
+                let layer = parents.get(entity).ok()
+                    .and_then(|parent| layers.get(parent.get()).ok());
+                layer.map(|layer| {
+                    let node_id = layer.node_id(node);
+                    let mut lines = vec![format!("Node {}", node_id), format!("{:?}", layer.layer_type)];
+                    lines.extend(latest(vec![node_id]));
+                    lines.join("\n")
+                })
+            } else if let Ok(layer) = layers.get(entity) {
+                let mut lines = vec![
+                    format!("Layer {}", layer.name),
+                    format!("{:?}, {} nodes", layer.layer_type, layer.nodes.len())
+                ];
+                lines.extend(latest(vec![layer.name.to_string()]));
+                Some(lines.join("\n"))
+            } else if series.contains(entity) {
+                series_content(entity, None, cursor)
+            } else {
+                parents.get(entity).ok()
+                    .filter(|parent| series.contains(parent.get()))
+                    .and_then(|parent| series_content(parent.get(), Some(entity), cursor))
+            }
+        });
+
+    for (mut style, children) in tooltip.iter_mut() {
+        match (&content, cursor) {
+            (Some(content), Some(cursor)) => {
+                style.display = Display::Flex;
+                style.left = Val::Px(cursor.x + TOOLTIP_OFFSET);
+                style.top = Val::Px(cursor.y + TOOLTIP_OFFSET);
+                children.iter()
+                    .for_each(|child| {
+                        let _ = text.get_mut(*child)
+                            .map(|mut text| if &text.sections[0].value != content {
+                                text.sections[0].value = content.clone();
+                            });
+                    });
+            }
+            _ => if style.display != Display::None {
+                style.display = Display::None;
+            }
+        }
+    }
+}
+
+/// The metric, column, step and value of the point of the series nearest to the cursor. When the
+/// line hovered is known only its points are considered, otherwise the points of all of the lines
+/// of the series. The timestep of the point is the one recorded for its vertex when it was drawn.
+fn series_tooltip<T: Component>(
+    graph_dim_entity: Entity,
+    line: Option<Entity>,
+    cursor: Vec2,
+    series: &Query<(&GraphDimComponent, &Parent, &SeriesPolylines)>,
+    metrics: &Query<&Metric<T>>,
+    metrics_state: &MetricsState,
+    cameras: &Query<(&Camera, &GlobalTransform), With<ActiveCamera>>,
+    transforms: &Query<&GlobalTransform>,
+    polylines: &Res<Assets<Polyline>>,
+) -> Option<String> {
+    let (graph_dim, metric_entity, series_polylines) = series.get(graph_dim_entity).ok()
+        .map(|(graph_dim, parent, series_polylines)| (graph_dim, parent.get(), series_polylines))?;
+    let metric = metrics.get(metric_entity).ok()?;
+    let (camera, camera_transform) = cameras.iter()
+        .find(|(camera, _)| camera.is_active)?;
+    let offset = viewport_offset(camera);
+
+    let (index, point, _) = series_polylines.lines.iter()
+        .enumerate()
+        .filter(|(_, (line_entity, _))| line.map(|line| line == *line_entity).or(Some(true)).unwrap())
+        .flat_map(|(index, (line_entity, polyline))| {
+            let transform = transforms.get(*line_entity).ok()?;
+            let polyline = polylines.get(polyline)?;
+            let points = polyline.vertices.iter()
+                .map(|vertex| camera.world_to_viewport(camera_transform, transform.transform_point(*vertex))
+                    .map(|point| point + offset))
+                .collect::<Vec<Option<Vec2>>>();
+            nearest_point(&points, cursor)
+                .map(|(point, distance)| (index, point, distance))
+        })
+        .min_by(|(_, _, first), (_, _, second)| first.total_cmp(second))?;
+
+    let step = *series_polylines.steps.get(index)?.get(point)?;
+    let value = metric.historical.value_at(&graph_dim.name, index, step)?;
+    Some(format!(
+        "{}\n{}[{}]\nstep {}: {:.4}",
+        metric_label(metrics_state, metric_entity), graph_dim.name, index, step, value
+    ))
+}
@@ -5,7 +5,7 @@ use bevy::log::{error, info};
 use bevy::hierarchy::BuildChildren;
 use crate::event::event_state::{HoverStateChange, StyleStateChangeEventData, Update, UpdateStateInPlace};
 use crate::event::event_state::StyleStateChangeEventData::ChangeComponentStyle;
-use crate::menu::{CollapsableMenuComponent, ConfigurationOptionEnum, DataType, DraggableComponent, Dropdown, DropdownOption, Menu, MenuInputType, MenuItemMetadata, MenuOption, MenuOptionInputType, MenuOptionType, MenuType, MetricsConfigurationOption, Radial, ScrollableMenuComponent, ScrollableMenuItemsBarComponent, ScrollingSidebarComponent, ScrollWheelComponent, SelectableType, Slider, SliderData, SliderKnob, UiComponent};
+use crate::menu::{CollapsableMenuComponent, ConfigurationOptionEnum, DataType, DraggableComponent, Dropdown, DropdownOption, Menu, MenuInputType, MenuItemMetadata, MenuOption, MenuOptionInputType, MenuOptionType, MenuType, MetricsConfigurationOption, ScrollableMenuComponent, ScrollableMenuItemsBarComponent, ScrollingSidebarComponent, ScrollWheelComponent, SelectableType, Slider, SliderData, SliderKnob, UiComponent};
 use crate::menu::menu_resource::{MENU, MenuResource};
 use crate::menu::ui_menu_event::change_style::UiChangeTypes;
 use crate::menu::UiComponent::CollapsableMenu;
@@ -21,11 +21,14 @@ use crate::ui_components::menu_components::BuildMenuResult;
 use crate::ui_components::menu_components::menu_types::dropdown_menu::{DrawDropdownMenuResult, DropdownMenuBuilder};
 use crate::ui_components::menu_components::menu_options::dropdown_menu_option::{DropdownMenuOptionBuilder, DropdownMenuOptionResult};
 use crate::ui_components::menu_components::menu_options::MenuOptionBuilder;
-use crate::ui_components::menu_components::menu_options::slider_menu_option::SliderMenuOptionResult;
+use crate::ui_components::menu_components::menu_options::slider_menu_option::{SliderMenuOptionBuilder, SliderMenuOptionResult};
 use crate::ui_components::menu_components::menu_types::base_menu::BaseMenu;
 use crate::ui_components::menu_components::menu_types::collapsable_menu::{CollapsableMenuBuilder, DrawCollapsableMenuResult};
 use crate::ui_components::menu_components::menu_types::root_collapsable::RootNodeBuilder;
 use crate::ui_components::menu_components::menu_types::submenu_builder::{DrawSubmenuResult, SubmenuBuilder};
+use crate::ui_components::menu_components::menu_types::form_input::FormInputBuilder;
+use crate::ui_components::menu_components::menu_types::radial_menu::RadialMenuBuilder;
+use crate::ui_components::menu_components::menu_types::scrollable_menu::ScrollableMenuBuilder;
 
 #[derive(Component, Debug, Clone)]
 pub struct UiIdentifiableComponent(pub f32);
@@ -71,10 +74,52 @@ pub fn create_menu(
                     &metadata
                 );
             }
-            MenuInputType::ScrollableMenu { .. } => {}
-            MenuInputType::Radial { .. } => {}
-            MenuInputType::FormInput { .. } => {}
-            MenuInputType::Slider { .. } => {}
+            MenuInputType::ScrollableMenu { options, option, metadata } => {
+                add_scrollable(
+                    &mut commands,
+                    &mut build_result,
+                    &mut materials,
+                    &mut meshes,
+                    &mut asset_server,
+                    root_parent,
+                    options,
+                    &metadata,
+                    option
+                );
+            }
+            MenuInputType::Radial { options, option, metadata } => {
+                let radial = RadialMenuBuilder {
+                    parent: Some(root_parent),
+                    menu_metadata: metadata,
+                    config_option: option,
+                    options,
+                };
+                let radial = radial.build(&mut commands, &mut materials, &mut meshes, &mut asset_server);
+                build_result.radial.insert(radial.radial_entity, radial);
+            }
+            MenuInputType::FormInput { metadata, option, .. } => {
+                let form_input = FormInputBuilder {
+                    parent: Some(root_parent),
+                    menu_metadata: metadata,
+                    config_option: option,
+                };
+                let form_input = form_input.build(&mut commands, &mut materials, &mut meshes, &mut asset_server);
+                build_result.form_input.insert(form_input.form_input_entity, form_input);
+            }
+            MenuInputType::Slider { metadata, slider_data, option } => {
+                let slider = SliderMenuOptionBuilder {
+                    parent: Some(root_parent),
+                    menu_option: None,
+                    config_option: option,
+                    parents: vec![],
+                    menu_input_type: Some(item),
+                    slider_data: slider_data.clone(),
+                    metadata: metadata.clone(),
+                    id_component: UiIdentifiableComponent(metadata.id),
+                };
+                let slider = slider.build(&mut commands, &mut materials, &mut meshes, &mut asset_server);
+                add_slider_entities(&mut build_result, &vec![slider]);
+            }
         }
     }
     menu_state.set(TransitionsState::PopulateOptionsBuilder);
@@ -154,6 +199,37 @@ fn add_dropdown(
     add_results_dropdown(&mut build_result, dropdown);
 }
 
+fn add_scrollable(
+    mut commands: &mut Commands,
+    mut build_result: &mut ResMut<BuildMenuResult>,
+    mut materials: &mut ResMut<Assets<ColorMaterial>>,
+    mut meshes: &mut ResMut<Assets<Mesh>>,
+    mut asset_server: &mut Res<AssetServer>,
+    root_parent: Entity,
+    options: &Vec<MenuOption>,
+    metadata: &MenuItemMetadata,
+    option: &ConfigurationOptionEnum,
+) {
+    let mut parents = vec![];
+    parents.push(metadata.clone());
+    let scrollable_builder = ScrollableMenuBuilder {
+        menu_metadata: &metadata,
+        config_option: option,
+        parent_menus: vec![],
+        base_menu: BaseMenu {
+            parent: root_parent,
+            menu_metadata: &metadata,
+            config_option: option,
+            parent_menus: vec![],
+            component: UiComponent::ScrollableMenuComponent(ScrollableMenuComponent::default()),
+        },
+        menu_option_builders: menu_options(options, &parents),
+    };
+    let scrollable = scrollable_builder.build(&mut commands, &mut materials, &mut meshes, &mut asset_server);
+    build_result.scrollable.insert(scrollable.scrollable_entity, scrollable.clone());
+    add_results_dropdown(&mut build_result, scrollable.dropdown_menu_result);
+}
+
 pub(crate) fn dropdown_component(options: &Vec<MenuOption>) -> UiComponent {
     UiComponent::Dropdown(
         Dropdown {
@@ -171,9 +247,9 @@ pub(crate) fn menu_options<'a>(options: &'a Vec<MenuOption>, parents: &'a Vec<Me
             MenuOptionInputType::CollapsableMenu => submenu_builder(parents, &opt),
             MenuOptionInputType::SubMenu => submenu_builder(parents, &opt),
             MenuOptionInputType::Activated => selected_option_builder(&opt, parents),
-            MenuOptionInputType::Radial => vec![],
-            MenuOptionInputType::FormInput => vec![],
-            MenuOptionInputType::Slider => vec![],
+            MenuOptionInputType::Radial => submenu_builder(parents, &opt),
+            MenuOptionInputType::FormInput => submenu_builder(parents, &opt),
+            MenuOptionInputType::Slider => slider_builder(&opt, parents),
         }
     }).collect()
 }
@@ -213,6 +289,18 @@ fn build_submenu<'a>(
                 get_submenu_builder(sub_menu, &parents, option, metadata, UiComponent::CollapsableMenu(CollapsableMenuComponent {}))
             )]
         }
+        MenuInputType::Radial { option, metadata, .. } => {
+            vec![(
+                opt.clone(),
+                get_submenu_builder(sub_menu, &parents, option, metadata, UiComponent::RadialComponent)
+            )]
+        }
+        MenuInputType::FormInput { option, metadata, .. } => {
+            vec![(
+                opt.clone(),
+                get_submenu_builder(sub_menu, &parents, option, metadata, UiComponent::FormInput)
+            )]
+        }
         _ => panic!("Submenu has incompatible menu input type")
     }
 }
@@ -236,6 +324,32 @@ fn get_submenu_builder<'a>(
     )
 }
 
+fn slider_builder<'a>(opt: &'a MenuOption, parent_metadata: &'a Vec<MenuItemMetadata>) -> Vec<(MenuOption, MenuOptionBuilder<'a>)> {
+    match &opt.data_type {
+        MenuOptionType::SubMenu {
+            sub_menu: sub_menu @ MenuInputType::Slider { metadata, slider_data, option },
+            ..
+        } => {
+            vec![(
+                opt.clone(),
+                MenuOptionBuilder::SliderMenuOptionBuilder(
+                    SliderMenuOptionBuilder {
+                        parent: None,
+                        menu_option: Some(opt),
+                        config_option: option,
+                        parents: parent_metadata.clone(),
+                        menu_input_type: Some(sub_menu),
+                        slider_data: slider_data.clone(),
+                        metadata: metadata.clone(),
+                        id_component: UiIdentifiableComponent(metadata.id),
+                    }
+                )
+            )]
+        }
+        _ => panic!("Slider menu option must have a slider sub menu.")
+    }
+}
+
 fn selected_option_builder<'a>(opt: &'a MenuOption, parent_metadata: &'a Vec<MenuItemMetadata>) -> Vec<(MenuOption, MenuOptionBuilder<'a>)> {
     match &opt.data_type {
         MenuOptionType::Primitive(option) => {
@@ -306,7 +420,12 @@ fn add_submenu_results(mut build_result: &mut &mut ResMut<BuildMenuResult>, subm
     submenu
         .iter()
         .for_each(|s| {
-            add_results_dropdown(&mut build_result, s.dropdown_menu_result.clone());
+            s.dropdown_menu_result.as_ref()
+                .map(|dropdown| add_results_dropdown(&mut build_result, dropdown.clone()));
+            s.radial.as_ref()
+                .map(|radial| build_result.radial.insert(radial.radial_entity, radial.clone()));
+            s.form_input.as_ref()
+                .map(|form_input| build_result.form_input.insert(form_input.form_input_entity, form_input.clone()));
             build_result.submenu_results.push(s.clone());
         });
 }
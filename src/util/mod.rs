@@ -26,17 +26,18 @@ pub fn group_by_key<K, V>(map: Vec<(K, V)>) -> HashMap<K, HashSet<V>>
     result
 }
 
+/// Evenly spaced hues, in degrees, at the same saturation and lightness.
 pub fn gen_color_from_list(length: f32) -> Vec<Color> {
     let range = 0..length as usize;
     range.map(|i| {
-            let hue = i as f32 / length as f32;
+            let hue = 360.0 * i as f32 / length as f32;
             let saturation = 0.5;
             let lightness = 0.5;
             Color::Hsla {
                 hue,
                 saturation,
                 lightness,
-                alpha: 175.0,
+                alpha: 1.0,
             }
         })
         .collect()
@@ -81,3 +82,67 @@ where
         }
     }
 }
+
+/// Runs, metrics and exports are named freely, so anything that is not a letter, a digit, an
+/// underscore, a dash or a dot is replaced to keep the name a single file name. A name of only
+/// dots, such as `.` or `..`, is replaced too, so that it cannot name the directory or its parent.
+pub fn file_name(name: &str) -> String {
+    if name.chars().all(|c| c == '.') {
+        return "_".repeat(name.len().max(1));
+    }
+    name.chars()
+        .map(|c| if c.is_alphanumeric() || c == '.' || c == '_' || c == '-' { c } else { '_' })
+        .collect()
+}
+
+/// Matches a value against a pattern where `*` matches any sequence of characters.
+pub fn glob_match(pattern: &str, value: &str) -> bool {
+    let parts = pattern.split('*').collect::<Vec<&str>>();
+    if parts.len() == 1 {
+        return pattern == value;
+    }
+    let mut remaining = value;
+    for (i, part) in parts.iter().enumerate() {
+        if i == 0 {
+            if !remaining.starts_with(part) {
+                return false;
+            }
+            remaining = &remaining[part.len()..];
+        } else if i == parts.len() - 1 {
+            return remaining.ends_with(part);
+        } else {
+            match remaining.find(part) {
+                Some(found) => remaining = &remaining[found + part.len()..],
+                None => return false
+            }
+        }
+    }
+    true
+}
+
+/// Scores a candidate by how well the query matches it as a case-insensitive subsequence, or None
+/// if it does not. Matches that are consecutive or at the start of a word score higher and the
+/// characters skipped before and between matches lower the score.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i32> {
+    let candidate = candidate.to_lowercase().chars().collect::<Vec<char>>();
+    let mut score = 0;
+    let mut previous: Option<usize> = None;
+    let mut position = 0;
+    for query_char in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = candidate[position..].iter()
+            .position(|c| *c == query_char)
+            .map(|found| found + position)?;
+        score += 1;
+        if found == 0 || !candidate[found - 1].is_alphanumeric() {
+            score += 8;
+        }
+        match previous {
+            Some(previous) if found == previous + 1 => score += 5,
+            Some(previous) => score -= (found - previous - 1) as i32,
+            None => score -= found.min(5) as i32
+        }
+        previous = Some(found);
+        position = found + 1;
+    }
+    Some(score)
+}
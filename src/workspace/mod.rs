@@ -0,0 +1,230 @@
+use std::env;
+use std::env::VarError;
+use std::fs;
+use std::fs::read_to_string;
+use std::path::Path;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::event::state_transition::state_transitions_plugin::TransitionsState;
+use crate::graph::radial::RadialGraphPoints;
+use crate::menu::{MenuData, MenuInputType, MenuOptionType};
+use crate::network::Node;
+use crate::shortcuts::keymap::PressUiEntity;
+use crate::workspace::snapshot::{load_initial_workspace, press_pending, read_workspace, restore_graphed_metrics, restore_workspace, save_workspace, WorkspaceRestore};
+use crate::workspace::workspace_menu::{select_workspace, update_workspace_menu};
+
+/// Reading the state of the menus, camera and graph into a workspace, and restoring it.
+pub(crate) mod snapshot;
+/// The menu listing the named workspaces, to switch between and save them.
+pub(crate) mod workspace_menu;
+
+pub const WORKSPACE_DIR: &str = "workspaces";
+pub const DEFAULT_WORKSPACE: &str = "default";
+
+/// The state that is saved to a workspace file: the menus that are expanded, the options that are
+/// selected, the visibility of the graph and network, the camera pose, and the metrics graphed.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Workspace {
+    #[serde(default)]
+    pub(crate) expanded_menus: Vec<f32>,
+    #[serde(default)]
+    pub(crate) options: Vec<OptionState>,
+    #[serde(default)]
+    pub(crate) graph_visible: Option<bool>,
+    #[serde(default)]
+    pub(crate) network_visible: Option<bool>,
+    #[serde(default)]
+    pub(crate) camera: Option<CameraPose>,
+    #[serde(default)]
+    pub(crate) graphed_metrics: Vec<GraphedMetric>
+}
+
+/// Whether the menu option with the id is Selected or Deselected.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct OptionState {
+    pub(crate) id: f32,
+    pub(crate) selected: bool
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CameraPose {
+    pub(crate) translation: [f32; 3],
+    pub(crate) rotation: [f32; 4],
+    pub(crate) pitch: f32,
+    pub(crate) yaw: f32
+}
+
+impl CameraPose {
+    pub(crate) fn transform(&self) -> Transform {
+        Transform::from_translation(Vec3::from_array(self.translation))
+            .with_rotation(Quat::from_array(self.rotation))
+    }
+}
+
+/// A metric of a run is identified by its name, as the entity changes between sessions. The
+/// strategy is the name of the GraphingStrategy it is drawn with.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GraphedMetric {
+    pub(crate) run_id: String,
+    pub(crate) metric_name: String,
+    pub(crate) strategy: String,
+    pub(crate) visible: bool
+}
+
+#[derive(Debug)]
+pub enum WorkspaceError {
+    Read {
+        path: String,
+        message: String
+    },
+    Write {
+        path: String,
+        message: String
+    },
+    Parse {
+        path: String,
+        message: String
+    }
+}
+
+impl Workspace {
+    pub(crate) fn path(dir: &str, name: &str) -> String {
+        Path::new(dir).join(format!("{}.json", name))
+            .to_str()
+            .map(|path| path.to_string())
+            .or(Some(format!("{}/{}.json", dir, name)))
+            .unwrap()
+    }
+
+    pub(crate) fn read(dir: &str, name: &str) -> Result<Workspace, WorkspaceError> {
+        let path = Self::path(dir, name);
+        let contents = read_to_string(&path)
+            .map_err(|e| WorkspaceError::Read { path: path.clone(), message: e.to_string() })?;
+        serde_json::from_str::<Workspace>(&contents)
+            .map_err(|e| WorkspaceError::Parse { path, message: e.to_string() })
+    }
+
+    pub(crate) fn write(&self, dir: &str, name: &str) -> Result<String, WorkspaceError> {
+        let path = Self::path(dir, name);
+        let write_error = |message: String| WorkspaceError::Write { path: path.clone(), message };
+        fs::create_dir_all(dir)
+            .map_err(|e| write_error(e.to_string()))?;
+        serde_json::to_string_pretty(self)
+            .map_err(|e| write_error(e.to_string()))
+            .and_then(|contents| fs::write(&path, contents).map_err(|e| write_error(e.to_string())))?;
+        Ok(path)
+    }
+}
+
+/// The named workspaces in the workspace directory, and the one that is saved to and was last
+/// restored.
+#[derive(Resource, Debug)]
+pub struct Workspaces {
+    pub(crate) dir: String,
+    pub(crate) current: String,
+    pub(crate) names: Vec<String>
+}
+
+impl Default for Workspaces {
+    /// The workspace opened at startup is WORKSPACE, or the default workspace.
+    fn default() -> Self {
+        Self::new(env::var("WORKSPACE")
+            .or(Ok::<String, VarError>(DEFAULT_WORKSPACE.to_string()))
+            .unwrap())
+    }
+}
+
+impl Workspaces {
+    /// The workspaces in the workspace directory, with the named one opened at startup.
+    pub(crate) fn new(current: String) -> Self {
+        let mut names = list_workspaces(WORKSPACE_DIR);
+        if !names.contains(&current) {
+            names.push(current.clone());
+            names.sort();
+        }
+        Self {
+            dir: WORKSPACE_DIR.to_string(),
+            current,
+            names
+        }
+    }
+}
+
+/// The names of the workspace files in the directory, sorted.
+pub(crate) fn list_workspaces(dir: &str) -> Vec<String> {
+    let mut names = fs::read_dir(dir)
+        .map(|entries| entries
+            .flat_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().map(|extension| extension == "json").or(Some(false)).unwrap())
+            .flat_map(|path| path.file_stem().and_then(|name| name.to_str()).map(|name| name.to_string()))
+            .collect::<Vec<String>>()
+        )
+        .or(Ok::<Vec<String>, std::io::Error>(vec![]))
+        .unwrap();
+    names.sort();
+    names
+}
+
+/// The first name of the form `workspace-n` that is not taken.
+pub(crate) fn next_workspace_name(names: &Vec<String>) -> String {
+    (1..)
+        .map(|n| format!("workspace-{}", n))
+        .find(|name| !names.contains(name))
+        .unwrap()
+}
+
+/// The ids of each menu, in the order the menus are nested, with the ids of its options. A menu is
+/// expanded when its options are displayed.
+pub(crate) fn menu_options(menu_data: &MenuData) -> Vec<(f32, Vec<f32>)> {
+    let mut menus = vec![];
+    menu_data.selectables.iter()
+        .chain(menu_data.sub_menus.iter().flat_map(|sub_menu| sub_menu.selectables.iter()))
+        .for_each(|selectable| collect_menu_options(selectable, &mut menus));
+    menus
+}
+
+fn collect_menu_options(input: &MenuInputType, menus: &mut Vec<(f32, Vec<f32>)>) {
+    match input {
+        MenuInputType::Dropdown { options, metadata, .. }
+        | MenuInputType::CollapsableMenuInputType { options, metadata, .. }
+        | MenuInputType::ScrollableMenu { options, metadata, .. }
+        | MenuInputType::Radial { options, metadata, .. } => {
+            menus.push((metadata.id, options.iter().map(|option| option.metadata.id).collect()));
+            options.iter()
+                .for_each(|option| if let MenuOptionType::SubMenu { sub_menu, .. } = &option.data_type {
+                    collect_menu_options(sub_menu, menus);
+                });
+        }
+        MenuInputType::FormInput { .. } | MenuInputType::Slider { .. } => {}
+    }
+}
+
+/// Sent to save the current state to, or restore the state from, the named workspace.
+#[derive(Event, Debug, Clone, PartialEq)]
+pub enum WorkspaceEvent {
+    Save(String),
+    Load(String)
+}
+
+pub struct WorkspacePlugin;
+
+impl Plugin for WorkspacePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<WorkspaceEvent>()
+            .add_event::<PressUiEntity>()
+            .init_resource::<Workspaces>()
+            .init_resource::<WorkspaceRestore>()
+            .add_startup_system(load_initial_workspace)
+            .add_system(read_workspace)
+            .add_system(restore_workspace
+                .after(read_workspace)
+                .run_if(in_state(TransitionsState::CheckDynamicStateTransitions))
+            )
+            .add_system(restore_graphed_metrics::<Node, RadialGraphPoints>)
+            .add_system(press_pending.after(restore_workspace))
+            .add_system(update_workspace_menu)
+            .add_system(select_workspace)
+            .add_systems(Last, save_workspace::<Node, RadialGraphPoints>);
+    }
+}
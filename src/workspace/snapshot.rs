@@ -0,0 +1,306 @@
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use crate::camera::{ActiveCamera, CameraMode, sync_orbit, ZoomableDraggableCamera};
+use crate::config::keymap::KeyAction;
+use crate::data_subscriber::metric_event::MetricsState;
+use crate::graph::draw_graph_points::GraphingStrategy;
+use crate::menu::{DataType, Menu, MetricsConfigurationOption};
+use crate::menu::config_menu_event::interaction_config_event_writer::{GraphMenuResultBuilder, NetworkMenuResultBuilder};
+use crate::menu::menu_resource::MenuResource;
+use crate::metrics::network_metrics::Metric;
+use crate::network::{Layer, Network, Node};
+use crate::shortcuts::keymap::{KeyActionEvent, PressUiEntity};
+use crate::ui_components::ui_menu_component::UiIdentifiableComponent;
+use crate::workspace::{CameraPose, GraphedMetric, menu_options, OptionState, Workspace, WorkspaceEvent, Workspaces};
+
+/// The menu options, by the T of their MetricsConfigurationOption.
+pub(crate) type MenuOptionQuery<'w, 's> = Query<'w, 's, (
+    Entity,
+    &'static UiIdentifiableComponent,
+    &'static Style,
+    AnyOf<(
+        &'static MetricsConfigurationOption<Menu>,
+        &'static MetricsConfigurationOption<Metric<Network>>,
+        &'static MetricsConfigurationOption<Network>,
+        &'static MetricsConfigurationOption<Layer>,
+        &'static MetricsConfigurationOption<Node>,
+    )>
+), With<Button>>;
+
+/// The workspace read at startup or switched to, which is restored once the menus have been built.
+/// The menu options are pressed one per frame, so that each goes through the state transitions on
+/// its own, and the graphed metrics are restored as the metrics are received.
+#[derive(Resource, Default, Debug)]
+pub struct WorkspaceRestore {
+    pub(crate) pending: Option<Workspace>,
+    pub(crate) presses: VecDeque<Entity>,
+    pub(crate) graphed_metrics: Vec<GraphedMetric>
+}
+
+/// Whether the option is selected. The graph and network menu options are left out, as they are
+/// restored from the visibility of the graph and network.
+pub(crate) fn selected_state<T>(option: &MetricsConfigurationOption<T>) -> Option<bool>
+    where T: Component + Send + Sync + Clone + Debug + Default + 'static
+{
+    match option {
+        MetricsConfigurationOption::GraphMenu(..) | MetricsConfigurationOption::NetworkMenu(..) => None,
+        _ => match option.data_type() {
+            DataType::Selected => Some(true),
+            DataType::Deselected => Some(false),
+            _ => None
+        }
+    }
+}
+
+fn option_selected(
+    (menu, metrics, network, layer, node): (
+        Option<&MetricsConfigurationOption<Menu>>,
+        Option<&MetricsConfigurationOption<Metric<Network>>>,
+        Option<&MetricsConfigurationOption<Network>>,
+        Option<&MetricsConfigurationOption<Layer>>,
+        Option<&MetricsConfigurationOption<Node>>,
+    )
+) -> Option<bool> {
+    menu.and_then(selected_state)
+        .or_else(|| metrics.and_then(selected_state))
+        .or_else(|| network.and_then(selected_state))
+        .or_else(|| layer.and_then(selected_state))
+        .or_else(|| node.and_then(selected_state))
+}
+
+/// The menus whose options are displayed.
+fn expanded_menus(menu_resource: &MenuResource, options: &MenuOptionQuery) -> Vec<f32> {
+    let displayed = options.iter()
+        .filter(|(_, _, style, _)| style.display != Display::None)
+        .map(|(_, id, _, _)| id.0)
+        .collect::<Vec<f32>>();
+    menu_options(&menu_resource.menu_data).into_iter()
+        .filter(|(_, option_ids)| option_ids.iter().any(|id| displayed.contains(id)))
+        .map(|(menu_id, _)| menu_id)
+        .collect()
+}
+
+fn is_visible(entity: Option<Entity>, visibilities: &Query<&Visibility>) -> Option<bool> {
+    entity.and_then(|entity| visibilities.get(entity).ok())
+        .map(|visibility| *visibility != Visibility::Hidden)
+}
+
+pub(crate) fn load_initial_workspace(workspaces: Res<Workspaces>, mut restore: ResMut<WorkspaceRestore>) {
+    match Workspace::read(&workspaces.dir, &workspaces.current) {
+        Ok(workspace) => restore.pending = Some(workspace),
+        Err(e) => info!("Not restoring workspace {}: {:?}.", &workspaces.current, e)
+    }
+}
+
+/// Saves the workspace when it is saved from the menu or keymap, and to the current workspace
+/// when the app exits.
+pub(crate) fn save_workspace<T, P>(
+    mut workspace_events: EventReader<WorkspaceEvent>,
+    mut key_events: EventReader<KeyActionEvent>,
+    mut exit_events: EventReader<AppExit>,
+    mut workspaces: ResMut<Workspaces>,
+    restore: Res<WorkspaceRestore>,
+    menu_resource: Res<MenuResource>,
+    options: MenuOptionQuery,
+    network_menu: Res<NetworkMenuResultBuilder>,
+    graph_menu: Res<GraphMenuResultBuilder>,
+    visibilities: Query<&Visibility>,
+    camera_query: Query<&Transform, With<ActiveCamera>>,
+    camera: Res<ZoomableDraggableCamera>,
+    metrics_state: Res<MetricsState>,
+    metrics: Query<Entity, With<Metric<T>>>,
+)
+    where
+        T: Component + Send + Sync + 'static,
+        P: GraphingStrategy<T>
+{
+    let mut names = workspace_events.iter()
+        .flat_map(|event| match event {
+            WorkspaceEvent::Save(name) => Some(name.clone()),
+            WorkspaceEvent::Load(_) => None
+        })
+        .collect::<Vec<String>>();
+    if key_events.iter().any(|KeyActionEvent(action)| *action == KeyAction::SaveWorkspace)
+        || exit_events.iter().count() > 0 {
+        names.push(workspaces.current.clone());
+    }
+    names.sort();
+    names.dedup();
+    if names.is_empty() {
+        return;
+    }
+
+    let mut graphed_metrics = metrics.iter()
+        .flat_map(|metric| metrics_state.run_metric(metric)
+            .map(|(run_id, metric_name)| GraphedMetric {
+                run_id: run_id.clone(),
+                metric_name: metric_name.clone(),
+                strategy: P::NAME.to_string(),
+                visible: is_visible(Some(metric), &visibilities).or(Some(true)).unwrap(),
+            })
+        )
+        .collect::<Vec<GraphedMetric>>();
+    restore.graphed_metrics.iter()
+        .filter(|pending| !graphed_metrics.iter()
+            .any(|graphed| graphed.run_id == pending.run_id && graphed.metric_name == pending.metric_name)
+        )
+        .for_each(|pending| graphed_metrics.push(pending.clone()));
+    graphed_metrics.sort_by(|first, second| (&first.run_id, &first.metric_name).cmp(&(&second.run_id, &second.metric_name)));
+
+    let workspace = Workspace {
+        expanded_menus: expanded_menus(&menu_resource, &options),
+        options: options.iter()
+            .flat_map(|(_, id, _, option)| option_selected(option)
+                .map(|selected| OptionState { id: id.0, selected })
+            )
+            .collect(),
+        graph_visible: is_visible(graph_menu.graph_parent_entity, &visibilities),
+        network_visible: is_visible(network_menu.network_parent_entity, &visibilities),
+        camera: camera_query.get_single().ok()
+            .map(|transform| CameraPose {
+                translation: transform.translation.to_array(),
+                rotation: transform.rotation.to_array(),
+                pitch: camera.pitch,
+                yaw: camera.yaw,
+            }),
+        graphed_metrics,
+    };
+
+    for name in names.into_iter() {
+        let _ = workspace.write(&workspaces.dir, &name)
+            .map(|path| info!("Saved workspace to {}.", path))
+            .or_else(|e| {
+                error!("Could not save workspace {}: {:?}", &name, e);
+                Err(e)
+            });
+        if !workspaces.names.contains(&name) {
+            workspaces.names.push(name);
+            workspaces.names.sort();
+        }
+    }
+}
+
+/// Reads the workspace switched to, to be restored.
+pub(crate) fn read_workspace(
+    mut workspace_events: EventReader<WorkspaceEvent>,
+    mut workspaces: ResMut<Workspaces>,
+    mut restore: ResMut<WorkspaceRestore>,
+) {
+    for event in workspace_events.iter() {
+        if let WorkspaceEvent::Load(name) = event {
+            let _ = Workspace::read(&workspaces.dir, name)
+                .map(|workspace| {
+                    restore.pending = Some(workspace);
+                    workspaces.current = name.clone();
+                })
+                .or_else(|e| {
+                    error!("Could not read workspace {}: {:?}", name, e);
+                    Err(e)
+                });
+        }
+    }
+}
+
+/// Menus and options that differ from the workspace are pressed, so that they are changed by the
+/// same state transitions as when they are clicked. The camera is moved directly.
+pub(crate) fn restore_workspace(
+    mut restore: ResMut<WorkspaceRestore>,
+    menu_resource: Res<MenuResource>,
+    options: MenuOptionQuery,
+    network_menu: Res<NetworkMenuResultBuilder>,
+    graph_menu: Res<GraphMenuResultBuilder>,
+    visibilities: Query<&Visibility>,
+    mut camera_query: Query<&mut Transform, With<ActiveCamera>>,
+    mut camera: ResMut<ZoomableDraggableCamera>,
+) {
+    let workspace = restore.pending.take();
+    if workspace.is_none() {
+        return;
+    }
+    let workspace = workspace.unwrap();
+    let button = |id: f32| options.iter()
+        .find(|(_, identifiable, _, _)| identifiable.0 == id)
+        .map(|(entity, _, _, _)| entity);
+
+    let expanded = expanded_menus(&menu_resource, &options);
+    let mut presses = menu_options(&menu_resource.menu_data).into_iter()
+        .map(|(menu_id, _)| menu_id)
+        .filter(|menu_id| expanded.contains(menu_id) != workspace.expanded_menus.contains(menu_id))
+        .flat_map(|menu_id| button(menu_id))
+        .collect::<Vec<Entity>>();
+
+    workspace.options.iter()
+        .filter(|saved| options.iter()
+            .find(|(_, id, _, _)| id.0 == saved.id)
+            .and_then(|(_, _, _, option)| option_selected(option))
+            .map(|selected| selected != saved.selected)
+            .or(Some(false))
+            .unwrap()
+        )
+        .flat_map(|saved| button(saved.id))
+        .for_each(|entity| presses.push(entity));
+
+    [
+        (workspace.graph_visible, graph_menu.graph_parent_entity, graph_menu.graph_menu_config_option),
+        (workspace.network_visible, network_menu.network_parent_entity, network_menu.network_menu_config_option)
+    ].into_iter()
+        .filter(|(saved, parent, _)| saved.is_some() && is_visible(*parent, &visibilities).is_some()
+            && *saved != is_visible(*parent, &visibilities))
+        .flat_map(|(_, _, option)| option)
+        .for_each(|entity| presses.push(entity));
+
+    restore.presses.extend(presses);
+    restore.graphed_metrics = workspace.graphed_metrics.clone();
+
+    if let Some(pose) = workspace.camera.as_ref() {
+        for mut transform in camera_query.iter_mut() {
+            transform.translation = Vec3::from_array(pose.translation);
+            transform.rotation = Quat::from_array(pose.rotation);
+        }
+        camera.pitch = pose.pitch;
+        camera.yaw = pose.yaw;
+        camera.target_rotation = None;
+        camera.target_translation = Some(Vec3::from_array(pose.translation));
+        if camera.mode == CameraMode::Orbit {
+            sync_orbit(&mut camera, &pose.transform());
+        }
+    }
+}
+
+pub(crate) fn press_pending(mut restore: ResMut<WorkspaceRestore>, mut press_writer: EventWriter<PressUiEntity>) {
+    restore.presses.pop_front()
+        .map(|entity| press_writer.send(PressUiEntity(entity)));
+}
+
+/// The metrics are received after the workspace is restored, so the visibility of each graphed
+/// metric is restored once its metric has been added.
+pub(crate) fn restore_graphed_metrics<T, P>(
+    mut commands: Commands,
+    mut restore: ResMut<WorkspaceRestore>,
+    metrics_state: Res<MetricsState>,
+    metrics: Query<Entity, With<Metric<T>>>,
+)
+    where
+        T: Component + Send + Sync + 'static,
+        P: GraphingStrategy<T>
+{
+    if restore.graphed_metrics.is_empty() {
+        return;
+    }
+    restore.graphed_metrics.retain(|graphed| {
+        if graphed.strategy != P::NAME {
+            return true;
+        }
+        let metric = metrics_state.entities.get(&(graphed.run_id.clone(), graphed.metric_name.clone()))
+            .map(|(entity, _)| *entity)
+            .filter(|entity| metrics.contains(*entity));
+        metric.map(|metric| {
+            let visibility = if graphed.visible { Visibility::Inherited } else { Visibility::Hidden };
+            commands.get_entity(metric)
+                .as_mut()
+                .map(|metric| metric.insert(visibility));
+        }).is_none()
+    });
+}
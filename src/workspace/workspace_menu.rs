@@ -0,0 +1,110 @@
+use bevy::prelude::*;
+use crate::config::theme::{DEFAULT_FONT, ThemeColor};
+use crate::theme::restyle::{ThemedBackground, ThemedText};
+use crate::workspace::{next_workspace_name, WorkspaceEvent, Workspaces};
+
+#[derive(Component, Default, Debug)]
+pub struct WorkspaceMenu;
+
+#[derive(Component, Debug, Clone, PartialEq)]
+pub enum WorkspaceMenuAction {
+    Switch(String),
+    Save,
+    New
+}
+
+/// The menu lists the workspaces, with the current workspace highlighted, and is rebuilt when a
+/// workspace is added or switched to.
+pub(crate) fn update_workspace_menu(
+    mut commands: Commands,
+    workspaces: Res<Workspaces>,
+    asset_server: Res<AssetServer>,
+    menus: Query<Entity, With<WorkspaceMenu>>,
+) {
+    if !workspaces.is_changed() {
+        return;
+    }
+    menus.iter().for_each(|menu| commands.entity(menu).despawn_recursive());
+
+    let menu = commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(10.0),
+                bottom: Val::Px(10.0),
+                display: Display::Flex,
+                flex_direction: FlexDirection::Row,
+                flex_wrap: FlexWrap::Wrap,
+                ..default()
+            },
+            ..default()
+        },
+        WorkspaceMenu::default()
+    )).id();
+
+    let mut entries = workspaces.names.iter()
+        .map(|name| {
+            let color = if name == &workspaces.current { ThemeColor::PanelHighlight } else { ThemeColor::PanelEntry };
+            (name.clone(), color, WorkspaceMenuAction::Switch(name.clone()))
+        })
+        .collect::<Vec<(String, ThemeColor, WorkspaceMenuAction)>>();
+    entries.push(("Save".to_string(), ThemeColor::Panel, WorkspaceMenuAction::Save));
+    entries.push(("New".to_string(), ThemeColor::Panel, WorkspaceMenuAction::New));
+
+    for (name, color, action) in entries.into_iter() {
+        let button = commands.spawn((
+            ButtonBundle {
+                style: Style {
+                    padding: UiRect::all(Val::Px(4.0)),
+                    margin: UiRect::all(Val::Px(2.0)),
+                    ..default()
+                },
+                ..default()
+            },
+            ThemedBackground(color),
+            action
+        ))
+            .with_children(|button| {
+                button.spawn((
+                    TextBundle {
+                        text: Text::from_section(name, TextStyle {
+                            font_size: 16.0,
+                            font: asset_server.load(DEFAULT_FONT),
+                            ..default()
+                        }),
+                        ..default()
+                    },
+                    ThemedText(ThemeColor::PanelText)
+                ));
+            })
+            .id();
+        commands.entity(menu).add_child(button);
+    }
+}
+
+/// Switching restores the workspace, saving writes the current workspace, and a new workspace is
+/// saved from the current state under the next free name and becomes the current workspace.
+pub(crate) fn select_workspace(
+    interactions: Query<(&Interaction, &WorkspaceMenuAction), Changed<Interaction>>,
+    mut workspaces: ResMut<Workspaces>,
+    mut workspace_writer: EventWriter<WorkspaceEvent>,
+) {
+    for (interaction, action) in interactions.iter() {
+        if !matches!(interaction, Interaction::Pressed) {
+            continue;
+        }
+        match action {
+            WorkspaceMenuAction::Switch(name) => {
+                if name != &workspaces.current {
+                    workspace_writer.send(WorkspaceEvent::Load(name.clone()));
+                }
+            }
+            WorkspaceMenuAction::Save => workspace_writer.send(WorkspaceEvent::Save(workspaces.current.clone())),
+            WorkspaceMenuAction::New => {
+                let name = next_workspace_name(&workspaces.names);
+                workspace_writer.send(WorkspaceEvent::Save(name.clone()));
+                workspaces.current = name;
+            }
+        }
+    }
+}